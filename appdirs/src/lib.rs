@@ -0,0 +1,298 @@
+//! Where the games in this collection keep their files.
+//!
+//! Each game has a config directory, for settings that the player edits, and
+//! a data directory, for the records, scores and saved games that it writes
+//! itself.  They are found in the usual places for the platform:
+//!
+//! | Platform | Config directory                     | Data directory                        |
+//! |----------|--------------------------------------|---------------------------------------|
+//! | Linux    | `$XDG_CONFIG_HOME/app` or `~/.config/app` | `$XDG_DATA_HOME/app` or `~/.local/share/app` |
+//! | macOS    | `~/Library/Application Support/app`  | `~/Library/Application Support/app`   |
+//! | Windows  | `%APPDATA%\app`                      | `%APPDATA%\app`                       |
+//!
+//! If the home directory can't be found, files are kept in the current
+//! directory instead, which is where the games used to keep them.
+//!
+//! ```no_run
+//! let dirs = appdirs::AppDirs::new("hanoi");
+//! let scores = dirs.data_file("hanoi.scores");
+//! appdirs::AppDirs::prepare(&scores)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! With the `settings` feature (on by default), settings of any type that
+//! serde can handle are read from and written to `settings.toml` in the
+//! config directory; see `AppDirs::load_settings`.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the settings file in the config directory.
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+/// The platforms whose conventions for the directories are followed.  Any
+/// platform other than macOS and Windows follows the XDG conventions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Xdg,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    /// Returns the platform that this was built for.
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Xdg
+        }
+    }
+}
+
+/// The directories that one game keeps its files in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppDirs {
+    config: Option<PathBuf>,
+    data: Option<PathBuf>,
+}
+
+impl AppDirs {
+    /// Returns the directories for the game with the given name on this
+    /// platform.
+    pub fn new(app: &str) -> Self {
+        Self::resolve(app, Platform::current(), |name| env::var_os(name))
+    }
+
+    /// Returns the directories for the game with the given name on the given
+    /// platform, looking up environment variables with the given function.
+    pub fn resolve(app: &str, platform: Platform, var: impl Fn(&str) -> Option<OsString>) -> Self {
+        // Relative paths are to be ignored, by the XDG specification, and
+        // there would be no telling what they were relative to anyway.
+        let absolute = |name: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let home = absolute("HOME");
+        let (config, data) = match platform {
+            Platform::Xdg => (
+                absolute("XDG_CONFIG_HOME")
+                    .or_else(|| home.as_ref().map(|home| home.join(".config"))),
+                absolute("XDG_DATA_HOME")
+                    .or_else(|| home.as_ref().map(|home| home.join(".local").join("share"))),
+            ),
+            Platform::MacOs => {
+                let support = home.map(|home| home.join("Library").join("Application Support"));
+                (support.clone(), support)
+            }
+            Platform::Windows => {
+                let roaming = absolute("APPDATA");
+                (roaming.clone(), roaming)
+            }
+        };
+        AppDirs {
+            config: config.map(|dir| dir.join(app)),
+            data: data.map(|dir| dir.join(app)),
+        }
+    }
+
+    /// Returns directories that are both the given one, such as for keeping
+    /// everything beside a portable copy of a game.
+    pub fn in_dir(dir: &Path) -> Self {
+        AppDirs {
+            config: Some(dir.to_path_buf()),
+            data: Some(dir.to_path_buf()),
+        }
+    }
+
+    /// Returns the config directory, if the home directory could be found.
+    pub fn config_dir(&self) -> Option<&Path> {
+        self.config.as_deref()
+    }
+
+    /// Returns the data directory, if the home directory could be found.
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data.as_deref()
+    }
+
+    /// Returns the path of the given file in the config directory, or in the
+    /// current directory if there is no config directory.
+    pub fn config_file(&self, name: &str) -> PathBuf {
+        in_dir(self.config_dir(), name)
+    }
+
+    /// Returns the path of the given file in the data directory, or in the
+    /// current directory if there is no data directory.
+    pub fn data_file(&self, name: &str) -> PathBuf {
+        in_dir(self.data_dir(), name)
+    }
+
+    /// Creates the directory that the given file is in, if it doesn't already
+    /// exist, so that the file can be written.
+    pub fn prepare(path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the settings from the settings file, which needn't exist yet.
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Settings {
+    ///     name: Option<String>,
+    /// }
+    ///
+    /// let dirs = appdirs::AppDirs::new("hanoi");
+    /// let settings: Settings = dirs.load_settings()?;
+    /// dirs.save_settings(&settings)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "settings")]
+    pub fn load_settings<T>(&self) -> io::Result<T>
+    where
+        T: serde::de::DeserializeOwned + Default,
+    {
+        match fs::read_to_string(self.config_file(SETTINGS_FILE)) {
+            Ok(text) => toml::from_str(&text).map_err(|err| invalid(err.message())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the settings to the settings file, creating the config
+    /// directory if need be.
+    #[cfg(feature = "settings")]
+    pub fn save_settings<T: serde::Serialize>(&self, settings: &T) -> io::Result<()> {
+        let path = self.config_file(SETTINGS_FILE);
+        let text = toml::to_string(settings).map_err(|err| invalid(&err.to_string()))?;
+        Self::prepare(&path)?;
+        fs::write(path, text)
+    }
+}
+
+// Returns the path of a file in the given directory, or the current one.
+fn in_dir(dir: Option<&Path>, name: &str) -> PathBuf {
+    match dir {
+        Some(dir) => dir.join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+// Builds an error for a settings file that couldn't be read or written.
+#[cfg(feature = "settings")]
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad settings: {}", msg.trim()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "settings")]
+    use serde::{Deserialize, Serialize};
+    #[cfg(feature = "settings")]
+    use std::process;
+
+    fn resolve(platform: Platform, vars: &[(&str, &str)]) -> AppDirs {
+        AppDirs::resolve("game", platform, |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value))
+        })
+    }
+
+    #[test]
+    fn test_xdg() {
+        let dirs = resolve(Platform::Xdg, &[("HOME", "/home/ann")]);
+        assert_eq!(dirs.config_dir(), Some(Path::new("/home/ann/.config/game")));
+        assert_eq!(
+            dirs.data_dir(),
+            Some(Path::new("/home/ann/.local/share/game"))
+        );
+        assert_eq!(
+            dirs.data_file("x.sav"),
+            Path::new("/home/ann/.local/share/game/x.sav")
+        );
+
+        let dirs = resolve(
+            Platform::Xdg,
+            &[
+                ("HOME", "/home/ann"),
+                ("XDG_CONFIG_HOME", "/cfg"),
+                ("XDG_DATA_HOME", "data"),
+            ],
+        );
+        assert_eq!(dirs.config_dir(), Some(Path::new("/cfg/game")));
+        assert_eq!(
+            dirs.data_dir(),
+            Some(Path::new("/home/ann/.local/share/game"))
+        );
+
+        let dirs = resolve(Platform::Xdg, &[]);
+        assert_eq!(dirs.config_dir(), None);
+        assert_eq!(dirs.data_file("x.sav"), Path::new("x.sav"));
+    }
+
+    #[test]
+    fn test_other_platforms() {
+        let dirs = resolve(Platform::MacOs, &[("HOME", "/Users/ann")]);
+        let support = Path::new("/Users/ann/Library/Application Support/game");
+        assert_eq!(dirs.config_dir(), Some(support));
+        assert_eq!(dirs.data_dir(), Some(support));
+
+        if cfg!(windows) {
+            let dirs = resolve(
+                Platform::Windows,
+                &[("APPDATA", r"C:\Users\ann\AppData\Roaming")],
+            );
+            assert_eq!(
+                dirs.config_dir(),
+                Some(Path::new(r"C:\Users\ann\AppData\Roaming\game"))
+            );
+        }
+        let dirs = resolve(Platform::Windows, &[("HOME", "/home/ann")]);
+        assert_eq!(dirs.data_dir(), None);
+    }
+
+    #[cfg(feature = "settings")]
+    #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+    struct Settings {
+        name: Option<String>,
+        level: u32,
+    }
+
+    #[test]
+    #[cfg(feature = "settings")]
+    fn test_settings() {
+        let dir = env::temp_dir().join(format!("appdirs-test-{}", process::id()));
+        let dirs = AppDirs::in_dir(&dir.join("nested"));
+        assert_eq!(
+            dirs.load_settings::<Settings>().unwrap(),
+            Settings::default()
+        );
+
+        let settings = Settings {
+            name: Some("Ann".to_string()),
+            level: 3,
+        };
+        dirs.save_settings(&settings).unwrap();
+        assert_eq!(dirs.load_settings::<Settings>().unwrap(), settings);
+
+        fs::write(dirs.config_file(SETTINGS_FILE), "level = \"high\"\n").unwrap();
+        let err = dirs.load_settings::<Settings>().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
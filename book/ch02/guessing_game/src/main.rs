@@ -1,11 +1,22 @@
+use clap::Parser;
 use rand::Rng;
 use std::cmp::Ordering;
 use std::io;
 
+/// Guess the number the computer is thinking of.
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// The largest number the computer may pick
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    max: u32,
+}
+
 fn main() {
+    let options = Options::parse();
     println!("Guess the number!");
 
-    let secret_number = rand::thread_rng().gen_range(1..=100);
+    let secret_number = rand::thread_rng().gen_range(1..=options.max);
 
     loop {
         println!("Please input your guess.");
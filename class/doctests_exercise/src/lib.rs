@@ -1,3 +1,26 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// The ways that an arithmetic operation can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// The divisor was zero.
+    DivideByZero,
+    /// The result does not fit in an i32.
+    Overflow,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MathError::DivideByZero => write!(f, "Divide-by-zero error"),
+            MathError::Overflow => write!(f, "Overflow error"),
+        }
+    }
+}
+
+impl Error for MathError {}
+
 /// This function divides two numbers.
 ///
 /// # Example #1: 10 / 2 == 5
@@ -28,6 +51,34 @@ pub fn div(a: i32, b: i32) -> i32 {
     a / b
 }
 
+/// This function divides two numbers, returning an error instead of
+/// panicking.
+///
+/// # Example: 10 / 2 == 5
+///
+/// ```
+/// let result = doctests_exercise::checked_div(10, 2);
+/// assert_eq!(result, Ok(5));
+/// ```
+///
+/// # Errors
+///
+/// Dividing by zero is an error, and so is dividing `i32::MIN` by -1, since
+/// the result is one more than `i32::MAX`.
+///
+/// ```
+/// use doctests_exercise::{checked_div, MathError};
+///
+/// assert_eq!(checked_div(1, 0), Err(MathError::DivideByZero));
+/// assert_eq!(checked_div(i32::MIN, -1), Err(MathError::Overflow));
+/// ```
+pub fn checked_div(a: i32, b: i32) -> Result<i32, MathError> {
+    if b == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    a.checked_div(b).ok_or(MathError::Overflow)
+}
+
 /// This function subtracts two numbers.
 ///
 /// # Example #1: 9 - 2 == 7
@@ -46,3 +97,129 @@ pub fn div(a: i32, b: i32) -> i32 {
 pub fn sub(a: i32, b: i32) -> i32 {
     a - b
 }
+
+/// This function subtracts two numbers, returning an error if the result
+/// doesn't fit in an i32.
+///
+/// # Example: 6 - 9 == -3
+///
+/// ```
+/// let result = doctests_exercise::checked_sub(6, 9);
+/// assert_eq!(result, Ok(-3));
+/// ```
+///
+/// # Errors
+///
+/// ```
+/// use doctests_exercise::{checked_sub, MathError};
+///
+/// assert_eq!(checked_sub(i32::MIN, 1), Err(MathError::Overflow));
+/// ```
+pub fn checked_sub(a: i32, b: i32) -> Result<i32, MathError> {
+    a.checked_sub(b).ok_or(MathError::Overflow)
+}
+
+/// This function adds two numbers.
+///
+/// # Example #1: 2 + 3 == 5
+///
+/// ```
+/// let result = doctests_exercise::add(2, 3);
+/// assert_eq!(result, 5);
+/// ```
+///
+/// # Example #2: -7 + 4 == -3
+///
+/// ```
+/// let result = doctests_exercise::add(-7, 4);
+/// assert_eq!(result, -3);
+/// ```
+///
+/// # Panics
+///
+/// The function panics if the result doesn't fit in an i32, even in a
+/// release build.
+///
+/// ```rust,should_panic
+/// let result = doctests_exercise::add(i32::MAX, 1);
+/// ```
+pub fn add(a: i32, b: i32) -> i32 {
+    match checked_add(a, b) {
+        Ok(result) => result,
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// This function adds two numbers, returning an error if the result doesn't
+/// fit in an i32.
+///
+/// # Example: 2 + 3 == 5
+///
+/// ```
+/// let result = doctests_exercise::checked_add(2, 3);
+/// assert_eq!(result, Ok(5));
+/// ```
+///
+/// # Errors
+///
+/// ```
+/// use doctests_exercise::{checked_add, MathError};
+///
+/// assert_eq!(checked_add(i32::MAX, 1), Err(MathError::Overflow));
+/// ```
+pub fn checked_add(a: i32, b: i32) -> Result<i32, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// This function multiplies two numbers.
+///
+/// # Example #1: 6 * 7 == 42
+///
+/// ```
+/// let result = doctests_exercise::mul(6, 7);
+/// assert_eq!(result, 42);
+/// ```
+///
+/// # Example #2: -4 * 5 == -20
+///
+/// ```
+/// let result = doctests_exercise::mul(-4, 5);
+/// assert_eq!(result, -20);
+/// ```
+///
+/// # Panics
+///
+/// The function panics if the result doesn't fit in an i32, even in a
+/// release build.
+///
+/// ```rust,should_panic
+/// let result = doctests_exercise::mul(i32::MAX, 2);
+/// ```
+pub fn mul(a: i32, b: i32) -> i32 {
+    match checked_mul(a, b) {
+        Ok(result) => result,
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// This function multiplies two numbers, returning an error if the result
+/// doesn't fit in an i32.
+///
+/// # Example: 6 * 7 == 42
+///
+/// ```
+/// let result = doctests_exercise::checked_mul(6, 7);
+/// assert_eq!(result, Ok(42));
+/// ```
+///
+/// # Errors
+///
+/// ```
+/// use doctests_exercise::{checked_mul, MathError};
+///
+/// assert_eq!(checked_mul(i32::MAX, 2), Err(MathError::Overflow));
+/// assert_eq!(checked_mul(i32::MIN, -1), Err(MathError::Overflow));
+/// ```
+pub fn checked_mul(a: i32, b: i32) -> Result<i32, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
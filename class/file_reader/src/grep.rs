@@ -0,0 +1,87 @@
+//! Line filtering with regular expressions, in the manner of `grep`.  Lines
+//! are tested one at a time as they are read, so a filter never needs more
+//! than the current line in memory.
+
+use regex::{Regex, RegexBuilder};
+
+/// The escape sequences placed around each match when highlighting: bold
+/// red, then back to normal.
+const MATCH_START: &str = "\x1b[1;31m";
+const MATCH_END: &str = "\x1b[0m";
+
+/// Matcher decides which lines to keep and marks the matches within them.
+pub struct Matcher {
+    regex: Regex,
+    invert: bool,
+}
+
+impl Matcher {
+    /// Compiles the pattern, ignoring case if asked to.  If `invert` is set,
+    /// only the lines that do *not* match are kept, as with `grep -v`.
+    pub fn new(pattern: &str, ignore_case: bool, invert: bool) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()?;
+        Ok(Self { regex, invert })
+    }
+
+    /// Returns true if the line should be kept.
+    pub fn keeps(&self, line: &str) -> bool {
+        self.regex.is_match(line) != self.invert
+    }
+
+    /// Returns the line with every (non-empty) match highlighted.  Inverted
+    /// matchers keep only lines without matches, so there is nothing to mark.
+    pub fn highlight(&self, line: &str) -> String {
+        let mut text = String::with_capacity(line.len());
+        let mut last = 0;
+        for found in self.regex.find_iter(line).filter(|found| !found.is_empty()) {
+            text.push_str(&line[last..found.start()]);
+            text.push_str(MATCH_START);
+            text.push_str(found.as_str());
+            text.push_str(MATCH_END);
+            last = found.end();
+        }
+        text.push_str(&line[last..]);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps() {
+        let matcher = Matcher::new(r"err(or)?\b", false, false).unwrap();
+        assert!(matcher.keeps("an error occurred"));
+        assert!(matcher.keeps("err: disk full"));
+        assert!(!matcher.keeps("ERROR: disk full"));
+        assert!(!matcher.keeps("all is well"));
+
+        let matcher = Matcher::new("error", true, false).unwrap();
+        assert!(matcher.keeps("ERROR: disk full"));
+
+        let matcher = Matcher::new("error", true, true).unwrap();
+        assert!(!matcher.keeps("ERROR: disk full"));
+        assert!(matcher.keeps("all is well"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let matcher = Matcher::new("o+", false, false).unwrap();
+        assert_eq!(
+            matcher.highlight("foo bar boo"),
+            "f\x1b[1;31moo\x1b[0m bar b\x1b[1;31moo\x1b[0m"
+        );
+        assert_eq!(matcher.highlight("bar"), "bar");
+
+        let matcher = Matcher::new("x*", false, false).unwrap();
+        assert_eq!(matcher.highlight("abc"), "abc");
+    }
+
+    #[test]
+    fn test_bad_pattern() {
+        assert!(Matcher::new("(unclosed", false, false).is_err());
+    }
+}
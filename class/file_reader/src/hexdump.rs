@@ -0,0 +1,138 @@
+//! Canonical hex+ASCII dumps, in the style of `hexdump -C`.  Each row shows
+//! the offset of its first byte, sixteen bytes in hex (split into two groups
+//! of eight), and the same bytes as text, with anything unprintable shown as
+//! a dot:
+//!
+//! ```
+//! use file_reader::hexdump::format_row;
+//!
+//! assert_eq!(
+//!     format_row(0x10, b"Hello, world!\n"),
+//!     "00000010  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        |Hello, world!.|"
+//! );
+//! ```
+
+use std::io::{self, ErrorKind, Read, Write};
+
+/// The number of bytes shown on each row.
+pub const ROW_BYTES: usize = 16;
+
+/// Formats a single row of the dump.  `bytes` holds at most ROW_BYTES bytes;
+/// a short final row is padded so that its text column lines up.
+pub fn format_row(offset: u64, bytes: &[u8]) -> String {
+    let mut row = format!("{:08x} ", offset);
+    for i in 0..ROW_BYTES {
+        if i % 8 == 0 {
+            row.push(' ');
+        }
+        match bytes.get(i) {
+            Some(byte) => row.push_str(&format!("{:02x} ", byte)),
+            None => row.push_str("   "),
+        }
+    }
+
+    row.push_str(" |");
+    row.extend(bytes.iter().map(|&byte| printable(byte)));
+    row.push('|');
+    row
+}
+
+/// Returns the character shown for a byte in the text column.
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// Writes a dump of everything in `input` to `output`, numbering the rows
+/// from `offset`.  The dump ends with a line holding the final offset, so an
+/// empty input produces just that line.
+pub fn dump<R: Read, W: Write>(mut input: R, mut output: W, mut offset: u64) -> io::Result<()> {
+    let mut buf = [0; ROW_BYTES];
+    loop {
+        let len = read_row(&mut input, &mut buf)?;
+        if len == 0 {
+            break;
+        }
+        writeln!(output, "{}", format_row(offset, &buf[..len]))?;
+        offset += len as u64;
+    }
+    writeln!(output, "{:08x}", offset)
+}
+
+/// Fills as much of a row as possible, since a single read may return fewer
+/// bytes than are available (from a pipe, for example).  Returns the number
+/// of bytes read, which is less than a full row only at the end of the input.
+fn read_row<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match input.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump_to_string(bytes: &[u8]) -> String {
+        let mut output = vec![];
+        dump(bytes, &mut output, 0).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_format_row() {
+        let bytes: Vec<u8> = (0x41..0x51).collect();
+        assert_eq!(
+            format_row(0, &bytes),
+            "00000000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|"
+        );
+        assert_eq!(
+            format_row(0xabcdef, &[0x00, 0xff, b' ', b'~']),
+            "00abcdef  00 ff 20 7e                                       |.. ~|"
+        );
+    }
+
+    #[test]
+    fn test_dump() {
+        assert_eq!(dump_to_string(b""), "00000000\n");
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        let text = dump_to_string(&bytes);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 17);
+        assert!(lines[0].ends_with("|................|"));
+        assert!(lines[2].starts_with("00000020  20 21 22"));
+        assert!(lines[4].ends_with("|@ABCDEFGHIJKLMNO|"));
+        assert_eq!(lines[16], "00000100");
+    }
+
+    #[test]
+    fn test_dump_short_reads() {
+        // A reader that returns one byte at a time must still fill each row.
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.is_empty() || buf.is_empty() {
+                    return Ok(0);
+                }
+                buf[0] = self.0[0];
+                self.0 = &self.0[1..];
+                Ok(1)
+            }
+        }
+
+        let bytes = b"0123456789abcdefXYZ";
+        let mut output = vec![];
+        dump(Trickle(bytes), &mut output, 0).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), dump_to_string(bytes));
+    }
+}
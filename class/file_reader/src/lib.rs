@@ -0,0 +1,5 @@
+//! Library support for the file reader, shared with anything else that needs
+//! to display the contents of a file.
+
+pub mod grep;
+pub mod hexdump;
@@ -1,36 +1,307 @@
-use std::env;
+use clap::Parser;
+use file_reader::grep::Matcher;
+use file_reader::hexdump;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-fn print_file(path: &String) -> Result<(), String> {
-    let file = File::open(&path);
-    let file = match file {
-        Ok(file) => file,
-        Err(error) => {
-            return Err(format!("Error opening file: {}", error));
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::process::ExitCode;
+
+/// The size of each block read while scanning backwards for `--tail`.
+const TAIL_BLOCK: usize = 64 * 1024;
+
+/// Which lines of the file to print.
+#[derive(Clone, Debug, PartialEq)]
+enum Lines {
+    All,
+    /// Lines `start` through `end`, numbered from 1 and inclusive.  An `end`
+    /// of None means the end of the file.
+    Range(usize, Option<usize>),
+    /// The last N lines.
+    Tail(usize),
+}
+
+/// Prints a file, or just some of its lines, optionally filtered by a
+/// pattern or dumped in hex.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Options {
+    /// The file to print
+    path: String,
+    /// Number each line
+    #[arg(short = 'n', long = "number")]
+    numbered: bool,
+    /// Print a hex dump of the file instead of its lines
+    #[arg(long, conflicts_with_all = ["numbered", "head", "tail", "range", "grep"])]
+    hex: bool,
+    /// Print only the first N lines
+    #[arg(long, value_name = "N", group = "lines")]
+    head: Option<usize>,
+    /// Print only the last N lines
+    #[arg(long, value_name = "N", group = "lines")]
+    tail: Option<usize>,
+    /// Print only lines START through END; either may be omitted
+    #[arg(long, value_name = "START:END", group = "lines", value_parser = parse_range)]
+    range: Option<Lines>,
+    /// Print only the lines that match a regular expression
+    #[arg(long, value_name = "PATTERN")]
+    grep: Option<String>,
+    /// Ignore case when matching the --grep pattern
+    #[arg(short, long, requires = "grep")]
+    ignore_case: bool,
+    /// Print the lines that don't match the --grep pattern instead
+    #[arg(short = 'v', long = "invert-match", requires = "grep")]
+    invert: bool,
+}
+
+impl Options {
+    /// Returns which lines to print, from whichever of `--head`, `--tail`, and
+    /// `--range` was given.
+    fn lines(&self) -> Lines {
+        match (self.head, self.tail, &self.range) {
+            (Some(count), _, _) => Lines::Range(1, Some(count)),
+            (_, Some(count), _) => Lines::Tail(count),
+            (_, _, Some(range)) => range.clone(),
+            _ => Lines::All,
         }
+    }
+}
+
+/// Parses a `START:END` range of line numbers.  Either end may be omitted, so
+/// `:10` is the first ten lines and `100:` is everything from line 100 on.
+fn parse_range(text: &str) -> Result<Lines, String> {
+    let invalid = || "expected START:END, such as 10:20".to_string();
+    let (start, end) = text.split_once(':').ok_or_else(invalid)?;
+
+    let start = match start {
+        "" => 1,
+        start => start.parse().map_err(|_| invalid())?,
+    };
+    let end = match end {
+        "" => None,
+        end => Some(end.parse().map_err(|_| invalid())?),
     };
 
+    if start == 0 {
+        Err("line numbers start at 1".to_string())
+    } else if end.is_some_and(|end| end < start) {
+        Err("the range ends before it starts".to_string())
+    } else {
+        Ok(Lines::Range(start, end))
+    }
+}
+
+/// Finds the offset at which the last `count` lines of the input begin, by
+/// reading blocks backwards from the end, so that only the tail of a large
+/// file is ever read.  A newline at the very end does not start a new line.
+fn tail_offset<R: Read + Seek>(input: &mut R, count: usize) -> io::Result<u64> {
+    let len = input.seek(SeekFrom::End(0))?;
+    if count == 0 {
+        return Ok(len);
+    }
+
+    let mut buf = vec![0; TAIL_BLOCK];
+    let mut pos = len;
+    let mut seen = 0;
+    while pos > 0 {
+        let start = pos.saturating_sub(TAIL_BLOCK as u64);
+        let block = &mut buf[..(pos - start) as usize];
+        input.seek(SeekFrom::Start(start))?;
+        input.read_exact(block)?;
+
+        for (i, &byte) in block.iter().enumerate().rev() {
+            let offset = start + i as u64;
+            if byte == b'\n' && offset + 1 != len {
+                seen += 1;
+                if seen == count {
+                    return Ok(offset + 1);
+                }
+            }
+        }
+        pos = start;
+    }
+    Ok(0)
+}
+
+/// Counts the lines that end before the given offset, so that `--tail` can
+/// number its lines without keeping the whole file.
+fn count_lines<R: Read>(input: R, offset: u64) -> io::Result<usize> {
+    let mut reader = BufReader::new(input.take(offset));
+    let mut count = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(count);
+        }
+        count += buf.iter().filter(|&&byte| byte == b'\n').count();
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+/// Prints the selected lines of the file, keeping only those that the
+/// matcher (if any) accepts.  Matches are highlighted on a terminal.
+fn print_file(options: &Options, matcher: Option<&Matcher>) -> Result<(), String> {
+    let mut file =
+        File::open(&options.path).map_err(|error| format!("Error opening file: {}", error))?;
+    let reading = |error: io::Error| format!("Error reading file: {}", error);
+
+    if options.hex {
+        let stdout = io::stdout().lock();
+        // A closed pipe (from `| head`, say) just means nobody wants the rest.
+        return match hexdump::dump(BufReader::new(file), stdout, 0) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result.map_err(reading),
+        };
+    }
+
+    // The number of the first line to read, and the range of lines to print.
+    let (first, start, end) = match options.lines() {
+        Lines::All => (1, 1, None),
+        Lines::Range(start, end) => (1, start, end),
+        Lines::Tail(count) => {
+            let offset = tail_offset(&mut file, count).map_err(reading)?;
+            let first = if options.numbered {
+                file.rewind().map_err(reading)?;
+                count_lines(&mut file, offset).map_err(reading)? + 1
+            } else {
+                1
+            };
+            file.seek(SeekFrom::Start(offset)).map_err(reading)?;
+            (first, first, None)
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    let highlight = stdout.is_terminal();
     let reader = BufReader::new(file);
-    for line in reader.lines() {
-        match line {
-            Ok(line) => println!("{}", line),
-            Err(error) => {
-                return Err(format!("Error reading file: {}", error));
+    for (number, line) in (first..).zip(reader.lines()) {
+        if end.is_some_and(|end| number > end) {
+            break;
+        } else if number < start {
+            line.map_err(reading)?;
+            continue;
+        }
+
+        let mut line = line.map_err(reading)?;
+        if let Some(matcher) = matcher {
+            if !matcher.keeps(&line) {
+                continue;
+            } else if highlight {
+                line = matcher.highlight(&line);
             }
         }
+
+        let written = if options.numbered {
+            writeln!(stdout, "{:>6}\t{}", number, line)
+        } else {
+            writeln!(stdout, "{}", line)
+        };
+        match written {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result.map_err(|error| format!("Error writing output: {}", error))?,
+        }
     }
     Ok(())
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        panic!("Usage: {} <filename>", args[0]);
+fn main() -> ExitCode {
+    let options = Options::parse();
+
+    let matcher = match &options.grep {
+        Some(pattern) => match Matcher::new(pattern, options.ignore_case, options.invert) {
+            Ok(matcher) => Some(matcher),
+            Err(error) => {
+                eprintln!("Invalid pattern: {}", error);
+                return ExitCode::from(2);
+            }
+        },
+        None => None,
+    };
+
+    if let Err(error) = print_file(&options, matcher.as_ref()) {
+        eprintln!("{}", error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use std::io::Cursor;
+
+    fn parse_args(text: &str) -> Result<Options, clap::Error> {
+        Options::try_parse_from(["file_reader"].into_iter().chain(text.split_whitespace()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        Options::command().debug_assert();
+
+        let options = parse_args("-n --tail 5 log.txt").unwrap();
+        assert_eq!(options.path, "log.txt");
+        assert!(options.numbered && !options.hex);
+        assert_eq!(options.lines(), Lines::Tail(5));
+        assert_eq!(options.grep, None);
+        assert_eq!(
+            parse_args("--head 3 a").unwrap().lines(),
+            Lines::Range(1, Some(3))
+        );
+        assert_eq!(parse_args("a").unwrap().lines(), Lines::All);
+        assert!(parse_args("--head 3 --tail 2 a").is_err());
+        assert!(parse_args("--head a").is_err());
+        assert!(parse_args("--tail").is_err());
+        assert!(parse_args("-x a").is_err());
+        assert!(parse_args("a b").is_err());
+        assert!(parse_args("-n").is_err());
+        assert!(parse_args("--hex a").unwrap().hex);
+        assert!(parse_args("--hex -n a").is_err());
+        assert!(parse_args("--hex --tail 2 a").is_err());
+
+        let options = parse_args("--grep err -i -v a").unwrap();
+        assert_eq!(options.grep.as_deref(), Some("err"));
+        assert!(options.ignore_case && options.invert);
+        assert!(parse_args("-i a").is_err());
+        assert!(parse_args("--hex --grep err a").is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("10:20"), Ok(Lines::Range(10, Some(20))));
+        assert_eq!(parse_range(":5"), Ok(Lines::Range(1, Some(5))));
+        assert_eq!(parse_range("7:"), Ok(Lines::Range(7, None)));
+        assert_eq!(parse_range("3:3"), Ok(Lines::Range(3, Some(3))));
+        assert!(parse_range("5").is_err());
+        assert!(parse_range("0:5").is_err());
+        assert!(parse_range("9:2").is_err());
+        assert!(parse_range("a:b").is_err());
+    }
+
+    #[test]
+    fn test_tail_offset() {
+        let text = b"one\ntwo\nthree\n";
+        let tail = |count| tail_offset(&mut Cursor::new(text), count).unwrap() as usize;
+        assert_eq!(&text[tail(0)..], b"");
+        assert_eq!(&text[tail(1)..], b"three\n");
+        assert_eq!(&text[tail(2)..], b"two\nthree\n");
+        assert_eq!(&text[tail(3)..], text);
+        assert_eq!(&text[tail(10)..], text);
+
+        let unterminated = b"one\ntwo";
+        assert_eq!(tail_offset(&mut Cursor::new(unterminated), 1).unwrap(), 4);
+        assert_eq!(tail_offset(&mut Cursor::new(b""), 3).unwrap(), 0);
     }
-    let path = &args[1];
 
-    if let Err(error) = print_file(path) {
-        panic!("{}", error);
+    #[test]
+    fn test_tail_offset_spans_blocks() {
+        let text: Vec<u8> = (0..50_000)
+            .flat_map(|n| format!("line {}\n", n).into_bytes())
+            .collect();
+        let offset = tail_offset(&mut Cursor::new(&text), 20_000).unwrap();
+        assert_eq!(
+            &text[offset as usize..offset as usize + 11],
+            b"line 30000\n"
+        );
+        assert_eq!(count_lines(Cursor::new(&text), offset).unwrap(), 30_000);
     }
 }
@@ -0,0 +1,110 @@
+//! Units of size and human-readable formatting of byte counts, shared by the
+//! `file_size` tool and anything else that wants to print sizes nicely.
+
+pub mod output;
+mod spec;
+pub mod usage;
+
+pub use spec::{SizeError, SizeSpec};
+
+/// A unit of size, such as "MB" (10^6 bytes) or "MiB" (2^20 bytes).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Unit {
+    pub name: &'static str,
+    pub long_name: &'static str,
+    pub bytes: u64,
+}
+
+/// The SI (decimal) units, from bytes up to petabytes.
+pub static SI_UNITS: [Unit; 6] = [
+    Unit { name: "B", long_name: "bytes", bytes: 1 },
+    Unit { name: "kB", long_name: "kilobytes", bytes: 1_000 },
+    Unit { name: "MB", long_name: "megabytes", bytes: 1_000_000 },
+    Unit { name: "GB", long_name: "gigabytes", bytes: 1_000_000_000 },
+    Unit { name: "TB", long_name: "terabytes", bytes: 1_000_000_000_000 },
+    Unit { name: "PB", long_name: "petabytes", bytes: 1_000_000_000_000_000 },
+];
+
+/// The IEC (binary) units, from bytes up to pebibytes.
+pub static IEC_UNITS: [Unit; 6] = [
+    Unit { name: "B", long_name: "bytes", bytes: 1 },
+    Unit { name: "KiB", long_name: "kibibytes", bytes: 1 << 10 },
+    Unit { name: "MiB", long_name: "mebibytes", bytes: 1 << 20 },
+    Unit { name: "GiB", long_name: "gibibytes", bytes: 1 << 30 },
+    Unit { name: "TiB", long_name: "tebibytes", bytes: 1 << 40 },
+    Unit { name: "PiB", long_name: "pebibytes", bytes: 1 << 50 },
+];
+
+/// The ways a size can be formatted: with SI (powers of 1000) or IEC (powers
+/// of 1024) units, using either the abbreviations or the long names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Si,
+    Iec,
+    SiLong,
+    IecLong,
+}
+
+impl Style {
+    /// Returns the units used by this style.
+    pub fn units(&self) -> &'static [Unit; 6] {
+        match self {
+            Style::Si | Style::SiLong => &SI_UNITS,
+            Style::Iec | Style::IecLong => &IEC_UNITS,
+        }
+    }
+
+    /// Checks whether this style uses the long unit names.
+    fn is_long(&self) -> bool {
+        matches!(self, Style::SiLong | Style::IecLong)
+    }
+}
+
+/// Formats a number of bytes in the largest unit that keeps the value at
+/// least 1, with two decimal places.
+///
+/// ```
+/// use file_size::{format_size, Style};
+///
+/// assert_eq!(format_size(1_509_949, Style::Iec), "1.44 MiB");
+/// assert_eq!(format_size(1_509_949, Style::Si), "1.51 MB");
+/// assert_eq!(format_size(1_509_949, Style::IecLong), "1.44 mebibytes");
+/// assert_eq!(format_size(512, Style::Si), "512 B");
+/// ```
+pub fn format_size(bytes: u64, style: Style) -> String {
+    format_size_with_precision(bytes, style, 2)
+}
+
+/// Formats a number of bytes like `format_size`, but with the given number
+/// of decimal places.  Whole bytes are always shown without decimals.
+///
+/// ```
+/// use file_size::{format_size_with_precision, Style};
+///
+/// assert_eq!(format_size_with_precision(1_610_612_736, Style::Iec, 0), "2 GiB");
+/// assert_eq!(format_size_with_precision(1_610_612_736, Style::Si, 4), "1.6106 GB");
+/// assert_eq!(format_size_with_precision(1, Style::SiLong, 3), "1 byte");
+/// ```
+pub fn format_size_with_precision(bytes: u64, style: Style, precision: usize) -> String {
+    let units = style.units();
+    let unit = units
+        .iter()
+        .rev()
+        .find(|unit| bytes >= unit.bytes)
+        .unwrap_or(&units[0]);
+
+    let value = if unit.bytes == 1 {
+        bytes.to_string()
+    } else {
+        format!("{:.*}", precision, bytes as f64 / unit.bytes as f64)
+    };
+
+    let name = if !style.is_long() {
+        unit.name
+    } else if bytes == 1 {
+        "byte"
+    } else {
+        unit.long_name
+    };
+    format!("{} {}", value, name)
+}
@@ -1,47 +1,162 @@
-use std::env;
-
-#[derive(Debug)]
-struct Size {
-    bytes: u64,
-    kilobytes: u64,
-    megabytes: u64,
-    gigabytes: u64,
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use file_size::output;
+use file_size::usage::{disk_usage, list_dir};
+use file_size::{format_size, SizeSpec, Style, Unit, IEC_UNITS, SI_UNITS};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Converts a size such as "1.5 GiB" between units or, given paths, reports
+/// their disk usage.
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// Follow symbolic links when measuring paths
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// Print the size in every unit at once, as a table
+    #[arg(long, conflicts_with_all = ["json", "raw_bytes"])]
+    table: bool,
+    /// Print the size as JSON, for scripts
+    #[arg(long, conflicts_with = "raw_bytes")]
+    json: bool,
+    /// Print nothing but the number of bytes, for scripts, or with paths, the
+    /// size of each in bytes
+    #[arg(long)]
+    raw_bytes: bool,
+    /// A size to convert, or the paths to measure
+    #[arg(
+        required = true,
+        allow_negative_numbers = true,
+        value_name = "SIZE | PATH"
+    )]
+    operands: Vec<String>,
+}
+
+/// Prints the size in each of the given units on a single line.
+fn print_breakdown(label: &str, bytes: u64, units: &[Unit]) {
+    let parts: Vec<String> = units[1..]
+        .iter()
+        .map(|unit| format!("{:.3} {}", bytes as f64 / unit.bytes as f64, unit.name))
+        .collect();
+    println!("{:<6} {}", label, parts.join(" | "));
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        println!("Usage: {} <size>", args[0]);
-        return;
+/// Returns a size as it is listed beside a path: in the largest IEC unit, or
+/// in bytes if `raw_bytes` is set.
+fn entry_size(bytes: u64, raw_bytes: bool) -> String {
+    if raw_bytes {
+        bytes.to_string()
+    } else {
+        format_size(bytes, Style::Iec)
+    }
+}
+
+/// Prints a size and a path on one line, in the manner of `du`.
+fn print_entry(bytes: u64, path: &Path, raw_bytes: bool) {
+    println!("{:>12}  {}", entry_size(bytes, raw_bytes), path.display());
+}
+
+/// Prints the size of each path.  For a directory, each of its entries is
+/// listed first, followed by the directory's total.  Returns the grand total
+/// and whether anything could not be read.
+fn print_usage(paths: &[&Path], follow_symlinks: bool, raw_bytes: bool) -> (u64, bool) {
+    let mut total = 0;
+    let mut failed = false;
+    for &path in paths {
+        let entries = if path.is_dir() {
+            list_dir(path).map_err(|err| {
+                eprintln!("Error reading {}: {}", path.display(), err);
+                failed = true;
+            })
+        } else {
+            Err(())
+        };
+
+        let bytes = match entries {
+            Ok(entries) => entries
+                .iter()
+                .map(|entry| {
+                    let bytes = measure(entry, follow_symlinks, &mut failed);
+                    print_entry(bytes, entry, raw_bytes);
+                    bytes
+                })
+                .sum(),
+            Err(()) => measure(path, follow_symlinks, &mut failed),
+        };
+        print_entry(bytes, path, raw_bytes);
+        total += bytes;
     }
+    (total, failed)
+}
 
-    let mut parts = args[1].split(" ").collect::<Vec<&str>>();
-    if parts.len() < 2 {
-        parts.push("b");
+/// Measures a single path, reporting any entries that could not be read and
+/// setting `failed` if there were any.
+fn measure(path: &Path, follow_symlinks: bool, failed: &mut bool) -> u64 {
+    let usage = disk_usage(path, follow_symlinks);
+    for (path, err) in &usage.errors {
+        eprintln!("Error reading {}: {}", path.display(), err);
     }
-    if parts.len() != 2 {
-        println!("Invalid size format. Please use the format: <number> <unit>");
-        return;
+    *failed |= !usage.errors.is_empty();
+    usage.bytes
+}
+
+/// Exits with status 0 on success, 1 if some paths could not be read, and 2
+/// for a usage error or an invalid size.
+fn main() -> ExitCode {
+    let options = Options::parse();
+    let follow_symlinks = options.follow_symlinks;
+    let operands = &options.operands;
+
+    let paths: Vec<&Path> = operands.iter().map(Path::new).collect();
+    if !paths.is_empty() && paths.iter().all(|path| path.symlink_metadata().is_ok()) {
+        if options.table || options.json {
+            Options::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--table and --json are only for a size, not paths",
+                )
+                .exit();
+        }
+        let (total, failed) = print_usage(&paths, follow_symlinks, options.raw_bytes);
+        if paths.len() > 1 {
+            println!("{:>12}  total", entry_size(total, options.raw_bytes));
+        }
+        return if failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        };
     }
 
-    let size = parts[0].parse::<u64>().unwrap();
-    let units = parts[1].to_lowercase();
-    let size = match units.as_str() {
-        "b" => size,
-        "kb" => size * 1024,
-        "mb" => size * 1024 * 1024,
-        "gb" => size * 1024 * 1024 * 1024,
-        _ => {
-            println!("Invalid units. Please use b, kb, mb, or gb.");
-            return;
+    let input = operands.join(" ");
+    let bytes = match input.parse::<SizeSpec>() {
+        Ok(spec) => spec.bytes(),
+        Err(err) => {
+            eprintln!("Invalid size: {}", err);
+            return ExitCode::from(2);
         }
     };
 
-    let size = Size{
-        bytes: size,
-        kilobytes: size / 1024,
-        megabytes: size / 1024 / 1024,
-        gigabytes: size / 1024 / 1024 / 1024,
-    }; 
-    println!("{:?}", size);
-}
\ No newline at end of file
+    if options.table {
+        print!("{}", output::table(bytes));
+        return ExitCode::SUCCESS;
+    } else if options.json {
+        println!("{}", output::json(bytes));
+        return ExitCode::SUCCESS;
+    } else if options.raw_bytes {
+        println!("{}", bytes);
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "{:<6} {} ({})",
+        "Size:",
+        format_size(bytes, Style::Iec),
+        format_size(bytes, Style::Si)
+    );
+    println!("{:<6} {}", "Bytes:", bytes);
+    print_breakdown("SI:", bytes, &SI_UNITS);
+    print_breakdown("IEC:", bytes, &IEC_UNITS);
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,103 @@
+//! Other ways of printing a converted size: as a table of its value in every
+//! unit at once, or as JSON for scripts to read.
+
+use crate::{format_size, Style, Unit, IEC_UNITS, SI_UNITS};
+
+/// The number of decimal places in the table.
+const PRECISION: usize = 3;
+
+/// Returns the value of a size in the given unit, as it appears in the table.
+/// Whole bytes are shown without decimals, but padded so that they line up
+/// with the values that have them.
+fn table_value(bytes: u64, unit: &Unit) -> String {
+    if unit.bytes == 1 {
+        format!("{}{:width$}", bytes, "", width = PRECISION + 1)
+    } else {
+        format!("{:.*}", PRECISION, bytes as f64 / unit.bytes as f64)
+    }
+}
+
+/// Returns a table of the size in each of the SI units beside each of the IEC
+/// units, with the decimal points lined up.
+///
+/// ```
+/// use file_size::output::table;
+///
+/// let table = table(1_572_864);
+/// assert!(table.starts_with("SI               IEC\n1572864     B    1572864     B\n"));
+/// assert!(table.contains("\n      1.573 MB         1.500 MiB\n"));
+/// ```
+pub fn table(bytes: u64) -> String {
+    let rows: Vec<(String, String)> = SI_UNITS
+        .iter()
+        .zip(IEC_UNITS.iter())
+        .map(|(si, iec)| (table_value(bytes, si), table_value(bytes, iec)))
+        .collect();
+    let si_width = rows.iter().map(|(si, _)| si.len()).max().unwrap_or(0);
+    let iec_width = rows.iter().map(|(_, iec)| iec.len()).max().unwrap_or(0);
+
+    let mut text = format!("{:<width$}IEC\n", "SI", width = si_width + 6);
+    for (i, (si, iec)) in rows.iter().enumerate() {
+        text.push_str(&format!(
+            "{:>si_width$} {:<3}  {:>iec_width$} {}\n",
+            si, SI_UNITS[i].name, iec, IEC_UNITS[i].name
+        ));
+    }
+    text
+}
+
+/// Returns the value of a size in each of the given units, as the members of
+/// a JSON object.
+fn json_units(bytes: u64, units: &[Unit]) -> String {
+    let members: Vec<String> = units
+        .iter()
+        .map(|unit| format!("\"{}\": {}", unit.name, bytes as f64 / unit.bytes as f64))
+        .collect();
+    format!("{{{}}}", members.join(", "))
+}
+
+/// Returns the size as a JSON object on one line: the number of bytes, the
+/// value in each unit, and the size as `format_size` gives it.
+///
+/// ```
+/// use file_size::output::json;
+///
+/// let json = json(1536);
+/// assert!(json.starts_with("{\"bytes\": 1536, \"si\": {\"B\": 1536, \"kB\": 1.536, "));
+/// assert!(json.contains("\"iec\": {\"B\": 1536, \"KiB\": 1.5, \"MiB\": 0.00146484375, "));
+/// assert!(json.ends_with("\"human\": {\"si\": \"1.54 kB\", \"iec\": \"1.50 KiB\"}}"));
+/// ```
+pub fn json(bytes: u64) -> String {
+    format!(
+        "{{\"bytes\": {}, \"si\": {}, \"iec\": {}, \"human\": {{\"si\": \"{}\", \"iec\": \"{}\"}}}}",
+        bytes,
+        json_units(bytes, &SI_UNITS),
+        json_units(bytes, &IEC_UNITS),
+        format_size(bytes, Style::Si),
+        format_size(bytes, Style::Iec)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let table = table(1_610_612_736);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "SI                  IEC");
+        assert_eq!(lines[1], "1610612736     B    1610612736     B");
+        assert_eq!(lines[2], "   1610612.736 kB      1572864.000 KiB");
+        assert_eq!(lines[4], "         1.611 GB            1.500 GiB");
+        assert_eq!(lines[6], "         0.000 PB            0.000 PiB");
+    }
+
+    #[test]
+    fn test_json() {
+        assert!(json(0).starts_with("{\"bytes\": 0, \"si\": {\"B\": 0, \"kB\": 0, "));
+        assert!(json(1 << 30).contains("\"GiB\": 1, "));
+        assert!(json(1 << 30).ends_with("\"human\": {\"si\": \"1.07 GB\", \"iec\": \"1.00 GiB\"}}"));
+    }
+}
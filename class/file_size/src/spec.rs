@@ -0,0 +1,152 @@
+//! Parsing of sizes written by people, such as "1.5GiB", "250 MB", or "3tb".
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{Unit, IEC_UNITS, SI_UNITS};
+
+/// A reminder of the accepted formats, included in every parse error.
+const ACCEPTED: &str = "expected a number and an optional unit, such as 1024, 1.5GiB, 250 MB, or 3tb \
+     (units: B, kB, MB, GB, TB, PB, KiB, MiB, GiB, TiB, PiB; case is ignored)";
+
+/// A size given as a (possibly fractional) number and a unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeSpec {
+    pub value: f64,
+    pub unit: &'static Unit,
+}
+
+impl SizeSpec {
+    /// Returns the size in bytes, rounded to the nearest whole byte.
+    pub fn bytes(&self) -> u64 {
+        (self.value * self.unit.bytes as f64).round() as u64
+    }
+}
+
+/// The ways that parsing a SizeSpec can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SizeError {
+    /// Nothing was given.
+    Empty,
+    /// The size was negative.
+    Negative,
+    /// The text did not start with a number.
+    MissingNumber(String),
+    /// The number was malformed, such as "1.2.3".
+    InvalidNumber(String),
+    /// The unit was not recognized.
+    UnknownUnit(String),
+    /// The size does not fit in 64 bits.
+    TooLarge,
+}
+
+impl Display for SizeError {
+    /// Describes what was wrong, followed by the accepted formats.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SizeError::Empty => write!(f, "no size given")?,
+            SizeError::Negative => write!(f, "sizes cannot be negative")?,
+            SizeError::MissingNumber(text) => write!(f, "'{}' does not start with a number", text)?,
+            SizeError::InvalidNumber(text) => write!(f, "'{}' is not a valid number", text)?,
+            SizeError::UnknownUnit(text) => write!(f, "'{}' is not a known unit", text)?,
+            SizeError::TooLarge => write!(f, "size is too large (the limit is 16 EiB)")?,
+        }
+        write!(f, "; {}", ACCEPTED)
+    }
+}
+
+impl Error for SizeError {}
+
+impl FromStr for SizeSpec {
+    type Err = SizeError;
+
+    /// Parses a number followed by an optional unit, with optional whitespace
+    /// between them.  Units are matched without regard to case, so "kb" is the
+    /// SI kilobyte (1000 bytes) and "kib" the IEC kibibyte (1024 bytes).  A
+    /// bare letter such as "k" or "G" is taken as the SI unit.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(SizeError::Empty);
+        } else if text.starts_with('-') {
+            return Err(SizeError::Negative);
+        }
+
+        let split = text
+            .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+            .unwrap_or(text.len());
+        let (number, unit) = (&text[..split], text[split..].trim());
+        if number.is_empty() {
+            return Err(SizeError::MissingNumber(text.to_string()));
+        }
+
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| SizeError::InvalidNumber(number.to_string()))?;
+        let unit = find_unit(unit).ok_or_else(|| SizeError::UnknownUnit(unit.to_string()))?;
+
+        if value * unit.bytes as f64 >= u64::MAX as f64 {
+            return Err(SizeError::TooLarge);
+        }
+        Ok(SizeSpec { value, unit })
+    }
+}
+
+/// Looks up a unit by name, ignoring case.  An empty unit means bytes.
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.to_lowercase();
+    let name = match name.as_str() {
+        "" | "b" | "byte" | "bytes" => "b".to_string(),
+        "k" | "m" | "g" | "t" | "p" => format!("{}b", name),
+        _ => name,
+    };
+    SI_UNITS
+        .iter()
+        .chain(IEC_UNITS.iter())
+        .find(|unit| unit.name.to_lowercase() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(text: &str) -> u64 {
+        text.parse::<SizeSpec>().unwrap().bytes()
+    }
+
+    #[test]
+    fn test_valid_sizes() {
+        assert_eq!(bytes("1024"), 1024);
+        assert_eq!(bytes("1.5GiB"), 1_610_612_736);
+        assert_eq!(bytes("250 MB"), 250_000_000);
+        assert_eq!(bytes("3tb"), 3_000_000_000_000);
+        assert_eq!(bytes("2 KIB"), 2048);
+        assert_eq!(bytes(" 7 bytes "), 7);
+        assert_eq!(bytes("1k"), 1000);
+        assert_eq!(bytes("1PiB"), 1 << 50);
+        assert_eq!(bytes(".5 kB"), 500);
+    }
+
+    #[test]
+    fn test_malformed_sizes() {
+        let parse = |text: &str| text.parse::<SizeSpec>().unwrap_err();
+        assert_eq!(parse(""), SizeError::Empty);
+        assert_eq!(parse("   "), SizeError::Empty);
+        assert_eq!(parse("-5 MB"), SizeError::Negative);
+        assert_eq!(parse("abc mb"), SizeError::MissingNumber("abc mb".to_string()));
+        assert_eq!(parse("mb"), SizeError::MissingNumber("mb".to_string()));
+        assert_eq!(parse("1.2.3 kb"), SizeError::InvalidNumber("1.2.3".to_string()));
+        assert_eq!(parse("."), SizeError::InvalidNumber(".".to_string()));
+        assert_eq!(parse("12 xb"), SizeError::UnknownUnit("xb".to_string()));
+        assert_eq!(parse("12 MB extra"), SizeError::UnknownUnit("MB extra".to_string()));
+        assert_eq!(parse("20000 PB"), SizeError::TooLarge);
+    }
+
+    #[test]
+    fn test_errors_explain_accepted_formats() {
+        let err = "abc mb".parse::<SizeSpec>().unwrap_err().to_string();
+        assert!(err.starts_with("'abc mb' does not start with a number"));
+        assert!(err.contains("1.5GiB"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Measuring the space taken up by files and directory trees, in the manner
+//! of a simple `du`.  Directories are walked in parallel.
+
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The result of measuring a path: the total size of every file found, plus
+/// any entries that could not be read (which are left out of the total).
+#[derive(Debug, Default)]
+pub struct Usage {
+    pub bytes: u64,
+    pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl Usage {
+    /// Folds another Usage into this one.
+    fn merge(mut self, other: Usage) -> Usage {
+        self.bytes += other.bytes;
+        self.errors.extend(other.errors);
+        self
+    }
+
+    /// Returns a Usage recording a single error.
+    fn error(path: &Path, error: io::Error) -> Usage {
+        Usage {
+            bytes: 0,
+            errors: vec![(path.to_path_buf(), error)],
+        }
+    }
+}
+
+/// Returns the size of a file, or the total size of all of the files beneath
+/// a directory.  Symbolic links are counted as links unless `follow_symlinks`
+/// is set, in which case they are followed (visiting each directory once, so
+/// that link cycles are harmless).
+pub fn disk_usage(path: &Path, follow_symlinks: bool) -> Usage {
+    let walker = Walker {
+        follow_symlinks,
+        visited: Mutex::new(HashSet::new()),
+    };
+    walker.walk(path)
+}
+
+/// Lists the entries of a directory, sorted by name.
+pub fn list_dir(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// Holds the options and shared state for a single traversal.
+struct Walker {
+    follow_symlinks: bool,
+    visited: Mutex<HashSet<PathBuf>>,
+}
+
+impl Walker {
+    /// Measures a single path, recursing into directories.
+    fn walk(&self, path: &Path) -> Usage {
+        let metadata = if self.follow_symlinks {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(err) => return Usage::error(path, err),
+        };
+
+        if !metadata.is_dir() {
+            return Usage {
+                bytes: metadata.len(),
+                errors: vec![],
+            };
+        }
+
+        if self.follow_symlinks {
+            let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            if !self.visited.lock().unwrap().insert(canonical) {
+                return Usage::default();
+            }
+        }
+
+        match list_dir(path) {
+            Ok(entries) => entries
+                .par_iter()
+                .map(|entry| self.walk(entry))
+                .reduce(Usage::default, Usage::merge),
+            Err(err) => Usage::error(path, err),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! An interactive editor for a single glyph.  The arrow keys move a cursor
+//! over the 8×8 grid, space toggles the pixel under it, and the glyph is shown
+//! as a u64 literal and as row bytes as it changes.  Saving writes the glyph
+//! into a font file, which is kept in the format it was in or else written in
+//! the hex text format, so it can be loaded with --file.
+
+use console::{style, Key, Term};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use character::font::{Font, Format, Glyph};
+
+/// The editor's state: the glyph being edited, its character code, and the
+/// cursor position.
+struct Editor {
+    glyph: Glyph,
+    code: u8,
+    row: usize,
+    col: usize,
+}
+
+impl Editor {
+    /// Draws the grid, the literals, the key help, and a message line.
+    fn draw(&self, term: &Term, message: &str) -> io::Result<()> {
+        term.clear_screen()?;
+        term.write_line(&format!("Glyph editor: character {:#04x}", self.code))?;
+        term.write_line(&format!("┌{}┐", "─".repeat(16)))?;
+        for row in 0..8 {
+            let mut line = String::from("│");
+            for col in 0..8 {
+                let cell = if self.glyph.pixel(row, col) {
+                    "██"
+                } else {
+                    "··"
+                };
+                if (row, col) == (self.row, self.col) {
+                    line.push_str(&style(cell).green().reverse().to_string());
+                } else {
+                    line.push_str(cell);
+                }
+            }
+            line.push('│');
+            term.write_line(&line)?;
+        }
+        term.write_line(&format!("└{}┘", "─".repeat(16)))?;
+
+        let bytes: Vec<String> = self
+            .glyph
+            .rows()
+            .iter()
+            .map(|row| format!("{:#04x}", row))
+            .collect();
+        term.write_line(&format!("u64:   {:#018x}", self.glyph.0))?;
+        term.write_line(&format!("bytes: [{}]", bytes.join(", ")))?;
+        term.write_line("")?;
+        term.write_line(
+            &style("arrows move   space toggle   c clear   i invert   s save   q quit")
+                .dim()
+                .to_string(),
+        )?;
+        term.write_line(&style(message).yellow().to_string())
+    }
+}
+
+/// Writes the glyph into the font file at `path`, keeping any other glyphs
+/// already there and the format that they are in, so that a ROM dump stays
+/// one.  A file that isn't a font is left alone.
+fn save(path: &Path, code: u8, glyph: Glyph) -> Result<(), String> {
+    let (mut font, format) = if path.exists() {
+        Font::load_with_format(path).map_err(|err| err.to_string())?
+    } else {
+        (Font::empty(), Format::Hex)
+    };
+    font.set_glyph(code, glyph);
+    fs::write(path, font.to_bytes(format)).map_err(|err| err.to_string())
+}
+
+/// Runs the editor on the given glyph until the player quits, saving to
+/// `path` on request.  Returns the glyph as last edited.
+pub fn edit(glyph: Glyph, code: u8, path: &Path) -> io::Result<Glyph> {
+    let term = Term::stdout();
+    let mut editor = Editor {
+        glyph,
+        code,
+        row: 0,
+        col: 0,
+    };
+    let mut message = String::new();
+
+    term.hide_cursor()?;
+    loop {
+        editor.draw(&term, &message)?;
+        message.clear();
+        match term.read_key()? {
+            Key::ArrowUp | Key::Char('k') => editor.row = (editor.row + 7) % 8,
+            Key::ArrowDown | Key::Char('j') => editor.row = (editor.row + 1) % 8,
+            Key::ArrowLeft | Key::Char('h') => editor.col = (editor.col + 7) % 8,
+            Key::ArrowRight | Key::Char('l') => editor.col = (editor.col + 1) % 8,
+            Key::Char(' ') | Key::Enter => {
+                let on = editor.glyph.pixel(editor.row, editor.col);
+                editor.glyph.set(editor.row, editor.col, !on);
+            }
+            Key::Char('c') => editor.glyph = Glyph::default(),
+            Key::Char('i') => editor.glyph = Glyph(!editor.glyph.0),
+            Key::Char('s') => {
+                message = match save(path, code, editor.glyph) {
+                    Ok(()) => format!("Saved to {}.", path.display()),
+                    Err(err) => format!("Could not save to {}: {}", path.display(), err),
+                };
+            }
+            Key::Char('q') | Key::Escape => break,
+            _ => {}
+        }
+    }
+    term.show_cursor()?;
+    Ok(editor.glyph)
+}
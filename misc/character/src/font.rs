@@ -0,0 +1,286 @@
+//! Glyphs and fonts.
+//!
+//! A font can be read from either of two formats:
+//!
+//! * A raw ROM dump of exactly 2048 bytes: 256 glyphs of eight bytes each,
+//!   one byte per row from the top, with the leftmost pixel in the high bit.
+//! * A hex text file with one glyph per line, giving the character code and
+//!   the glyph as a u64 in the form that this program prints, separated by a
+//!   colon.  Blank lines and anything after a `#` are ignored:
+//!
+//! ```text
+//! # A smiling face
+//! 01: 7e81a581bd99817e
+//! ```
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The number of glyphs in a font.
+pub const GLYPHS: usize = 256;
+
+/// The size of a raw ROM dump, in bytes.
+pub const ROM_SIZE: usize = GLYPHS * 8;
+
+/// The formats that a font file can be in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A raw ROM dump.
+    Rom,
+    /// The hex text format.
+    Hex,
+}
+
+/// An 8×8 glyph, packed into a u64.  The top row is in the most significant
+/// byte, and within each row the leftmost pixel is the least significant bit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Glyph(pub u64);
+
+impl Glyph {
+    /// Builds a glyph from eight row bytes, top row first, with the leftmost
+    /// pixel in the high bit of each byte (the usual order for ROM dumps).
+    pub fn from_rows(rows: [u8; 8]) -> Self {
+        Glyph(
+            rows.iter()
+                .fold(0, |data, &row| (data << 8) | row.reverse_bits() as u64),
+        )
+    }
+
+    /// Returns the eight row bytes, in the same order as `from_rows`.
+    pub fn rows(&self) -> [u8; 8] {
+        let mut rows = self.0.to_be_bytes();
+        for row in rows.iter_mut() {
+            *row = row.reverse_bits();
+        }
+        rows
+    }
+
+    /// Returns whether the pixel at the given row and column (both counted
+    /// from 0 at the top left) is set.
+    pub fn pixel(&self, row: usize, col: usize) -> bool {
+        self.0 & Self::mask(row, col) != 0
+    }
+
+    /// Sets or clears the pixel at the given row and column.
+    pub fn set(&mut self, row: usize, col: usize, on: bool) {
+        if on {
+            self.0 |= Self::mask(row, col);
+        } else {
+            self.0 &= !Self::mask(row, col);
+        }
+    }
+
+    /// Returns the bit that holds the pixel at the given row and column.
+    fn mask(row: usize, col: usize) -> u64 {
+        1 << ((7 - row) * 8 + col)
+    }
+}
+
+/// The ways that loading a font can fail.
+#[derive(Debug)]
+pub enum FontError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file was neither a ROM dump nor valid hex text.
+    Parse { line: usize, msg: String },
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "{}", err),
+            FontError::Parse { line, msg } => write!(f, "line {}: {}", line, msg),
+        }
+    }
+}
+
+impl Error for FontError {}
+
+impl From<io::Error> for FontError {
+    fn from(err: io::Error) -> Self {
+        FontError::Io(err)
+    }
+}
+
+/// A font of 256 glyphs, indexed by character code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Font {
+    glyphs: Vec<Glyph>,
+}
+
+impl Default for Font {
+    /// Returns a font that is blank except for the smiling face (character 1)
+    /// that this program has always drawn.
+    fn default() -> Self {
+        let mut font = Font::empty();
+        font.set_glyph(1, Glyph(0x7e81a581bd99817e));
+        font
+    }
+}
+
+impl Font {
+    /// Returns a font in which every glyph is blank.
+    pub fn empty() -> Self {
+        Font {
+            glyphs: vec![Glyph::default(); GLYPHS],
+        }
+    }
+
+    /// Loads a font from a file.  A file of exactly ROM_SIZE bytes is taken
+    /// to be a ROM dump unless it happens to be valid hex text.
+    pub fn load(path: &Path) -> Result<Self, FontError> {
+        Self::load_with_format(path).map(|(font, _)| font)
+    }
+
+    /// Loads a font from a file as `load` does, along with the format that
+    /// it was in.
+    pub fn load_with_format(path: &Path) -> Result<(Self, Format), FontError> {
+        let bytes = fs::read(path)?;
+        let text = std::str::from_utf8(&bytes).ok();
+        match text.map(Self::parse_hex) {
+            Some(Ok(font)) => Ok((font, Format::Hex)),
+            _ if bytes.len() == ROM_SIZE => Ok((Self::from_rom(&bytes)?, Format::Rom)),
+            Some(Err(err)) => Err(err),
+            None => Ok((Self::from_rom(&bytes)?, Format::Rom)),
+        }
+    }
+
+    /// Builds a font from a raw ROM dump.
+    pub fn from_rom(bytes: &[u8]) -> Result<Self, FontError> {
+        if bytes.len() != ROM_SIZE {
+            return Err(FontError::Parse {
+                line: 0,
+                msg: format!("a ROM dump must be {} bytes, not {}", ROM_SIZE, bytes.len()),
+            });
+        }
+        let glyphs = bytes
+            .chunks_exact(8)
+            .map(|rows| Glyph::from_rows(rows.try_into().unwrap()))
+            .collect();
+        Ok(Font { glyphs })
+    }
+
+    /// Parses a font in the hex text format.  Any characters not given are
+    /// left blank.
+    pub fn parse_hex(text: &str) -> Result<Self, FontError> {
+        let mut font = Font::empty();
+        for (index, line) in text.lines().enumerate() {
+            let error = |msg: &str| FontError::Parse {
+                line: index + 1,
+                msg: msg.to_string(),
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (code, data) = line
+                .split_once(':')
+                .ok_or_else(|| error("expected a character code, a colon, and a glyph"))?;
+            let code = u8::from_str_radix(code.trim(), 16)
+                .map_err(|_| error("the character code must be hex, from 00 to ff"))?;
+            let data = data.trim();
+            let data = data.strip_prefix("0x").unwrap_or(data);
+            if data.len() != 16 {
+                return Err(error("a glyph must be 16 hex digits"));
+            }
+            let data = u64::from_str_radix(data, 16)
+                .map_err(|_| error("a glyph must be 16 hex digits"))?;
+            font.set_glyph(code, Glyph(data));
+        }
+        Ok(font)
+    }
+
+    /// Returns the glyph for the given character code.
+    pub fn glyph(&self, code: u8) -> Glyph {
+        self.glyphs[code as usize]
+    }
+
+    /// Replaces the glyph for the given character code.
+    pub fn set_glyph(&mut self, code: u8, glyph: Glyph) {
+        self.glyphs[code as usize] = glyph;
+    }
+
+    /// Writes the font as a raw ROM dump.
+    pub fn to_rom(&self) -> Vec<u8> {
+        self.glyphs.iter().flat_map(|glyph| glyph.rows()).collect()
+    }
+
+    /// Writes the font in the given format.
+    pub fn to_bytes(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Rom => self.to_rom(),
+            Format::Hex => self.to_hex().into_bytes(),
+        }
+    }
+
+    /// Writes the font in the hex text format, skipping blank glyphs.
+    pub fn to_hex(&self) -> String {
+        self.glyphs
+            .iter()
+            .enumerate()
+            .filter(|(_, glyph)| glyph.0 != 0)
+            .map(|(code, glyph)| format!("{:02x}: {:016x}\n", code, glyph.0))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMILEY_ROWS: [u8; 8] = [0x7e, 0x81, 0xa5, 0x81, 0xbd, 0x99, 0x81, 0x7e];
+
+    #[test]
+    fn test_glyph_rows() {
+        let glyph = Glyph::from_rows(SMILEY_ROWS);
+        assert_eq!(glyph, Glyph(0x7e81a581bd99817e));
+        assert_eq!(glyph.rows(), SMILEY_ROWS);
+
+        // An "L" shape shows which way round the bits go.
+        let glyph = Glyph::from_rows([0x80, 0x80, 0, 0, 0, 0, 0, 0xff]);
+        assert!(glyph.pixel(0, 0));
+        assert!(!glyph.pixel(0, 7));
+        assert!(glyph.pixel(7, 7));
+        assert!(!glyph.pixel(6, 7));
+    }
+
+    #[test]
+    fn test_set() {
+        let mut glyph = Glyph::default();
+        glyph.set(2, 3, true);
+        assert!(glyph.pixel(2, 3));
+        assert_eq!(glyph.rows()[2], 0x10);
+        glyph.set(2, 3, false);
+        assert_eq!(glyph, Glyph::default());
+    }
+
+    #[test]
+    fn test_from_rom() {
+        let mut rom = vec![0; ROM_SIZE];
+        rom[8..16].copy_from_slice(&SMILEY_ROWS);
+        let font = Font::from_rom(&rom).unwrap();
+        assert_eq!(font, Font::default());
+        assert_eq!(font.to_rom(), rom);
+        assert!(Font::from_rom(&rom[1..]).is_err());
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        let font = Font::parse_hex("# comment\n\n01: 7e81a581bd99817e  # smiley\n").unwrap();
+        assert_eq!(font, Font::default());
+        assert_eq!(Font::parse_hex(&font.to_hex()).unwrap(), font);
+
+        let line = |text: &str| match Font::parse_hex(text) {
+            Err(FontError::Parse { line, .. }) => line,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(line("\n7e81a581bd99817e"), 2);
+        assert_eq!(line("100: 7e81a581bd99817e"), 1);
+        assert_eq!(line("41: 7e81"), 1);
+        assert_eq!(line("41: 7e81a581bd99817g"), 1);
+    }
+}
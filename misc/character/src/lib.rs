@@ -0,0 +1,5 @@
+//! Loading and drawing 8×8 bitmap fonts, such as those in the character ROMs
+//! of old PCs and home computers.
+
+pub mod font;
+pub mod render;
@@ -1,13 +1,125 @@
-fn main() {
-    let data: u64 = 0x7e81a581bd99817e;
-    for r in (0..8).rev() {
-        for c in 0..8 {
-            if data & (1 << (r * 8 + c)) != 0 {
-                print!("#");
-            } else {
-                print!(" ");
+mod editor;
+
+use character::font::Font;
+use character::render::{glyph_lines, grid_lines, Style};
+use clap::Parser;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Draws the glyphs of an 8×8 character font, or edits one of them.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Options {
+    /// The font to load, either a 2048-byte ROM dump or a hex text file
+    #[arg(long, value_name = "FONT")]
+    file: Option<PathBuf>,
+    /// The character to draw or edit, either itself or its code in decimal
+    /// or hex
+    #[arg(long = "char", value_name = "C", default_value = "1", value_parser = parse_char)]
+    code: u8,
+    /// Draw every glyph in the font as a grid
+    #[arg(long, conflicts_with = "edit")]
+    all: bool,
+    /// Edit the glyph interactively
+    #[arg(long)]
+    edit: bool,
+    /// The font file that the editor saves to, which is written in the hex
+    /// text format unless it is already a ROM dump
+    #[arg(long, value_name = "FONT", default_value = "glyphs.hex")]
+    out: PathBuf,
+    /// How to draw each pixel: hash, block, half, or braille
+    #[arg(long, default_value = "hash", value_parser = parse_style)]
+    style: Style,
+    /// How many times to enlarge each pixel
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=8))]
+    scale: u8,
+}
+
+fn parse_style(text: &str) -> Result<Style, String> {
+    Style::from_name(text).ok_or_else(|| "expected hash, block, half, or braille".to_string())
+}
+
+// Parses the character to draw, which is either a single character (such as
+// "A") or a code in decimal or hex (such as "65" or "0x41").
+fn parse_char(text: &str) -> Result<u8, String> {
+    let mut chars = text.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) if !ch.is_ascii_digit() => u8::try_from(ch as u32).ok(),
+        _ => match text.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16).ok(),
+            None => text.parse().ok(),
+        },
+    };
+    code.ok_or_else(|| "expected a character or a code from 0 to 255".to_string())
+}
+
+fn main() -> ExitCode {
+    let options = Options::parse();
+
+    let font = match &options.file {
+        Some(path) => match Font::load(path) {
+            Ok(font) => font,
+            Err(error) => {
+                eprintln!("Error loading {}: {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Font::default(),
+    };
+
+    if options.edit {
+        return match editor::edit(font.glyph(options.code), options.code, &options.out) {
+            Ok(glyph) => {
+                println!("{:#018x}", glyph.0);
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                eprintln!("Terminal error: {}", error);
+                ExitCode::FAILURE
             }
-        }
-        println!();
+        };
+    }
+
+    let scale = usize::from(options.scale);
+    let lines = if options.all {
+        grid_lines(&font, options.style, scale)
+    } else {
+        glyph_lines(font.glyph(options.code), options.style, scale)
+    };
+    for line in lines {
+        println!("{}", line);
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_options() {
+        Options::command().debug_assert();
+
+        let options =
+            Options::try_parse_from(["character", "--char", "A", "--scale", "2"]).unwrap();
+        assert_eq!(options.code, 65);
+        assert_eq!(options.scale, 2);
+        assert_eq!(options.style, Style::Hash);
+        assert!(Options::try_parse_from(["character", "--all", "--edit"]).is_err());
+        assert!(Options::try_parse_from(["character", "--scale", "9"]).is_err());
+        assert!(Options::try_parse_from(["character", "--style", "dots"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_char() {
+        assert_eq!(parse_char("A"), Ok(65));
+        assert_eq!(parse_char("65"), Ok(65));
+        assert_eq!(parse_char("0x41"), Ok(65));
+        assert_eq!(parse_char("7"), Ok(7));
+        assert_eq!(parse_char("é"), Ok(0xe9));
+        assert!(parse_char("256").is_err());
+        assert!(parse_char("☺").is_err());
+        assert!(parse_char("AB").is_err());
     }
 }
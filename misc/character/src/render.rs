@@ -0,0 +1,190 @@
+//! Drawing glyphs as text, in one of several styles and at any integer scale.
+
+use crate::font::{Font, Glyph};
+
+/// The ways that a glyph can be drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    /// One `#` per pixel.
+    #[default]
+    Hash,
+    /// Two full blocks (`██`) per pixel, which look roughly square.
+    Block,
+    /// Two rows of pixels per line, using the half blocks `▀` and `▄`.
+    HalfBlock,
+    /// Braille characters, each holding two columns and four rows of pixels.
+    Braille,
+}
+
+impl Style {
+    /// Looks up a style by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hash" => Some(Style::Hash),
+            "block" => Some(Style::Block),
+            "half" => Some(Style::HalfBlock),
+            "braille" => Some(Style::Braille),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the glyph's pixels, with each one repeated `scale` times in both
+/// directions.
+fn scaled_pixels(glyph: Glyph, scale: usize) -> Vec<Vec<bool>> {
+    (0..8 * scale)
+        .map(|row| {
+            (0..8 * scale)
+                .map(|col| glyph.pixel(row / scale, col / scale))
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the lines of text that draw a glyph in the given style, scaled up
+/// by an integer factor (which must be at least 1).
+pub fn glyph_lines(glyph: Glyph, style: Style, scale: usize) -> Vec<String> {
+    let pixels = scaled_pixels(glyph, scale);
+    let size = pixels.len();
+    let at = |row: usize, col: usize| row < size && col < size && pixels[row][col];
+
+    match style {
+        Style::Hash | Style::Block => {
+            let (on, off) = match style {
+                Style::Hash => ("#", " "),
+                _ => ("██", "  "),
+            };
+            pixels
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&pixel| if pixel { on } else { off })
+                        .collect()
+                })
+                .collect()
+        }
+        Style::HalfBlock => (0..size)
+            .step_by(2)
+            .map(|row| {
+                (0..size)
+                    .map(|col| match (at(row, col), at(row + 1, col)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    })
+                    .collect()
+            })
+            .collect(),
+        Style::Braille => {
+            // The bit for each dot, by row and then column within the cell.
+            const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+            (0..size)
+                .step_by(4)
+                .map(|row| {
+                    (0..size)
+                        .step_by(2)
+                        .map(|col| {
+                            let mut bits = 0;
+                            for (dy, dots) in DOTS.iter().enumerate() {
+                                for (dx, dot) in dots.iter().enumerate() {
+                                    if at(row + dy, col + dx) {
+                                        bits |= dot;
+                                    }
+                                }
+                            }
+                            char::from_u32(0x2800 + bits).unwrap()
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Returns the lines of text that draw the whole font as a grid of 16×16
+/// glyphs.  Each row of glyphs is labeled with the code of its first glyph.
+pub fn grid_lines(font: &Font, style: Style, scale: usize) -> Vec<String> {
+    let width = glyph_lines(Glyph::default(), style, scale)[0]
+        .chars()
+        .count();
+    let mut lines = vec![];
+    let header: String = (0..16)
+        .map(|col| format!("{:<w$x}", col, w = width + 1))
+        .collect();
+    lines.push(format!("    {}", header.trim_end()));
+
+    for first in (0..=255u8).step_by(16) {
+        let glyphs: Vec<Vec<String>> = (first..=first + 15)
+            .map(|code| glyph_lines(font.glyph(code), style, scale))
+            .collect();
+        for row in 0..glyphs[0].len() {
+            let label = if row == 0 {
+                format!("{:02x}  ", first)
+            } else {
+                "    ".to_string()
+            };
+            let cells: Vec<&str> = glyphs.iter().map(|glyph| glyph[row].as_str()).collect();
+            lines.push(
+                format!("{}{}", label, cells.join(" "))
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An "L" shape with a dot, which shows which way round everything goes.
+    fn glyph() -> Glyph {
+        Glyph::from_rows([0x80, 0x80, 0x80, 0x82, 0, 0, 0, 0xff])
+    }
+
+    #[test]
+    fn test_hash() {
+        let lines = glyph_lines(glyph(), Style::Hash, 1);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "#       ");
+        assert_eq!(lines[3], "#     # ");
+        assert_eq!(lines[7], "########");
+    }
+
+    #[test]
+    fn test_block_scaled() {
+        let lines = glyph_lines(glyph(), Style::Block, 2);
+        assert_eq!(lines.len(), 16);
+        assert_eq!(lines[0], format!("████{}", " ".repeat(28)));
+        assert_eq!(lines[1], lines[0]);
+        assert_eq!(lines[15], "█".repeat(32));
+    }
+
+    #[test]
+    fn test_half_block() {
+        let lines = glyph_lines(glyph(), Style::HalfBlock, 1);
+        assert_eq!(lines, vec!["█       ", "█     ▄ ", "        ", "▄▄▄▄▄▄▄▄"]);
+    }
+
+    #[test]
+    fn test_braille() {
+        let lines = glyph_lines(glyph(), Style::Braille, 1);
+        assert_eq!(lines, vec!["⡇⠀⠀⡀", "⣀⣀⣀⣀"]);
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        let lines = grid_lines(&Font::default(), Style::Hash, 1);
+        assert_eq!(lines.len(), 1 + 16 * 9);
+        assert!(lines[0].starts_with("    0        1        2"));
+        assert_eq!(lines[1], "00            ######");
+        assert_eq!(lines[10], "10");
+
+        let lines = grid_lines(&Font::default(), Style::Braille, 1);
+        assert_eq!(lines.len(), 1 + 16 * 3);
+        assert!(lines[0].starts_with("    0    1    2"));
+    }
+}
@@ -0,0 +1,464 @@
+//! The terminal interface for the puzzle: it asks which puzzle to play (unless
+//! that was decided up front by a `Setup`), then runs the game loop until the
+//! puzzle is solved or the player quits.
+
+use crate::export;
+use crate::save;
+use crate::scores::Scores;
+use crate::screen::{Input, Screen, Selection};
+pub use crate::screen::Style;
+use crate::{Board, Mode, Move, Step};
+use appdirs::AppDirs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Limits on the size of the puzzle, chosen so that the board still fits on a
+// reasonably sized terminal.
+pub const MAX_DISCS: usize = 12;
+pub const MAX_PEGS: usize = 9;
+
+// The bicolor puzzle is solved by exhaustive search, so it is kept small.
+pub const MAX_PAIRS: usize = 4;
+
+// The file in the data directory used to save and restore a puzzle in progress.
+const SAVE_FILE: &str = "hanoi.sav";
+
+// The file in the data directory that keeps the best results for each size of puzzle.
+const SCORES_FILE: &str = "hanoi.scores";
+
+// The number of discs in the tutorial, unless another is chosen.
+const TUTORIAL_DISCS: usize = 3;
+
+// The recursive strategy, which the tutorial shows at every step.
+const STRATEGY: [&str; 2] = [
+    "To move a tower, move the discs above its largest out of the way,",
+    "then the largest, then the rest back on top of it.",
+];
+
+/// The puzzles that can be played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Puzzle {
+    Classic,
+    Scrambled,
+    Bicolor,
+}
+
+impl Puzzle {
+    /// Looks up a puzzle by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Puzzle::Classic),
+            "scrambled" => Some(Puzzle::Scrambled),
+            "bicolor" => Some(Puzzle::Bicolor),
+            _ => None,
+        }
+    }
+}
+
+/// The choices for a new game that have been made up front, such as on the
+/// command line.  Anything left as None is asked for when the game starts.
+#[derive(Clone, Debug, Default)]
+pub struct Setup {
+    pub puzzle: Option<Puzzle>,
+    /// The number of discs or, for the bicolor puzzle, pairs of discs.
+    pub discs: Option<usize>,
+    pub pegs: Option<usize>,
+    /// The target peg, numbered from 1.
+    pub target: Option<usize>,
+    /// Start a new game without offering to resume the saved one.
+    pub new_game: bool,
+    /// Play the classic puzzle with three pegs as a lesson, making only the
+    /// moves that the recursive strategy calls for, and then alone.
+    pub tutorial: bool,
+    /// How the board is drawn.
+    pub style: Style,
+}
+
+impl Setup {
+    /// Checks that the choices are within the limits of the chosen puzzle.
+    pub fn check(&self) -> Result<(), String> {
+        let puzzle = self.puzzle.unwrap_or(Puzzle::Classic);
+        let (max_discs, max_pegs) = match puzzle {
+            Puzzle::Bicolor => (MAX_PAIRS, 3),
+            _ => (MAX_DISCS, MAX_PEGS),
+        };
+        let pegs = self.pegs.unwrap_or(if self.tutorial { 3 } else { max_pegs });
+        let min_target = if puzzle == Puzzle::Scrambled { 1 } else { 2 };
+
+        if self.discs.is_some_and(|discs| !(1..=max_discs).contains(&discs)) {
+            Err(format!("the number of discs must be from 1 to {}", max_discs))
+        } else if puzzle == Puzzle::Bicolor && pegs != 3 {
+            Err("the bicolor puzzle always has 3 pegs".to_string())
+        } else if !(3..=max_pegs).contains(&pegs) {
+            Err(format!("the number of pegs must be from 3 to {}", max_pegs))
+        } else if self.tutorial && (puzzle != Puzzle::Classic || pegs != 3) {
+            Err("the tutorial is for the classic puzzle with 3 pegs".to_string())
+        } else if self.target.is_some_and(|target| !(min_target..=pegs).contains(&target)) {
+            Err(format!("the target peg must be from {} to {}", min_target, pegs))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Keeps track of the time spent on the puzzle, including any time carried
+// over from a restored game.
+struct Clock {
+    start: Instant,
+    offset: Duration,
+}
+
+impl Clock {
+    fn new(offset: Duration) -> Self {
+        Self { start: Instant::now(), offset }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.offset + self.start.elapsed()
+    }
+}
+
+fn read_number(prompt: &str, min: usize, max: usize) -> usize {
+    loop {
+        println!("{} ({}-{}): ", prompt, min, max);
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).expect("Could not read line");
+        if let Ok(n) = input.trim().parse::<usize>() {
+            if (min..=max).contains(&n) {
+                return n;
+            }
+        }
+    }
+}
+
+// Plays back a solution from the current position, pausing briefly after
+// each move so that the player can follow along.
+fn auto_solve(screen: &mut Screen, board: &mut Board, clock: &Clock) {
+    let moves = board.optimal_moves();
+    let delay = Duration::from_millis((10_000 / moves.len().max(1) as u64).clamp(20, 500));
+
+    screen.draw_board(board, None, clock.elapsed(), "").unwrap_or(());
+    for move_ in moves {
+        thread::sleep(delay);
+        board.apply(move_).expect("Solver produced an illegal move");
+        screen.draw_board(board, None, clock.elapsed(), "").unwrap_or(());
+    }
+}
+
+// Lifts the top disc from the peg under the cursor or, if a disc is already
+// lifted, tries to drop it there.  During a lesson, only the given move is
+// allowed.  Returns a message explaining any problem.
+fn select(board: &mut Board, selection: &mut Selection, lesson: Option<Move>) -> String {
+    let peg = selection.cursor;
+    let wrong = |lesson: Move| format!("The lesson moves the top disc from peg {} to peg {}.", lesson.from + 1, lesson.to + 1);
+    match (selection.lifted, lesson) {
+        (None, _) if board.top(peg).is_none() => "There is no disc on that peg to lift.".to_string(),
+        (None, Some(lesson)) if lesson.from != peg => wrong(lesson),
+        (Some(from), Some(lesson)) if from != peg && lesson.to != peg => wrong(lesson),
+        (None, _) => {
+            selection.lifted = Some(peg);
+            String::new()
+        }
+        (Some(from), _) if from == peg => {
+            selection.lifted = None;
+            String::new()
+        }
+        (Some(from), _) => match board.apply(Move { from, to: peg }) {
+            Ok(()) => {
+                selection.lifted = None;
+                String::new()
+            }
+            Err(reason) => reason.to_string(),
+        },
+    }
+}
+
+// Sets up a new board, asking for whichever choices the setup leaves open.
+fn new_game(setup: &Setup) -> Board {
+    if setup.tutorial {
+        let target = setup.target.unwrap_or(3);
+        return Board::classic(setup.discs.unwrap_or(TUTORIAL_DISCS), 3, target - 1);
+    }
+    let puzzle = setup.puzzle.unwrap_or_else(|| {
+        println!("Puzzles:");
+        println!("  1. Classic");
+        println!("  2. Scrambled start");
+        println!("  3. Bicolor");
+        match read_number("Choose a puzzle", 1, 3) {
+            1 => Puzzle::Classic,
+            2 => Puzzle::Scrambled,
+            _ => Puzzle::Bicolor,
+        }
+    });
+
+    if puzzle == Puzzle::Bicolor {
+        let pairs = setup
+            .discs
+            .unwrap_or_else(|| read_number("Enter number of pairs of discs", 1, MAX_PAIRS));
+        let target = setup
+            .target
+            .filter(|&target| target <= 3)
+            .unwrap_or_else(|| read_number("Enter peg for the second tower", 2, 3));
+        return Board::bicolor(pairs, 3, target - 1);
+    }
+
+    let discs = setup
+        .discs
+        .unwrap_or_else(|| read_number("Enter number of discs", 1, MAX_DISCS));
+    let pegs = setup
+        .pegs
+        .unwrap_or_else(|| read_number("Enter number of pegs", 3, MAX_PEGS));
+
+    // A target chosen up front may not fit the number of pegs chosen since.
+    let target = |min| {
+        setup
+            .target
+            .filter(|&target| target <= pegs)
+            .unwrap_or_else(|| read_number("Enter target peg", min, pegs))
+    };
+    if puzzle == Puzzle::Classic {
+        Board::classic(discs, pegs, target(2) - 1)
+    } else {
+        Board::scrambled(discs, pegs, target(1) - 1, &mut rand::thread_rng())
+    }
+}
+
+// Returns the path of the given file in the data directory.
+fn data_file(name: &str) -> PathBuf {
+    AppDirs::new("hanoi").data_file(name)
+}
+
+// Writes a file in the data directory, creating the directory if need be.
+fn write_data<F: FnOnce(&Path) -> io::Result<()>>(path: &Path, write: F) -> io::Result<()> {
+    AppDirs::prepare(path)?;
+    write(path)
+}
+
+// Asks whether to resume the saved game, if there is one.
+fn ask_resume(save_file: &Path) -> Option<(Board, Duration)> {
+    if !save_file.exists() {
+        return None;
+    }
+    loop {
+        println!("Resume saved game? (y/n): ");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).expect("Could not read line");
+        match input.trim() {
+            "y" | "Y" => match save::load_game(save_file) {
+                Ok(saved) => return Some(saved),
+                Err(err) => {
+                    println!("Could not restore game: {}", err);
+                    return None;
+                }
+            },
+            "n" | "N" => return None,
+            _ => continue,
+        }
+    }
+}
+
+/// Plays the puzzle on the terminal, resuming the saved game if the player
+/// asks to.  The setup must already have been checked.
+pub fn play(setup: &Setup) {
+    println!("Towers of Hanoi");
+    println!();
+    let save_file = data_file(SAVE_FILE);
+    let scores_file = data_file(SCORES_FILE);
+    let mut scores = Scores::load(&scores_file).unwrap_or_default();
+    if !scores.table().is_empty() {
+        println!("Best results:");
+        println!("{}", scores.table());
+    }
+
+    let resumed = if setup.new_game || setup.tutorial { None } else { ask_resume(&save_file) };
+    let fresh = resumed.is_none();
+    let (mut board, elapsed) = match resumed {
+        Some(saved) => saved,
+        None => (new_game(setup), Duration::ZERO),
+    };
+
+    // Only a classic puzzle played from the start here counts as a result,
+    // since a restored game may have been scrambled or taken from elsewhere.
+    // A tutorial doesn't count, even once the player goes on alone.
+    let mut ranked = fresh && is_standard_start(&board) && !setup.tutorial;
+
+    // The lesson's steps, while it lasts.  It is always played from the
+    // start, so the step to take is the one numbered by the moves made.
+    let start = board.clone();
+    let mut lesson: Option<Vec<Step>> = if setup.tutorial { board.lesson() } else { None };
+
+    let mut screen = Screen::new(setup.style);
+    let mut clock = Clock::new(elapsed);
+    let mut solved_by_computer = false;
+    let mut message = String::new();
+    let mut selection = Selection::default();
+
+    while !board.is_solved() {
+        screen.draw_board(&board, Some(&selection), clock.elapsed(), &message).unwrap_or(());
+        let step = lesson.as_ref().map(|steps| &steps[board.moves()]);
+        if let Some(step) = step {
+            screen.draw_panel(&board, &lesson_panel(step)).unwrap_or(());
+        }
+        let expected = step.map(|step| step.move_);
+
+        let pegs = board.pegs();
+        message = match screen.read_input(pegs) {
+            Input::Left => {
+                selection.cursor = (selection.cursor + pegs - 1) % pegs;
+                String::new()
+            }
+            Input::Right => {
+                selection.cursor = (selection.cursor + 1) % pegs;
+                String::new()
+            }
+            Input::Select => select(&mut board, &mut selection, expected),
+            Input::Post(peg) => {
+                selection.cursor = peg;
+                select(&mut board, &mut selection, expected)
+            }
+            Input::Cancel => {
+                selection.lifted = None;
+                String::new()
+            }
+            Input::Save | Input::Load | Input::Solve if lesson.is_some() => "Not during the lesson.".to_string(),
+            Input::Save => match write_data(&save_file, |path| save::save_game(path, &board, clock.elapsed())) {
+                Ok(()) => "Game saved.".to_string(),
+                Err(err) => format!("Could not save game: {}", err),
+            },
+            Input::Load => match save::load_game(&save_file) {
+                Ok((saved, elapsed)) => {
+                    board = saved;
+                    ranked = false;
+                    clock = Clock::new(elapsed);
+                    selection = Selection::default();
+                    "Game restored.".to_string()
+                }
+                Err(err) => format!("Could not restore game: {}", err),
+            },
+            Input::Hint => match board.hint() {
+                Some(move_) => {
+                    screen.flash_move(&board, move_).unwrap_or(());
+                    format!("Move the top disc from peg {} to peg {}.", move_.from + 1, move_.to + 1)
+                }
+                None => String::new(),
+            },
+            Input::Solve => {
+                auto_solve(&mut screen, &mut board, &clock);
+                solved_by_computer = true;
+                break;
+            }
+            Input::Quit => break,
+        };
+
+        // Once the lesson is done, the player tries the same puzzle alone.
+        if lesson.is_some() && board.is_solved() {
+            screen.draw_board(&board, None, clock.elapsed(), "Well done!").unwrap_or(());
+            thread::sleep(Duration::from_secs(2));
+            lesson = None;
+            board = start.clone();
+            clock = Clock::new(Duration::ZERO);
+            selection = Selection::default();
+            message = "Now try it on your own!".to_string();
+        }
+    }
+
+    if board.is_solved() {
+        screen.report_win(&board, clock.elapsed(), solved_by_computer).unwrap_or(());
+        if ranked && !solved_by_computer {
+            let elapsed = Duration::from_secs(clock.elapsed().as_secs());
+            if scores.record(board.discs(), board.pegs(), board.moves(), elapsed) {
+                println!("That's a new best!");
+                if let Err(err) = write_data(&scores_file, |path| scores.save(path)) {
+                    println!("Could not save scores: {}", err);
+                }
+            }
+            println!();
+            println!("Best results:");
+            print!("{}", scores.table());
+        }
+    }
+}
+
+// Returns the lines of the panel that explains a step of the lesson: the
+// strategy, then each goal that the step is part of, indented by how deeply
+// it is nested, then the move itself.
+fn lesson_panel(step: &Step) -> Vec<String> {
+    let mut lines: Vec<String> = STRATEGY.iter().map(|line| line.to_string()).collect();
+    for (depth, goal) in step.goals.iter().enumerate() {
+        lines.push(format!("{}{}", "  ".repeat(depth), goal));
+    }
+    let disc = step.goals.last().map_or(0, |goal| goal.discs);
+    lines.push(format!("So move disc {} from peg {} to peg {}.", disc, step.move_.from + 1, step.move_.to + 1));
+    lines
+}
+
+/// Writes the moves that solve the classic puzzle of the setup to the given
+/// file, or to stdout if there is none, without playing it.  There are 3 pegs
+/// unless the setup says otherwise.  The setup must already have been
+/// checked, and must give the number of discs.
+pub fn export(setup: &Setup, path: Option<&str>) -> io::Result<()> {
+    let discs = setup.discs.expect("The number of discs is needed to export a solution");
+    let pegs = setup.pegs.unwrap_or(3);
+    let board = Board::classic(discs, pegs, setup.target.unwrap_or(pegs) - 1);
+    export::write_solution(path, &board)
+}
+
+// Checks whether the board is a classic puzzle that hasn't been started,
+// which is the only kind whose results are comparable.
+fn is_standard_start(board: &Board) -> bool {
+    board.mode() == Mode::Classic && board.moves() == 0 && board.stack(0).len() == board.discs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_setup() {
+        let setup = |puzzle, discs, pegs, target| Setup {
+            puzzle,
+            discs,
+            pegs,
+            target,
+            new_game: false,
+            tutorial: false,
+            style: Style::default(),
+        };
+        assert!(Setup::default().check().is_ok());
+        assert!(setup(None, Some(12), Some(9), Some(9)).check().is_ok());
+        assert!(setup(None, Some(13), None, None).check().is_err());
+        assert!(setup(None, None, Some(2), None).check().is_err());
+        assert!(setup(None, None, Some(4), Some(5)).check().is_err());
+        assert!(setup(Some(Puzzle::Classic), None, None, Some(1)).check().is_err());
+        assert!(setup(Some(Puzzle::Scrambled), None, None, Some(1)).check().is_ok());
+        assert!(setup(Some(Puzzle::Bicolor), Some(4), None, Some(3)).check().is_ok());
+        assert!(setup(Some(Puzzle::Bicolor), Some(5), None, None).check().is_err());
+        assert!(setup(Some(Puzzle::Bicolor), None, Some(4), None).check().is_err());
+
+        let tutorial = |puzzle, pegs| Setup { puzzle, pegs, tutorial: true, ..Setup::default() };
+        assert!(tutorial(None, None).check().is_ok());
+        assert!(tutorial(Some(Puzzle::Classic), Some(3)).check().is_ok());
+        assert!(tutorial(None, Some(4)).check().is_err());
+        assert!(tutorial(Some(Puzzle::Scrambled), None).check().is_err());
+    }
+
+    #[test]
+    fn test_lesson() {
+        let steps = Board::new(2, 3).lesson().unwrap();
+        let panel = lesson_panel(&steps[1]);
+        assert_eq!(&panel[2..], ["Move discs 1-2 from peg 1 to peg 3", "So move disc 2 from peg 1 to peg 3."]);
+
+        // Only the lesson's move can be made.
+        let mut board = Board::new(2, 3);
+        let mut selection = Selection::default();
+        let lesson = Some(steps[0].move_);
+        assert_eq!(select(&mut board, &mut selection, lesson), "");
+        selection.cursor = 2;
+        assert_eq!(select(&mut board, &mut selection, lesson), "The lesson moves the top disc from peg 1 to peg 2.");
+        selection.cursor = 1;
+        assert_eq!(select(&mut board, &mut selection, lesson), "");
+        assert_eq!(board.stack(1), &[0]);
+    }
+}
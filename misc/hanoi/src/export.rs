@@ -0,0 +1,78 @@
+//! Writing out a solution to the puzzle as a list of moves, for printing
+//! rather than playing.
+//!
+//! The list starts with comment lines describing the puzzle, then gives one
+//! move per line, numbered from 1, with the disc moved and the pegs it moves
+//! between, all numbered from 1 as the game shows them:
+//!
+//! ```text
+//! # Towers of Hanoi: 3 discs on 3 pegs, from peg 1 to peg 3
+//! # 7 moves, the fewest possible
+//! 1. disc 1 from peg 1 to peg 3
+//! 2. disc 2 from peg 1 to peg 2
+//! ```
+
+use std::fs;
+use std::io::Result;
+
+use crate::Board;
+
+/// Writes the moves that solve the puzzle from the given position to the
+/// given file, or to stdout if there is none.
+pub fn write_solution(path: Option<&str>, board: &Board) -> Result<()> {
+    let text = solution_text(board);
+    match path {
+        Some(path) => fs::write(path, text),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the list of moves that solve the puzzle from the given position.
+fn solution_text(board: &Board) -> String {
+    let moves = board.optimal_moves();
+    let how = if board.pegs() == 3 { "the fewest possible" } else { "by the Frame–Stewart algorithm" };
+    let mut text = format!(
+        "# Towers of Hanoi: {} discs on {} pegs, from peg 1 to peg {}\n# {} moves, {}\n",
+        board.discs(),
+        board.pegs(),
+        board.target() + 1,
+        moves.len(),
+        how
+    );
+
+    // The disc that each move takes is whichever is on top at the time.
+    let mut board = board.clone();
+    for (i, move_) in moves.into_iter().enumerate() {
+        let disc = board.top(move_.from).expect("Solver moved from an empty peg");
+        board.apply(move_).expect("Solver produced an illegal move");
+        text.push_str(&format!("{}. disc {} from peg {} to peg {}\n", i + 1, disc + 1, move_.from + 1, move_.to + 1));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_text() {
+        let text = solution_text(&Board::new(2, 3));
+        assert_eq!(
+            text,
+            "# Towers of Hanoi: 2 discs on 3 pegs, from peg 1 to peg 3\n\
+             # 3 moves, the fewest possible\n\
+             1. disc 1 from peg 1 to peg 2\n\
+             2. disc 2 from peg 1 to peg 3\n\
+             3. disc 1 from peg 2 to peg 3\n"
+        );
+
+        let text = solution_text(&Board::classic(10, 4, 1));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "# 49 moves, by the Frame–Stewart algorithm");
+        assert_eq!(lines.len(), 2 + 49);
+        assert!(lines[0].ends_with("from peg 1 to peg 2"));
+    }
+}
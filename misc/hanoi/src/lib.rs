@@ -0,0 +1,496 @@
+//! The pure game logic for the Towers of Hanoi, with no I/O of any kind.  The
+//! terminal interface in the `app` module (and its `screen`, `save`, `scores`,
+//! and `export` helpers) is a thin layer on top of this, so that other programs
+//! can run the game too.
+//!
+//! Discs are numbered from 0 upward, and each peg holds a stack of discs
+//! listed from the bottom up, so the top disc of a peg is the last element of
+//! its stack.  In the classic puzzle a disc's number is its size; in the
+//! bicolor variant, disc `d` has size `d / 2` and color `d % 2`.
+//!
+//! ```
+//! use hanoi::{Board, Move};
+//!
+//! let mut board = Board::new(3, 3);
+//! for move_ in board.optimal_moves() {
+//!     board.apply(move_).unwrap();
+//! }
+//! assert!(board.is_solved());
+//! assert_eq!(board.moves(), 7);
+//! assert!(board.apply(Move { from: 0, to: 1 }).is_err());
+//! ```
+
+pub mod app;
+mod export;
+mod save;
+mod scores;
+mod screen;
+mod solver;
+
+use rand::Rng;
+use std::fmt::{Display, Error, Formatter};
+
+/// A single move of the top disc on one peg to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The reasons that a move can be rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    NoSuchPeg,
+    SamePeg,
+    EmptyPeg,
+    LargerOnSmaller,
+}
+
+impl Display for MoveError {
+    /// Explains the problem in terms suitable for showing to the player.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let msg = match self {
+            MoveError::NoSuchPeg => "There is no such peg.",
+            MoveError::SamePeg => "The disc is already on that peg.",
+            MoveError::EmptyPeg => "There is no disc on that peg to move.",
+            MoveError::LargerOnSmaller => "You can't put a larger disc on a smaller one.",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// Part of moving a tower by the recursive strategy: moving the given number
+/// of smallest discs, which form a tower, from one peg to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Goal {
+    pub discs: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Display for Goal {
+    /// Describes the goal with the discs and pegs numbered from 1.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self.discs {
+            1 => write!(f, "Move disc 1 from peg {} to peg {}", self.from + 1, self.to + 1),
+            n => write!(f, "Move discs 1-{} from peg {} to peg {}", n, self.from + 1, self.to + 1),
+        }
+    }
+}
+
+/// A step of a lesson: the move to make, and the goals that it is part of,
+/// from moving the whole tower down to the goal whose largest disc it moves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub move_: Move,
+    pub goals: Vec<Goal>,
+}
+
+/// The rules in play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Every disc has a different size, and the goal is to stack them all on
+    /// the target peg.
+    Classic,
+    /// Discs come in pairs of equal size but different color, and start out
+    /// as two towers of alternating colors on the first peg and the target
+    /// peg.  Equal sizes may be stacked, and the goal is to leave the first
+    /// peg holding only color 0 and the target peg only color 1.
+    Bicolor,
+}
+
+/// The board consists of any number of stacks of discs, one per peg, along
+/// with the rules in play and the peg that the discs must be moved to.
+#[derive(Clone, Debug)]
+pub struct Board {
+    mode: Mode,
+    discs: usize,
+    stacks: Vec<Vec<usize>>,
+    target: usize,
+    moves: usize,
+}
+
+impl Board {
+    /// Returns a new classic Board with all of the discs stacked on the first
+    /// peg, to be moved to the last peg.
+    pub fn new(discs: usize, pegs: usize) -> Self {
+        Self::classic(discs, pegs, pegs - 1)
+    }
+
+    /// Returns a new classic Board with all of the discs stacked on the first
+    /// peg, to be moved to the given target peg.
+    pub fn classic(discs: usize, pegs: usize, target: usize) -> Self {
+        let mut stacks = vec![vec![]; pegs];
+        stacks[0] = (0..discs).rev().collect();
+        Self { mode: Mode::Classic, discs, stacks, target, moves: 0 }
+    }
+
+    /// Returns a new classic Board with the discs scattered randomly (but
+    /// legally) across the pegs, to be gathered on the target peg.  The
+    /// position is never already solved unless there is only one disc.
+    pub fn scrambled<R: Rng>(discs: usize, pegs: usize, target: usize, rng: &mut R) -> Self {
+        loop {
+            let mut stacks = vec![vec![]; pegs];
+            for disc in (0..discs).rev() {
+                stacks[rng.gen_range(0..pegs)].push(disc);
+            }
+            let board = Self { mode: Mode::Classic, discs, stacks, target, moves: 0 };
+            if !board.is_solved() || discs <= 1 {
+                return board;
+            }
+        }
+    }
+
+    /// Returns a new bicolor Board with the given number of pairs of discs.
+    /// The first peg and the target peg each start with a tower of alternating
+    /// colors, with color 0 at the bottom of the first peg and color 1 at the
+    /// bottom of the target peg.
+    pub fn bicolor(pairs: usize, pegs: usize, target: usize) -> Self {
+        let mut stacks = vec![vec![]; pegs];
+        for size in (0..pairs).rev() {
+            let color = (pairs - 1 - size) % 2;
+            stacks[0].push(size * 2 + color);
+            stacks[target].push(size * 2 + (1 - color));
+        }
+        Self { mode: Mode::Bicolor, discs: pairs * 2, stacks, target, moves: 0 }
+    }
+
+    /// Returns a Board with the given rules, stacks (each listed from the
+    /// bottom up), target peg, and move count, checking that every disc
+    /// appears exactly once and that no disc sits on a smaller one.
+    pub fn from_stacks(mode: Mode, stacks: Vec<Vec<usize>>, target: usize, moves: usize) -> Result<Self, String> {
+        if stacks.len() < 3 {
+            return Err("at least three pegs are required".to_string());
+        } else if target >= stacks.len() {
+            return Err("the target peg does not exist".to_string());
+        }
+
+        let discs = stacks.iter().map(|stack| stack.len()).sum();
+        if mode == Mode::Bicolor && (discs % 2 != 0 || target == 0) {
+            return Err("a bicolor puzzle needs pairs of discs and two towers".to_string());
+        }
+
+        let board = Self { mode, discs, stacks, target, moves };
+        let mut seen = vec![false; discs];
+        for stack in &board.stacks {
+            if stack.windows(2).any(|pair| board.size(pair[0]) < board.size(pair[1])) {
+                return Err("a larger disc sits on a smaller one".to_string());
+            }
+            for &disc in stack {
+                match seen.get_mut(disc) {
+                    Some(seen @ false) => *seen = true,
+                    _ => return Err("discs are missing or duplicated".to_string()),
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /// Returns the rules in play.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the number of discs.
+    pub fn discs(&self) -> usize {
+        self.discs
+    }
+
+    /// Returns the number of different disc sizes.
+    pub fn sizes(&self) -> usize {
+        match self.mode {
+            Mode::Classic => self.discs,
+            Mode::Bicolor => self.discs / 2,
+        }
+    }
+
+    /// Returns the size of the given disc.
+    pub fn size(&self, disc: usize) -> usize {
+        match self.mode {
+            Mode::Classic => disc,
+            Mode::Bicolor => disc / 2,
+        }
+    }
+
+    /// Returns the color of the given disc, if discs are colored.
+    pub fn color(&self, disc: usize) -> Option<usize> {
+        match self.mode {
+            Mode::Classic => None,
+            Mode::Bicolor => Some(disc % 2),
+        }
+    }
+
+    /// Returns the number of pegs.
+    pub fn pegs(&self) -> usize {
+        self.stacks.len()
+    }
+
+    /// Returns the peg that the discs must be moved to.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Returns the number of moves made so far.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Returns the discs on the given peg, from the bottom up.
+    pub fn stack(&self, peg: usize) -> &[usize] {
+        &self.stacks[peg]
+    }
+
+    /// Returns the disc on top of the given peg, if there is one.
+    pub fn top(&self, peg: usize) -> Option<usize> {
+        self.stacks.get(peg).and_then(|stack| stack.last().copied())
+    }
+
+    /// Checks whether the given move is legal without making it.
+    pub fn check(&self, move_: Move) -> Result<(), MoveError> {
+        if move_.from >= self.pegs() || move_.to >= self.pegs() {
+            return Err(MoveError::NoSuchPeg);
+        } else if move_.from == move_.to {
+            return Err(MoveError::SamePeg);
+        }
+        match (self.top(move_.from), self.top(move_.to)) {
+            (None, _) => Err(MoveError::EmptyPeg),
+            (Some(from), Some(to)) if self.size(from) > self.size(to) => Err(MoveError::LargerOnSmaller),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns every legal move in the current position.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for from in 0..self.pegs() {
+            for to in 0..self.pegs() {
+                let move_ = Move { from, to };
+                if self.check(move_).is_ok() {
+                    moves.push(move_);
+                }
+            }
+        }
+        moves
+    }
+
+    /// Moves the top disc from one peg to another, or explains why the move
+    /// is not allowed.
+    pub fn apply(&mut self, move_: Move) -> Result<(), MoveError> {
+        self.check(move_)?;
+        let disc = self.stacks[move_.from].pop().unwrap();
+        self.stacks[move_.to].push(disc);
+        self.moves += 1;
+        Ok(())
+    }
+
+    /// Checks whether the goal of the puzzle has been reached.
+    pub fn is_solved(&self) -> bool {
+        match self.mode {
+            Mode::Classic => self.stacks[self.target].len() == self.discs,
+            Mode::Bicolor => {
+                self.stacks[0].len() + self.stacks[self.target].len() == self.discs
+                    && self.stacks[0].iter().all(|&disc| disc % 2 == 0)
+                    && self.stacks[self.target].iter().all(|&disc| disc % 2 == 1)
+            }
+        }
+    }
+
+    /// Returns a sequence of moves that solves the puzzle from the current
+    /// position.  For classic puzzles this is optimal with three pegs, and
+    /// uses the Frame–Stewart algorithm to move whole towers with more.
+    /// Bicolor puzzles are solved by exhaustive search, so they should be
+    /// kept small.
+    pub fn optimal_moves(&self) -> Vec<Move> {
+        match self.mode {
+            Mode::Classic => solver::gather(self),
+            Mode::Bicolor => solver::search(self),
+        }
+    }
+
+    /// Returns the next move of a shortest solution from the current
+    /// position, however the player got there, or None if the puzzle is
+    /// solved.  Every position is searched when there are few enough of them,
+    /// so this can be shorter than the first of `optimal_moves` with more
+    /// than three pegs.
+    pub fn hint(&self) -> Option<Move> {
+        solver::next_move(self)
+    }
+
+    /// Returns the steps of a lesson in the recursive strategy, which solves
+    /// the puzzle in the fewest moves, or None unless this is a classic
+    /// puzzle with three pegs and every disc on the first.
+    pub fn lesson(&self) -> Option<Vec<Step>> {
+        if self.mode != Mode::Classic || self.pegs() != 3 || self.stacks[0].len() != self.discs {
+            return None;
+        }
+        Some(solver::lesson(Goal { discs: self.discs, from: 0, to: self.target }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_new_board_stacks_first_peg() {
+        let board = Board::new(4, 3);
+        assert_eq!(board.stack(0), &[3, 2, 1, 0]);
+        assert_eq!(board.top(0), Some(0));
+        assert_eq!(board.top(1), None);
+        assert!(!board.is_solved());
+    }
+
+    #[test]
+    fn test_apply_moves_top_disc() {
+        let mut board = Board::new(3, 3);
+        assert_eq!(board.apply(Move { from: 0, to: 2 }), Ok(()));
+        assert_eq!(board.stack(0), &[2, 1]);
+        assert_eq!(board.stack(2), &[0]);
+        assert_eq!(board.moves(), 1);
+    }
+
+    #[test]
+    fn test_larger_on_smaller_is_rejected() {
+        let mut board = Board::new(3, 3);
+        board.apply(Move { from: 0, to: 2 }).unwrap();
+        assert_eq!(board.apply(Move { from: 0, to: 2 }), Err(MoveError::LargerOnSmaller));
+        assert_eq!(board.apply(Move { from: 0, to: 1 }), Ok(()));
+        assert_eq!(board.apply(Move { from: 2, to: 1 }), Ok(()));
+        assert_eq!(board.stack(1), &[1, 0]);
+        assert_eq!(board.moves(), 3);
+    }
+
+    #[test]
+    fn test_other_illegal_moves_are_rejected() {
+        let mut board = Board::new(2, 3);
+        assert_eq!(board.apply(Move { from: 1, to: 2 }), Err(MoveError::EmptyPeg));
+        assert_eq!(board.apply(Move { from: 0, to: 0 }), Err(MoveError::SamePeg));
+        assert_eq!(board.apply(Move { from: 0, to: 3 }), Err(MoveError::NoSuchPeg));
+        assert_eq!(board.moves(), 0);
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let mut board = Board::new(3, 3);
+        assert_eq!(board.legal_moves(), vec![Move { from: 0, to: 1 }, Move { from: 0, to: 2 }]);
+        board.apply(Move { from: 0, to: 2 }).unwrap();
+        assert_eq!(
+            board.legal_moves(),
+            vec![Move { from: 0, to: 1 }, Move { from: 2, to: 0 }, Move { from: 2, to: 1 }]
+        );
+    }
+
+    /// Applies the board's own solution and checks that it solves the puzzle
+    /// in the given number of moves.
+    fn assert_solves(mut board: Board, expected: usize) {
+        let moves = board.optimal_moves();
+        assert_eq!(moves.len(), expected, "{:?}", board);
+        for move_ in moves {
+            board.apply(move_).unwrap();
+        }
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn test_optimal_moves_solve_the_puzzle() {
+        for (discs, pegs, expected) in [(1, 3, 1), (5, 3, 31), (10, 3, 1023), (10, 4, 49), (8, 5, 23)] {
+            assert_solves(Board::new(discs, pegs), expected);
+        }
+    }
+
+    #[test]
+    fn test_optimal_moves_from_the_middle() {
+        let mut board = Board::new(4, 3);
+        for move_ in board.optimal_moves().into_iter().take(5) {
+            board.apply(move_).unwrap();
+        }
+        assert_solves(board, 10);
+
+        let board = Board::from_stacks(Mode::Classic, vec![vec![2], vec![0], vec![1]], 2, 0).unwrap();
+        assert_solves(board, 7);
+    }
+
+    #[test]
+    fn test_scrambled_boards_are_solvable() {
+        let mut rng = StdRng::seed_from_u64(1634);
+        for pegs in 3..=5 {
+            for target in 0..pegs {
+                let mut board = Board::scrambled(7, pegs, target, &mut rng);
+                assert!(!board.is_solved());
+                for move_ in board.optimal_moves() {
+                    board.apply(move_).unwrap();
+                }
+                assert!(board.is_solved());
+                assert_eq!(board.target(), target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        // Following the hints from any position takes the fewest moves.
+        for (mut board, expected) in [
+            (Board::new(4, 3), 15),
+            (Board::new(5, 4), 13),
+            (Board::from_stacks(Mode::Classic, vec![vec![3], vec![2, 1], vec![0], vec![]], 0, 0).unwrap(), 4),
+            (Board::bicolor(2, 3, 2), Board::bicolor(2, 3, 2).optimal_moves().len()),
+        ] {
+            while let Some(move_) = board.hint() {
+                board.apply(move_).unwrap();
+            }
+            assert!(board.is_solved());
+            assert_eq!(board.moves(), expected, "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn test_lesson() {
+        let board = Board::new(3, 3);
+        let steps = board.lesson().unwrap();
+        let moves: Vec<Move> = steps.iter().map(|step| step.move_).collect();
+        assert_eq!(moves, board.optimal_moves());
+        assert_eq!(
+            steps[0].goals,
+            [Goal { discs: 3, from: 0, to: 2 }, Goal { discs: 2, from: 0, to: 1 }, Goal { discs: 1, from: 0, to: 2 }]
+        );
+        assert_eq!(steps[3].goals, [Goal { discs: 3, from: 0, to: 2 }]);
+        assert_eq!(steps[3].goals[0].to_string(), "Move discs 1-3 from peg 1 to peg 3");
+
+        assert!(Board::new(3, 4).lesson().is_none());
+        assert!(Board::bicolor(2, 3, 2).lesson().is_none());
+        let mut started = Board::new(3, 3);
+        started.apply(Move { from: 0, to: 2 }).unwrap();
+        assert!(started.lesson().is_none());
+    }
+
+    #[test]
+    fn test_bicolor() {
+        let board = Board::bicolor(2, 3, 2);
+        assert_eq!(board.stack(0), &[2, 1]);
+        assert_eq!(board.stack(2), &[3, 0]);
+        assert!(!board.is_solved());
+
+        let mut equal_sizes = Board::bicolor(1, 3, 1);
+        assert_eq!(equal_sizes.apply(Move { from: 0, to: 1 }), Ok(()));
+
+        for pairs in 1..=3 {
+            let mut board = Board::bicolor(pairs, 3, 2);
+            for move_ in board.optimal_moves() {
+                board.apply(move_).unwrap();
+            }
+            assert!(board.is_solved());
+        }
+    }
+
+    #[test]
+    fn test_from_stacks_validates() {
+        assert!(Board::from_stacks(Mode::Classic, vec![vec![2, 0], vec![1], vec![]], 2, 0).is_ok());
+        assert!(Board::from_stacks(Mode::Classic, vec![vec![0, 2], vec![1], vec![]], 2, 0).is_err());
+        assert!(Board::from_stacks(Mode::Classic, vec![vec![2, 0], vec![0], vec![]], 2, 0).is_err());
+        assert!(Board::from_stacks(Mode::Classic, vec![vec![1, 0], vec![]], 1, 0).is_err());
+        assert!(Board::from_stacks(Mode::Classic, vec![vec![1, 0], vec![], vec![]], 3, 0).is_err());
+        assert!(Board::from_stacks(Mode::Bicolor, vec![vec![1, 0], vec![], vec![]], 2, 0).is_ok());
+    }
+}
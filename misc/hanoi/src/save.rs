@@ -0,0 +1,212 @@
+//! Saving and restoring a puzzle in progress.
+//!
+//! The save file is a small text file with one `key value` pair per line,
+//! followed by one `peg` line per peg listing its discs from the bottom up:
+//!
+//! ```text
+//! mode classic
+//! target 2
+//! discs 4
+//! moves 3
+//! elapsed 12
+//! peg 3 2
+//! peg 1
+//! peg 0
+//! ```
+
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{Board, Mode};
+
+use crate::app::{MAX_DISCS, MAX_PAIRS, MAX_PEGS};
+
+/// Writes the board, its move count, and the elapsed time to the given file.
+pub fn save_game(path: &Path, board: &Board, elapsed: Duration) -> Result<()> {
+    let mode = match board.mode() {
+        Mode::Classic => "classic",
+        Mode::Bicolor => "bicolor",
+    };
+    let mut text = format!(
+        "mode {}\ntarget {}\ndiscs {}\nmoves {}\nelapsed {}\n",
+        mode,
+        board.target(),
+        board.discs(),
+        board.moves(),
+        elapsed.as_secs()
+    );
+    for peg in 0..board.pegs() {
+        text.push_str("peg");
+        for disc in board.stack(peg) {
+            text.push_str(&format!(" {}", disc));
+        }
+        text.push('\n');
+    }
+    fs::write(path, text)
+}
+
+/// Reads a board and the elapsed time back from the given file, checking that
+/// the stacks describe a legal position.
+pub fn load_game(path: &Path) -> Result<(Board, Duration)> {
+    parse_game(&fs::read_to_string(path)?)
+}
+
+// Reads a board and the elapsed time from the contents of a save file.
+fn parse_game(text: &str) -> Result<(Board, Duration)> {
+    let mut mode = Mode::Classic;
+    let mut target = None;
+    let mut discs = None;
+    let mut moves = None;
+    let mut elapsed = None;
+    let mut stacks = vec![];
+
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("mode") => {
+                mode = match fields.next() {
+                    Some("classic") => Mode::Classic,
+                    Some("bicolor") => Mode::Bicolor,
+                    _ => return Err(invalid("unknown mode")),
+                }
+            }
+            Some("target") => target = Some(parse_value(fields.next())?),
+            Some("discs") => discs = Some(parse_value(fields.next())?),
+            Some("moves") => moves = Some(parse_value(fields.next())?),
+            Some("elapsed") => elapsed = Some(parse_value(fields.next())?),
+            Some("peg") => stacks.push(
+                fields
+                    .map(|field| parse_value(Some(field)).map(|disc| disc as usize))
+                    .collect::<Result<Vec<usize>>>()?,
+            ),
+            Some(key) => return Err(invalid(&format!("unknown key '{}'", key))),
+            None => continue,
+        }
+    }
+
+    let discs = discs.ok_or_else(|| invalid("missing disc count"))? as usize;
+    let moves = moves.ok_or_else(|| invalid("missing move count"))? as usize;
+    let elapsed = Duration::from_secs(elapsed.ok_or_else(|| invalid("missing elapsed time"))?);
+
+    // The same limits as for a new puzzle, so that an edited save can't ask
+    // the solver for a hint on a bicolor board too big to search.
+    let (max_discs, max_pegs) = match mode {
+        Mode::Classic => (MAX_DISCS, MAX_PEGS),
+        Mode::Bicolor => (MAX_PAIRS * 2, 3),
+    };
+    if !(1..=max_discs).contains(&discs) {
+        return Err(invalid("disc count out of range"));
+    } else if !(3..=max_pegs).contains(&stacks.len()) {
+        return Err(invalid("peg count out of range"));
+    }
+
+    // The target defaults to the last peg, as in the classic puzzle.
+    let target = target.map_or(stacks.len() - 1, |target| target as usize);
+    let board = Board::from_stacks(mode, stacks, target, moves).map_err(|err| invalid(&err))?;
+    if board.discs() != discs {
+        return Err(invalid("discs are missing or duplicated"));
+    }
+    Ok((board, elapsed))
+}
+
+// Parses a single non-negative number from the save file.
+fn parse_value(field: Option<&str>) -> Result<u64> {
+    field
+        .and_then(|field| field.parse::<u64>().ok())
+        .ok_or_else(|| invalid("malformed number"))
+}
+
+// Builds an error describing a corrupt save file.
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("bad save file: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_round_trip() {
+        let stacks = vec![vec![3, 2], vec![1], vec![], vec![0]];
+        let board = Board::from_stacks(Mode::Classic, stacks, 3, 5).unwrap();
+        let path = env::temp_dir().join(format!("hanoi-test-{}.sav", process::id()));
+        save_game(&path, &board, Duration::from_secs(75)).unwrap();
+        let (loaded, elapsed) = load_game(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.mode(), Mode::Classic);
+        assert_eq!(loaded.discs(), 4);
+        assert_eq!(loaded.pegs(), 4);
+        assert_eq!(loaded.target(), 3);
+        assert_eq!(loaded.moves(), 5);
+        for peg in 0..board.pegs() {
+            assert_eq!(loaded.stack(peg), board.stack(peg));
+        }
+        assert_eq!(elapsed, Duration::from_secs(75));
+    }
+
+    #[test]
+    fn test_rejected() {
+        let error = |text: &str| parse_game(text).unwrap_err().to_string();
+        let game = "mode classic\ntarget 2\ndiscs 3\nmoves 1\nelapsed 9\npeg 2 1\npeg 0\npeg\n";
+        assert!(parse_game(game).is_ok());
+
+        // Truncated.
+        assert_eq!(
+            error(&game[..game.find("elapsed").unwrap()]),
+            "bad save file: missing elapsed time"
+        );
+        assert_eq!(
+            error(&game[..game.find("peg 0").unwrap()]),
+            "bad save file: peg count out of range"
+        );
+        assert_eq!(
+            error(&game[..game.find("iscs").unwrap()]),
+            "bad save file: unknown key 'd'"
+        );
+
+        // Bad numbers.
+        assert_eq!(
+            error(&game.replace("moves 1", "moves one")),
+            "bad save file: malformed number"
+        );
+        assert_eq!(
+            error(&game.replace("discs 3", "discs -3")),
+            "bad save file: malformed number"
+        );
+        assert_eq!(
+            error(&game.replace("peg 0", "peg x")),
+            "bad save file: malformed number"
+        );
+        assert_eq!(
+            error(&game.replace("moves 1", "moves")),
+            "bad save file: malformed number"
+        );
+
+        // Out of range.
+        assert_eq!(
+            error(&game.replace("discs 3", "discs 0")),
+            "bad save file: disc count out of range"
+        );
+        assert_eq!(
+            error(&game.replace("discs 3", "discs 13")),
+            "bad save file: disc count out of range"
+        );
+        assert_eq!(
+            error(&game.replace("peg\n", "peg\n".repeat(8).as_str())),
+            "bad save file: peg count out of range"
+        );
+        // A bicolor puzzle is held to the smaller limits of a new one.
+        let bicolor = format!(
+            "mode bicolor\ntarget 2\ndiscs 12\nmoves 0\nelapsed 0\npeg 11 10 9 8 7 6 5 4 3 2 1 0\n{}",
+            "peg\n".repeat(8)
+        );
+        assert_eq!(error(&bicolor), "bad save file: disc count out of range");
+        let bicolor = bicolor.replace("discs 12", "discs 8");
+        assert_eq!(error(&bicolor), "bad save file: peg count out of range");
+    }
+}
@@ -0,0 +1,126 @@
+//! Keeping the best results for each size of the classic puzzle.
+//!
+//! The scores file is a small text file with one line per size of puzzle,
+//! giving the number of discs and pegs, then the fewest moves and the shortest
+//! time (in seconds) that it has been solved in, which may come from
+//! different games:
+//!
+//! ```text
+//! 3 3 7 9
+//! 5 4 15 41
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::time::Duration;
+
+/// The best results for one size of puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Best {
+    pub moves: usize,
+    pub time: Duration,
+}
+
+/// The best results for each size of puzzle, by the number of discs and pegs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scores {
+    best: BTreeMap<(usize, usize), Best>,
+}
+
+impl Scores {
+    /// Reads the scores from the given file, which needn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the scores to the given file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut text = String::new();
+        for (&(discs, pegs), best) in &self.best {
+            text.push_str(&format!("{} {} {} {}\n", discs, pegs, best.moves, best.time.as_secs()));
+        }
+        fs::write(path, text)
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut scores = Self::default();
+        for line in text.lines() {
+            let fields = line
+                .split_whitespace()
+                .map(|field| field.parse::<u64>().map_err(|_| invalid("malformed number")))
+                .collect::<Result<Vec<u64>>>()?;
+            match fields[..] {
+                [] => continue,
+                [discs, pegs, moves, secs] => {
+                    let best = Best { moves: moves as usize, time: Duration::from_secs(secs) };
+                    scores.best.insert((discs as usize, pegs as usize), best);
+                }
+                _ => return Err(invalid("wrong number of fields")),
+            }
+        }
+        Ok(scores)
+    }
+
+    /// Records a solution of the given size of puzzle, returning whether it
+    /// was the best yet in moves or in time.
+    pub fn record(&mut self, discs: usize, pegs: usize, moves: usize, time: Duration) -> bool {
+        match self.best.get_mut(&(discs, pegs)) {
+            Some(best) if moves >= best.moves && time >= best.time => false,
+            Some(best) => {
+                best.moves = best.moves.min(moves);
+                best.time = best.time.min(time);
+                true
+            }
+            None => {
+                self.best.insert((discs, pegs), Best { moves, time });
+                true
+            }
+        }
+    }
+
+    /// Returns a table of the best results, or nothing if there are none.
+    pub fn table(&self) -> String {
+        if self.best.is_empty() {
+            return String::new();
+        }
+        let mut table = "Discs  Pegs  Moves   Time\n".to_string();
+        for (&(discs, pegs), best) in &self.best {
+            let secs = best.time.as_secs();
+            let time = format!("{}:{:02}", secs / 60, secs % 60);
+            table.push_str(&format!("{:>5}  {:>4}  {:>5}  {:>5}\n", discs, pegs, best.moves, time));
+        }
+        table
+    }
+}
+
+// Builds an error describing a corrupt scores file.
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("bad scores file: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut scores = Scores::default();
+        let secs = Duration::from_secs;
+        assert!(scores.record(3, 3, 9, secs(20)));
+        assert!(scores.record(3, 3, 7, secs(30)));
+        assert!(!scores.record(3, 3, 8, secs(25)));
+        assert!(scores.record(3, 3, 11, secs(12)));
+        assert!(scores.record(5, 4, 13, secs(75)));
+        assert_eq!(scores.table(), "Discs  Pegs  Moves   Time\n    3     3      7   0:12\n    5     4     13   1:15\n");
+        assert_eq!(Scores::parse("3 3 7 12\n\n5 4 13 75\n").unwrap(), scores);
+        assert!(Scores::parse("3 3 7\n").is_err());
+        assert!(Scores::parse("3 3 seven 12\n").is_err());
+        assert_eq!(Scores::default().table(), "");
+    }
+}
@@ -0,0 +1,372 @@
+//! This module contains the Screen type, which encapsulates all of the
+//! terminal I/O for the game.  The layout is computed from the number of discs
+//! and pegs on the board and the size of the terminal, so that any board size
+//! can be drawn without hard-coded widths.
+//!
+//! A `Style` chooses how the discs and pegs are drawn.  By default each disc is
+//! a solid block of its own color, which not every player can tell apart, so
+//! discs can also be filled with patterns, which differ from one size to the
+//! next (or, in the bicolor puzzle, between the colors).  The board can also be
+//! drawn in nothing but ASCII, for terminals that lack the block and
+//! box-drawing characters.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+use tui_common::color::{self, GRAY, LT_BLUE, LT_CYAN, LT_GREEN, LT_MAGENTA, LT_RED, LT_WHITE, LT_YELLOW, WHITE};
+use tui_common::{Key, Terminal};
+
+use crate::{Board, Move};
+
+/// How the board is drawn, as chosen on the command line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Fill each disc with a pattern as well as a color, and use colors that
+    /// can be told apart with the common kinds of colorblindness.
+    pub patterns: bool,
+    /// Draw with nothing but ASCII characters.
+    pub ascii: bool,
+}
+
+/// The characters that the board is drawn with, in Unicode or in ASCII.
+struct Glyphs {
+    /// The fill of a disc when patterns aren't used.
+    solid: char,
+    /// The fills of the discs when they are, taken in turn by size or color.
+    patterns: [char; 4],
+    peg_top: &'static str,
+    peg: &'static str,
+    base: &'static str,
+    help: &'static str,
+}
+
+/// The colors of the discs of the classic puzzle, taken in turn by size.
+const DISC_COLORS: [u8; 7] = [
+    color::RED,
+    color::GREEN,
+    color::YELLOW,
+    color::BLUE,
+    color::MAGENTA,
+    color::CYAN,
+    color::WHITE,
+];
+
+const UNICODE: Glyphs = Glyphs {
+    solid: '▓',
+    patterns: ['▓', '░', '█', '▒'],
+    peg_top: "╭╮",
+    peg: "││",
+    base: "▔",
+    help: "←/→ peg   Enter lift/drop   Esc cancel   h hint   a solve   s save   l load   q quit",
+};
+
+const ASCII: Glyphs = Glyphs {
+    solid: '#',
+    patterns: ['#', '=', '%', ':'],
+    peg_top: "..",
+    peg: "||",
+    base: "-",
+    help: "<-/-> peg   Enter lift/drop   Esc cancel   h hint   a solve   s save   l load   q quit",
+};
+
+impl Style {
+    fn glyphs(self) -> &'static Glyphs {
+        if self.ascii {
+            &ASCII
+        } else {
+            &UNICODE
+        }
+    }
+
+    /// Returns the color used to draw the given disc: its own color in the
+    /// bicolor puzzle, or one chosen by size otherwise.  With patterns, the
+    /// bicolor puzzle's colors are blue and yellow rather than red and blue.
+    fn disc_color(self, board: &Board, disc: usize) -> u8 {
+        match board.color(disc) {
+            Some(0) if self.patterns => LT_YELLOW,
+            Some(0) => LT_RED,
+            Some(_) => LT_BLUE,
+            None => DISC_COLORS[board.size(disc) % DISC_COLORS.len()],
+        }
+    }
+
+    /// Returns the text of the given disc, at the given half-width.
+    fn disc_text(self, board: &Board, disc: usize, half: usize) -> String {
+        let glyphs = self.glyphs();
+        let fill = if self.patterns {
+            let patterns = &glyphs.patterns;
+            let index = board.color(disc).unwrap_or_else(|| board.size(disc));
+            patterns[index % patterns.len()]
+        } else {
+            glyphs.solid
+        };
+        fill.to_string().repeat(half * 2)
+    }
+}
+
+/// The keys the player may press while choosing a move.
+pub enum Input {
+    Left,
+    Right,
+    Select,
+    Cancel,
+    Post(usize),
+    Solve,
+    Hint,
+    Save,
+    Load,
+    Quit,
+}
+
+/// Selection tracks which peg the cursor is on and, if the player has lifted
+/// a disc, which peg it was lifted from.  The lifted disc stays on the Board
+/// until it is dropped; it is only drawn as hovering.
+#[derive(Default)]
+pub struct Selection {
+    pub cursor: usize,
+    pub lifted: Option<usize>,
+}
+
+/// Screen encapsulates the display and input for the game.  Like the one in
+/// reversi, it borrows a Board as needed rather than owning one.
+pub struct Screen {
+    term: Terminal,
+    style: Style,
+}
+
+/// Layout holds the dimensions used to draw a particular board on a particular
+/// terminal.
+struct Layout {
+    /// Width of the column used for each peg.
+    col_width: usize,
+    /// Number of different disc sizes.
+    sizes: usize,
+}
+
+impl Layout {
+    /// Computes the layout for the given board, shrinking the columns if the
+    /// terminal is too narrow to draw every disc at full width.
+    fn new(board: &Board, term_width: usize) -> Self {
+        let full_width = board.sizes() * 2 + 4;
+        let fit_width = term_width / board.pegs();
+        Self {
+            col_width: full_width.min(fit_width).max(4),
+            sizes: board.sizes(),
+        }
+    }
+
+    /// Returns the x coordinate just left of the center of the given peg.
+    fn center(&self, peg: usize) -> usize {
+        peg * self.col_width + self.col_width / 2
+    }
+
+    /// Returns the half-width of a disc of the given size, scaled so that the
+    /// largest disc leaves one blank column on either side of the peg's column.
+    fn half_width(&self, size: usize) -> usize {
+        let max_half = (self.col_width - 2) / 2;
+        if self.sizes <= 1 {
+            1
+        } else {
+            1 + size * (max_half - 1) / (self.sizes - 1)
+        }
+    }
+}
+
+impl Screen {
+    /// Rows above the pegs, reserved for a hovering disc.
+    const TOP: usize = 1;
+
+    /// Creates a new Screen instance, which draws in the given style.
+    pub fn new(style: Style) -> Self {
+        Self {
+            term: Terminal::new(),
+            style,
+        }
+    }
+
+    /// Draws the board, followed by the move count, the elapsed time, and a
+    /// status line containing the given message (which may be empty).  If a
+    /// selection is given, the peg under the cursor is highlighted and any
+    /// lifted disc is drawn hovering above it.
+    pub fn draw_board(
+        &mut self,
+        board: &Board,
+        selection: Option<&Selection>,
+        elapsed: Duration,
+        message: &str,
+    ) -> io::Result<()> {
+        let (_, term_width) = self.term.size();
+        let layout = Layout::new(board, term_width);
+        let n = board.discs();
+        let cursor = selection.map(|selection| selection.cursor);
+        let lifted = selection.and_then(|selection| selection.lifted);
+
+        self.term.clear_screen()?;
+        for peg in 0..board.pegs() {
+            let color = if cursor == Some(peg) { LT_GREEN } else { LT_WHITE };
+            self.draw_peg(&layout, peg, n, color)?;
+        }
+
+        for peg in 0..board.pegs() {
+            let stack = board.stack(peg);
+            for (elem, &disc) in stack.iter().enumerate() {
+                let half = layout.half_width(board.size(disc));
+                let (x, y) = if elem + 1 == stack.len() && lifted == Some(peg) {
+                    (layout.center(cursor.unwrap_or(peg)) + 1 - half, 0)
+                } else {
+                    (layout.center(peg) + 1 - half, Self::TOP + n - elem)
+                };
+                let text = self.style.disc_text(board, disc, half);
+                self.term.draw_text(x, y, self.style.disc_color(board, disc), text.as_str())?;
+            }
+        }
+
+        let y = Self::TOP + n + 1;
+        let base = self.style.glyphs().base.repeat(layout.col_width * board.pegs());
+        self.term.draw_text(0, y, LT_WHITE, base.as_str())?;
+        for peg in 0..board.pegs() {
+            let (color, label) = if cursor == Some(peg) {
+                (LT_GREEN, format!("[{}]", peg + 1))
+            } else if peg == board.target() {
+                (LT_YELLOW, format!(" {} ", peg + 1))
+            } else {
+                (GRAY, format!(" {} ", peg + 1))
+            };
+            self.term.draw_text(layout.center(peg), y + 1, color, label.as_str())?;
+        }
+
+        self.term.draw_text(0, y + 2, WHITE, "Moves: ")?;
+        self.term.draw_text(7, y + 2, LT_WHITE, board.moves().to_string().as_str())?;
+        self.term.draw_text(14, y + 2, WHITE, "Time: ")?;
+        self.term.draw_text(20, y + 2, LT_WHITE, format_time(elapsed).as_str())?;
+
+        self.term.draw_text(0, y + 3, GRAY, self.style.glyphs().help)?;
+        self.term.draw_text(0, y + 4, LT_YELLOW, message)?;
+        self.term.goto_xy(0, y + 5)
+    }
+
+    /// Draws a panel of text to the right of the board as last drawn, one
+    /// line per row from the top.  There is room for as many lines as there
+    /// are discs, plus four.
+    pub fn draw_panel(&mut self, board: &Board, lines: &[String]) -> io::Result<()> {
+        let (_, term_width) = self.term.size();
+        let layout = Layout::new(board, term_width);
+        let x = layout.col_width * board.pegs() + 2;
+        for (y, line) in lines.iter().enumerate() {
+            let color = if y + 1 == lines.len() { LT_CYAN } else { WHITE };
+            self.term.draw_text(x, y, color, line.as_str())?;
+        }
+        self.term.goto_xy(0, Self::TOP + board.discs() + 6)
+    }
+
+    /// Draws the given peg, which is tall enough for `n` discs, in the given
+    /// color.
+    fn draw_peg(&mut self, layout: &Layout, peg: usize, n: usize, color: u8) -> io::Result<()> {
+        let x = layout.center(peg);
+        let glyphs = self.style.glyphs();
+        self.term.draw_text(x, Self::TOP, color, glyphs.peg_top)?;
+        for row in 0..n {
+            self.term.draw_text(x, Self::TOP + row + 1, color, glyphs.peg)?;
+        }
+        Ok(())
+    }
+
+    /// Flashes the two pegs of a move on the board as last drawn, the peg to
+    /// move from in cyan and the one to move to in magenta.
+    pub fn flash_move(&mut self, board: &Board, move_: Move) -> io::Result<()> {
+        let (_, term_width) = self.term.size();
+        let layout = Layout::new(board, term_width);
+        for _ in 0..3 {
+            self.draw_peg(&layout, move_.from, board.discs(), LT_CYAN)?;
+            self.draw_peg(&layout, move_.to, board.discs(), LT_MAGENTA)?;
+            thread::sleep(Duration::from_millis(250));
+            self.draw_peg(&layout, move_.from, board.discs(), LT_WHITE)?;
+            self.draw_peg(&layout, move_.to, board.discs(), LT_WHITE)?;
+            thread::sleep(Duration::from_millis(150));
+        }
+        Ok(())
+    }
+
+    /// Reads a single command key from the player, ignoring anything else.
+    /// The number keys jump straight to a peg and select it.
+    pub fn read_input(&mut self, pegs: usize) -> Input {
+        loop {
+            match self.term.read_key().expect("Terminal error") {
+                Key::ArrowLeft => return Input::Left,
+                Key::ArrowRight => return Input::Right,
+                Key::Enter | Key::Char(' ') => return Input::Select,
+                Key::Escape => return Input::Cancel,
+                Key::Char(ch) => {
+                    if let Some(digit) = ch.to_digit(10) {
+                        if (1..=pegs as u32).contains(&digit) {
+                            return Input::Post((digit - 1) as usize);
+                        }
+                    } else if ch == 'a' {
+                        return Input::Solve;
+                    } else if ch == 'h' {
+                        return Input::Hint;
+                    } else if ch == 's' {
+                        return Input::Save;
+                    } else if ch == 'l' {
+                        return Input::Load;
+                    } else if ch == 'q' {
+                        return Input::Quit;
+                    }
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Reports the end of the game, whether the player solved the puzzle or
+    /// watched the computer do it.
+    pub fn report_win(&mut self, board: &Board, elapsed: Duration, by_computer: bool) -> io::Result<()> {
+        let text = if by_computer {
+            format!("Solved in {} moves.", board.moves())
+        } else {
+            format!("You won in {} moves ({})!", board.moves(), format_time(elapsed))
+        };
+        self.term.draw_text(0, Self::TOP + board.discs() + 5, LT_WHITE, text.as_str())?;
+        self.term.write_line("")
+    }
+}
+
+/// Formats a duration as minutes and seconds.
+fn format_time(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style() {
+        let board = Board::new(3, 3);
+        let plain = Style::default();
+        assert_eq!(plain.disc_text(&board, 0, 2), "▓▓▓▓");
+        assert_eq!(plain.disc_text(&board, 2, 1), "▓▓");
+
+        // Each size has a pattern that differs from the next.
+        let patterns = Style { patterns: true, ascii: false };
+        let texts: Vec<String> = (0..3).map(|disc| patterns.disc_text(&board, disc, 1)).collect();
+        assert_eq!(texts, ["▓▓", "░░", "██"]);
+
+        let ascii = Style { patterns: true, ascii: true };
+        assert_eq!(ascii.disc_text(&board, 1, 2), "====");
+        assert!(ASCII.help.is_ascii() && ASCII.peg_top.is_ascii() && ASCII.peg.is_ascii());
+        assert_eq!(Style { patterns: false, ascii: true }.disc_text(&board, 1, 1), "##");
+    }
+
+    #[test]
+    fn test_bicolor_style() {
+        let board = Board::bicolor(2, 3, 2);
+        let patterns = Style { patterns: true, ascii: false };
+        // The two discs of each size differ in pattern, and not only color.
+        assert_ne!(patterns.disc_text(&board, 0, 1), patterns.disc_text(&board, 1, 1));
+        assert_eq!(patterns.disc_text(&board, 0, 1), patterns.disc_text(&board, 2, 1));
+        assert_eq!(patterns.disc_color(&board, 0), LT_YELLOW);
+        assert_eq!(Style::default().disc_color(&board, 0), LT_RED);
+    }
+}
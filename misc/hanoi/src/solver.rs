@@ -0,0 +1,213 @@
+//! This module computes solutions to the puzzle.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Board, Goal, Mode, Move, Step};
+
+/// The discs on each peg, which identifies a position during a search.
+type Stacks = Vec<Vec<usize>>;
+
+/// The most positions that a puzzle may have for `next_move` to search them
+/// all, which keeps a hint to a fraction of a second.
+const SEARCH_POSITIONS: f64 = 100_000.0;
+
+/// Returns the first move of a shortest solution from the current position,
+/// or None if the puzzle is already solved.  Classic puzzles with three pegs
+/// are gathered, which is optimal; anything else is searched position by
+/// position when there are few enough positions, since Frame–Stewart towers
+/// are not always the shortest way from a scattered position.  Larger classic
+/// puzzles fall back to gathering.
+pub fn next_move(board: &Board) -> Option<Move> {
+    if board.is_solved() {
+        return None;
+    }
+    let positions = (board.pegs() as f64).powi(board.discs() as i32);
+    let moves = if board.mode() == Mode::Classic && (board.pegs() == 3 || positions > SEARCH_POSITIONS) {
+        gather(board)
+    } else {
+        search(board)
+    };
+    moves.first().copied()
+}
+
+/// Computes a solution for a classic puzzle from any legal position.  The
+/// largest disc that is not yet on the target peg must move there exactly
+/// once, so we first gather every smaller disc onto a spare peg, move it, and
+/// then move the smaller discs on top of it as a single tower using the
+/// Frame–Stewart algorithm.  Discs that already form a tower are moved with
+/// Frame–Stewart directly.  With three pegs this is optimal, and from the
+/// standard starting position it is exactly the Frame–Stewart solution.
+pub fn gather(board: &Board) -> Vec<Move> {
+    let mut positions = vec![0; board.discs()];
+    for peg in 0..board.pegs() {
+        for &disc in board.stack(peg) {
+            positions[disc] = peg;
+        }
+    }
+
+    let mut solver = Solver {
+        costs: HashMap::new(),
+        positions,
+        pegs: board.pegs(),
+        moves: vec![],
+    };
+    solver.gather(board.discs(), board.target());
+    solver.moves
+}
+
+/// Computes a shortest solution by breadth-first search over positions.  This
+/// works for any rules, but the number of positions grows exponentially, so
+/// it is only used for the (small) bicolor puzzles.
+pub fn search(board: &Board) -> Vec<Move> {
+    // Maps each position reached to the position and move that reached it.
+    let mut previous: HashMap<Stacks, Option<(Stacks, Move)>> = HashMap::new();
+    let mut queue = VecDeque::from([board.clone()]);
+    previous.insert(board.stacks.clone(), None);
+
+    while let Some(current) = queue.pop_front() {
+        if current.is_solved() {
+            let mut moves = vec![];
+            let mut stacks = current.stacks;
+            while let Some(Some((prior, move_))) = previous.remove(&stacks) {
+                moves.push(move_);
+                stacks = prior;
+            }
+            moves.reverse();
+            return moves;
+        }
+
+        for move_ in current.legal_moves() {
+            let mut next = current.clone();
+            next.apply(move_).unwrap();
+            if !previous.contains_key(&next.stacks) {
+                previous.insert(next.stacks.clone(), Some((current.stacks.clone(), move_)));
+                queue.push_back(next);
+            }
+        }
+    }
+    vec![]
+}
+
+/// Returns the steps of the recursive strategy for the given goal on three
+/// pegs: move every disc but the largest out of the way to the third peg,
+/// move the largest, and move the rest back on top of it, each of those
+/// smaller towers being moved the same way.
+pub fn lesson(goal: Goal) -> Vec<Step> {
+    let mut steps = vec![];
+    teach(goal, &mut vec![], &mut steps);
+    steps
+}
+
+/// Adds the steps for the given goal, which is part of the given goals.
+fn teach(goal: Goal, goals: &mut Vec<Goal>, steps: &mut Vec<Step>) {
+    if goal.discs == 0 {
+        return;
+    }
+    let via = 3 - goal.from - goal.to;
+    goals.push(goal);
+    teach(Goal { discs: goal.discs - 1, from: goal.from, to: via }, goals, steps);
+    steps.push(Step { move_: Move { from: goal.from, to: goal.to }, goals: goals.clone() });
+    teach(Goal { discs: goal.discs - 1, from: via, to: goal.to }, goals, steps);
+    goals.pop();
+}
+
+/// Holds the working state of the solver: the memoized Frame–Stewart move
+/// counts for each (discs, pegs) pair, the peg that each disc is on, and the
+/// moves generated so far.
+struct Solver {
+    costs: HashMap<(usize, usize), u64>,
+    positions: Vec<usize>,
+    pegs: usize,
+    moves: Vec<Move>,
+}
+
+impl Solver {
+    /// Returns the minimum number of moves needed to transfer `n` discs using
+    /// `p` pegs.  With only two pegs, anything more than one disc is impossible.
+    fn cost(&mut self, n: usize, p: usize) -> u64 {
+        if n <= 1 {
+            return n as u64;
+        } else if p < 3 {
+            return u64::MAX / 4;
+        } else if let Some(&cost) = self.costs.get(&(n, p)) {
+            return cost;
+        }
+        let k = self.split(n, p);
+        let cost = 2 * self.cost(k, p) + self.cost(n - k, p - 1);
+        self.costs.insert((n, p), cost);
+        cost
+    }
+
+    /// Returns the number of discs `k` to park on an intermediate peg so that
+    /// 2*cost(k, p) + cost(n-k, p-1) is minimized.
+    fn split(&mut self, n: usize, p: usize) -> usize {
+        let mut best = n - 1;
+        let mut best_cost = u64::MAX;
+        for k in 1..n {
+            let cost = 2 * self.cost(k, p) + self.cost(n - k, p - 1);
+            if cost < best_cost {
+                best = k;
+                best_cost = cost;
+            }
+        }
+        best
+    }
+
+    /// Records a move of the smallest disc on `from`, which must be its top.
+    fn push(&mut self, from: usize, to: usize) {
+        let disc = self.positions.iter().position(|&peg| peg == from).unwrap();
+        self.positions[disc] = to;
+        self.moves.push(Move { from, to });
+    }
+
+    /// Gathers the `n` smallest discs, which may be scattered anywhere, onto
+    /// peg `to`.  Every larger disc is assumed to be out of the way.
+    fn gather(&mut self, n: usize, to: usize) {
+        if n == 0 {
+            return;
+        }
+        let largest = n - 1;
+        let from = self.positions[largest];
+        if from == to {
+            self.gather(n - 1, to);
+            return;
+        }
+
+        if self.positions[..n].iter().all(|&peg| peg == from) {
+            let spares: Vec<usize> = (0..self.pegs).filter(|&peg| peg != from && peg != to).collect();
+            self.transfer(n, from, to, &spares);
+            return;
+        }
+
+        let mid = (0..self.pegs).find(|&peg| peg != from && peg != to).unwrap();
+        self.gather(n - 1, mid);
+        self.push(from, to);
+        let spares: Vec<usize> = (0..self.pegs).filter(|&peg| peg != mid && peg != to).collect();
+        self.transfer(n - 1, mid, to, &spares);
+    }
+
+    /// Moves the top `n` discs from peg `from` to peg `to` as a tower, using
+    /// the `spares` pegs as temporary storage.
+    fn transfer(&mut self, n: usize, from: usize, to: usize, spares: &[usize]) {
+        if n == 0 {
+            return;
+        } else if n == 1 {
+            self.push(from, to);
+            return;
+        }
+
+        let k = self.split(n, spares.len() + 2);
+        let mid = spares[0];
+        let rest = &spares[1..];
+
+        let mut parked_spares = rest.to_vec();
+        parked_spares.push(to);
+        self.transfer(k, from, mid, &parked_spares);
+
+        self.transfer(n - k, from, to, rest);
+
+        let mut unparked_spares = rest.to_vec();
+        unparked_spares.push(from);
+        self.transfer(k, mid, to, &unparked_spares);
+    }
+}
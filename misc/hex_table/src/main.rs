@@ -1,15 +1,276 @@
-fn main() {
-    print!("    ");
-    for b in 0x0..=0xf {
-        print!("{:3x}", b);
+use clap::{Parser, ValueEnum};
+use console::{style, StyledObject, Term};
+use std::ops::Range;
+
+// The operations that can fill the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operation {
+    Multiply,
+    Add,
+    Xor,
+    And,
+    Or,
+}
+
+impl Operation {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "multiply" | "mul" | "*" => Some(Operation::Multiply),
+            "add" | "+" => Some(Operation::Add),
+            "xor" | "^" => Some(Operation::Xor),
+            "and" | "&" => Some(Operation::And),
+            "or" | "|" => Some(Operation::Or),
+            _ => None,
+        }
+    }
+
+    // The operands are limited to 32 bits, so no result can overflow.
+    fn apply(self, a: u32, b: u32) -> u64 {
+        let (a, b) = (a as u64, b as u64);
+        match self {
+            Operation::Multiply => a * b,
+            Operation::Add => a + b,
+            Operation::Xor => a ^ b,
+            Operation::And => a & b,
+            Operation::Or => a | b,
+        }
+    }
+}
+
+// When to color the table.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Prints a table of an operation on two ranges of numbers, in any base.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Options {
+    /// The base to print numbers in, from 2 to 36
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u32).range(2..=36))]
+    base: u32,
+    /// The row operands, as START..END or START..=END
+    #[arg(long, default_value = "0..16", value_parser = parse_range)]
+    rows: Range<u32>,
+    /// The column operands, as START..END or START..=END
+    #[arg(long, default_value = "0..16", value_parser = parse_range)]
+    cols: Range<u32>,
+    /// The operation: one of multiply, add, xor, and, or
+    #[arg(long = "op", value_name = "OP", default_value = "multiply", value_parser = parse_operation)]
+    operation: Operation,
+    /// When to color the table
+    #[arg(long, value_enum, default_value_t = Color::Auto)]
+    color: Color,
+    /// The width to wrap the table to, instead of the terminal's
+    #[arg(long)]
+    width: Option<usize>,
+}
+
+fn parse_operation(text: &str) -> Result<Operation, String> {
+    Operation::parse(text).ok_or_else(|| "expected one of multiply, add, xor, and, or".to_string())
+}
+
+// Parses a range of operands, either `START..END` (which excludes END, as in
+// Rust) or `START..=END`.
+fn parse_range(text: &str) -> Result<Range<u32>, String> {
+    let invalid = || "expected a range such as 0..16".to_string();
+    let (start, end) = text.split_once("..").ok_or_else(invalid)?;
+    let start: u32 = start.parse().map_err(|_| invalid())?;
+    let end: u32 = match end.strip_prefix('=') {
+        Some(end) => end
+            .parse::<u32>()
+            .ok()
+            .and_then(|end| end.checked_add(1))
+            .ok_or_else(invalid)?,
+        None => end.parse().map_err(|_| invalid())?,
+    };
+
+    if start >= end {
+        Err("the range is empty".to_string())
+    } else {
+        Ok(start..end)
+    }
+}
+
+// Formats a number in the given base, using lowercase letters for the digits
+// past 9.
+fn to_base(mut n: u64, base: u32) -> String {
+    let mut digits = vec![];
+    loop {
+        digits.push(char::from_digit((n % base as u64) as u32, base).unwrap());
+        n /= base as u64;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+// The widths used to print the table: each cell is wide enough for the
+// largest value (or column heading) plus a space, and each row label for the
+// largest row number.
+struct Layout {
+    cell: usize,
+    label: usize,
+}
+
+impl Layout {
+    fn new(options: &Options) -> Self {
+        let base = options.base;
+        let max_value = options
+            .rows
+            .clone()
+            .flat_map(|a| {
+                options
+                    .cols
+                    .clone()
+                    .map(move |b| options.operation.apply(a, b))
+            })
+            .max()
+            .unwrap_or(0);
+        let max_col = options.cols.end as u64 - 1;
+        let max_row = options.rows.end as u64 - 1;
+        Layout {
+            cell: to_base(max_value, base)
+                .len()
+                .max(to_base(max_col, base).len())
+                + 1,
+            label: to_base(max_row, base).len() + 1,
+        }
+    }
+
+    // Returns how many columns fit in the given width, always at least one.
+    fn columns_within(&self, width: usize) -> usize {
+        (width.saturating_sub(self.label + 2) / self.cell).max(1)
+    }
+}
+
+// Styles a cell's text, which has already been padded: powers of two stand
+// out, and zeros fade into the background.
+fn style_cell(text: String, value: u64) -> StyledObject<String> {
+    if value == 0 {
+        style(text).dim()
+    } else if value.is_power_of_two() {
+        style(text).yellow().bold()
+    } else {
+        style(text)
     }
-    println!();
-    println!("   +-------------------------------------------------");
-    for a in 0x0..=0xf {
-        print!("{:2x} |", a);
-        for b in 0x0..=0xf {
-            print!("{:3x}", a*b);
+}
+
+// Prints the table.  If it is wider than `width`, the columns are split into
+// blocks that each fit, which are printed one after another.
+fn print_table(options: &Options, width: Option<usize>) {
+    let base = options.base;
+    let layout = Layout::new(options);
+    let per_block = width.map_or(options.cols.len(), |width| layout.columns_within(width));
+    let cols: Vec<u32> = options.cols.clone().collect();
+
+    for (i, block) in cols.chunks(per_block).enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print!("{:w$}", "", w = layout.label + 2);
+        for &b in block {
+            let text = format!("{:>w$}", to_base(b as u64, base), w = layout.cell);
+            print!("{}", style(text).cyan());
         }
         println!();
+        println!(
+            "{:w$}+{}",
+            "",
+            "-".repeat(layout.cell * block.len() + 1),
+            w = layout.label + 1
+        );
+        for a in options.rows.clone() {
+            let text = format!("{:>w$}", to_base(a as u64, base), w = layout.label);
+            print!("{} |", style(text).cyan());
+            for &b in block {
+                let value = options.operation.apply(a, b);
+                let text = format!("{:>w$}", to_base(value, base), w = layout.cell);
+                print!("{}", style_cell(text, value));
+            }
+            println!();
+        }
+    }
+}
+
+fn main() {
+    let options = Options::parse();
+
+    // The console crate already turns colors off when stdout isn't a
+    // terminal, so only an explicit choice needs to override it.
+    match options.color {
+        Color::Always => console::set_colors_enabled(true),
+        Color::Never => console::set_colors_enabled(false),
+        Color::Auto => {}
+    }
+
+    // Wrap to the terminal, but never when writing to a file or a pipe,
+    // where the terminal has no size.
+    let width = options
+        .width
+        .or_else(|| Term::stdout().size_checked().map(|(_, cols)| cols as usize));
+    print_table(&options, width);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse_args(text: &str) -> Result<Options, clap::Error> {
+        Options::try_parse_from(["hex_table"].into_iter().chain(text.split_whitespace()))
+    }
+
+    #[test]
+    fn test_to_base() {
+        assert_eq!(to_base(0, 16), "0");
+        assert_eq!(to_base(255, 16), "ff");
+        assert_eq!(to_base(5, 2), "101");
+        assert_eq!(to_base(35, 36), "z");
+        assert_eq!(to_base(u64::MAX, 36), "3w5e11264sgsf");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("0..32"), Ok(0..32));
+        assert_eq!(parse_range("1..=12"), Ok(1..13));
+        assert!(parse_range("5..5").is_err());
+        assert!(parse_range("0-32").is_err());
+        assert!(parse_range("0..=4294967295").is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        Options::command().debug_assert();
+
+        let options = parse_args("--base 2 --rows 1..=4 --op xor").unwrap();
+        assert_eq!(options.base, 2);
+        assert_eq!(options.rows, 1..5);
+        assert_eq!(options.cols, 0..16);
+        assert_eq!(options.operation, Operation::Xor);
+
+        assert!(parse_args("--base 1").is_err());
+        assert!(parse_args("--base 37").is_err());
+        assert!(parse_args("--op divide").is_err());
+        assert!(parse_args("--cols").is_err());
+
+        let options = parse_args("--color never --width 80").unwrap();
+        assert_eq!(options.color, Color::Never);
+        assert_eq!(options.width, Some(80));
+        assert!(parse_args("--color sometimes").is_err());
+    }
+
+    #[test]
+    fn test_columns_within() {
+        let options = parse_args("").unwrap();
+        let layout = Layout::new(&options);
+        assert_eq!((layout.cell, layout.label), (3, 2));
+        assert_eq!(layout.columns_within(52), 16);
+        assert_eq!(layout.columns_within(40), 12);
+        assert_eq!(layout.columns_within(3), 1);
     }
 }
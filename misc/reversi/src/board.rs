@@ -1,14 +1,83 @@
 //! This module contains the Board type, which represents the state of the game
 //! itself, with all I/O elements kept separate in the `screen.rs` module.
 
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use crate::profile::EvalParams;
+
+/// The rules that a game is played by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// The modern rules, which start with two discs of each player's in the
+    /// center and give the game to whoever has the most discs at the end.
+    #[default]
+    Othello,
+    /// The original rules, which start with an empty board: the players
+    /// take turns placing the first four discs in the center squares, as
+    /// they please and without flipping anything.
+    Reversi,
+    /// The modern start, but whoever has the fewest discs at the end wins.
+    Anti,
+}
+
+impl Variant {
+    /// Every variant, in the order that they are listed.
+    pub const ALL: [Variant; 3] = [Variant::Othello, Variant::Reversi, Variant::Anti];
+
+    /// Looks up a variant by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|variant| variant.name() == name)
+    }
+
+    /// Returns the name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Othello => "othello",
+            Variant::Reversi => "reversi",
+            Variant::Anti => "anti",
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Board represents the state of the board.  For ease of coding, we use
 /// a single-dimensional array of 64 elements, each of which is 0 for empty,
 /// 1 for the human player, or 2 for the computer player.
 ///
 /// We keep track of the score explicitly simply for the sake of efficiency.
+/// The board also knows the variant of the rules that it is played by.
+///
+/// Boards are small enough to copy freely, which lets the search try out
+/// moves on copies and hand a board to a background thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Board {
     squares: [[u8; 8]; 8],
     scores: [i32; 2],
+    variant: Variant,
+}
+
+/// Ray describes what a move would do along the line of squares in one
+/// direction from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ray {
+    /// The move flips this many of the opponent's pieces.
+    Flips(i32),
+    /// The move is at the edge of the board.
+    Edge,
+    /// The next square is empty.
+    Empty,
+    /// The next square holds one of the player's own pieces.
+    Own,
+    /// This many of the opponent's pieces come next, but there is no piece of
+    /// the player's beyond them to close the line.
+    Open(i32),
 }
 
 impl Board {
@@ -28,8 +97,8 @@ impl Board {
         [1, 1],   // down & right
     ];
 
-    /// The weights to assign to moves to each space on the board.  These
-    /// values were assigned by rough experience in game play.
+    /// The weights to assign to each space on the board.  These values were
+    /// assigned by rough experience in game play.
     const VALUES: [[i32; 8]; 8] = [
         [10, 2, 8, 6, 6, 8, 2, 10],
         [2, 1, 3, 4, 4, 3, 1, 2],
@@ -43,44 +112,81 @@ impl Board {
 
     /// Returns a new Board with the initial pieces placed.
     pub fn new() -> Self {
-        let mut board = Self {
-            squares: [[Self::EMPTY; 8]; 8],
-            scores: [2, 2],
-        };
-        board.set(3, 3, Self::HUMAN);
-        board.set(4, 4, Self::HUMAN);
-        board.set(3, 4, Self::COMPUTER);
-        board.set(4, 3, Self::COMPUTER);
+        Self::start(Variant::Othello)
+    }
+
+    /// Returns a new Board for the given variant, with the initial pieces
+    /// placed if it has any.
+    pub fn start(variant: Variant) -> Self {
+        let mut board = Self::empty();
+        board.variant = variant;
+        if variant != Variant::Reversi {
+            board.put(3, 3, Self::HUMAN);
+            board.put(4, 4, Self::HUMAN);
+            board.put(3, 4, Self::COMPUTER);
+            board.put(4, 3, Self::COMPUTER);
+        }
         board
     }
 
+    /// The corners, in the order in which they are given as a handicap.
+    const CORNERS: [(i32, i32); 4] = [(0, 0), (7, 7), (7, 0), (0, 7)];
+
+    /// Returns a Board with no pieces on it, from which positions can be
+    /// composed with `put`.
+    pub fn empty() -> Self {
+        Self {
+            squares: [[Self::EMPTY; 8]; 8],
+            scores: [0, 0],
+            variant: Variant::Othello,
+        }
+    }
+
+    /// Returns the variant of the rules that the board is played by.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Changes the variant of the rules that the board is played by, as for
+    /// a composed position.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// Returns whether any of the four center squares is still empty.
+    fn center_open(&self) -> bool {
+        (3..5).any(|row| (3..5).any(|col| self.get(col, row) == Self::EMPTY))
+    }
+
+    /// Returns whether the game is in the opening of the original rules,
+    /// while any of the center squares is still empty, when the only moves
+    /// are to them.
+    pub fn in_opening(&self) -> bool {
+        self.variant == Variant::Reversi && self.center_open()
+    }
+
+    /// Checks that a composed position can be played from with the given
+    /// player to move.  Every game fills the four center squares before
+    /// anything else, and there must be a move left for one of the players.
+    pub fn check_setup(&self, player: u8) -> Result<(), String> {
+        if self.center_open() {
+            Err("The center squares must be filled.".to_string())
+        } else if self.game_over() {
+            Err("Neither player can move.".to_string())
+        } else if self.get_moves(player).is_empty() {
+            Err("The player to move has to pass.".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
     /// Counts the number of pieces that a player would flip in the given
     /// direction if they were to move to (col, row).  We assume that we have
     /// already checked that the space is empty.
     fn count_in_dir(&self, col: i32, row: i32, player: u8, dir: usize) -> i32 {
-        let other = player ^ 0b11; // 1 -> 2, 2 -> 1
-        let mut col = col;
-        let mut row = row;
-        let mut found = 0;
-
-        loop {
-            col += Self::OFFSETS[dir][0];
-            if !(0..8).contains(&col) {
-                return 0;
-            }
-            row += Self::OFFSETS[dir][1];
-            if !(0..8).contains(&row) {
-                return 0;
-            }
-
-            let val = self.get(col, row);
-            if val == player {
-                return found;
-            } else if val == other {
-                found += 1;
-            } else {
-                return 0;
-            }
+        match self.look_in_dir(col, row, player, dir) {
+            Ray::Flips(count) => count,
+            _ => 0,
         }
     }
 
@@ -98,16 +204,128 @@ impl Board {
         }
     }
 
+    /// Returns whether the given player may move to (col, row): to an empty
+    /// center square in the opening, and otherwise to a square from which
+    /// they flip something.
+    pub fn is_legal(&self, col: i32, row: i32, player: u8) -> bool {
+        if self.in_opening() {
+            (3..5).contains(&col) && (3..5).contains(&row) && self.get(col, row) == Self::EMPTY
+        } else {
+            self.count_move(col, row, player) > 0
+        }
+    }
+
     /// Makes the given move for the given player and adjusts the scores
-    /// accordingly.  We assume that the move is already known to be valid.
-    pub fn do_move(&mut self, col: i32, row: i32, player: u8) {
+    /// accordingly, returning the squares of the pieces that were flipped,
+    /// one line at a time and outward from the move along each.  We assume
+    /// that the move is already known to be valid.
+    pub fn do_move(&mut self, col: i32, row: i32, player: u8) -> Vec<(i32, i32)> {
         self.set(col, row, player);
         self.scores[(player - 1) as usize] += 1;
+        let mut flipped = Vec::new();
         for dir in 0..8 {
             if self.count_in_dir(col, row, player, dir) > 0 {
-                self.flip_dir(col, row, player, dir);
+                self.flip_dir(col, row, player, dir, &mut flipped);
+            }
+        }
+        flipped
+    }
+
+    /// Looks along the line of squares from (col, row) in the given direction
+    /// for the pieces that the player would flip by moving there.  We assume
+    /// that we have already checked that the space is empty.
+    fn look_in_dir(&self, col: i32, row: i32, player: u8, dir: usize) -> Ray {
+        let other = player ^ 0b11; // 1 -> 2, 2 -> 1
+        let mut col = col;
+        let mut row = row;
+        let mut found = 0;
+
+        loop {
+            col += Self::OFFSETS[dir][0];
+            row += Self::OFFSETS[dir][1];
+            let on_board = (0..8).contains(&col) && (0..8).contains(&row);
+            let val = if on_board {
+                self.get(col, row)
+            } else {
+                Self::EMPTY
+            };
+            if val == other {
+                found += 1;
+                continue;
+            }
+            return match (found, on_board, val == player) {
+                (0, false, _) => Ray::Edge,
+                (0, true, true) => Ray::Own,
+                (0, true, false) => Ray::Empty,
+                (_, true, true) => Ray::Flips(found),
+                _ => Ray::Open(found),
+            };
+        }
+    }
+
+    /// Returns the number of empty squares.
+    pub fn empties(&self) -> i32 {
+        64 - self.scores[0] - self.scores[1]
+    }
+
+    /// Scores the board from the point of view of the given player, as the
+    /// weights of the squares, the pieces, and the moves that they have less
+    /// those that their opponent has, each weighted as the params say, and
+    /// with any noise added.  When the fewest discs win, everything that is
+    /// good for a player under the usual rules counts against them.
+    pub fn evaluate(&self, player: u8, params: &EvalParams) -> i32 {
+        let other = player ^ 0b11;
+        let mut squares = 0;
+        for row in 0..8 {
+            for col in 0..8 {
+                let value = self.get(col, row);
+                let weight = Self::VALUES[row as usize][col as usize];
+                if value == player {
+                    squares += weight;
+                } else if value != Self::EMPTY {
+                    squares -= weight;
+                }
             }
         }
+        let mut score = params.squares * squares;
+        if params.pieces != 0 {
+            let pieces = self.scores[(player - 1) as usize] - self.scores[(other - 1) as usize];
+            score += params.pieces * pieces;
+        }
+        if params.mobility != 0 {
+            let moves = self.get_moves(player).len() as i32 - self.get_moves(other).len() as i32;
+            score += params.mobility * moves;
+        }
+        if self.variant == Variant::Anti {
+            score = -score;
+        }
+        if params.noise > 0 {
+            // The noise is the computer's, so it counts against the human.
+            let mut hasher = DefaultHasher::new();
+            (self, params.seed).hash(&mut hasher);
+            let noise = (hasher.finish() % (params.noise as u64 * 2 + 1)) as i32 - params.noise;
+            score += if player == Self::COMPUTER {
+                noise
+            } else {
+                -noise
+            };
+        }
+        score
+    }
+
+    /// Explains what a move by the given player to (col, row) would do in
+    /// each direction, in the order right, up & right, up, and so on around
+    /// to down & right.  The move is legal if it flips pieces in any of them.
+    /// Returns None if the square is already taken.
+    pub fn explain_move(&self, col: i32, row: i32, player: u8) -> Option<[Ray; 8]> {
+        if self.get(col, row) != Self::EMPTY {
+            return None;
+        }
+        let mut rays = [Ray::Edge; 8];
+        for (dir, ray) in rays.iter_mut().enumerate() {
+            *ray = self.look_in_dir(col, row, player, dir);
+        }
+        Some(rays)
     }
 
     /// Flips the piece in the given square to that of the other player and
@@ -121,9 +339,16 @@ impl Board {
     }
 
     /// Flips the pieces in the given direction to that of the other player
-    /// and adjusts the scores accordingly.  We assume that the direction is
-    /// already known to be valid.
-    fn flip_dir(&mut self, col: i32, row: i32, player: u8, dir: usize) {
+    /// and adjusts the scores accordingly, adding their squares to `flipped`.
+    /// We assume that the direction is already known to be valid.
+    fn flip_dir(
+        &mut self,
+        col: i32,
+        row: i32,
+        player: u8,
+        dir: usize,
+        flipped: &mut Vec<(i32, i32)>,
+    ) {
         let mut col = col;
         let mut row = row;
         loop {
@@ -133,6 +358,7 @@ impl Board {
                 return;
             }
             self.flip(col, row);
+            flipped.push((col, row));
         }
     }
 
@@ -153,7 +379,7 @@ impl Board {
         let mut moves = Vec::new();
         for row in 0..8 {
             for col in 0..8 {
-                if self.count_move(col, row, player) > 0 {
+                if self.is_legal(col, row, player) {
                     moves.push((col, row));
                 }
             }
@@ -170,28 +396,211 @@ impl Board {
         }
     }
 
+    /// Returns how many discs the given player wins by at the end of the
+    /// game, or loses by if it is negative, under the board's variant.
+    pub fn margin(&self, player: u8) -> i32 {
+        let other = player ^ 0b11;
+        let margin = self.scores[(player - 1) as usize] - self.scores[(other - 1) as usize];
+        match self.variant {
+            Variant::Anti => -margin,
+            _ => margin,
+        }
+    }
+
+    /// Gives the player a handicap of pieces in the given number of corners,
+    /// up to all 4 of them.
+    pub fn place_corners(&mut self, count: usize, player: u8) {
+        for &(col, row) in Self::CORNERS.iter().take(count) {
+            self.set(col, row, player);
+            self.scores[(player - 1) as usize] += 1;
+        }
+    }
+
+    /// Puts the given value in the square at (col, row), whatever was there
+    /// before, and adjusts the scores accordingly.  This is for composing
+    /// positions rather than making moves.
+    pub fn put(&mut self, col: i32, row: i32, value: u8) {
+        let old = self.get(col, row);
+        if old != Self::EMPTY {
+            self.scores[(old - 1) as usize] -= 1;
+        }
+        if value != Self::EMPTY {
+            self.scores[(value - 1) as usize] += 1;
+        }
+        self.set(col, row, value);
+    }
+
     /// Sets the square at (col, row) to the given value.
     fn set(&mut self, col: i32, row: i32, value: u8) {
         self.squares[row as usize][col as usize] = value;
     }
+}
 
-    /// Picks a move from the given set of possible moves for the given player.
-    ///
-    /// Because this is a throwaway game written purely for the sake of learning
-    /// Rust, we don't implement minimax or alpha-beta pruning or any of that
-    /// good stuff, just an evaluation function based on position heuristics and
-    /// the number of pieces flipped.
-    pub fn select_move(&self, moves: Vec<(i32, i32)>, player: u8) -> (i32, i32) {
-        let mut best = moves[0];
-        let mut best_score = -1;
-        for move_ in moves {
-            let mult = Self::VALUES[move_.1 as usize][move_.0 as usize];
-            let score = self.count_move(move_.0, move_.1, player) * mult;
-            if score > best_score {
-                best = move_;
-                best_score = score;
-            }
+/// Returns the name of the square at (col, row), such as "d3" for row d and
+/// column 3, as the player types it.
+pub fn square_name(col: i32, row: i32) -> String {
+    format!("{}{}", ((row as u8) + 97) as char, col + 1)
+}
+
+/// Returns the (col, row) of the square with the given name, as
+/// `square_name` gives it, or None if there is no such square.
+pub fn parse_square(name: &str) -> Option<(i32, i32)> {
+    match name.as_bytes() {
+        &[row @ b'a'..=b'h', col @ b'1'..=b'8'] => Some(((col - b'1') as i32, (row - b'a') as i32)),
+        _ => None,
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+
+    #[test]
+    fn test_check_setup() {
+        let mut board = Board::empty();
+        board.put(3, 3, Board::HUMAN);
+        board.put(4, 4, Board::HUMAN);
+        board.put(3, 4, Board::COMPUTER);
+        assert!(board.check_setup(Board::HUMAN).is_err());
+
+        board.put(4, 3, Board::COMPUTER);
+        assert_eq!(board, Board::new());
+        assert_eq!(board.check_setup(Board::COMPUTER), Ok(()));
+
+        board.put(3, 4, Board::HUMAN);
+        board.put(4, 3, Board::HUMAN);
+        assert_eq!(board.get_score(Board::COMPUTER), Some(0));
+        assert!(board.check_setup(Board::HUMAN).is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut board = Board::new();
+        board.do_move(4, 2, Board::HUMAN);
+        let params = EvalParams::default();
+        assert_eq!(board.evaluate(Board::HUMAN, &params), 7);
+        assert_eq!(board.evaluate(Board::COMPUTER, &params), -7);
+
+        let greedy = Profile::Greedy.params(0);
+        assert_eq!(board.evaluate(Board::HUMAN, &greedy), 3);
+
+        // The noise is the same for the same position, and opposite for the
+        // other player, but differs with the seed.
+        let drunk = Profile::Drunk.params(1);
+        let score = board.evaluate(Board::HUMAN, &drunk);
+        assert!((7 - 20..=7 + 20).contains(&score));
+        assert_eq!(board.evaluate(Board::COMPUTER, &drunk), -score);
+        assert!((2..100)
+            .any(|seed| { board.evaluate(Board::HUMAN, &Profile::Drunk.params(seed)) != score }));
+
+        // Hemmed into a corner, the human has no moves to the computer's
+        // three.
+        let mut cornered = Board::empty();
+        for (col, row) in [(0, 0), (1, 0), (0, 1)] {
+            cornered.put(col, row, Board::COMPUTER);
+        }
+        cornered.put(1, 1, Board::HUMAN);
+        let squares = cornered.evaluate(Board::HUMAN, &params);
+        let mobility = Profile::Mobility.params(0);
+        assert_eq!(cornered.evaluate(Board::HUMAN, &mobility), squares - 12);
+    }
+
+    #[test]
+    fn test_square_names() {
+        assert_eq!(square_name(2, 3), "d3");
+        assert_eq!(parse_square("d3"), Some((2, 3)));
+        assert_eq!(parse_square("h8"), Some((7, 7)));
+        assert_eq!(parse_square("i1"), None);
+        assert_eq!(parse_square("a9"), None);
+        assert_eq!(parse_square("a10"), None);
+    }
+
+    #[test]
+    fn test_variants() {
+        assert_eq!(Variant::from_name("anti"), Some(Variant::Anti));
+        assert_eq!(Variant::from_name("go"), None);
+        assert_eq!(Board::start(Variant::Othello), Board::new());
+
+        // The original game starts by filling the center, flipping nothing.
+        let mut board = Board::start(Variant::Reversi);
+        assert_eq!(board.get_score(Board::HUMAN), Some(0));
+        assert_eq!(
+            board.get_moves(Board::HUMAN),
+            vec![(3, 3), (4, 3), (3, 4), (4, 4)]
+        );
+        for (col, row, player) in [(3, 3, 1), (4, 3, 1), (3, 4, 2)] {
+            assert!(board.do_move(col, row, player).is_empty());
         }
-        best
+        assert_eq!(board.get_moves(Board::COMPUTER), vec![(4, 4)]);
+        board.do_move(4, 4, Board::COMPUTER);
+        assert!(!board.in_opening());
+        assert!(!board.is_legal(3, 2, Board::HUMAN));
+        assert_eq!(
+            board.get_moves(Board::HUMAN),
+            vec![(2, 5), (3, 5), (4, 5), (5, 5)]
+        );
+
+        // When the fewest discs win, the evaluation and the margin turn
+        // around.
+        let params = EvalParams::default();
+        let mut anti = Board::start(Variant::Anti);
+        let mut board = Board::new();
+        anti.do_move(4, 2, Board::HUMAN);
+        board.do_move(4, 2, Board::HUMAN);
+        assert_eq!(anti.evaluate(Board::HUMAN, &params), -7);
+        assert_eq!(board.margin(Board::HUMAN), 3);
+        assert_eq!(anti.margin(Board::HUMAN), -3);
+    }
+
+    #[test]
+    fn test_do_move() {
+        let mut board = Board::new();
+        board.put(5, 3, Board::COMPUTER);
+        assert_eq!(board.do_move(6, 3, Board::HUMAN), vec![(5, 3), (4, 3)]);
+        assert_eq!(board.get_score(Board::HUMAN), Some(5));
+        assert_eq!(board.get_score(Board::COMPUTER), Some(1));
+    }
+
+    #[test]
+    fn test_explain_move() {
+        let board = Board::new();
+        assert_eq!(board.explain_move(3, 3, Board::HUMAN), None);
+        assert_eq!(
+            board.explain_move(2, 4, Board::HUMAN),
+            Some([
+                Ray::Flips(1),
+                Ray::Own,
+                Ray::Empty,
+                Ray::Empty,
+                Ray::Empty,
+                Ray::Empty,
+                Ray::Empty,
+                Ray::Empty,
+            ])
+        );
+        assert_eq!(
+            board.explain_move(0, 0, Board::COMPUTER),
+            Some([
+                Ray::Empty,
+                Ray::Edge,
+                Ray::Edge,
+                Ray::Edge,
+                Ray::Edge,
+                Ray::Edge,
+                Ray::Empty,
+                Ray::Empty,
+            ])
+        );
+        assert_eq!(
+            board.explain_move(5, 5, Board::COMPUTER).unwrap()[3],
+            Ray::Open(2)
+        );
     }
 }
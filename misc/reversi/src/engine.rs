@@ -0,0 +1,195 @@
+//! Playing the computer's side without the board on the screen, for other
+//! programs to use, over a line-based protocol after UCI, the one that chess
+//! engines speak.  Each line on standard input is a command, and the engine
+//! answers on standard output:
+//!
+//! ```text
+//! > isready
+//! < readyok
+//! > position c5 c4
+//! > go depth 4
+//! < info depth 4 score 3
+//! < bestmove c3
+//! > quit
+//! ```
+//!
+//! `position` sets up the board after the given moves from the start of the
+//! game, and may also be written `position startpos moves c5 c4`.  A player
+//! who can't move passes without it being said.  `go` searches for the best
+//! move of the player to move, to the given depth or else as deep as the
+//! computer looks in play, and answers `bestmove none` once the game is
+//! over.  Anything that can't be done is answered with `error` and why.
+
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::AtomicBool;
+
+use crate::board::{self, Board, Variant};
+use crate::profile::EvalParams;
+use crate::search;
+
+/// The engine, and the position that it has been given.
+pub struct Engine {
+    variant: Variant,
+    params: EvalParams,
+    board: Board,
+    /// The player to move, who moved first if the board is as it started.
+    turn: u8,
+}
+
+impl Engine {
+    /// Returns an engine for games by the given rules, which scores
+    /// positions by the given params, with the board as it starts.
+    pub fn new(variant: Variant, params: EvalParams) -> Self {
+        Self {
+            variant,
+            params,
+            board: Board::start(variant),
+            turn: Board::HUMAN,
+        }
+    }
+
+    /// Sets up the board after the given moves from the start of the game.
+    /// The board is left as it was if any of them is illegal.
+    pub fn position(&mut self, moves: &[&str]) -> Result<(), String> {
+        let mut board = Board::start(self.variant);
+        let mut turn = Board::HUMAN;
+        for (i, name) in moves.iter().enumerate() {
+            if board.get_moves(turn).is_empty() {
+                turn ^= 0b11; // The player to move has to pass.
+            }
+            match board::parse_square(name) {
+                Some((col, row)) if board.is_legal(col, row, turn) => {
+                    board.do_move(col, row, turn);
+                }
+                _ => return Err(format!("move {} ({}) is illegal", i + 1, name)),
+            }
+            turn ^= 0b11;
+        }
+        if board.get_moves(turn).is_empty() {
+            turn ^= 0b11;
+        }
+        self.board = board;
+        self.turn = turn;
+        Ok(())
+    }
+
+    /// Returns the answer to a single command, one line to each line of the
+    /// answer, which may be empty.
+    pub fn respond(&mut self, line: &str) -> String {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => String::new(),
+            Some("isready") => "readyok\n".to_string(),
+            Some("position") => {
+                let moves: Vec<&str> = words
+                    .filter(|&word| word != "startpos" && word != "moves")
+                    .collect();
+                match self.position(&moves) {
+                    Ok(()) => String::new(),
+                    Err(err) => format!("error {}\n", err),
+                }
+            }
+            Some("go") => match (words.next(), words.next()) {
+                (None, _) => self.go(search::DEPTH),
+                (Some("depth"), Some(depth)) => match depth.parse::<u32>() {
+                    Ok(depth) if depth > 0 => self.go(depth),
+                    _ => format!("error bad depth {}\n", depth),
+                },
+                _ => "error expected go depth N\n".to_string(),
+            },
+            Some(command) => format!("error unknown command {}\n", command),
+        }
+    }
+
+    /// Searches for the best move of the player to move, to the given depth.
+    fn go(&self, depth: u32) -> String {
+        let never = AtomicBool::new(false);
+        match search::search(&self.board, self.turn, depth, None, &self.params, &never) {
+            Some(found) => format!(
+                "info depth {} score {}\nbestmove {}\n",
+                found.depth,
+                found.score,
+                board::square_name(found.move_.0, found.move_.1)
+            ),
+            None => "bestmove none\n".to_string(),
+        }
+    }
+
+    /// Answers the commands from the given input until it ends or says
+    /// `quit`.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim() == "quit" {
+                break;
+            }
+            output.write_all(self.respond(&line).as_bytes())?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let input = "isready\nposition c5 c4\ngo depth 3\nposition c5 c5\nlook\nquit\ngo\n";
+        let mut output = Vec::new();
+        let mut engine = Engine::new(Variant::Othello, EvalParams::default());
+        engine.run(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "readyok");
+        assert!(lines[1].starts_with("info depth 3 score "));
+        assert_eq!(
+            lines[3..],
+            ["error move 2 (c5) is illegal", "error unknown command look"]
+        );
+
+        // The search finds the same move as the computer would in play.
+        let mut board = Board::new();
+        board.do_move(4, 2, Board::HUMAN);
+        board.do_move(3, 2, Board::COMPUTER);
+        let never = AtomicBool::new(false);
+        let found = search::search(
+            &board,
+            Board::HUMAN,
+            3,
+            None,
+            &EvalParams::default(),
+            &never,
+        );
+        let name = board::square_name(found.unwrap().move_.0, found.unwrap().move_.1);
+        assert_eq!(lines[2], format!("bestmove {}", name));
+    }
+
+    #[test]
+    fn test_position() {
+        let mut engine = Engine::new(Variant::Othello, EvalParams::default());
+        assert_eq!(engine.respond("go depth 0"), "error bad depth 0\n");
+        assert_eq!(engine.respond("go deep"), "error expected go depth N\n");
+        assert_eq!(engine.respond("position startpos moves c5 c4"), "");
+        assert_eq!(engine.turn, Board::HUMAN);
+        assert_eq!(engine.board.get(3, 2), Board::COMPUTER);
+
+        // An illegal move leaves the position as it was.
+        assert_eq!(
+            engine.respond("position c5 z9"),
+            "error move 2 (z9) is illegal\n"
+        );
+        assert_eq!(engine.board.get(3, 2), Board::COMPUTER);
+
+        // The first four discs of the original rules go in the center.
+        let mut engine = Engine::new(Variant::Reversi, EvalParams::default());
+        assert!(engine.respond("go depth 1").contains("bestmove d4\n"));
+        assert_eq!(engine.respond("position d4 e5 d5"), "");
+        assert_eq!(engine.turn, Board::COMPUTER);
+        assert!(engine.respond("go depth 1").contains("bestmove e4\n"));
+
+        engine.board = Board::empty();
+        assert_eq!(engine.respond("go"), "bestmove none\n");
+    }
+}
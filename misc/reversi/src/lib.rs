@@ -0,0 +1,479 @@
+//! Reversi against the computer on the terminal.  The rules live in `board`,
+//! the computer's strategy in `search` and its personalities in `profile`,
+//! the players' records in `stats`, the record of each game in `transcript`
+//! and the write-up of a finished one in `report`, and all of the terminal
+//! I/O in `screen`, while `engine` plays for other programs without it.  The
+//! records and the player's `Settings` are kept in the directories that
+//! `appdirs` finds.
+
+pub mod board;
+pub mod engine;
+pub mod profile;
+pub mod report;
+pub mod screen;
+pub mod search;
+pub mod stats;
+pub mod transcript;
+
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use appdirs::AppDirs;
+use serde::{Deserialize, Serialize};
+
+use board::{Board, Variant};
+use profile::{EvalParams, Profile};
+use screen::{Choice, Notice, Screen};
+use search::Ponder;
+use stats::{Outcome, Record, Stats};
+use transcript::{Move, Source, Transcript};
+use tui_common::Key;
+
+/// The file in the data directory that keeps each player's record against
+/// the computer.
+const STATS_FILE: &str = "reversi.stats";
+
+/// How long the computer must think for the human to be notified when it's
+/// their turn again.
+const LONG_THINK: Duration = Duration::from_secs(2);
+
+/// The player's standing choices, read from the settings file.  Any that are
+/// given on the command line take their place.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The name to keep the human's record under.
+    pub name: Option<String>,
+    /// The name of the profile that the computer starts out playing with.
+    pub profile: Option<String>,
+    /// How to let the human know that it's their turn: "bell" or "flash".
+    pub notify: Option<String>,
+    /// How many seconds the human has for each move.
+    pub clock: Option<u64>,
+}
+
+impl Settings {
+    /// Reads the settings from the settings file, which needn't exist yet.
+    pub fn load() -> Result<Settings, String> {
+        dirs().load_settings().map_err(|err| err.to_string())
+    }
+
+    /// Returns the choices that the settings make, with the rest left as
+    /// they are by default.  The setup still has to be checked.
+    pub fn setup(&self) -> Result<Setup, String> {
+        let bad =
+            |key: &str, expected: &str| format!("bad settings: {}: expected {}", key, expected);
+        let profile = match &self.profile {
+            Some(name) => Profile::from_name(name)
+                .ok_or_else(|| bad("profile", "greedy, positional, mobility, or drunk"))?,
+            None => Profile::default(),
+        };
+        let notice = match &self.notify {
+            Some(name) => {
+                Some(Notice::from_name(name).ok_or_else(|| bad("notify", "bell or flash"))?)
+            }
+            None => None,
+        };
+        Ok(Setup {
+            name: self.name.clone(),
+            profile,
+            notice,
+            clock: self.clock.map(Duration::from_secs),
+            ..Setup::default()
+        })
+    }
+}
+
+/// Returns the directories that the game keeps its files in.
+pub fn dirs() -> AppDirs {
+    AppDirs::new("reversi")
+}
+
+/// The choices for a game that are made up front, such as on the command
+/// line.
+#[derive(Clone, Debug, Default)]
+pub struct Setup {
+    /// The name that the human's record is kept under, which defaults to
+    /// their login name.
+    pub name: Option<String>,
+    /// The number of corners, up to 4, that the human starts out holding.
+    pub corners: usize,
+    /// Whether the computer searches less deeply against a player who has
+    /// lost more games than they have won.
+    pub adaptive: bool,
+    /// Whether to compose the position to play from in the board editor.
+    /// Games from composed positions don't count toward the record.
+    pub edit: bool,
+    /// The file to write the game's transcript to, if any.
+    pub transcript: Option<String>,
+    /// The profile that the computer starts out playing with.
+    pub profile: Profile,
+    /// The rules to play by.  Games in which the fewest discs win don't
+    /// count toward the record.
+    pub variant: Variant,
+    /// How to let the human know that it's their turn after the computer
+    /// has thought for a long time, if at all.
+    pub notice: Option<Notice>,
+    /// How long the human has for each move, if there is a limit.  Running
+    /// out of time loses the game.
+    pub clock: Option<Duration>,
+}
+
+impl Setup {
+    /// Checks that the choices are within limits.
+    pub fn check(&self) -> Result<(), String> {
+        if self.corners > 4 {
+            Err("there are only 4 corners".to_string())
+        } else if self.corners > 0 && self.edit {
+            Err("a composed position can't have a handicap".to_string())
+        } else if self.clock.is_some_and(|clock| clock.is_zero()) {
+            Err("the clock needs some time on it".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the name that the human's record is kept under.
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| "Human".to_string())
+    }
+
+    /// Returns how many plies less deeply than usual the computer searches
+    /// against a player with the given record.  With the adaptive handicap,
+    /// that is one ply for each game more that they have lost than won, until
+    /// the computer looks only at its own move.
+    fn reduction(&self, record: Record) -> u32 {
+        if self.adaptive {
+            record
+                .losses
+                .saturating_sub(record.wins)
+                .min(search::DEPTH - 1)
+        } else {
+            0
+        }
+    }
+}
+
+/// Describes the best move on the board for the given player, found by
+/// playing the game out if the board is nearly full, or else by searching as
+/// far ahead as the computer can while the player waits.
+fn analyze(board: &Board, player: u8) -> String {
+    let name = match player {
+        Board::HUMAN => "Human",
+        _ => "Computer",
+    };
+    if board.empties() <= search::SOLVE_EMPTIES {
+        match search::solve(board, player) {
+            Some(((col, row), margin)) => {
+                let result = match margin.cmp(&0) {
+                    Ordering::Greater => format!("wins by {}", margin),
+                    Ordering::Less => format!("loses by {}", -margin),
+                    Ordering::Equal => "ties".to_string(),
+                };
+                format!(
+                    "{} plays {} and {} with best play.",
+                    name,
+                    board::square_name(col, row),
+                    result
+                )
+            }
+            None => format!("{} has to pass.", name),
+        }
+    } else {
+        let never = AtomicBool::new(false);
+        let params = EvalParams::default();
+        match search::search(board, player, search::ANALYSIS_DEPTH, None, &params, &never) {
+            Some(found) => format!(
+                "{} plays {}, which scores {} looking {} moves ahead.",
+                name,
+                board::square_name(found.move_.0, found.move_.1),
+                found.score,
+                found.depth
+            ),
+            None => format!("{} has to pass.", name),
+        }
+    }
+}
+
+/// Runs the board editor, starting from the usual opening position, until
+/// the human chooses to play from the position they composed or quits.
+/// Returns the board and the player to move.
+fn edit(screen: &mut Screen) -> Option<(Board, u8)> {
+    let mut board = Board::new();
+    let mut turn = Board::HUMAN;
+    let mut cursor = (3, 3);
+    let mut message = String::new();
+
+    loop {
+        screen
+            .draw_editor(&board, cursor, turn, &message)
+            .unwrap_or(());
+        message = String::new();
+        match screen.read_key() {
+            Key::ArrowLeft => cursor.0 = (cursor.0 + 7) % 8,
+            Key::ArrowRight => cursor.0 = (cursor.0 + 1) % 8,
+            Key::ArrowUp => cursor.1 = (cursor.1 + 7) % 8,
+            Key::ArrowDown => cursor.1 = (cursor.1 + 1) % 8,
+            Key::Char('h') => board.put(cursor.0, cursor.1, Board::HUMAN),
+            Key::Char('c') => board.put(cursor.0, cursor.1, Board::COMPUTER),
+            Key::Char(' ') => board.put(cursor.0, cursor.1, Board::EMPTY),
+            Key::Char('n') => board = Board::empty(),
+            Key::Char('r') => board = Board::new(),
+            Key::Char('t') => turn ^= 0b11,
+            Key::Char('a') => match board.check_setup(turn) {
+                Ok(()) => message = analyze(&board, turn),
+                Err(err) => message = err,
+            },
+            Key::Char('p') => match board.check_setup(turn) {
+                Ok(()) => return Some((board, turn)),
+                Err(err) => message = err,
+            },
+            Key::Char('q') | Key::Escape => return None,
+            _ => (),
+        }
+    }
+}
+
+/// Plays a single game, with the human moving first unless the position was
+/// composed otherwise, until neither player can move or the human quits.
+/// The setup must already have been checked.
+pub fn play(setup: &Setup) {
+    let name = setup.name();
+    let stats_file = dirs().data_file(STATS_FILE);
+    let mut stats = Stats::load(&stats_file).unwrap_or_default();
+    let reduction = setup.reduction(stats.record(&name));
+    let mut screen = Screen::new();
+
+    let (mut board, first) = if setup.edit {
+        match edit(&mut screen) {
+            Some((mut board, turn)) => {
+                board.set_variant(setup.variant);
+                (board, turn)
+            }
+            None => return,
+        }
+    } else {
+        let mut board = Board::start(setup.variant);
+        board.place_corners(setup.corners, Board::HUMAN);
+        (board, Board::HUMAN)
+    };
+
+    let mut transcript = Transcript::new(&name, board, first);
+    // The noise in the computer's evaluation differs from game to game.
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64);
+    let mut profile = setup.profile;
+    let mut params = profile.params(seed);
+    screen.set_profile(profile);
+    let mut turn = first ^ 0b11;
+    let mut last_move = (-1, -1);
+    let mut pondered = None;
+    let mut long_think = false;
+    let mut turn_started = None;
+    let mut forfeited = false;
+
+    while !board.game_over() {
+        turn ^= 0b11; // 1 -> 2, 2 -> 1
+        let turn_moves = board.get_moves(turn);
+        if turn_moves.is_empty() {
+            continue;
+        }
+
+        screen.draw_board(&board).unwrap_or(());
+        if last_move.0 != -1 {
+            screen.report_move(last_move.0, last_move.1).unwrap_or(());
+        }
+
+        if turn == Board::HUMAN {
+            // The computer thinks about its reply while the human does.
+            let ponder = Ponder::start(&board, search::PONDER_DEPTH - reduction, &params);
+            if let (true, Some(notice)) = (long_think, setup.notice) {
+                screen.notify(notice).unwrap_or(());
+                long_think = false;
+            }
+            // Changing the profile doesn't stop the clock.
+            let started = *turn_started.get_or_insert_with(Instant::now);
+            let deadline = setup.clock.map(|clock| started + clock);
+            match screen.read_move(&board, deadline) {
+                Choice::Move(move_) => {
+                    turn_started = None;
+                    transcript.moves.push(Move {
+                        player: Board::HUMAN,
+                        square: move_,
+                        eval: search::score_move(
+                            &board,
+                            Board::HUMAN,
+                            move_,
+                            search::DEPTH,
+                            &params,
+                        ),
+                        time: started.elapsed(),
+                        source: Source::Human,
+                        analysis: None,
+                    });
+                    let flipped = board.do_move(move_.0, move_.1, Board::HUMAN);
+                    pondered = ponder.finish(move_);
+                    screen.animate_move(&board, move_, &flipped).unwrap_or(());
+                }
+                Choice::NextProfile => {
+                    profile = profile.next();
+                    params = profile.params(seed);
+                    screen.set_profile(profile);
+                    // The human is still to move, and pondering starts over.
+                    turn ^= 0b11;
+                    continue;
+                }
+                Choice::Quit => break,
+                Choice::TimedOut => {
+                    forfeited = true;
+                    break;
+                }
+            }
+            screen.draw_board(&board).unwrap_or(());
+            screen.wait_for_key();
+        } else {
+            let depth = search::DEPTH - reduction;
+            let started = Instant::now();
+            let (found, source) =
+                search::computer_move(&board, pondered.take(), depth, &params).unwrap();
+            long_think = started.elapsed() >= LONG_THINK;
+            last_move = found.move_;
+            transcript.moves.push(Move {
+                player: Board::COMPUTER,
+                square: last_move,
+                eval: found.score,
+                time: started.elapsed(),
+                source,
+                analysis: None,
+            });
+            let flipped = board.do_move(last_move.0, last_move.1, Board::COMPUTER);
+            screen
+                .animate_move(&board, last_move, &flipped)
+                .unwrap_or(());
+        }
+    }
+
+    screen.draw_board(&board).unwrap_or(());
+    if forfeited {
+        screen.report_forfeit().unwrap_or(());
+    } else {
+        screen.report_winner(&board).unwrap_or(());
+    }
+    if let Some(path) = &setup.transcript {
+        transcript.save(path).unwrap_or(());
+    }
+
+    // Only finished games from the usual start count toward the record,
+    // along with those lost on time, and only if the most discs won.
+    if (board.game_over() || forfeited) && !setup.edit && setup.variant != Variant::Anti {
+        stats.add(
+            &name,
+            match board.margin(Board::HUMAN).cmp(&0) {
+                _ if forfeited => Outcome::Loss,
+                Ordering::Greater => Outcome::Win,
+                Ordering::Less => Outcome::Loss,
+                Ordering::Equal => Outcome::Tie,
+            },
+        );
+        AppDirs::prepare(&stats_file)
+            .and_then(|()| stats.save(&stats_file))
+            .unwrap_or(());
+    }
+    screen
+        .report_record(&name, stats.record(&name))
+        .unwrap_or(());
+
+    // A game that came to an end can be written up beside its transcript.
+    if let Some(path) = &setup.transcript {
+        if (board.game_over() || forfeited) && screen.offer_analysis() {
+            let path = report::file_name(path);
+            let result = report::analysis(&transcript).and_then(|text| fs::write(&path, text));
+            screen.report_analysis(&path, &result).unwrap_or(());
+        }
+    }
+}
+
+/// Reads the transcript in the given file and returns it annotated, with
+/// every move searched again more deeply than the computer searches in play.
+pub fn annotate(path: &str) -> Result<String, String> {
+    let mut transcript = Transcript::load(path).map_err(|err| format!("{}: {}", path, err))?;
+    transcript
+        .annotate(search::ANNOTATION_DEPTH)
+        .map_err(|err| format!("{}: {}", path, err))?;
+    Ok(transcript.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction() {
+        let record = |wins, losses| Record {
+            wins,
+            losses,
+            ties: 0,
+        };
+        let fixed = Setup::default();
+        let adaptive = Setup {
+            adaptive: true,
+            ..Setup::default()
+        };
+        assert_eq!(fixed.reduction(record(0, 2)), 0);
+        assert_eq!(adaptive.reduction(record(0, 2)), 2);
+        assert_eq!(adaptive.reduction(record(3, 4)), 1);
+        assert_eq!(adaptive.reduction(record(5, 4)), 0);
+        assert_eq!(adaptive.reduction(record(0, 20)), search::DEPTH - 1);
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(Setup::default().check(), Ok(()));
+        let corners = Setup {
+            corners: 5,
+            ..Setup::default()
+        };
+        assert!(corners.check().is_err());
+        let clock = |secs| Setup {
+            clock: Some(Duration::from_secs(secs)),
+            ..Setup::default()
+        };
+        assert_eq!(clock(10).check(), Ok(()));
+        assert!(clock(0).check().is_err());
+    }
+
+    #[test]
+    fn test_settings_setup() {
+        let settings = Settings {
+            name: Some("Ann".to_string()),
+            profile: Some("drunk".to_string()),
+            notify: Some("bell".to_string()),
+            clock: Some(30),
+        };
+        let setup = settings.setup().unwrap();
+        assert_eq!(setup.name.as_deref(), Some("Ann"));
+        assert_eq!(setup.profile, Profile::Drunk);
+        assert_eq!(setup.notice, Some(Notice::Bell));
+        assert_eq!(setup.clock, Some(Duration::from_secs(30)));
+        assert_eq!(
+            Settings::default().setup().unwrap().profile,
+            Profile::default()
+        );
+
+        let settings = Settings {
+            notify: Some("siren".to_string()),
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.setup().unwrap_err(),
+            "bad settings: notify: expected bell or flash"
+        );
+    }
+}
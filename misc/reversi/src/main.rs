@@ -1,38 +1,121 @@
-mod board;
-mod screen;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use reversi::board::Variant;
+use reversi::engine::Engine;
+use reversi::profile::Profile;
+use reversi::screen::Notice;
+use reversi::{Settings, Setup};
+use std::io;
+use std::process;
+use std::time::Duration;
 
-fn main() {
-    let mut board = board::Board::new();
-    let mut screen = screen::Screen::new();
+/// Plays Reversi against the computer.  The defaults for --name, --profile,
+/// --notify, and --clock can be set in the settings file.
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// The name to keep your record under, instead of your login name
+    #[arg(long)]
+    name: Option<String>,
+    /// Start out holding this many corners, as a handicap
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    corners: usize,
+    /// Have the computer look less far ahead while you have lost more games
+    /// than you have won
+    #[arg(long)]
+    adaptive: bool,
+    /// Compose the position to start from in the board editor, for practice
+    #[arg(long, conflicts_with = "corners")]
+    setup: bool,
+    /// The computer's personality to start with: greedy, positional,
+    /// mobility, or drunk [default: positional]
+    #[arg(long, value_parser = parse_profile)]
+    profile: Option<Profile>,
+    /// The rules to play by: othello (the modern game), reversi (the
+    /// original, where the first four discs are placed in the center), or
+    /// anti (the fewest discs win) [default: othello]
+    #[arg(long, value_parser = parse_variant)]
+    variant: Option<Variant>,
+    /// When it's your turn after the computer has thought for a while, let
+    /// you know with a bell or a flash of the screen
+    #[arg(long, value_parser = parse_notice)]
+    notify: Option<Notice>,
+    /// Play against the clock, losing the game if you take longer than this
+    /// many seconds over a move
+    #[arg(long, value_name = "SECONDS")]
+    clock: Option<u64>,
+    /// Write a transcript of the game to this file, with the computer's
+    /// evaluation of each move, and offer at the end to write an analysis
+    /// of the game beside it
+    #[arg(long, value_name = "FILE")]
+    transcript: Option<String>,
+    /// Instead of playing, search every move in this transcript again more
+    /// deeply and print it with the blunders marked
+    #[arg(long, value_name = "FILE", exclusive = true)]
+    annotate: Option<String>,
+    /// Instead of playing, answer `position` and `go depth N` on standard
+    /// input with `bestmove`, for other programs to play against, by the
+    /// --profile and --variant given
+    #[arg(long, conflicts_with_all = ["name", "corners", "adaptive", "setup", "notify", "clock", "transcript"])]
+    engine: bool,
+}
 
-    let mut turn = board::Board::COMPUTER;
-    let mut last_move = (-1, -1);
+fn parse_profile(text: &str) -> Result<Profile, String> {
+    Profile::from_name(text)
+        .ok_or_else(|| "expected greedy, positional, mobility, or drunk".to_string())
+}
 
-    while !board.game_over() {
-        turn ^= 0b11; // 1 -> 2, 2 -> 1
-        let turn_moves = board.get_moves(turn);
-        if turn_moves.is_empty() {
-            continue;
-        }
+fn parse_variant(text: &str) -> Result<Variant, String> {
+    Variant::from_name(text).ok_or_else(|| "expected othello, reversi, or anti".to_string())
+}
 
-        screen.draw_board(&board).unwrap_or(());
-        if last_move.0 != -1 {
-            screen.report_move(last_move.0, last_move.1).unwrap_or(());
-        }
+fn parse_notice(text: &str) -> Result<Notice, String> {
+    Notice::from_name(text).ok_or_else(|| "expected bell or flash".to_string())
+}
 
-        if turn == board::Board::HUMAN {
-            match screen.read_move(&board) {
-                Some(move_) => board.do_move(move_.0, move_.1, board::Board::HUMAN),
-                None => break,
+fn main() {
+    let options = Options::parse();
+    if let Some(path) = options.annotate {
+        match reversi::annotate(&path) {
+            Ok(text) => print!("{}", text),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
             }
-            screen.draw_board(&board).unwrap_or(());
-            screen.wait_for_key();
-        } else {
-            last_move = board.select_move(turn_moves, turn);
-            board.do_move(last_move.0, last_move.1, board::Board::COMPUTER);
         }
+        return;
     }
-
-    screen.draw_board(&board).unwrap_or(());
-    screen.report_winner(&board).unwrap_or(());
+    if options.engine {
+        let params = options.profile.unwrap_or_default().params(0);
+        let mut engine = Engine::new(options.variant.unwrap_or_default(), params);
+        if let Err(err) = engine.run(io::stdin().lock(), io::stdout()) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return;
+    }
+    let defaults = match Settings::load().and_then(|settings| settings.setup()) {
+        Ok(defaults) => defaults,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    let setup = Setup {
+        name: options.name.or(defaults.name),
+        corners: options.corners,
+        adaptive: options.adaptive,
+        edit: options.setup,
+        transcript: options.transcript,
+        profile: options.profile.unwrap_or(defaults.profile),
+        variant: options.variant.unwrap_or_default(),
+        notice: options.notify.or(defaults.notice),
+        clock: options.clock.map(Duration::from_secs).or(defaults.clock),
+    };
+    if let Err(err) = setup.check() {
+        Options::command()
+            .error(ErrorKind::ValueValidation, err)
+            .exit();
+    }
+    reversi::play(&setup);
 }
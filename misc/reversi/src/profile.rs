@@ -0,0 +1,135 @@
+//! This module contains the computer's personalities, which weigh positions
+//! up in their own ways so that it doesn't play the same game every time.
+//!
+//! Each profile is a set of `EvalParams`: weights for the squares held, the
+//! pieces held, and the moves left open, and an amount of noise to add.  The
+//! noise is a hash of the position rather than truly random, so that a search
+//! always sees the same score for the same position, and it changes from game
+//! to game with the seed.
+
+use std::fmt::{self, Display, Formatter};
+
+/// How the computer scores a position that it doesn't search any further.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalParams {
+    /// The weight of the squares held, by the value of each square.
+    pub squares: i32,
+    /// The weight of each piece held.
+    pub pieces: i32,
+    /// The weight of each move that a player has.
+    pub mobility: i32,
+    /// The most that noise adds to or takes away from a score.
+    pub noise: i32,
+    /// The seed for the noise.
+    pub seed: u64,
+}
+
+impl Default for EvalParams {
+    /// Returns the weights that the computer has always played by, which
+    /// value only the squares.
+    fn default() -> Self {
+        Self {
+            squares: 1,
+            pieces: 0,
+            mobility: 0,
+            noise: 0,
+            seed: 0,
+        }
+    }
+}
+
+/// One of the computer's personalities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// Takes as many pieces as it can.
+    Greedy,
+    /// Goes for the corners and edges, and stays away from the squares next
+    /// to them.
+    #[default]
+    Positional,
+    /// Keeps its own choices open and takes the human's away.
+    Mobility,
+    /// Plays positionally, more or less, but with a good deal of noise.
+    Drunk,
+}
+
+impl Profile {
+    /// Every profile, in the order that they are cycled through.
+    pub const ALL: [Profile; 4] = [
+        Profile::Greedy,
+        Profile::Positional,
+        Profile::Mobility,
+        Profile::Drunk,
+    ];
+
+    /// Looks up a profile by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|profile| profile.name() == name)
+    }
+
+    /// Returns the name of the profile.
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Greedy => "greedy",
+            Profile::Positional => "positional",
+            Profile::Mobility => "mobility",
+            Profile::Drunk => "drunk",
+        }
+    }
+
+    /// Returns the profile after this one, going back to the first after the
+    /// last.
+    pub fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&profile| profile == self)
+            .unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the weights of the profile, with the given seed for any noise.
+    pub fn params(self, seed: u64) -> EvalParams {
+        let params = EvalParams {
+            seed,
+            ..EvalParams::default()
+        };
+        match self {
+            Profile::Greedy => EvalParams {
+                squares: 0,
+                pieces: 1,
+                ..params
+            },
+            Profile::Positional => params,
+            Profile::Mobility => EvalParams {
+                mobility: 4,
+                ..params
+            },
+            Profile::Drunk => EvalParams {
+                noise: 20,
+                ..params
+            },
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        for profile in Profile::ALL {
+            assert_eq!(Profile::from_name(profile.name()), Some(profile));
+        }
+        assert_eq!(Profile::from_name("sober"), None);
+        assert_eq!(Profile::Drunk.next(), Profile::Greedy);
+        assert_eq!(Profile::Positional.params(7).seed, 7);
+        assert_eq!(Profile::Positional.params(0), EvalParams::default());
+    }
+}
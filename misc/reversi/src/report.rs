@@ -0,0 +1,181 @@
+//! Writing up a finished game from its transcript, for the human to look
+//! over afterwards: how the evaluation went from move to move, drawn as a
+//! sparkline, the moves that swung it the most, and where the discs ended
+//! up.
+//!
+//! ```text
+//! Analysis of Alice's game against the computer
+//!
+//! Result: Human 40, Computer 24
+//!
+//! Evaluation for Alice after each move, from -22 to +22:
+//! --~~--.-~^^~~-...
+//!
+//! Biggest swings:
+//!   12. Computer f6  +4 to -18  (-22)
+//!
+//! Final discs:
+//!     1 2 3 4 5 6 7 8
+//!   a H H H C C . . .
+//!   ...
+//! ```
+//!
+//! The evaluation is the deeper search's, for moves that have been
+//! annotated, and otherwise the one the computer made at the time.
+
+use std::io::Result;
+
+use crate::board::{self, Board};
+use crate::transcript::{Move, Transcript};
+
+/// The characters of the sparkline, from the lowest evaluation to the
+/// highest.  An even game is drawn in the middle.
+const LEVELS: [char; 5] = ['_', '.', '-', '~', '^'];
+
+/// The most swings that are listed.
+pub const SWINGS: usize = 3;
+
+/// Returns the evaluation of a move from the human's side.
+fn human_eval(move_: &Move) -> i32 {
+    let eval = move_.analysis.map_or(move_.eval, |analysis| analysis.score);
+    match move_.player {
+        Board::HUMAN => eval,
+        _ => -eval,
+    }
+}
+
+/// Returns the biggest of the given evaluations in either direction.
+fn largest(evals: &[i32]) -> i32 {
+    evals.iter().map(|eval| eval.abs()).max().unwrap_or(0)
+}
+
+/// Draws the given evaluations as a line of characters, one to each, scaled
+/// so that the biggest in either direction reaches the top or the bottom.
+pub fn sparkline(evals: &[i32]) -> String {
+    let top = largest(evals).max(1);
+    let middle = (LEVELS.len() / 2) as i32;
+    evals
+        .iter()
+        .map(|&eval| {
+            // Rounded to the nearest level, away from the middle.
+            let level = (eval * middle * 2 + top * eval.signum()) / (top * 2);
+            LEVELS[(middle + level) as usize]
+        })
+        .collect()
+}
+
+/// Returns the analysis of the game in the given transcript, or an error if
+/// its moves can't be played out.
+pub fn analysis(transcript: &Transcript) -> Result<String> {
+    let end = *transcript.boards()?.last().unwrap();
+    let evals: Vec<i32> = transcript.moves.iter().map(human_eval).collect();
+    let mut text = format!(
+        "Analysis of {}'s game against the computer\n\nResult: Human {}, Computer {}\n\n",
+        transcript.name,
+        end.get_score(Board::HUMAN).unwrap(),
+        end.get_score(Board::COMPUTER).unwrap()
+    );
+
+    let top = largest(&evals);
+    text.push_str(&format!(
+        "Evaluation for {} after each move, from {:+} to {:+}:\n{}\n\n",
+        transcript.name,
+        -top,
+        top,
+        sparkline(&evals)
+    ));
+
+    // A swing is how far a move changed the evaluation from the one before.
+    let mut swings: Vec<(usize, i32, i32)> = (0..evals.len())
+        .map(|i| (i, if i == 0 { 0 } else { evals[i - 1] }, evals[i]))
+        .filter(|&(_, before, after)| before != after)
+        .collect();
+    swings.sort_by_key(|&(i, before, after)| (-(after - before).abs(), i));
+    text.push_str("Biggest swings:\n");
+    if swings.is_empty() {
+        text.push_str("  none\n");
+    }
+    for &(i, before, after) in swings.iter().take(SWINGS) {
+        let move_ = &transcript.moves[i];
+        text.push_str(&format!(
+            "  {}. {} {}  {:+} to {:+}  ({:+})\n",
+            i + 1,
+            match move_.player {
+                Board::HUMAN => "Human",
+                _ => "Computer",
+            },
+            board::square_name(move_.square.0, move_.square.1),
+            before,
+            after,
+            after - before
+        ));
+    }
+
+    text.push_str("\nFinal discs:\n    1 2 3 4 5 6 7 8\n");
+    for row in 0..8 {
+        text.push_str(&format!("  {}", ((row as u8) + 97) as char));
+        for col in 0..8 {
+            text.push_str(match end.get(col, row) {
+                Board::HUMAN => " H",
+                Board::COMPUTER => " C",
+                _ => " .",
+            });
+        }
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Returns the name of the file that the analysis of a game is written to,
+/// beside its transcript.
+pub fn file_name(transcript: &str) -> String {
+    format!("{}.analysis", transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Source;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0]), "--");
+        assert_eq!(sparkline(&[-20, -10, -4, 0, 4, 10, 20]), "_.---~^");
+        assert_eq!(sparkline(&[1, -1, 0]), "^_-");
+    }
+
+    #[test]
+    fn test_analysis() {
+        let mut transcript = Transcript::new("Alice", Board::new(), Board::HUMAN);
+        for (player, square, eval) in [
+            (Board::HUMAN, (4, 2), 4),
+            (Board::COMPUTER, (3, 2), -2),
+            (Board::HUMAN, (2, 2), 11),
+        ] {
+            transcript.moves.push(Move {
+                player,
+                square,
+                eval,
+                time: Duration::from_secs(1),
+                source: Source::Human,
+                analysis: None,
+            });
+        }
+        let text = analysis(&transcript).unwrap();
+        assert!(text.starts_with(
+            "Analysis of Alice's game against the computer\n\n\
+             Result: Human 6, Computer 1\n\n\
+             Evaluation for Alice after each move, from -11 to +11:\n~-^\n\n\
+             Biggest swings:\n\
+             \x20 3. Human c3  +2 to +11  (+9)\n\
+             \x20 1. Human c5  +0 to +4  (+4)\n\
+             \x20 2. Computer c4  +4 to +2  (-2)\n\n\
+             Final discs:\n    1 2 3 4 5 6 7 8\n"
+        ));
+        assert!(text.contains("\n  c . . H H H . . .\n  d . . . H H . . .\n  e . . . C H . . .\n"));
+
+        assert_eq!(file_name("game.txt"), "game.txt.analysis");
+    }
+}
@@ -2,11 +2,48 @@
 //! for the game.  This is done with an unbuffered terminal so that we can
 //! respond to individual keystrokes.
 
-use console::Term;
 use std::cmp::Ordering;
 use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use tui_common::color::{GRAY, GREEN, LT_BLUE, LT_RED, LT_WHITE, LT_YELLOW, RED, WHITE};
+use tui_common::{Key, Terminal};
 
 use crate::board;
+use crate::profile::Profile;
+use crate::stats::Record;
+
+/// What the human chose to do when asked for a move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice {
+    /// Move to the (col, row).
+    Move((i32, i32)),
+    /// Have the computer play with its next profile.
+    NextProfile,
+    Quit,
+    /// The human ran out of time to move.
+    TimedOut,
+}
+
+/// How to let the human know that it's their turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notice {
+    /// Ring the terminal's bell.
+    Bell,
+    /// Flash the screen.
+    Flash,
+}
+
+impl Notice {
+    /// Looks up a notice by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bell" => Some(Notice::Bell),
+            "flash" => Some(Notice::Flash),
+            _ => None,
+        }
+    }
+}
 
 /// Screen encapsulates the display and input for the game.  All output must
 /// be done through a singleton instance of Screen rather than stdout for
@@ -15,64 +52,74 @@ use crate::board;
 /// A Screen does *not* encapsulate an instance of Board, but rather borrows one
 /// as needed for I/O.
 pub struct Screen {
-    term: Term,
+    term: Terminal,
+    /// The square of the last move, which is highlighted.
+    last_move: Option<(i32, i32)>,
+    /// The profile that the computer is playing with, if it has been shown.
+    profile: Option<Profile>,
 }
 
 impl Screen {
-    const BLACK: u8 = 30;
-    const RED: u8 = 31;
-    const GREEN: u8 = 32;
-    const YELLOW: u8 = 33;
-    const BLUE: u8 = 34;
-    //const MAGENTA: u8 = 35;
-    //const CYAN: u8 = 36;
-    const WHITE: u8 = 37;
-
-    const GRAY: u8 = Screen::BLACK + 60;
-    const LT_RED: u8 = Screen::RED + 60;
-    //const LT_GREEN: u8 = Screen::GREEN + 60;
-    const LT_YELLOW: u8 = Screen::YELLOW + 60;
-    const LT_BLUE: u8 = Screen::BLUE + 60;
-    //const LT_MAGENTA: u8 = Screen::MAGENTA + 60;
-    //const LT_CYAN: u8 = Screen::CYAN + 60;
-    const LT_WHITE: u8 = Screen::WHITE + 60;
+    /// The arrow for each direction in the order that `Board::explain_move`
+    /// gives them, with the change in column and row that it points to.
+    const ARROWS: [(&'static str, (i32, i32)); 8] = [
+        ("→", (1, 0)),
+        ("↗", (1, -1)),
+        ("↑", (0, -1)),
+        ("↖", (-1, -1)),
+        ("←", (-1, 0)),
+        ("↙", (-1, 1)),
+        ("↓", (0, 1)),
+        ("↘", (1, 1)),
+    ];
+
+    /// How long to wait between the pieces that a move flips.
+    const FLIP_DELAY: Duration = Duration::from_millis(80);
 
     /// Creates a new Screen instance.
     pub fn new() -> Self {
         Self {
-            term: Term::stdout(),
+            term: Terminal::new(),
+            last_move: None,
+            profile: None,
         }
     }
 
-    // Clears the screen, homes the cursor, and sets the current color to
-    // bright white.
-    fn clear_screen(&mut self) -> io::Result<()> {
-        self.term
-            .write_str(format!("\x1b[2J\x1b[H\x1b[{}m", Self::LT_WHITE).as_str())
+    /// Shows a move that has just been made on the board, which is otherwise
+    /// as last drawn: the new piece appears, highlighted as the last move,
+    /// and then the pieces that it flipped turn over one by one, in the order
+    /// that `Board::do_move` gave them.
+    pub fn animate_move(
+        &mut self,
+        board: &board::Board,
+        move_: (i32, i32),
+        flipped: &[(i32, i32)],
+    ) -> io::Result<()> {
+        self.last_move = Some(move_);
+        self.draw_square(board, move_.0, move_.1)?;
+        for &(col, row) in flipped {
+            thread::sleep(Self::FLIP_DELAY);
+            self.draw_square(board, col, row)?;
+        }
+        thread::sleep(Self::FLIP_DELAY);
+        Ok(())
     }
 
     // Draws the given Board on the screen.
     pub fn draw_board(&mut self, board: &board::Board) -> io::Result<()> {
-        self.clear_screen()?;
-        self.draw_box(2, 1, 19, 10, Self::GRAY)?;
-        self.draw_text(4, 0, Self::GREEN, "1 2 3 4 5 6 7 8")?;
-        self.draw_text(4, 11, Self::GREEN, "1 2 3 4 5 6 7 8")?;
+        self.term.clear_screen()?;
+        self.term.draw_box(2, 1, 19, 10, GRAY)?;
+        self.draw_text(4, 0, GREEN, "1 2 3 4 5 6 7 8")?;
+        self.draw_text(4, 11, GREEN, "1 2 3 4 5 6 7 8")?;
         for y in 0..8 {
             let ch = ((y as u8) + 97) as char;
-            self.draw_text(0, y + 2, Self::GREEN, format!("{}", ch).as_str())?;
-            self.draw_text(22, y + 2, Self::GREEN, format!("{}", ch).as_str())?;
+            self.draw_text(0, y + 2, GREEN, format!("{}", ch).as_str())?;
+            self.draw_text(22, y + 2, GREEN, format!("{}", ch).as_str())?;
         }
 
         for row in 0..8 {
             for col in 0..8 {
-                let x = col * 2 + 4;
-                let y = row + 2;
-                match board.get(col, row) {
-                    board::Board::EMPTY => self.draw_text(x, y, Self::WHITE, ".")?,
-                    board::Board::HUMAN => self.draw_text(x, y, Self::LT_RED, "⓿")?,
-                    board::Board::COMPUTER => self.draw_text(x, y, Self::LT_BLUE, "⓿")?,
-                    _ => panic!("Internal error in board state"),
-                }
+                self.draw_square(board, col, row)?;
             }
         }
 
@@ -80,51 +127,84 @@ impl Screen {
             "Human:    {}",
             board.get_score(board::Board::HUMAN).unwrap()
         );
-        self.draw_text(28, 2, Self::LT_RED, human.as_str())?;
+        self.draw_text(28, 2, LT_RED, human.as_str())?;
 
         let computer = format!(
             "Computer: {}",
             board.get_score(board::Board::COMPUTER).unwrap()
         );
-        self.draw_text(28, 3, Self::LT_BLUE, computer.as_str())
-    }
+        self.draw_text(28, 3, LT_BLUE, computer.as_str())?;
 
-    /// Draws a box in the given color and at the given 0-based (x, y)
-    /// coordinates.
-    fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, color: u8) -> io::Result<()> {
-        if width < 2 || height < 2 {
-            return Ok(());
+        let rules = match board.variant() {
+            board::Variant::Othello => None,
+            board::Variant::Reversi => Some("Rules:    reversi"),
+            board::Variant::Anti => Some("Rules:    anti (fewest discs win)"),
+        };
+        if let Some(rules) = rules {
+            self.draw_text(28, 1, GRAY, rules)?;
         }
 
-        self.set_color(color)?;
-        self.goto_xy(x, y)?;
-        self.term.write_str("┌")?;
-        for _ in 1..=(width - 2) {
-            self.term.write_str("─")?;
+        if let Some(profile) = self.profile {
+            let text = format!("Profile:  {} (p)", profile);
+            self.draw_text(28, 4, GRAY, text.as_str())?;
         }
-        self.term.write_str("┐")?;
+        Ok(())
+    }
 
-        for y_offset in 1..=(height - 2) {
-            self.goto_xy(x, y + y_offset)?;
-            self.term.write_str("│")?;
-            self.goto_xy(x + width - 1, y + y_offset)?;
-            self.term.write_str("│")?;
+    /// Draws the board editor, with the player to move, the keys to press,
+    /// and a message, leaving the cursor on the square at (col, row).
+    pub fn draw_editor(
+        &mut self,
+        board: &board::Board,
+        cursor: (i32, i32),
+        turn: u8,
+        message: &str,
+    ) -> io::Result<()> {
+        self.draw_board(board)?;
+        let (color, player) = match turn {
+            board::Board::HUMAN => (LT_RED, "Human"),
+            _ => (LT_BLUE, "Computer"),
+        };
+        self.draw_text(28, 5, color, format!("{} to move", player).as_str())?;
+        let keys = [
+            "Arrows: choose square",
+            "h/c:    human/computer",
+            "space:  empty square",
+            "n/r:    clear/reset board",
+            "t:      other player to move",
+            "a:      analyze position",
+            "p:      play from here",
+            "q:      quit",
+        ];
+        for (y, text) in keys.iter().enumerate() {
+            self.draw_text(28, 7 + y as i32, GRAY, text)?;
         }
+        self.draw_text(0, 13, LT_YELLOW, message)?;
+        self.term
+            .goto_xy((cursor.0 * 2 + 4) as usize, (cursor.1 + 2) as usize)
+    }
 
-        self.goto_xy(x, y + height - 1)?;
-        self.term.write_str("└")?;
-        for _ in 1..=(width - 2) {
-            self.term.write_str("─")?;
+    /// Draws the piece in the square at (col, row), highlighting it if it
+    /// was the last move.
+    fn draw_square(&mut self, board: &board::Board, col: i32, row: i32) -> io::Result<()> {
+        let (color, text) = match board.get(col, row) {
+            board::Board::EMPTY => (WHITE, "."),
+            board::Board::HUMAN => (LT_RED, "⓿"),
+            board::Board::COMPUTER => (LT_BLUE, "⓿"),
+            _ => panic!("Internal error in board state"),
+        };
+        let (x, y) = ((col * 2 + 4) as usize, (row + 2) as usize);
+        if self.last_move == Some((col, row)) {
+            self.term.draw_text_on(x, y, color, GRAY, text)
+        } else {
+            self.term.draw_text(x, y, color, text)
         }
-        self.term.write_str("┘")
     }
 
     /// Draws text in the given color and at the given 0-based (x, y)
-    /// coordinates.
+    /// coordinates, which are signed to match the Board's.
     fn draw_text(&mut self, x: i32, y: i32, color: u8, text: &str) -> io::Result<()> {
-        self.goto_xy(x, y)?;
-        self.set_color(color)?;
-        self.term.write_str(text)
+        self.term.draw_text(x as usize, y as usize, color, text)
     }
 
     /// Indicates the valid player moves on the screen.
@@ -133,93 +213,222 @@ impl Screen {
             for col in 0..8 {
                 let x = col * 2 + 4;
                 let y = row + 2;
-                if (board.get(col, row) == board::Board::EMPTY)
-                    && (board.count_move(col, row, board::Board::HUMAN) > 0)
-                {
-                    self.draw_text(x, y, Self::RED, "?")?;
+                if board.is_legal(col, row, board::Board::HUMAN) {
+                    self.draw_text(x, y, RED, "?")?;
                 }
             }
         }
         Ok(())
     }
 
-    /// Moves the cursor to the given 0-based (x, y) coordinates.
-    fn goto_xy(&mut self, x: i32, y: i32) -> io::Result<()> {
-        self.term
-            .write_str(format!("\x1b[{};{}H", y + 1, x + 1).as_str())
+    /// Explains why the human can't move to (col, row), with an arrow on each
+    /// of the neighboring squares and a line below the board for each
+    /// direction saying what is found that way.  In the opening of the
+    /// original game, the only reason is that the square isn't in the
+    /// center.
+    fn explain_move(&mut self, board: &board::Board, col: i32, row: i32) -> io::Result<()> {
+        let name = board::square_name(col, row);
+        let rays = match board.explain_move(col, row, board::Board::HUMAN) {
+            Some(rays) => rays,
+            None => {
+                return self.draw_text(28, 11, LT_YELLOW, format!("{} is taken!", name).as_str())
+            }
+        };
+        if board.in_opening() {
+            return self.draw_text(0, 13, LT_YELLOW, "The first four discs go in the center!");
+        }
+
+        self.draw_text(
+            0,
+            13,
+            LT_YELLOW,
+            format!("Nothing flips from {}:", name).as_str(),
+        )?;
+        for (dir, ray) in rays.iter().enumerate() {
+            let (arrow, (dx, dy)) = Self::ARROWS[dir];
+            if *ray != board::Ray::Edge {
+                self.draw_text((col + dx) * 2 + 4, row + dy + 2, LT_YELLOW, arrow)?;
+            }
+            let text = match ray {
+                board::Ray::Flips(count) => format!("{} flips {}", arrow, count),
+                board::Ray::Edge => format!("{} the edge of the board", arrow),
+                board::Ray::Empty => format!("{} an empty square", arrow),
+                board::Ray::Own => format!("{} your own piece, with nothing between", arrow),
+                board::Ray::Open(count) => {
+                    format!("{} {} of mine, with none of yours beyond", arrow, count)
+                }
+            };
+            self.draw_text(2, 14 + dir as i32, WHITE, text.as_str())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a single key press.
+    pub fn read_key(&mut self) -> Key {
+        self.term.read_key().expect("Terminal error")
+    }
+
+    /// Lets the human know that it's their turn.
+    pub fn notify(&mut self, notice: Notice) -> io::Result<()> {
+        match notice {
+            Notice::Bell => self.term.bell(),
+            Notice::Flash => self.term.flash(),
+        }
+    }
+
+    /// Reads a character, leaving the cursor at (x, y).  If there is a
+    /// deadline, the time left is shown in the sidebar, counting down each
+    /// second, and None is returned if it runs out.
+    fn read_char_by(&mut self, deadline: Option<Instant>, x: i32, y: i32) -> Option<char> {
+        let Some(deadline) = deadline else {
+            return Some(self.term.read_char().expect("Terminal error"));
+        };
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let millis = left.as_millis() as u64;
+            let text = format!("Clock:    {}s ", millis.div_ceil(1000));
+            let color = if millis > 5000 { WHITE } else { LT_YELLOW };
+            self.draw_text(28, 5, color, text.as_str()).unwrap_or(());
+            self.draw_text(x, y, WHITE, "").unwrap_or(());
+            if left.is_zero() {
+                return None;
+            }
+            // The clock is drawn again when the second that it shows is up.
+            let tick = match millis % 1000 {
+                0 => 1000,
+                tick => tick,
+            };
+            let key = self.term.read_key_within(Duration::from_millis(tick));
+            match key.expect("Terminal error") {
+                Some(Key::Char(ch)) => return Some(ch),
+                Some(Key::Enter) => return Some('\n'),
+                _ => (),
+            }
+        }
     }
 
     /// Reads a row (a-h) and column (1-8) from the user and translates it into
-    /// a zero-based (col, row) tuple.  Only valid moves are accepted.
-    pub fn read_move(&mut self, board: &board::Board) -> Option<(i32, i32)> {
+    /// a zero-based (col, row) tuple.  Only valid moves are accepted.  In
+    /// place of a row, the user can also change the computer's profile.  With
+    /// a deadline, the user has until then to choose.
+    pub fn read_move(&mut self, board: &board::Board, deadline: Option<Instant>) -> Choice {
         loop {
             self.draw_valid_moves(board).unwrap_or(());
-            self.draw_text(28, 8, Self::WHITE, "Row (a-h)? ")
+            self.draw_text(28, 8, WHITE, "Row (a-h)? ")
                 .unwrap_or(());
             let mut row = -1;
             while row == -1 {
-                let ch = self.term.read_char().expect("Terminal error");
+                let Some(ch) = self.read_char_by(deadline, 39, 8) else {
+                    return Choice::TimedOut;
+                };
                 if ch == 'q' {
-                    return None;
+                    return Choice::Quit;
+                } else if ch == 'p' {
+                    return Choice::NextProfile;
                 } else if ('a'..='h').contains(&ch) {
                     row = (ch as i32) - ('a' as i32);
-                    self.draw_text(39, 8, Self::LT_WHITE, format!("{}", ch).as_str())
+                    self.draw_text(39, 8, LT_WHITE, format!("{}", ch).as_str())
                         .unwrap_or(());
                 }
             }
 
             let mut col = -1;
-            self.draw_text(28, 9, Self::WHITE, "Col (1-8)? ")
+            self.draw_text(28, 9, WHITE, "Col (1-8)? ")
                 .unwrap_or(());
             while col == -1 {
-                let ch = self.term.read_char().expect("Terminal error");
+                let Some(ch) = self.read_char_by(deadline, 39, 9) else {
+                    return Choice::TimedOut;
+                };
                 if ch == 'q' {
-                    return None;
+                    return Choice::Quit;
                 } else if ('1'..='8').contains(&ch) {
                     col = (ch as i32) - ('1' as i32);
-                    self.draw_text(39, 9, Self::LT_WHITE, format!("{}", ch).as_str())
+                    self.draw_text(39, 9, LT_WHITE, format!("{}", ch).as_str())
                         .unwrap_or(());
                 }
             }
 
-            if board.count_move(col, row, board::Board::HUMAN) > 0 {
-                return Some((col, row));
+            if board.is_legal(col, row, board::Board::HUMAN) {
+                return Choice::Move((col, row));
+            }
+            self.explain_move(board, col, row).unwrap_or(());
+            if self.read_char_by(deadline, 0, 13).is_none() {
+                return Choice::TimedOut;
             }
-            self.draw_text(28, 11, Self::LT_YELLOW, "Invalid move!")
-                .unwrap_or(());
-            self.term.read_char().expect("Terminal error");
             self.draw_board(board).unwrap_or(());
         }
     }
 
+    /// Shows the profile that the computer is playing with, from the next time
+    /// the board is drawn.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = Some(profile);
+    }
+
     /// Informs the player of the computer's move.
     pub fn report_move(&mut self, col: i32, row: i32) -> io::Result<()> {
-        let text = format!("I moved to {}{}.", ((row as u8) + 97) as char, col + 1);
-        self.draw_text(28, 6, Self::LT_WHITE, text.as_str())
+        let text = format!("I moved to {}.", board::square_name(col, row));
+        self.draw_text(28, 6, LT_WHITE, text.as_str())
     }
 
     /// Reports on the winner of the game.
     pub fn report_winner(&mut self, board: &board::Board) -> io::Result<()> {
-        let human = board.get_score(board::Board::HUMAN);
-        let computer = board.get_score(board::Board::COMPUTER);
-        let text = match human.cmp(&computer) {
+        let text = match board.margin(board::Board::HUMAN).cmp(&0) {
             Ordering::Greater => "You win!",
             Ordering::Less => "I win!",
             Ordering::Equal => "It's a tie!",
         };
-        self.draw_text(28, 8, Self::LT_WHITE, text)?;
-        self.goto_xy(0, 20)
+        self.draw_text(28, 8, LT_WHITE, text)?;
+        self.term.goto_xy(0, 20)
+    }
+
+    /// Reports that the human took too long to move, and so lost.
+    pub fn report_forfeit(&mut self) -> io::Result<()> {
+        self.draw_text(28, 8, LT_WHITE, "Time's up!  I win!")?;
+        self.term.goto_xy(0, 20)
     }
 
-    /// Sets the current terminal color to the one given.
-    fn set_color(&mut self, color: u8) -> io::Result<()> {
-        self.term.write_str(format!("\x1b[{}m", color).as_str())
+    /// Shows the human's record against the computer.
+    pub fn report_record(&mut self, name: &str, record: Record) -> io::Result<()> {
+        let text = format!(
+            "{}: {} won, {} lost, {} tied",
+            name, record.wins, record.losses, record.ties
+        );
+        self.draw_text(28, 10, LT_WHITE, text.as_str())?;
+        self.term.goto_xy(0, 20)
+    }
+
+    /// Asks whether to write an analysis of the game, and returns whether
+    /// the user said yes.
+    pub fn offer_analysis(&mut self) -> bool {
+        self.draw_text(28, 11, LT_WHITE, "Write an analysis (y/n)? ")
+            .unwrap_or(());
+        let answer = self.term.read_char().expect("Terminal error");
+        self.term.goto_xy(0, 20).unwrap_or(());
+        answer == 'y'
+    }
+
+    /// Reports where the analysis of the game was written, or why it
+    /// couldn't be.
+    pub fn report_analysis(&mut self, path: &str, result: &io::Result<()>) -> io::Result<()> {
+        let text = match result {
+            Ok(()) => format!("Analysis written to {}.", path),
+            Err(err) => format!("Couldn't write {}: {}", path, err),
+        };
+        self.draw_text(0, 13, LT_YELLOW, text.as_str())?;
+        self.term.goto_xy(0, 20)
     }
 
     /// Waits for the user to press a key, then discards it.
     pub fn wait_for_key(&mut self) {
-        self.draw_text(28, 9, Self::LT_WHITE, "Press any key...")
+        self.draw_text(28, 9, LT_WHITE, "Press any key...")
             .unwrap_or(());
         self.term.read_char().expect("Terminal error");
     }
 }
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,387 @@
+//! This module contains the computer's search for a move, which is a negamax
+//! search with alpha-beta pruning that deepens one ply at a time.  A search
+//! can be stopped from another thread, which lets the computer ponder its
+//! reply in the background while the human is choosing a move.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::board::Board;
+use crate::profile::EvalParams;
+use crate::transcript::Source;
+
+/// How many plies the computer looks ahead before moving.
+pub const DEPTH: u32 = 4;
+
+/// How many plies the computer looks ahead while pondering, if the human
+/// gives it the time.  A handicap lessens this as much as `DEPTH`.
+pub const PONDER_DEPTH: u32 = 8;
+
+/// How many plies the computer looks ahead when analyzing a position.
+pub const ANALYSIS_DEPTH: u32 = 6;
+
+/// How many plies the computer looks ahead when annotating a transcript,
+/// which is as deep as it ever looks in play.
+pub const ANNOTATION_DEPTH: u32 = PONDER_DEPTH;
+
+/// The most empty squares for which `solve` is quick enough to wait for.
+pub const SOLVE_EMPTIES: i32 = 10;
+
+/// The score of a won game, which is more than any evaluation can reach.
+const WIN: i32 = 10_000;
+
+/// The best move that a search found, with its score and how deeply it was
+/// searched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub move_: (i32, i32),
+    pub score: i32,
+    pub depth: u32,
+}
+
+/// Searches for the best move for the given player, one ply deeper at a time
+/// up to the given depth, scoring positions by the given params and trying
+/// `first` ahead of the other moves if it is legal.  Setting `stop` ends the
+/// search early with the result of the deepest search that finished, though
+/// a search of one ply always finishes.  Returns None if the player has no
+/// move.
+pub fn search(
+    board: &Board,
+    player: u8,
+    depth: u32,
+    first: Option<(i32, i32)>,
+    params: &EvalParams,
+    stop: &AtomicBool,
+) -> Option<Found> {
+    let mut moves = board.get_moves(player);
+    if let Some(i) = first.and_then(|first| moves.iter().position(|&m| m == first)) {
+        moves[..=i].rotate_right(1);
+    }
+
+    let mut found = None;
+    for depth in 1..=depth {
+        match search_root(board, player, &moves, depth, params, stop) {
+            Some((i, score)) => {
+                // The best move so far is searched first at the next depth,
+                // where it gives the most cutoffs.
+                moves[..=i].rotate_right(1);
+                found = Some(Found {
+                    move_: moves[0],
+                    score,
+                    depth,
+                });
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+/// Plays the game out perfectly from the given board, returning the best
+/// move for the given player and the number of pieces by which they win, or
+/// lose if it is negative.  Returns None if the player has no move.  This
+/// takes too long unless the board is nearly full; see `SOLVE_EMPTIES`.
+pub fn solve(board: &Board, player: u8) -> Option<((i32, i32), i32)> {
+    // Every ply fills a square, except for passes, which never come twice in
+    // a row, so this reaches the end of the game on every line.
+    let depth = board.empties() as u32 * 2 + 1;
+    let moves = board.get_moves(player);
+    let never = AtomicBool::new(false);
+    let (i, score) = search_root(board, player, &moves, depth, &EvalParams::default(), &never)?;
+    Some((moves[i], score - WIN * score.signum()))
+}
+
+/// Returns the score of the given move for the given player, searched to the
+/// given depth, as `search` would score it if it were the best.
+pub fn score_move(
+    board: &Board,
+    player: u8,
+    move_: (i32, i32),
+    depth: u32,
+    params: &EvalParams,
+) -> i32 {
+    let mut next = *board;
+    next.do_move(move_.0, move_.1, player);
+    let never = AtomicBool::new(false);
+    -negamax(
+        &next,
+        player ^ 0b11,
+        depth - 1,
+        -WIN * 2,
+        WIN * 2,
+        params,
+        &never,
+    )
+    .unwrap()
+}
+
+/// Searches each of the given moves to the given depth, returning the index
+/// of the best and its score, or None if there are no moves or the search was
+/// stopped.
+fn search_root(
+    board: &Board,
+    player: u8,
+    moves: &[(i32, i32)],
+    depth: u32,
+    params: &EvalParams,
+    stop: &AtomicBool,
+) -> Option<(usize, i32)> {
+    let mut best = None;
+    let mut alpha = -WIN * 2;
+    for (i, &(col, row)) in moves.iter().enumerate() {
+        let mut next = *board;
+        next.do_move(col, row, player);
+        let score = -negamax(
+            &next,
+            player ^ 0b11,
+            depth - 1,
+            -WIN * 2,
+            -alpha,
+            params,
+            stop,
+        )?;
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some((i, score));
+        }
+    }
+    best
+}
+
+/// Returns the score of the board for the given player, who is to move,
+/// searched to the given depth within the (alpha, beta) window and scored by
+/// the given params, or None if the search was stopped.
+fn negamax(
+    board: &Board,
+    player: u8,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    params: &EvalParams,
+    stop: &AtomicBool,
+) -> Option<i32> {
+    if depth == 0 {
+        return Some(board.evaluate(player, params));
+    }
+    if stop.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let other = player ^ 0b11; // 1 -> 2, 2 -> 1
+    let moves = board.get_moves(player);
+    if moves.is_empty() {
+        if board.get_moves(other).is_empty() {
+            return Some(final_score(board, player));
+        }
+        // The player has to pass.
+        return negamax(board, other, depth - 1, -beta, -alpha, params, stop).map(|score| -score);
+    }
+
+    for (col, row) in moves {
+        let mut next = *board;
+        next.do_move(col, row, player);
+        let score = -negamax(&next, other, depth - 1, -beta, -alpha, params, stop)?;
+        if score >= beta {
+            return Some(score);
+        }
+        alpha = alpha.max(score);
+    }
+    Some(alpha)
+}
+
+/// Scores a finished game for the given player, preferring any win to any
+/// loss, and bigger wins to smaller ones.
+fn final_score(board: &Board, player: u8) -> i32 {
+    let margin = board.margin(player);
+    WIN * margin.signum() + margin
+}
+
+/// The human's move that pondering guessed, and what it found about the
+/// computer's reply.
+type Guess = (Option<(i32, i32)>, Option<Found>);
+
+/// Ponder is the computer thinking on the human's time.  It guesses the
+/// human's move, then searches for its reply to that move in a background
+/// thread until the human actually moves.
+pub struct Ponder {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Guess>>,
+}
+
+impl Ponder {
+    /// Starts pondering a board on which the human is to move, looking as
+    /// far as the given depth for the reply, which is scored by the given
+    /// params.
+    pub fn start(board: &Board, depth: u32, params: &EvalParams) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let board = *board;
+        let params = *params;
+        let handle = thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                // The guess is quick, so it isn't stopped partway.
+                let never = AtomicBool::new(false);
+                let predicted = match search(&board, Board::HUMAN, DEPTH - 1, None, &params, &never)
+                {
+                    Some(found) => found.move_,
+                    None => return (None, None),
+                };
+                let mut next = board;
+                next.do_move(predicted.0, predicted.1, Board::HUMAN);
+                let reply = search(&next, Board::COMPUTER, depth, None, &params, &stop);
+                (Some(predicted), reply)
+            }
+        });
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stops pondering now that the human has made the given move, and
+    /// returns what was found about the reply if the guess was right.
+    pub fn finish(mut self, human: (i32, i32)) -> Option<Found> {
+        self.stop.store(true, Ordering::Relaxed);
+        let (predicted, reply) = self.handle.take()?.join().ok()?;
+        if predicted == Some(human) {
+            reply
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Ponder {
+    /// Stops the background search if the game ends without a move.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Returns the computer's move on the given board, searched to the given
+/// depth and scored by the given params, using what pondering found about it
+/// if anything, along with where the move came from.  A search that went deep
+/// enough is used as it is, and the move of a shallower one is searched
+/// first.
+pub fn computer_move(
+    board: &Board,
+    pondered: Option<Found>,
+    depth: u32,
+    params: &EvalParams,
+) -> Option<(Found, Source)> {
+    match pondered {
+        Some(found) if found.depth >= depth => Some((found, Source::Ponder(found.depth))),
+        _ => {
+            let first = pondered.map(|found| found.move_);
+            search(
+                board,
+                Board::COMPUTER,
+                depth,
+                first,
+                params,
+                &AtomicBool::new(false),
+            )
+            .map(|found| (found, Source::Search(found.depth)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_finishes_one_ply_when_stopped() {
+        let board = Board::new();
+        let params = EvalParams::default();
+        let found = search(
+            &board,
+            Board::HUMAN,
+            DEPTH,
+            None,
+            &params,
+            &AtomicBool::new(true),
+        );
+        assert_eq!(found.map(|found| found.depth), Some(1));
+        assert!(board
+            .get_moves(Board::HUMAN)
+            .contains(&found.unwrap().move_));
+    }
+
+    #[test]
+    fn test_search_is_deterministic() {
+        let board = Board::new();
+        let params = EvalParams::default();
+        let stop = AtomicBool::new(false);
+        let found = search(&board, Board::COMPUTER, DEPTH, None, &params, &stop).unwrap();
+        assert_eq!(found.depth, DEPTH);
+        assert_eq!(
+            search(&board, Board::COMPUTER, DEPTH, None, &params, &stop),
+            Some(found)
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        // The human can take the last square, which flips both computer
+        // pieces for a win by 64 to 0.
+        let mut board = Board::empty();
+        for row in 0..8 {
+            for col in 0..8 {
+                board.put(col, row, Board::HUMAN);
+            }
+        }
+        board.put(7, 7, Board::EMPTY);
+        board.put(6, 7, Board::COMPUTER);
+        board.put(7, 6, Board::COMPUTER);
+        assert_eq!(solve(&board, Board::HUMAN), Some(((7, 7), 64)));
+        assert_eq!(solve(&board, Board::COMPUTER), None);
+
+        // With another square open next to it, the game ends with that
+        // square empty, since neither player can take it.
+        board.put(6, 7, Board::EMPTY);
+        assert_eq!(solve(&board, Board::HUMAN), Some(((7, 7), 63)));
+    }
+
+    #[test]
+    fn test_score_move() {
+        let board = Board::new();
+        let params = EvalParams::default();
+        let stop = AtomicBool::new(false);
+        let found = search(&board, Board::HUMAN, 3, None, &params, &stop).unwrap();
+        assert_eq!(
+            score_move(&board, Board::HUMAN, found.move_, 3, &params),
+            found.score
+        );
+        for move_ in board.get_moves(Board::HUMAN) {
+            assert!(score_move(&board, Board::HUMAN, move_, 3, &params) <= found.score);
+        }
+    }
+
+    #[test]
+    fn test_ponder() {
+        let board = Board::new();
+        let params = EvalParams::default();
+        let stop = AtomicBool::new(false);
+        let predicted = search(&board, Board::HUMAN, DEPTH - 1, None, &params, &stop)
+            .unwrap()
+            .move_;
+        let found = Ponder::start(&board, PONDER_DEPTH, &params)
+            .finish(predicted)
+            .unwrap();
+        let mut next = board;
+        next.do_move(predicted.0, predicted.1, Board::HUMAN);
+        assert!(next.get_moves(Board::COMPUTER).contains(&found.move_));
+
+        let other = board
+            .get_moves(Board::HUMAN)
+            .into_iter()
+            .find(|&m| m != predicted)
+            .unwrap();
+        assert_eq!(
+            Ponder::start(&board, PONDER_DEPTH, &params).finish(other),
+            None
+        );
+    }
+}
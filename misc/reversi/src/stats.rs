@@ -0,0 +1,142 @@
+//! Keeping each player's record of wins and losses against the computer.
+//!
+//! The stats file is a small text file with one line per player, giving their
+//! wins, losses, and ties, then their name:
+//!
+//! ```text
+//! 3 5 1 Alice
+//! 0 2 0 Bob Smith
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// The outcome of a finished game, from the human's side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Tie,
+}
+
+/// A player's results against the computer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+impl Record {
+    /// Adds the outcome of a game to the record.
+    pub fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Tie => self.ties += 1,
+        }
+    }
+}
+
+/// The records of every player, by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    records: BTreeMap<String, Record>,
+}
+
+impl Stats {
+    /// Returns the record of the given player, who may not have played yet.
+    pub fn record(&self, name: &str) -> Record {
+        self.records.get(name).copied().unwrap_or_default()
+    }
+
+    /// Adds the outcome of a game to the given player's record.
+    pub fn add(&mut self, name: &str, outcome: Outcome) {
+        self.records
+            .entry(name.to_string())
+            .or_default()
+            .add(outcome);
+    }
+
+    /// Reads the stats from the given file, which needn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the stats to the given file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut stats = Self::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.splitn(4, ' ');
+            let record = Record {
+                wins: parse_value(fields.next())?,
+                losses: parse_value(fields.next())?,
+                ties: parse_value(fields.next())?,
+            };
+            match fields.next() {
+                Some(name) if !name.is_empty() => stats.records.insert(name.to_string(), record),
+                _ => return Err(invalid("missing name")),
+            };
+        }
+        Ok(stats)
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (name, record) in &self.records {
+            writeln!(
+                f,
+                "{} {} {} {}",
+                record.wins, record.losses, record.ties, name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Parses a single count from the stats file.
+fn parse_value(field: Option<&str>) -> Result<u32> {
+    field
+        .and_then(|field| field.parse::<u32>().ok())
+        .ok_or_else(|| invalid("malformed number"))
+}
+
+// Builds an error describing a corrupt stats file.
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("bad stats file: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut stats = Stats::default();
+        stats.add("Bob Smith", Outcome::Loss);
+        stats.add("Alice", Outcome::Win);
+        stats.add("Alice", Outcome::Tie);
+        let text = stats.to_string();
+        assert_eq!(text, "1 0 1 Alice\n0 1 0 Bob Smith\n");
+        assert_eq!(Stats::parse(&text).unwrap(), stats);
+        assert_eq!(stats.record("Carol"), Record::default());
+    }
+
+    #[test]
+    fn test_bad_stats() {
+        assert!(Stats::parse("1 2 Alice\n").is_err());
+        assert!(Stats::parse("1 2 3\n").is_err());
+        assert!(Stats::parse("1 2 3 \n").is_err());
+    }
+}
@@ -0,0 +1,489 @@
+//! Keeping a transcript of each game, move by move, with what the computer
+//! made of each move as it was played.
+//!
+//! The transcript file is laid out after the PGN files of chess: a few tags
+//! about the game, then a line for each move giving its evaluation for the
+//! player who made it, the time it took, and where it came from.
+//!
+//! ```text
+//! [Human "Alice"]
+//! [Position "...........................HC......CH..........................."]
+//! [First "Human"]
+//! [Result "40-24"]
+//!
+//! 1. Human c5 {+4 3.2s human}
+//! 2. Computer c4 {-2 0.4s ponder 8}
+//! ```
+//!
+//! A game played by other rules than the usual ones has a `Variant` tag
+//! after the position, such as `[Variant "anti"]`.
+//!
+//! Annotating a transcript searches every position again, more deeply, and
+//! adds the score of the move played and of the best move after it, with
+//! "??" before the blunders:
+//!
+//! ```text
+//! 7. Human b2 {-3 5.0s human} ?? {depth 6: -18, best a1 +6}
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use crate::board::{self, Board, Variant};
+use crate::profile::EvalParams;
+use crate::search;
+
+/// How much worse than the best move a move must score to be a blunder,
+/// which is as much as a corner is worth.
+pub const BLUNDER: i32 = 10;
+
+/// Where a move came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The human chose it.
+    Human,
+    /// The computer searched for it to the given depth on its own time.
+    Search(u32),
+    /// The computer searched for it to the given depth while pondering.
+    Ponder(u32),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Source::Human => write!(f, "human"),
+            Source::Search(depth) => write!(f, "search {}", depth),
+            Source::Ponder(depth) => write!(f, "ponder {}", depth),
+        }
+    }
+}
+
+/// What a deeper search found about a move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    /// How many plies the search looked ahead.
+    pub depth: u32,
+    /// The score of the move played.
+    pub score: i32,
+    /// The best move.
+    pub best: (i32, i32),
+    /// The score of the best move.
+    pub best_score: i32,
+}
+
+impl Analysis {
+    /// Was the move played a blunder?
+    pub fn is_blunder(&self) -> bool {
+        self.best_score - self.score >= BLUNDER
+    }
+}
+
+/// A move in a transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    /// The player who made it.
+    pub player: u8,
+    pub square: (i32, i32),
+    /// The score of the move for the player who made it, as the computer
+    /// saw it at the time.
+    pub eval: i32,
+    /// How long the player took over it.
+    pub time: Duration,
+    pub source: Source,
+    /// What annotating the transcript found about it, if it has been.
+    pub analysis: Option<Analysis>,
+}
+
+/// The moves of a game, and the position that they were made from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transcript {
+    /// The name of the human.
+    pub name: String,
+    pub start: Board,
+    /// The player who moved first.
+    pub first: u8,
+    pub moves: Vec<Move>,
+}
+
+impl Transcript {
+    /// Returns a transcript of a game that has yet to start from the given
+    /// position.
+    pub fn new(name: &str, start: Board, first: u8) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+            first,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Searches every position in the game again to the given depth, and
+    /// notes what it found about each move.  The search has no personality,
+    /// whichever the computer played with.
+    pub fn annotate(&mut self, depth: u32) -> Result<()> {
+        let boards = self.boards()?;
+        let params = EvalParams::default();
+        let never = AtomicBool::new(false);
+        for (move_, board) in self.moves.iter_mut().zip(boards) {
+            // A legal move was played, so there was a move to find.
+            let found = search::search(&board, move_.player, depth, None, &params, &never).unwrap();
+            let score = if found.move_ == move_.square {
+                found.score
+            } else {
+                search::score_move(&board, move_.player, move_.square, depth, &params)
+            };
+            move_.analysis = Some(Analysis {
+                depth,
+                score,
+                best: found.move_,
+                best_score: found.score,
+            });
+        }
+        Ok(())
+    }
+
+    /// Plays the moves out from the starting position, returning the board
+    /// before each move and then the board after the last.  Fails at the
+    /// first move that is illegal or made out of turn.
+    pub(crate) fn boards(&self) -> Result<Vec<Board>> {
+        let mut board = self.start;
+        let mut turn = self.first;
+        let mut boards = vec![board];
+        for (i, move_) in self.moves.iter().enumerate() {
+            if board.get_moves(turn).is_empty() {
+                turn ^= 0b11; // The player to move has to pass.
+            }
+            let (col, row) = move_.square;
+            if move_.player != turn || !board.is_legal(col, row, turn) {
+                return Err(invalid(&format!("move {} is illegal", i + 1)));
+            }
+            board.do_move(col, row, turn);
+            boards.push(board);
+            turn ^= 0b11;
+        }
+        Ok(boards)
+    }
+
+    /// Reads a transcript from the given file.
+    pub fn load(path: &str) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Writes the transcript to the given file.
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut transcript = Self::new("", Board::empty(), Board::HUMAN);
+        let mut variant = Variant::default();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) {
+                Some(tag) => {
+                    let (name, value) = tag.split_once(' ').ok_or_else(|| invalid("bad tag"))?;
+                    let value = value
+                        .strip_prefix('"')
+                        .and_then(|value| value.strip_suffix('"'))
+                        .ok_or_else(|| invalid("bad tag"))?;
+                    match name {
+                        "Human" => transcript.name = value.to_string(),
+                        "Position" => transcript.start = parse_position(value)?,
+                        "First" => transcript.first = parse_player(Some(value))?,
+                        "Variant" => {
+                            variant = Variant::from_name(value)
+                                .ok_or_else(|| invalid("unknown variant"))?
+                        }
+                        // The result follows from the moves.
+                        _ => (),
+                    }
+                }
+                None => transcript.moves.push(parse_move(line)?),
+            }
+        }
+        transcript.start.set_variant(variant);
+        transcript.boards()?;
+        Ok(transcript)
+    }
+}
+
+impl Display for Transcript {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "[Human \"{}\"]", self.name)?;
+        let mut position = String::new();
+        for row in 0..8 {
+            for col in 0..8 {
+                position.push(match self.start.get(col, row) {
+                    Board::HUMAN => 'H',
+                    Board::COMPUTER => 'C',
+                    _ => '.',
+                });
+            }
+        }
+        writeln!(f, "[Position \"{}\"]", position)?;
+        if self.start.variant() != Variant::default() {
+            writeln!(f, "[Variant \"{}\"]", self.start.variant())?;
+        }
+        writeln!(f, "[First \"{}\"]", player_name(self.first))?;
+        if let Some(board) = self.boards().ok().and_then(|boards| boards.last().copied()) {
+            if board.game_over() {
+                writeln!(
+                    f,
+                    "[Result \"{}-{}\"]",
+                    board.get_score(Board::HUMAN).unwrap(),
+                    board.get_score(Board::COMPUTER).unwrap()
+                )?;
+            }
+        }
+        writeln!(f)?;
+
+        for (i, move_) in self.moves.iter().enumerate() {
+            write!(
+                f,
+                "{}. {} {} {{{:+} {:.1}s {}}}",
+                i + 1,
+                player_name(move_.player),
+                board::square_name(move_.square.0, move_.square.1),
+                move_.eval,
+                move_.time.as_secs_f64(),
+                move_.source
+            )?;
+            if let Some(analysis) = move_.analysis {
+                if analysis.is_blunder() {
+                    write!(f, " ??")?;
+                }
+                write!(
+                    f,
+                    " {{depth {}: {:+}, best {} {:+}}}",
+                    analysis.depth,
+                    analysis.score,
+                    board::square_name(analysis.best.0, analysis.best.1),
+                    analysis.best_score
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Returns the name of a player as the transcript gives it.
+fn player_name(player: u8) -> &'static str {
+    match player {
+        Board::HUMAN => "Human",
+        _ => "Computer",
+    }
+}
+
+// Parses the name of a player.
+fn parse_player(field: Option<&str>) -> Result<u8> {
+    match field {
+        Some("Human") => Ok(Board::HUMAN),
+        Some("Computer") => Ok(Board::COMPUTER),
+        _ => Err(invalid("unknown player")),
+    }
+}
+
+// Parses a starting position of 64 squares, row by row.
+fn parse_position(text: &str) -> Result<Board> {
+    if text.len() != 64 {
+        return Err(invalid("bad position"));
+    }
+    let mut board = Board::empty();
+    for (i, ch) in (0..).zip(text.chars()) {
+        let value = match ch {
+            'H' => Board::HUMAN,
+            'C' => Board::COMPUTER,
+            '.' => Board::EMPTY,
+            _ => return Err(invalid("bad position")),
+        };
+        board.put(i % 8, i / 8, value);
+    }
+    Ok(board)
+}
+
+// Parses the line for a move, along with any analysis of it.
+fn parse_move(line: &str) -> Result<Move> {
+    let (head, rest) = line
+        .split_once('{')
+        .ok_or_else(|| invalid("missing evaluation"))?;
+    let (notes, rest) = rest
+        .split_once('}')
+        .ok_or_else(|| invalid("missing evaluation"))?;
+
+    let mut fields = head.split_whitespace();
+    if !fields.next().is_some_and(|number| number.ends_with('.')) {
+        return Err(invalid("missing move number"));
+    }
+    let player = parse_player(fields.next())?;
+    let square = parse_square(fields.next())?;
+
+    let mut notes = notes.split_whitespace();
+    let eval = parse_value(notes.next())?;
+    let time = notes
+        .next()
+        .and_then(|time| time.strip_suffix('s'))
+        .and_then(|time| time.parse::<f64>().ok())
+        .and_then(|time| Duration::try_from_secs_f64(time).ok())
+        .ok_or_else(|| invalid("malformed time"))?;
+    let source = match (notes.next(), notes.next()) {
+        (Some("human"), None) => Source::Human,
+        (Some("search"), depth @ Some(_)) => Source::Search(parse_value(depth)?),
+        (Some("ponder"), depth @ Some(_)) => Source::Ponder(parse_value(depth)?),
+        _ => return Err(invalid("unknown source")),
+    };
+
+    // Whether the move was a blunder follows from the scores.
+    let rest = rest.trim().trim_start_matches("??").trim();
+    let analysis = match rest
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    {
+        Some(analysis) => {
+            let analysis = analysis.replace([':', ','], " ");
+            let mut fields = analysis.split_whitespace();
+            if fields.next() != Some("depth") {
+                return Err(invalid("bad analysis"));
+            }
+            let depth = parse_value(fields.next())?;
+            let score = parse_value(fields.next())?;
+            if fields.next() != Some("best") {
+                return Err(invalid("bad analysis"));
+            }
+            Some(Analysis {
+                depth,
+                score,
+                best: parse_square(fields.next())?,
+                best_score: parse_value(fields.next())?,
+            })
+        }
+        None if rest.is_empty() => None,
+        None => return Err(invalid("bad analysis")),
+    };
+
+    Ok(Move {
+        player,
+        square,
+        eval,
+        time,
+        source,
+        analysis,
+    })
+}
+
+// Parses the name of a square.
+fn parse_square(field: Option<&str>) -> Result<(i32, i32)> {
+    field
+        .and_then(board::parse_square)
+        .ok_or_else(|| invalid("bad square"))
+}
+
+// Parses a single number from the transcript.
+fn parse_value<T: std::str::FromStr>(field: Option<&str>) -> Result<T> {
+    field
+        .and_then(|field| field.parse::<T>().ok())
+        .ok_or_else(|| invalid("malformed number"))
+}
+
+// Builds an error describing a corrupt transcript.
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("bad transcript: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\
+[Human \"Alice\"]
+[Position \"...........................HC......CH...........................\"]
+[First \"Human\"]
+
+1. Human c5 {+4 3.2s human}
+2. Computer c4 {-2 0.4s ponder 8}
+";
+
+    #[test]
+    fn test_round_trip() {
+        let transcript = Transcript::parse(TEXT).unwrap();
+        assert_eq!(transcript.name, "Alice");
+        assert_eq!(transcript.start, Board::new());
+        assert_eq!(transcript.moves[1].source, Source::Ponder(8));
+        assert_eq!(transcript.moves[1].square, (3, 2));
+        assert_eq!(transcript.to_string(), TEXT);
+    }
+
+    #[test]
+    fn test_variants() {
+        // Under the original rules, the center is filled first.
+        let mut transcript = Transcript::new("Alice", Board::start(Variant::Reversi), Board::HUMAN);
+        for (player, square) in [
+            (Board::HUMAN, (3, 3)),
+            (Board::COMPUTER, (4, 3)),
+            (Board::HUMAN, (3, 4)),
+            (Board::COMPUTER, (4, 4)),
+            (Board::HUMAN, (5, 2)),
+        ] {
+            transcript.moves.push(Move {
+                player,
+                square,
+                eval: 0,
+                time: Duration::from_secs(1),
+                source: Source::Human,
+                analysis: None,
+            });
+        }
+        let text = transcript.to_string();
+        let tags = format!("[Position \"{}\"]\n[Variant \"reversi\"]\n", ".".repeat(64));
+        assert!(text.contains(&tags));
+        assert_eq!(Transcript::parse(&text).unwrap(), transcript);
+    }
+
+    #[test]
+    fn test_bad_transcripts() {
+        // The computer can't move first when the human is to.
+        let out_of_turn = TEXT.replace("1. Human", "1. Computer");
+        assert!(Transcript::parse(&out_of_turn).is_err());
+        assert!(Transcript::parse(&TEXT.replace("c5", "a1")).is_err());
+        assert!(Transcript::parse(&TEXT.replace("ponder 8", "book")).is_err());
+        assert!(Transcript::parse(&TEXT.replace("HC......", "HX......")).is_err());
+        let variant = TEXT.replace("[First", "[Variant \"checkers\"]\n[First");
+        assert!(Transcript::parse(&variant).is_err());
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut transcript = Transcript::parse(TEXT).unwrap();
+        transcript.annotate(3).unwrap();
+        for move_ in &transcript.moves {
+            let analysis = move_.analysis.unwrap();
+            assert_eq!(analysis.depth, 3);
+            assert!(analysis.score <= analysis.best_score);
+        }
+        let text = transcript.to_string();
+        assert_eq!(Transcript::parse(&text).unwrap(), transcript);
+
+        // Giving up a corner is a blunder.
+        let analysis = Analysis {
+            depth: 6,
+            score: -18,
+            best: (0, 0),
+            best_score: 6,
+        };
+        assert!(analysis.is_blunder());
+        let mut transcript = Transcript::new("Bob", Board::new(), Board::HUMAN);
+        transcript.moves.push(Move {
+            player: Board::HUMAN,
+            square: (4, 2),
+            eval: -3,
+            time: Duration::from_secs(5),
+            source: Source::Human,
+            analysis: Some(analysis),
+        });
+        assert!(transcript
+            .to_string()
+            .ends_with("1. Human c5 {-3 5.0s human} ?? {depth 6: -18, best a1 +6}\n"));
+    }
+}
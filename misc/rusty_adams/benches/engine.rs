@@ -0,0 +1,79 @@
+//! These benchmarks time the three things the crate spends most of its time
+//! on: parsing a game file, writing a game back out, and playing a game.
+//! Run them with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rusty_adams::game::interpreter::Interpreter;
+use rusty_adams::game::writer::write_game;
+
+/// The largest game in the collection.
+const GAME: &str = "games/adv10.dat";
+
+/// A game file that ends partway through, for timing how the parser fails.
+const DAMAGED: &str = "tests/fixtures/truncated.dat";
+
+/// The commands played over and over in the playthrough, which between them
+/// run the actions, the built-in GET and DROP, and the room descriptions.
+const SCRIPT: [&str; 5] = ["get key", "i", "drop key", "look", "take rock"];
+
+/// The number of turns in the playthrough.
+const TURNS: usize = 500;
+
+fn bench_load_game(c: &mut Criterion) {
+    c.bench_function("load_game", |b| {
+        b.iter(|| rusty_adams::load_game(black_box(GAME)).unwrap())
+    });
+}
+
+fn bench_parse_game(c: &mut Criterion) {
+    let data = std::fs::read(GAME).unwrap();
+    let damaged = std::fs::read(DAMAGED).unwrap();
+    let mut group = c.benchmark_group("parse_game");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("whole", |b| {
+        b.iter(|| rusty_adams::load_game_from_bytes(black_box(&data)).unwrap())
+    });
+    group.throughput(Throughput::Bytes(damaged.len() as u64));
+    group.bench_function("damaged", |b| {
+        b.iter(|| rusty_adams::load_game_from_bytes(black_box(&damaged)).unwrap_err())
+    });
+    group.finish();
+}
+
+fn bench_write_game(c: &mut Criterion) {
+    let game = rusty_adams::load_game(GAME).unwrap();
+    c.bench_function("write_game", |b| {
+        b.iter(|| {
+            let mut out = Vec::new();
+            write_game(&mut out, black_box(&game)).unwrap();
+            out
+        })
+    });
+}
+
+fn bench_playthrough(c: &mut Criterion) {
+    c.bench_function("playthrough", |b| {
+        b.iter_batched(
+            || {
+                let game = rusty_adams::load_game("tests/fixtures/get_drop.dat").unwrap();
+                Interpreter::new(game)
+            },
+            |mut interpreter| {
+                interpreter.start();
+                for command in SCRIPT.iter().cycle().take(TURNS) {
+                    black_box(interpreter.command(command));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_load_game,
+    bench_parse_game,
+    bench_write_game,
+    bench_playthrough
+);
+criterion_main!(benches);
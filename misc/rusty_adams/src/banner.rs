@@ -0,0 +1,84 @@
+//! This module finds the banner that is shown when a game starts: a piece
+//! of text or ASCII art, such as the output of FIGlet, that stands in for
+//! the title screens the games never had.
+//!
+//! A game's banner is kept in a file named for its adventure number, such as
+//! `banner01.txt` for Adventureland, in the directory of the game file or
+//! any directory of the games path.  A game with no banner file of its own
+//! gets `DEFAULT`.  Banners are shown just as they are written, without
+//! being wrapped, so they should fit the narrowest screen the game will be
+//! played on.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The banner for games that have none of their own.  It fits a screen of
+/// 40 columns, and has nothing but printable ASCII for dumb terminals.
+pub const DEFAULT: &str = "\
+*************************************
+*                                   *
+*      R U S T Y   A D A M S        *
+*                                   *
+*  Adventures in the Scott Adams    *
+*  tradition                        *
+*                                   *
+*************************************
+
+";
+
+/// Returns the name of the banner file for the given adventure number.
+pub fn file_name(adventure: i32) -> String {
+    format!("banner{:02}.txt", adventure)
+}
+
+/// Returns the banner for the given adventure from the first of the given
+/// directories that has a banner file for it, or None if none of them do.
+/// The banner ends with a blank line, to set it apart from the game.
+pub fn find(dirs: &[PathBuf], adventure: i32) -> Option<String> {
+    let name = file_name(adventure);
+    dirs.iter()
+        .find_map(|dir| fs::read_to_string(dir.join(&name)).ok())
+        .map(|text| format!("{}\n\n", text.trim_end()))
+}
+
+/// Returns the banner for the game at the given path, looking in its own
+/// directory before the given ones, or `DEFAULT` if there is no banner file
+/// for it.
+pub fn for_game(path: Option<&Path>, adventure: i32, dirs: &[PathBuf]) -> String {
+    let own = path.and_then(Path::parent).map(Path::to_path_buf);
+    let dirs: Vec<PathBuf> = own.into_iter().chain(dirs.iter().cloned()).collect();
+    find(&dirs, adventure).unwrap_or_else(|| DEFAULT.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(file_name(1), "banner01.txt");
+        assert_eq!(file_name(14), "banner14.txt");
+
+        let dir = env::temp_dir().join(format!("banner-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("banner05.txt"), "THE COUNT\n\n\n").unwrap();
+        let dirs = [PathBuf::from("no/such/dir"), dir.clone()];
+        assert_eq!(find(&dirs, 5).as_deref(), Some("THE COUNT\n\n"));
+        assert_eq!(find(&dirs, 6), None);
+
+        let game = dir.join("adv05.dat");
+        assert_eq!(for_game(Some(&game), 5, &[]), "THE COUNT\n\n");
+        assert_eq!(for_game(Some(&game), 6, &dirs), DEFAULT);
+        assert_eq!(for_game(None, 5, &[]), DEFAULT);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_fits() {
+        assert!(DEFAULT.lines().all(
+            |line| line.len() < 40 && line.chars().all(|ch| ch.is_ascii_graphic() || ch == ' ')
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+//! This module plays games automatically, for testing the interpreter.
+//!
+//! A `BotPlayer` chooses each command from what the game just said, and
+//! `soak` has one play a game for many turns, starting it over whenever it
+//! ends, and reports any turn on which the interpreter panicked or gave up.
+//! The built-in `Bot` only gives commands made of words from the game's own
+//! vocabulary, so that its turns reach the actions rather than stopping at
+//! "You use word(s) I don't know!".
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Duration;
+
+use crate::game::interpreter::Interpreter;
+use crate::game::{Game, RoomId};
+
+/// The most actions that a turn may run during a soak before the turn is
+/// abandoned and reported, which is far more than any real turn needs.
+pub const ACTION_BUDGET: usize = 10_000;
+
+/// Something that can play a game, one command at a time.
+pub trait BotPlayer {
+    /// Returns the next command to give, given the interpreter and what the
+    /// game said in reply to the last one.
+    fn next_command(&mut self, interpreter: &Interpreter, said: &str) -> String;
+}
+
+/// How the built-in bot chooses its commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Any verb, with any noun or none.
+    Random,
+    /// A command that hasn't yet been tried in the room that the player is
+    /// in, if there are any left, which takes the bot much further into a
+    /// game than chance does.
+    Greedy,
+}
+
+impl Strategy {
+    /// Looks up a strategy by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Strategy::Random),
+            "greedy" => Some(Strategy::Greedy),
+            _ => None,
+        }
+    }
+}
+
+/// The built-in bot, which gives commands of a verb and an optional noun
+/// from the game's vocabulary.
+pub struct Bot {
+    strategy: Strategy,
+    rng: StdRng,
+    verbs: Vec<String>,
+    nouns: Vec<String>,
+    /// The commands that have been given in each room, which only the greedy
+    /// strategy keeps.
+    tried: HashSet<(RoomId, String)>,
+}
+
+impl Bot {
+    /// Returns a bot for the given game, which chooses its commands by the
+    /// given strategy and with a random number generator seeded from the
+    /// given seed, so that its games can be repeated.
+    pub fn new(game: &Game, strategy: Strategy, seed: u64) -> Bot {
+        let words = |groups: Vec<crate::game::WordGroup>| {
+            groups
+                .into_iter()
+                .map(|group| group.word)
+                .filter(|word| word.chars().next().is_some_and(char::is_alphanumeric))
+                .collect()
+        };
+        Bot {
+            strategy,
+            rng: StdRng::seed_from_u64(seed),
+            verbs: words(game.verb_groups()),
+            nouns: words(game.noun_groups()),
+            tried: HashSet::new(),
+        }
+    }
+
+    /// Returns a command chosen at random.
+    fn random_command(&mut self) -> String {
+        let verb = match self.verbs.choose(&mut self.rng) {
+            Some(verb) => verb.clone(),
+            None => return "LOOK".to_string(),
+        };
+        match self.nouns.choose(&mut self.rng) {
+            Some(noun) if self.rng.gen_bool(0.75) => format!("{} {}", verb, noun),
+            _ => verb,
+        }
+    }
+}
+
+impl BotPlayer for Bot {
+    fn next_command(&mut self, interpreter: &Interpreter, _said: &str) -> String {
+        // Trying a few commands at random finds an untried one quickly, for
+        // as long as there are many left.
+        let tries = match self.strategy {
+            Strategy::Random => 1,
+            Strategy::Greedy => 20,
+        };
+        let mut command = self.random_command();
+        for _ in 1..tries {
+            if !self.tried.contains(&(interpreter.room(), command.clone())) {
+                break;
+            }
+            command = self.random_command();
+        }
+        if self.strategy == Strategy::Greedy {
+            self.tried.insert((interpreter.room(), command.clone()));
+        }
+        command
+    }
+}
+
+/// What came of a soak.
+#[derive(Debug, Default, PartialEq)]
+pub struct Soak {
+    /// The number of turns played.
+    pub turns: usize,
+    /// The number of games that ended, whether won, lost or quit.
+    pub finished: usize,
+    /// Describes each turn on which the interpreter panicked or gave up,
+    /// along with the command it was given.
+    pub failures: Vec<String>,
+}
+
+/// Has the given bot play a game for the given number of turns, with at
+/// least the given pause between them, starting the game over whenever it
+/// ends or the interpreter panics.  The game is loaded afresh for each start.
+pub fn soak(
+    load: impl Fn() -> Result<Game, String>,
+    bot: &mut dyn BotPlayer,
+    turns: usize,
+    pause: Duration,
+) -> Result<Soak, String> {
+    let mut soak = Soak::default();
+    let mut interpreter = None;
+    let mut said = String::new();
+    while soak.turns < turns {
+        let current = match &mut interpreter {
+            Some(interpreter) => interpreter,
+            None => {
+                let mut new = Interpreter::new(load()?);
+                new.set_action_budget(Some(ACTION_BUDGET));
+                said = new.start();
+                interpreter.insert(new)
+            }
+        };
+
+        let command = bot.next_command(current, &said);
+        soak.turns += 1;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let result = current.try_command(&command);
+            current.take_save_request();
+            result
+        }));
+        match result {
+            Ok(Ok(text)) => said = text,
+            Ok(Err(err)) => {
+                soak.failures
+                    .push(format!("turn {}: {:?}: {}", soak.turns, command, err));
+                said = String::new();
+            }
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|msg| msg.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                soak.failures.push(format!(
+                    "turn {}: {:?}: panicked: {}",
+                    soak.turns, command, message
+                ));
+                interpreter = None;
+                continue;
+            }
+        }
+        if current.is_finished() {
+            soak.finished += 1;
+            interpreter = None;
+        }
+        if !pause.is_zero() {
+            thread::sleep(pause);
+        }
+    }
+    Ok(soak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bot() {
+        let game = crate::load_demo().unwrap();
+        let mut bot = Bot::new(&game, Strategy::Greedy, 1);
+        let mut interpreter = Interpreter::new(game);
+        let said = interpreter.start();
+        let verbs = bot.verbs.clone();
+        let mut commands = HashSet::new();
+        for _ in 0..20 {
+            let command = bot.next_command(&interpreter, &said);
+            let verb = command.split(' ').next().unwrap();
+            assert!(verbs.iter().any(|word| word == verb), "{}", command);
+            commands.insert(command);
+        }
+        // Standing still, the greedy bot seldom repeats itself.
+        assert!(commands.len() > 15);
+
+        // The random bot has no use for the commands it has given.
+        let mut bot = Bot::new(interpreter.game(), Strategy::Random, 1);
+        bot.next_command(&interpreter, &said);
+        assert!(bot.tried.is_empty());
+    }
+
+    #[test]
+    fn test_soak() {
+        let game = crate::load_demo().unwrap();
+        let mut bot = Bot::new(&game, Strategy::Random, 7);
+        let soak = soak(crate::load_demo, &mut bot, 500, Duration::ZERO).unwrap();
+        assert_eq!(soak.turns, 500);
+        assert_eq!(soak.failures, Vec::<String>::new());
+    }
+}
@@ -0,0 +1,388 @@
+//! This module finds game files by name, so that players can ask for
+//! "adventureland" or "4" rather than giving the path to `adv01.dat` or
+//! `adv04.dat`.
+//!
+//! The games are looked for in each directory of the games path, which is
+//! made up of (in order) a directory given on the command line, the
+//! directories listed in the `RUSTY_ADAMS_GAMES` environment variable, those
+//! listed one per line in the config file, and finally `./games`.
+//!
+//! It can also survey a directory of games, describing each one in a row of
+//! the catalogue that `rusty_adams list` prints.
+
+use appdirs::AppDirs;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The environment variable listing directories to search for games,
+/// separated as in `PATH`.
+pub const GAMES_VAR: &str = "RUSTY_ADAMS_GAMES";
+
+/// Describes one of the known games.
+#[derive(Debug, PartialEq)]
+pub struct Title {
+    /// The name of the game file, without the `.dat` extension.
+    pub file: &'static str,
+    /// The number Scott Adams gave the adventure, if any.
+    pub adventure: Option<u32>,
+    /// The game's title.
+    pub name: &'static str,
+}
+
+/// The games of the Scott Adams collection, which are the ones that ship in
+/// the `games` directory.
+#[rustfmt::skip]
+pub static TITLES: [Title; 18] = [
+    Title { file: "adv01", adventure: Some(1), name: "Adventureland" },
+    Title { file: "adv02", adventure: Some(2), name: "Pirate Adventure" },
+    Title { file: "adv03", adventure: Some(3), name: "Secret Mission" },
+    Title { file: "adv04", adventure: Some(4), name: "Voodoo Castle" },
+    Title { file: "adv05", adventure: Some(5), name: "The Count" },
+    Title { file: "adv06", adventure: Some(6), name: "Strange Odyssey" },
+    Title { file: "adv07", adventure: Some(7), name: "Mystery Fun House" },
+    Title { file: "adv08", adventure: Some(8), name: "Pyramid of Doom" },
+    Title { file: "adv09", adventure: Some(9), name: "Ghost Town" },
+    Title { file: "adv10", adventure: Some(10), name: "Savage Island, Part I" },
+    Title { file: "adv11", adventure: Some(11), name: "Savage Island, Part II" },
+    Title { file: "adv12", adventure: Some(12), name: "The Golden Voyage" },
+    Title { file: "adv13", adventure: Some(13), name: "Sorcerer of Claymorgue Castle" },
+    Title { file: "adv14a", adventure: Some(14), name: "Return to Pirate's Isle" },
+    Title { file: "adv14b", adventure: Some(14), name: "Buckaroo Banzai" },
+    Title { file: "quest1", adventure: None, name: "The Hulk" },
+    Title { file: "quest2", adventure: None, name: "Spiderman" },
+    Title { file: "sampler1", adventure: None, name: "Adventureland (sampler version)" },
+];
+
+/// Describes one game file in a directory, as far as it could be parsed.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Entry {
+    /// The name of the game file.
+    pub file: String,
+    /// The game's title, if it is one of `TITLES`.
+    pub title: Option<&'static str>,
+    /// The adventure number from the footer, or from `TITLES` if the game
+    /// couldn't be parsed.
+    pub adventure: Option<i32>,
+    /// The version from the footer, such as "4.16".
+    pub version: Option<String>,
+    /// The number of rooms.  This and the other counts come from whatever
+    /// could be parsed if the rest of the game couldn't be.
+    pub rooms: Option<i32>,
+    /// The number of items.
+    pub items: Option<i32>,
+    /// The number of treasures.
+    pub treasures: Option<i32>,
+    /// Why the game couldn't be parsed, if it couldn't.
+    pub error: Option<String>,
+}
+
+impl Entry {
+    /// Parses the game file at the given path, falling back to whatever could
+    /// be parsed of it if the whole game can't be.
+    pub fn new(path: &Path) -> Entry {
+        let title = title_of(path);
+        let mut entry = Entry {
+            file: path
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().into_owned()),
+            title: title.map(|title| title.name),
+            adventure: None,
+            version: None,
+            rooms: None,
+            items: None,
+            treasures: None,
+            error: None,
+        };
+
+        let path = path.to_string_lossy();
+        match crate::load_game_partial(&path) {
+            Ok(game) => {
+                let counts = game.header_counts();
+                entry.adventure = Some(game.adventure());
+                entry.version = Some(format!(
+                    "{}.{:02}",
+                    game.version() / 100,
+                    game.version() % 100
+                ));
+                entry.rooms = Some(counts.rooms);
+                entry.items = Some(counts.items);
+                entry.treasures = Some(game.treasures() as i32);
+            }
+            Err(partial) => {
+                entry.adventure = title.and_then(|title| title.adventure).map(|n| n as i32);
+                if let Some(counts) = partial.header_counts() {
+                    entry.rooms = Some(counts.rooms);
+                    entry.items = Some(counts.items);
+                    entry.treasures = Some(
+                        partial
+                            .treasures()
+                            .map_or(counts.treasures, |treasures| treasures as i32),
+                    );
+                }
+                entry.error = Some(partial.error().to_string());
+            }
+        }
+        entry
+    }
+}
+
+/// Surveys the game files in a directory, in order of their names.
+pub fn survey(dir: &Path) -> io::Result<Vec<Entry>> {
+    Ok(crate::list_games(dir)?
+        .iter()
+        .map(|path| Entry::new(path))
+        .collect())
+}
+
+/// Lays out a survey as a table, with a heading and a line per game.
+pub fn table(entries: &[Entry]) -> Vec<String> {
+    let show = |value: &Option<i32>| value.map_or("-".to_string(), |n| n.to_string());
+    let mut rows = vec![[
+        "File",
+        "Title",
+        "Adv",
+        "Version",
+        "Rooms",
+        "Items",
+        "Treasures",
+        "Status",
+    ]
+    .map(String::from)];
+    for entry in entries {
+        rows.push([
+            entry.file.clone(),
+            entry.title.unwrap_or("-").to_string(),
+            show(&entry.adventure),
+            entry.version.clone().unwrap_or("-".to_string()),
+            show(&entry.rooms),
+            show(&entry.items),
+            show(&entry.treasures),
+            entry.error.clone().unwrap_or("ok".to_string()),
+        ]);
+    }
+
+    // Text columns are aligned on the left, and numbers on the right.
+    let mut widths = [0; 8];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .enumerate()
+                .map(|(i, (cell, width))| match i {
+                    2..=6 => format!("{:>width$}", cell),
+                    _ => format!("{:<width$}", cell),
+                })
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Returns the known title of a game file, if it is one of `TITLES`.
+pub fn title_of(path: &Path) -> Option<&'static Title> {
+    let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+    TITLES.iter().find(|title| title.file == stem)
+}
+
+/// Returns the directories to search for games, in order.  The given
+/// directory comes first, if there is one.
+pub fn games_path(dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dir.into_iter().map(PathBuf::from).collect();
+    if let Some(value) = env::var_os(GAMES_VAR) {
+        dirs.extend(env::split_paths(&value));
+    }
+    if let Some(path) = config_file() {
+        if let Ok(text) = fs::read_to_string(path) {
+            dirs.extend(
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from),
+            );
+        }
+    }
+    dirs.push(PathBuf::from("games"));
+    dirs
+}
+
+/// Returns the path of the config file listing game directories, which is
+/// `games_path` in the config directory that `appdirs` finds.
+pub fn config_file() -> Option<PathBuf> {
+    let dirs = AppDirs::new("rusty_adams");
+    Some(dirs.config_dir()?.join("games_path"))
+}
+
+/// Finds the game file that a player asked for, which may be a path, an
+/// adventure number, the name of a game file, or all or part of a title.
+pub fn resolve(name: &str, dirs: &[PathBuf]) -> Result<PathBuf, String> {
+    if Path::new(name).is_file() {
+        return Ok(PathBuf::from(name));
+    }
+
+    let file = match find_title(name)? {
+        Some(title) => title.file,
+        None => name,
+    };
+    for dir in dirs {
+        if let Some(path) = find_file(dir, file) {
+            return Ok(path);
+        }
+    }
+    Err(format!(
+        "Can't find the game '{}' in any of the games directories",
+        name
+    ))
+}
+
+/// Looks up a game in `TITLES` by its adventure number, its file name, or
+/// all or part of its title, ignoring case and punctuation.  A name that
+/// matches nothing returns None, and one that matches more than one title
+/// is an error that explains which.
+pub fn find_title(name: &str) -> Result<Option<&'static Title>, String> {
+    let key = normalize(name);
+    let matches: Vec<&Title> = match key.parse::<u32>() {
+        Ok(number) => TITLES
+            .iter()
+            .filter(|title| title.adventure == Some(number))
+            .collect(),
+        Err(_) => {
+            let exact: Vec<&Title> = TITLES
+                .iter()
+                .filter(|title| title.file == key || normalize(title.name) == key)
+                .collect();
+            if exact.is_empty() && !key.is_empty() {
+                TITLES
+                    .iter()
+                    .filter(|title| normalize(title.name).contains(&key))
+                    .collect()
+            } else {
+                exact
+            }
+        }
+    };
+
+    match matches[..] {
+        [] => Ok(None),
+        [title] => Ok(Some(title)),
+        _ => {
+            let names: Vec<String> = matches
+                .iter()
+                .map(|title| format!("{} ({})", title.name, title.file))
+                .collect();
+            Err(format!("'{}' could be any of: {}", name, names.join(", ")))
+        }
+    }
+}
+
+/// Reduces a name to its lowercase letters and digits.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect()
+}
+
+/// Finds `<file>.dat` in a directory, ignoring case, as the collection's files
+/// are sometimes named in capitals.
+fn find_file(dir: &Path, file: &str) -> Option<PathBuf> {
+    let wanted = format!("{}.dat", file.to_lowercase());
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().to_lowercase() == wanted)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_title() {
+        let file = |name| find_title(name).unwrap().map(|title| title.file);
+        assert_eq!(file("adventureland"), Some("adv01"));
+        assert_eq!(file("4"), Some("adv04"));
+        assert_eq!(file("ADV12"), Some("adv12"));
+        assert_eq!(file("claymorgue"), Some("adv13"));
+        assert_eq!(file("pirate adventure"), Some("adv02"));
+        assert_eq!(file("return-to-pirate's"), Some("adv14a"));
+        assert!(find_title("pirate")
+            .unwrap_err()
+            .contains("Pirate Adventure (adv02)"));
+        assert!(find_title("14").unwrap_err().contains("Buckaroo Banzai"));
+        assert_eq!(file("zork"), None);
+        assert_eq!(file("99"), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dirs = [PathBuf::from("no/such/dir"), PathBuf::from("games")];
+        assert_eq!(
+            resolve("count", &dirs),
+            Ok(PathBuf::from("games/adv05.dat"))
+        );
+        assert_eq!(
+            resolve("sampler1", &dirs),
+            Ok(PathBuf::from("games/sampler1.dat"))
+        );
+        assert_eq!(
+            resolve("games/adv02.dat", &[]),
+            Ok(PathBuf::from("games/adv02.dat"))
+        );
+        assert!(resolve("count", &dirs[..1]).is_err());
+        assert!(resolve("zork", &dirs).is_err());
+    }
+
+    #[test]
+    fn test_survey() {
+        let entries = survey(Path::new("tests/fixtures")).unwrap();
+        let truncated = entries
+            .iter()
+            .find(|entry| entry.file == "truncated.dat")
+            .unwrap();
+        assert_eq!(truncated.version, None);
+        assert_eq!(truncated.rooms, Some(2));
+        assert!(truncated.error.is_some());
+
+        let entry = Entry::new(Path::new("games/adv01.dat"));
+        assert_eq!(entry.title, Some("Adventureland"));
+        assert_eq!(entry.adventure, Some(1));
+        assert_eq!(entry.version.as_deref(), Some("4.16"));
+        assert_eq!(
+            (entry.rooms, entry.items, entry.treasures),
+            (Some(34), Some(66), Some(13))
+        );
+
+        let lines = table(&[entry]);
+        assert_eq!(
+            lines,
+            [
+                "File       Title          Adv  Version  Rooms  Items  Treasures  Status",
+                "adv01.dat  Adventureland    1     4.16     34     66         13  ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_title_of() {
+        assert_eq!(
+            title_of(Path::new("games/ADV09.DAT")).unwrap().name,
+            "Ghost Town"
+        );
+        assert_eq!(title_of(Path::new("games/mine.dat")), None);
+    }
+}
@@ -0,0 +1,634 @@
+//! This module is where the game's text meets the player's terminal.
+//!
+//! `Stdio` simply writes the text to standard output and reads commands from
+//! standard input.  `Crossterm` does the same through the `crossterm` crate,
+//! which also works with the Windows console: it turns on the console's
+//! handling of virtual terminal sequences where it can, drops escape
+//! sequences where it can't, and reports the width of the window so that the
+//! text can be wrapped to it, since the Windows console breaks lines in the
+//! middle of words.  Neither reads from the terminal device itself, so both
+//! work the same under ConPTY, over SSH and with redirected input.  `Dumb` is
+//! for serial consoles and teletypes, which can't
+//! be relied on to understand anything but printable characters, carriage
+//! returns and line feeds.  It never sends control characters or escape
+//! sequences, ends lines with CR LF so that raw links return the carriage, and
+//! stops with `--more--` whenever a screenful has gone by since the player
+//! last typed, so that nothing scrolls away unread.
+//!
+//! On a Unix terminal, `Crossterm` also lets the player edit the command
+//! they are typing and recall the ones they typed before with the up and
+//! down arrows, from the game's history (see `history`).  The Windows
+//! console has its own line editing and recall, which it keeps.
+//!
+//! `Backend` chooses between them.  A console can also keep a transcript of
+//! the game; see `transcript`.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, IsTerminal, Lines, StdinLock, Stdout, Write};
+
+use crate::history::History;
+
+/// What the player sees when the output stops for them to catch up.
+pub const MORE: &str = "--more--";
+
+/// The height of a screen, in lines, if none is given.
+pub const DEFAULT_LINES: usize = 24;
+
+/// Says who wrote a piece of a game's transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Role {
+    /// A command that the player typed.
+    Player,
+    /// What the game said, before it was wrapped to the screen.
+    Engine,
+}
+
+/// A terminal that the game can be played on.
+pub trait Console {
+    /// Shows the given text, which has already been wrapped to the width of
+    /// the screen.
+    fn write(&mut self, text: &str) -> io::Result<()>;
+
+    /// Reads a line typed by the player, without its line ending, or returns
+    /// None at the end of the input.
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+
+    /// Notes a command that the player gave or the game's reply to it, for
+    /// consoles that keep a transcript.  Prompts and the player's answers to
+    /// them aren't noted.
+    fn transcribe(&mut self, _role: Role, _text: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the width of the screen, if the console knows it and the
+    /// text should be wrapped to it.
+    fn columns(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the history of the commands typed on the console, for
+    /// consoles that keep one.
+    fn history(&mut self) -> Option<&mut History> {
+        None
+    }
+}
+
+impl<C: Console + ?Sized> Console for Box<C> {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        (**self).write(text)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        (**self).read_line()
+    }
+
+    fn transcribe(&mut self, role: Role, text: &str) -> io::Result<()> {
+        (**self).transcribe(role, text)
+    }
+
+    fn columns(&self) -> Option<usize> {
+        (**self).columns()
+    }
+
+    fn history(&mut self) -> Option<&mut History> {
+        (**self).history()
+    }
+}
+
+/// The ways of playing on a terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Standard input and output, as they are.
+    Stdio,
+    /// The terminal through `crossterm`, if this was built with it.
+    Crossterm,
+    /// A dumb terminal or teletype, with a screen of the given height.
+    Dumb(usize),
+}
+
+impl Backend {
+    /// Looks up a backend by the name used on the command line.  A dumb
+    /// terminal has its own options, so it isn't one of them.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stdio" => Some(Backend::Stdio),
+            "crossterm" => Some(Backend::Crossterm),
+            _ => None,
+        }
+    }
+
+    /// Chooses a backend for standard output: `crossterm` if this was built
+    /// with it and the output is a terminal, and plain standard output for
+    /// files and pipes.
+    pub fn detect() -> Self {
+        if cfg!(feature = "crossterm") && io::stdout().is_terminal() {
+            Backend::Crossterm
+        } else {
+            Backend::Stdio
+        }
+    }
+
+    /// Returns a console on standard input and output that works this way.
+    /// Without `crossterm`, its backend falls back to plain standard output.
+    pub fn open(self) -> Box<dyn Console> {
+        match self {
+            #[cfg(feature = "crossterm")]
+            Backend::Crossterm => Box::new(Crossterm::new()),
+            Backend::Dumb(height) => Box::new(Dumb::stdio(height)),
+            _ => Box::new(Stdio::new()),
+        }
+    }
+}
+
+/// The terminal on standard input and output, left to deal with the text as
+/// it will.
+pub struct Stdio {
+    lines: Lines<StdinLock<'static>>,
+}
+
+impl Stdio {
+    pub fn new() -> Stdio {
+        Stdio {
+            lines: io::stdin().lock().lines(),
+        }
+    }
+}
+
+impl Default for Stdio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console for Stdio {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        print!("{}", text);
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        io::stdout().flush()?;
+        self.lines.next().transpose()
+    }
+}
+
+/// The terminal on standard input and output, driven through `crossterm`.
+#[cfg(feature = "crossterm")]
+pub struct Crossterm {
+    output: Stdout,
+    input: StdinLock<'static>,
+    /// Set if the terminal understands escape sequences.
+    ansi: bool,
+    /// Set if commands are read a key at a time, so that they can be edited
+    /// and recalled.
+    editing: bool,
+    history: History,
+}
+
+#[cfg(feature = "crossterm")]
+impl Crossterm {
+    pub fn new() -> Crossterm {
+        // On Windows, this also turns on virtual terminal processing.
+        #[cfg(windows)]
+        let ansi = crossterm::ansi_support::supports_ansi();
+        #[cfg(not(windows))]
+        let ansi = std::env::var("TERM").map_or(true, |term| term != "dumb");
+        Crossterm {
+            output: io::stdout(),
+            input: io::stdin().lock(),
+            ansi,
+            editing: cfg!(unix) && ansi && io::stdin().is_terminal(),
+            history: History::new(),
+        }
+    }
+
+    /// Reads a line a key at a time, with the terminal in raw mode, echoing
+    /// what the player types and redrawing the line when it changes other
+    /// than by typing at the end of it.
+    fn edit_line(&mut self) -> io::Result<Option<String>> {
+        use crossterm::cursor::{RestorePosition, SavePosition};
+        use crossterm::style::Print;
+        use crossterm::terminal::{Clear, ClearType};
+
+        let _raw = RawMode::enter()?;
+        let mut editor = Editor::new(self.history.commands());
+        crossterm::execute!(self.output, SavePosition)?;
+        loop {
+            let shown = editor.line.clone();
+            let step = match next_key(&mut self.input)? {
+                Some(key) => editor.key(key),
+                None => Step::End,
+            };
+            match step {
+                Step::Editing => (),
+                Step::Line(line) => {
+                    crossterm::execute!(self.output, Print("\r\n"))?;
+                    return Ok(Some(line));
+                }
+                Step::End => {
+                    crossterm::execute!(self.output, Print("\r\n"))?;
+                    return Ok(None);
+                }
+            }
+            match editor.line.strip_prefix(shown.as_str()) {
+                Some(typed) => crossterm::execute!(self.output, Print(typed))?,
+                None => crossterm::execute!(
+                    self.output,
+                    RestorePosition,
+                    Clear(ClearType::FromCursorDown),
+                    Print(&editor.line)
+                )?,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Default for Crossterm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Console for Crossterm {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        let text = if self.ansi {
+            text.to_string()
+        } else {
+            strip_escapes(text)
+        };
+        crossterm::queue!(self.output, crossterm::style::Print(text))
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.output.flush()?;
+        if self.editing {
+            return self.edit_line();
+        }
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        line.truncate(line.trim_end_matches(['\r', '\n']).len());
+        Ok(Some(line))
+    }
+
+    fn columns(&self) -> Option<usize> {
+        match crossterm::terminal::size() {
+            Ok((columns, _)) if columns > 0 => Some(columns as usize),
+            _ => None,
+        }
+    }
+
+    fn history(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+}
+
+/// Keeps the terminal in raw mode for as long as it lives.
+#[cfg(feature = "crossterm")]
+struct RawMode;
+
+#[cfg(feature = "crossterm")]
+impl RawMode {
+    fn enter() -> io::Result<RawMode> {
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// A key that the player pressed while typing a command.
+#[cfg(feature = "crossterm")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    /// The up arrow, or Ctrl-P.
+    Up,
+    /// The down arrow, or Ctrl-N.
+    Down,
+    /// Ctrl-U, which clears the line.
+    Clear,
+    /// Ctrl-C.
+    Interrupt,
+    /// Ctrl-D.
+    EndOfInput,
+    /// Any other control key or escape sequence.
+    Ignored,
+}
+
+/// Reads the next key from input that a terminal in raw mode sends, or
+/// returns None at the end of the input.
+#[cfg(feature = "crossterm")]
+fn next_key(input: &mut impl BufRead) -> io::Result<Option<Key>> {
+    let mut next = || -> io::Result<Option<u8>> {
+        let byte = input.fill_buf()?.first().copied();
+        if byte.is_some() {
+            input.consume(1);
+        }
+        Ok(byte)
+    };
+    let Some(byte) = next()? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x08 | 0x7f => Key::Backspace,
+        0x10 => Key::Up,
+        0x0e => Key::Down,
+        0x15 => Key::Clear,
+        0x03 => Key::Interrupt,
+        0x04 => Key::EndOfInput,
+        0x1b => match next()? {
+            // Arrows are sent as CSI or SS3 sequences, which end with a
+            // letter or other final byte.
+            Some(b'[' | b'O') => {
+                let mut last = None;
+                while let Some(byte) = next()? {
+                    last = Some(byte);
+                    if (b'@'..=b'~').contains(&byte) {
+                        break;
+                    }
+                }
+                match last {
+                    Some(b'A') => Key::Up,
+                    Some(b'B') => Key::Down,
+                    _ => Key::Ignored,
+                }
+            }
+            _ => Key::Ignored,
+        },
+        byte if byte < 0x20 => Key::Ignored,
+        byte => {
+            // The rest of a character that takes more than one byte.
+            let mut bytes = vec![byte];
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            while bytes.len() < len {
+                match next()? {
+                    Some(byte) => bytes.push(byte),
+                    None => break,
+                }
+            }
+            match std::str::from_utf8(&bytes) {
+                Ok(text) => text.chars().next().map_or(Key::Ignored, Key::Char),
+                Err(_) => Key::Ignored,
+            }
+        }
+    };
+    Ok(Some(key))
+}
+
+/// What became of a command after a key was pressed.
+#[cfg(feature = "crossterm")]
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// The player is still typing it.
+    Editing,
+    /// The player entered it.
+    Line(String),
+    /// The player asked to stop.
+    End,
+}
+
+/// The command that the player is typing, with the commands that they typed
+/// before it to recall.
+#[cfg(feature = "crossterm")]
+struct Editor<'a> {
+    history: &'a [String],
+    /// The place in the history of the command being shown, which is the
+    /// length of the history for the one being typed.
+    recalled: usize,
+    /// What the player had typed before recalling an older command, to come
+    /// back to.
+    draft: String,
+    line: String,
+}
+
+#[cfg(feature = "crossterm")]
+impl<'a> Editor<'a> {
+    fn new(history: &'a [String]) -> Editor<'a> {
+        Editor {
+            history,
+            recalled: history.len(),
+            draft: String::new(),
+            line: String::new(),
+        }
+    }
+
+    /// Changes the command as the given key says.
+    fn key(&mut self, key: Key) -> Step {
+        match key {
+            Key::Char(ch) => self.line.push(ch),
+            Key::Backspace => {
+                self.line.pop();
+            }
+            Key::Clear => self.line.clear(),
+            Key::Up if self.recalled > 0 => {
+                if self.recalled == self.history.len() {
+                    self.draft = std::mem::take(&mut self.line);
+                }
+                self.recalled -= 1;
+                self.line = self.history[self.recalled].clone();
+            }
+            Key::Down if self.recalled < self.history.len() => {
+                self.recalled += 1;
+                self.line = match self.history.get(self.recalled) {
+                    Some(command) => command.clone(),
+                    None => std::mem::take(&mut self.draft),
+                };
+            }
+            Key::Enter => return Step::Line(std::mem::take(&mut self.line)),
+            Key::EndOfInput if self.line.is_empty() => return Step::End,
+            Key::Interrupt => return Step::End,
+            _ => (),
+        }
+        Step::Editing
+    }
+}
+
+/// Returns text without the escape character that starts each escape
+/// sequence, or the rest of a control sequence after it, so that a terminal
+/// that doesn't understand them doesn't show them.
+fn strip_escapes(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            out.push(ch);
+        } else if chars.next_if_eq(&'[').is_some() {
+            // A control sequence ends with a letter or other final byte.
+            while chars.next().is_some_and(|ch| !('@'..='~').contains(&ch)) {}
+        }
+    }
+    out
+}
+
+/// A dumb terminal with a screen of a given height, or a teletype.
+pub struct Dumb<R, W> {
+    input: R,
+    output: W,
+    /// The number of lines on the screen, or zero to never stop.
+    height: usize,
+    /// The number of lines that have ended since the player last typed.
+    lines: usize,
+}
+
+impl<R: BufRead, W: Write> Dumb<R, W> {
+    /// Returns a dumb terminal that reads from and writes to the given
+    /// streams, stopping whenever a screen of the given height is full.  A
+    /// height of zero never stops, as suits a teletype.
+    pub fn new(input: R, output: W, height: usize) -> Dumb<R, W> {
+        Dumb {
+            input,
+            output,
+            height,
+            lines: 0,
+        }
+    }
+
+    /// Waits for the player to press Enter before going on.  The last line of
+    /// the screen is kept for the prompt.
+    fn more(&mut self) -> io::Result<()> {
+        write!(self.output, "{}", MORE)?;
+        self.output.flush()?;
+        if self.input.read_line(&mut String::new())? == 0 {
+            // There is no one to wait for.
+            self.height = 0;
+        }
+        self.lines = 0;
+        Ok(())
+    }
+}
+
+impl Dumb<StdinLock<'static>, Stdout> {
+    /// Returns a dumb terminal on standard input and output.
+    pub fn stdio(height: usize) -> Self {
+        Dumb::new(io::stdin().lock(), io::stdout(), height)
+    }
+}
+
+impl<R: BufRead, W: Write> Console for Dumb<R, W> {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        let mut out = String::new();
+        for ch in strip_escapes(text).chars() {
+            match ch {
+                '\n' => {
+                    out.push_str("\r\n");
+                    self.lines += 1;
+                    if self.height > 1 && self.lines >= self.height - 1 {
+                        self.output.write_all(out.as_bytes())?;
+                        out.clear();
+                        self.more()?;
+                    }
+                }
+                '\t' => out.push(' '),
+                ch if ch.is_control() => (),
+                ch => out.push(ch),
+            }
+        }
+        self.output.write_all(out.as_bytes())
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        self.lines = 0;
+        line.truncate(line.trim_end_matches(['\r', '\n']).len());
+        Ok(Some(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dumb() {
+        let mut output = Vec::new();
+        let mut console = Dumb::new(&b"\nlook\r\n"[..], &mut output, 4);
+        console.write("one\ntwo\n\x1b[1mthree\tfour\nfive").unwrap();
+        assert_eq!(console.read_line().unwrap(), Some("look".to_string()));
+        console.write("\nsix\n").unwrap();
+        assert_eq!(console.read_line().unwrap(), None);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "one\r\ntwo\r\nthree four\r\n--more--five\r\nsix\r\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_escapes() {
+        assert_eq!(strip_escapes("\x1b[1;31mRed\x1b[0m alert\x1b"), "Red alert");
+        assert_eq!(strip_escapes("café\ttab\n"), "café\ttab\n");
+    }
+
+    #[cfg(feature = "crossterm")]
+    #[test]
+    fn test_editor() {
+        let history = ["go north".to_string(), "get lamp".to_string()];
+        let edit = |mut input: &[u8]| {
+            let mut editor = Editor::new(&history);
+            loop {
+                match next_key(&mut input).unwrap().map(|key| editor.key(key)) {
+                    Some(Step::Editing) => (),
+                    Some(Step::Line(line)) => return Some(line),
+                    Some(Step::End) | None => return None,
+                }
+            }
+        };
+        assert_eq!(edit(b"look\r").as_deref(), Some("look"));
+        assert_eq!(edit(b"lookk\x7f\x1b[D\r").as_deref(), Some("look"));
+        assert_eq!(edit(b"\x1b[A\r").as_deref(), Some("get lamp"));
+        assert_eq!(edit(b"\x1bOA\x1bOA\x1b[A\r").as_deref(), Some("go north"));
+        // Going back down returns to what was being typed.
+        assert_eq!(
+            edit(b"sc\x1b[A\x1b[A\x1b[B\x1b[Bore\r").as_deref(),
+            Some("score")
+        );
+        assert_eq!(
+            edit(b"\x1b[A\x15caf\xc3\xa9\n").as_deref(),
+            Some("caf\u{e9}")
+        );
+        assert_eq!(edit(b"say\x04\x03"), None);
+        assert_eq!(edit(b"say"), None);
+    }
+
+    #[test]
+    fn test_backend() {
+        assert_eq!(Backend::from_name("crossterm"), Some(Backend::Crossterm));
+        assert_eq!(Backend::from_name("dumb"), None);
+        assert_eq!(Backend::from_name("curses"), None);
+    }
+
+    #[test]
+    fn test_dumb_without_input() {
+        // With no one to press Enter, it stops once and then carries on.
+        let mut output = Vec::new();
+        let mut console = Dumb::new(&b""[..], &mut output, 2);
+        console.write("one\ntwo\nthree\n").unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "one\r\n--more--two\r\nthree\r\n"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! This module measures every game in a directory and adds up how they use
+//! their vocabularies, for `rusty_adams corpus-stats`.
+//!
+//! Each game is measured by `game::metrics`, and gets a row of the games
+//! table.  The words table has a row for each verb and noun that any game
+//! has an action for, saying how many games use it and for how many actions
+//! in all.  Words are compared as the games spell them, in capitals, so a
+//! game that keeps four letters of each word has words of its own.
+//!
+//! Both tables can be written as CSV, and with the `serde` feature the whole
+//! corpus can be serialized, for example to JSON with `serde_json`.
+
+use crate::catalog;
+use crate::game::metrics::{self, Metrics};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// Holds the measures of the games in a directory.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Corpus {
+    /// The games that could be loaded, in order of their file names.
+    pub games: Vec<GameStats>,
+    /// The verbs and nouns that the games have actions for, the most widely
+    /// used first.
+    pub words: Vec<WordStats>,
+    /// The games that couldn't be loaded.
+    pub failures: Vec<Failure>,
+}
+
+/// Holds the measures of one game.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GameStats {
+    /// The name of the game file.
+    pub file: String,
+    /// The game's title, if it is one of `catalog::TITLES`.
+    pub title: Option<&'static str>,
+    /// The game's measures.
+    pub metrics: Metrics,
+}
+
+/// Holds how one word is used across the games.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WordStats {
+    /// The word, in capitals.
+    pub word: String,
+    /// Whether the word is a "verb" or a "noun".
+    pub kind: &'static str,
+    /// The number of games with actions for the word.
+    pub games: usize,
+    /// The number of actions for the word, in all of those games.
+    pub actions: usize,
+}
+
+/// Holds why a game couldn't be loaded.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Failure {
+    /// The name of the game file.
+    pub file: String,
+    /// The error from loading it.
+    pub error: String,
+}
+
+/// The columns of the games table.
+const GAME_COLUMNS: [&str; 18] = [
+    "file",
+    "title",
+    "actions",
+    "occurrences",
+    "verbs",
+    "nouns",
+    "verbs_used",
+    "actions_per_verb",
+    "nouns_used",
+    "messages",
+    "message_length",
+    "longest_message",
+    "rooms",
+    "exits",
+    "one_way_exits",
+    "dead_ends",
+    "unreachable",
+    "exits_per_room",
+];
+
+/// Measures the game files in a directory.
+pub fn survey(dir: &Path) -> io::Result<Corpus> {
+    let mut corpus = Corpus::default();
+    for path in crate::list_games(dir)? {
+        let file = path
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().into_owned());
+        match crate::load_game(&path.to_string_lossy()) {
+            Ok(game) => corpus.games.push(GameStats {
+                file,
+                title: catalog::title_of(&path).map(|title| title.name),
+                metrics: metrics::measure(&game),
+            }),
+            Err(error) => corpus.failures.push(Failure { file, error }),
+        }
+    }
+    corpus.words = count_words(&corpus.games);
+    Ok(corpus)
+}
+
+/// Adds up the verbs and nouns of the games, putting the words that the
+/// most games use first, then those with the most actions.
+fn count_words(games: &[GameStats]) -> Vec<WordStats> {
+    let mut counts: BTreeMap<(&'static str, &str), (usize, usize)> = BTreeMap::new();
+    for game in games {
+        for (kind, usage) in [
+            ("verb", &game.metrics.verb_usage),
+            ("noun", &game.metrics.noun_usage),
+        ] {
+            for word in usage {
+                let count = counts.entry((kind, &word.word)).or_default();
+                count.0 += 1;
+                count.1 += word.actions;
+            }
+        }
+    }
+    let mut words: Vec<WordStats> = counts
+        .into_iter()
+        .map(|((kind, word), (games, actions))| WordStats {
+            word: word.to_string(),
+            kind,
+            games,
+            actions,
+        })
+        .collect();
+    words.sort_by(|a, b| b.games.cmp(&a.games).then(b.actions.cmp(&a.actions)));
+    words
+}
+
+impl Corpus {
+    /// Returns the games table as CSV, with a header row.
+    pub fn games_csv(&self) -> String {
+        let mut csv = csv_row(GAME_COLUMNS.iter().map(|column| column.to_string()));
+        for game in &self.games {
+            let m = &game.metrics;
+            csv.push_str(&csv_row([
+                game.file.clone(),
+                game.title.unwrap_or_default().to_string(),
+                m.actions.to_string(),
+                m.occurrences.to_string(),
+                m.verbs.to_string(),
+                m.nouns.to_string(),
+                m.verbs_used.to_string(),
+                format!("{:.2}", m.actions_per_verb),
+                m.noun_usage.len().to_string(),
+                m.messages.to_string(),
+                format!("{:.1}", m.message_length),
+                m.longest_message.to_string(),
+                m.rooms.to_string(),
+                m.exits.to_string(),
+                m.one_way_exits.to_string(),
+                m.dead_ends.to_string(),
+                m.unreachable.to_string(),
+                format!("{:.2}", m.exits as f64 / m.rooms.max(1) as f64),
+            ]));
+        }
+        csv
+    }
+
+    /// Returns the words table as CSV, with a header row.
+    pub fn words_csv(&self) -> String {
+        let mut csv = csv_row(["word", "kind", "games", "actions"].map(String::from));
+        for word in &self.words {
+            csv.push_str(&csv_row([
+                word.word.clone(),
+                word.kind.to_string(),
+                word.games.to_string(),
+                word.actions.to_string(),
+            ]));
+        }
+        csv
+    }
+}
+
+/// Joins the fields of a CSV row, quoting any that need it.
+fn csv_row(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_row() {
+        assert_eq!(
+            csv_row(["adv10.dat", "Savage Island, Part I", "say \"hi\""].map(String::from)),
+            "adv10.dat,\"Savage Island, Part I\",\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_survey() {
+        let corpus = survey(Path::new("tests/fixtures")).unwrap();
+        assert!(corpus
+            .failures
+            .iter()
+            .any(|failure| failure.file == "truncated.dat"));
+        assert!(corpus.games.iter().all(|game| game.file != "truncated.dat"));
+
+        let csv = corpus.games_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0].split(',').count(), GAME_COLUMNS.len());
+        assert_eq!(lines.len(), corpus.games.len() + 1);
+        assert!(corpus
+            .words
+            .windows(2)
+            .all(|pair| pair[0].games >= pair[1].games));
+    }
+}
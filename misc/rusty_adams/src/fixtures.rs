@@ -0,0 +1,231 @@
+//! This module publishes the tiny games that the tests are played on, each
+//! with a script of commands and the replies that the interpreter must give,
+//! as a conformance suite for other implementations of the engine, such as
+//! a port to another language or a frontend for the web.  It is only built
+//! with the `test-fixtures` feature.
+//!
+//! Each fixture exercises one part of the engine.  The replies are exactly
+//! what `Interpreter::start` and `Interpreter::command` return, before any
+//! wrapping, with the automatic actions run by the fixture's policy:
+//!
+//! ```
+//! for fixture in &rusty_adams::fixtures::FIXTURES {
+//!     let mut interpreter = fixture.interpreter().unwrap();
+//!     assert_eq!(fixture.check(&mut interpreter), Ok(()));
+//! }
+//! ```
+
+use crate::game::interpreter::{Interpreter, OccurrencePolicy};
+use crate::game::Game;
+
+/// One of the games of the suite, with its script.
+#[derive(Debug)]
+pub struct Fixture {
+    /// The name of the game, which is also the name of its file in
+    /// `tests/fixtures`.
+    pub name: &'static str,
+    /// What part of the engine the game exercises.
+    pub about: &'static str,
+    /// The game file.
+    pub data: &'static [u8],
+    /// When the automatic actions run.
+    pub occurrences: OccurrencePolicy,
+    /// What the game says when it starts.
+    pub opening: &'static str,
+    /// The commands to give, in order, each with the reply to expect.
+    pub script: &'static [Exchange],
+}
+
+/// One command of a fixture's script.
+#[derive(Debug)]
+pub struct Exchange {
+    /// The command, as the player types it.
+    pub command: &'static str,
+    /// What the game must say in reply.
+    pub reply: &'static str,
+}
+
+/// Builds a fixture's script from pairs of commands and replies.
+macro_rules! script {
+    ($(($command:literal, $reply:literal)),* $(,)?) => {
+        &[$(Exchange { command: $command, reply: $reply }),*]
+    };
+}
+
+/// The conformance suite.
+pub static FIXTURES: [Fixture; 6] = [
+    Fixture {
+        name: "get_drop",
+        about: "GET and DROP by autograb names, GET ALL and DROP ALL, and the carrying limit",
+        data: include_bytes!("../tests/fixtures/get_drop.dat"),
+        occurrences: OccurrencePolicy::Percent,
+        opening: "I'm in a small room\n\nObvious exits: none.\n\n\
+                  I can also see: Brass key - Heavy rock - Painting\n",
+        script: script![
+            ("i", "I'm carrying:\nNothing.\n"),
+            (
+                "get key",
+                "O.K.\nI'm in a small room\n\nObvious exits: none.\n\n\
+                 I can also see: Heavy rock - Painting\n"
+            ),
+            ("get rock", "It's too heavy to lift.\n"),
+            ("i", "I'm carrying:\nBrass key.\n"),
+            (
+                "drop all",
+                "Brass key: O.K.\nI'm in a small room\n\nObvious exits: none.\n\n\
+                 I can also see: Brass key - Heavy rock - Painting\n"
+            ),
+            (
+                "look",
+                "I'm in a small room\n\nObvious exits: none.\n\n\
+                 I can also see: Brass key - Heavy rock - Painting\n"
+            ),
+        ],
+    },
+    Fixture {
+        name: "dark_room",
+        about: "darkness, moving in the dark, and the light source burning out",
+        data: include_bytes!("../tests/fixtures/dark_room.dat"),
+        occurrences: OccurrencePolicy::Percent,
+        opening: "I'm in a forest clearing\n\nObvious exits: Down.\n\nI can also see: Lamp\n",
+        script: script![
+            ("i", "I'm carrying:\nNothing.\n"),
+            ("w", "I can't go in that direction.\n"),
+            ("d", "I can't see. It is too dark!\n"),
+            (
+                "u",
+                "Dangerous to move in the dark!\nI'm in a forest clearing\n\n\
+                 Obvious exits: Down.\n\nI can also see: Lamp\n"
+            ),
+            (
+                "get lamp",
+                "O.K.\nI'm in a forest clearing\n\nObvious exits: Down.\n\n"
+            ),
+            (
+                "d",
+                "I'm in a damp cave\n\nObvious exits: Up.\n\nI can also see: Crystal\n"
+            ),
+            ("i", "I'm carrying:\nLamp.\n"),
+            (
+                "score",
+                "I don't understand your command.\nYour light has run out.\n"
+            ),
+        ],
+    },
+    Fixture {
+        name: "counter",
+        about: "the counter actions and conditions, counting down to the end of the game",
+        data: include_bytes!("../tests/fixtures/counter.dat"),
+        occurrences: OccurrencePolicy::Percent,
+        opening: "I'm standing beside a bomb.\n\nObvious exits: none.\n\n\
+                  I can also see: Bomb - Red wire\nThe bomb ticks. Its timer reads:\n3\n",
+        script: script![
+            (
+                "wait",
+                "Time passes.\nThe bomb ticks. Its timer reads:\n2\n"
+            ),
+            (
+                "wind timer",
+                "I wind the timer back.\nThe bomb ticks. Its timer reads:\n3\n"
+            ),
+            (
+                "wait",
+                "Time passes.\nThe bomb ticks. Its timer reads:\n2\n"
+            ),
+            ("cut wire", "Click. The timer stops.\n"),
+            ("wait", "Time passes.\n"),
+        ],
+    },
+    Fixture {
+        name: "continue",
+        about: "chains of actions joined by Continue",
+        data: include_bytes!("../tests/fixtures/continue.dat"),
+        occurrences: OccurrencePolicy::Percent,
+        opening: "I'm in a workshop\n\nObvious exits: none.\n\nI can also see: Widget - Button\n",
+        script: script![
+            (
+                "push button",
+                "Click!\nA bell rings.\nA door opens.\nThe door creaks.\nSomething stirs.\n"
+            ),
+            ("push button", "I can't do that yet.\n"),
+            ("push widget", "I don't understand your command.\n"),
+            ("wait", "I don't understand your command.\n"),
+        ],
+    },
+    Fixture {
+        name: "moved",
+        about: "the moved and not moved conditions, and items swapping places",
+        data: include_bytes!("../tests/fixtures/moved.dat"),
+        occurrences: OccurrencePolicy::Percent,
+        opening: "I'm in a hall\n\nObvious exits: East.\n\nI can also see: Coin\n",
+        script: script![
+            ("read coin", "The coin is where it started.\n"),
+            (
+                "open door",
+                "The coin and the painting trade places.\nI'm in a hall\n\n\
+                 Obvious exits: East.\n\nI can also see: Painting\n"
+            ),
+            ("read coin", "The coin has been moved.\n"),
+            (
+                "e",
+                "I'm in a gallery\n\nObvious exits: West.\n\nI can also see: Coin\n"
+            ),
+            ("read painting", "The painting has been moved.\n"),
+        ],
+    },
+    Fixture {
+        name: "chance",
+        about: "an automatic action with a small chance, made to run every turn",
+        data: include_bytes!("../tests/fixtures/chance.dat"),
+        occurrences: OccurrencePolicy::Always,
+        opening: "I'm in a meadow\n\nObvious exits: none.\n\nI can also see: Bird\n\
+                  A bird sings.\n",
+        script: script![
+            ("wait", "Time passes.\nA bird sings.\n"),
+            ("wait", "Time passes.\nA bird sings.\n"),
+        ],
+    },
+];
+
+impl Fixture {
+    /// Loads the fixture's game.
+    pub fn load(&self) -> Result<Game, String> {
+        crate::load_game_from_bytes(self.data)
+    }
+
+    /// Returns an interpreter for the fixture's game, set up to run the
+    /// automatic actions as the script expects.
+    pub fn interpreter(&self) -> Result<Interpreter, String> {
+        let mut interpreter = Interpreter::new(self.load()?);
+        interpreter.set_occurrence_policy(self.occurrences);
+        Ok(interpreter)
+    }
+
+    /// Starts the game in the given interpreter and plays the script,
+    /// returning an error that describes the first reply that isn't the
+    /// one expected.
+    pub fn check(&self, interpreter: &mut Interpreter) -> Result<(), String> {
+        let mismatch = |command: &str, expected: &str, actual: &str| {
+            format!(
+                "{}: after {:?}, expected {:?} but got {:?}",
+                self.name, command, expected, actual
+            )
+        };
+        let opening = interpreter.start();
+        if opening != self.opening {
+            return Err(mismatch("the start", self.opening, &opening));
+        }
+        for exchange in self.script {
+            let reply = interpreter.command(exchange.command);
+            if reply != exchange.reply {
+                return Err(mismatch(exchange.command, exchange.reply, &reply));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a fixture by its name.
+pub fn find(name: &str) -> Option<&'static Fixture> {
+    FIXTURES.iter().find(|fixture| fixture.name == name)
+}
@@ -0,0 +1,272 @@
+//! This module wraps the interpreter's output to the width of the screen, in
+//! the style of the platform being reproduced.
+//!
+//! The Apple II's 40 columns and the TRS-80's 64 were filled right to the
+//! edge, with the text simply carrying on at the start of the next line, even
+//! in the middle of a word and with no hyphen.  ScottFree instead moves a word
+//! that won't fit onto the next line, and leaves the last two columns free.
+//!
+//! Widths are measured in columns rather than bytes or characters, so that
+//! translated games with accented letters or wide characters wrap correctly.
+//!
+//! It also decides how the player is spoken of, which the interpreter follows
+//! in describing rooms and in its own messages.
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The screen widths offered on the command line, which are those of the
+/// Apple II, the TRS-80, and a terminal.
+pub const COLUMNS: [usize; 3] = [40, 64, 80];
+
+/// Decides where a line may be broken when it reaches the edge of the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Breaking {
+    /// Words that won't fit move to the next line, as in ScottFree.
+    Words,
+    /// Lines break wherever they reach the edge, as on the original screens.
+    Anywhere,
+}
+
+/// Decides whether the player is spoken of as "I" or as "you", and so how
+/// rooms are described.  A room whose description begins with `*` in the
+/// game file is shown exactly as written, whatever the perspective.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Perspective {
+    /// "I'm in a dismal swamp", as in the original games.
+    #[default]
+    FirstPerson,
+    /// "You're in a dismal swamp", as some later ports had it.
+    SecondPerson,
+    /// "You are dismal swamp", just as ScottFree has it with `-y`.
+    ScottFree,
+}
+
+impl Perspective {
+    /// Looks up a perspective by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "first" => Some(Perspective::FirstPerson),
+            "second" => Some(Perspective::SecondPerson),
+            "scottfree" => Some(Perspective::ScottFree),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the player is spoken of as "you".
+    pub fn is_you(self) -> bool {
+        self != Perspective::FirstPerson
+    }
+
+    /// Returns the line that describes a room, given its description and
+    /// whether it is to be shown as written.
+    pub fn room(self, description: &str, is_literal: bool) -> String {
+        if is_literal {
+            return description.to_string();
+        }
+        match self {
+            Perspective::FirstPerson => format!("I'm in a {}", description),
+            Perspective::SecondPerson => format!("You're in a {}", description),
+            Perspective::ScottFree => format!("You are {}", description),
+        }
+    }
+}
+
+/// Wraps text to a screen of a given width, keeping track of the column that
+/// the output has reached from one piece of text to the next.
+#[derive(Debug)]
+pub struct Formatter {
+    /// The number of columns that a line can fill, if the text is wrapped.
+    width: Option<usize>,
+    breaking: Breaking,
+    /// The column that the output has reached.
+    column: usize,
+}
+
+impl Formatter {
+    /// Returns a formatter for a screen with the given number of columns.
+    ///
+    /// ```
+    /// use rusty_adams::format::{Breaking, Formatter};
+    ///
+    /// let mut formatter = Formatter::new(16, Breaking::Words);
+    /// assert_eq!(
+    ///     formatter.format("I'm in a dismal swamp\n"),
+    ///     "I'm in a\ndismal swamp\n"
+    /// );
+    /// let mut formatter = Formatter::new(16, Breaking::Anywhere);
+    /// assert_eq!(
+    ///     formatter.format("I'm in a sandy cove\n"),
+    ///     "I'm in a sandy c\nove\n"
+    /// );
+    /// ```
+    pub fn new(columns: usize, breaking: Breaking) -> Formatter {
+        let width = match breaking {
+            Breaking::Words => columns.saturating_sub(2),
+            Breaking::Anywhere => columns,
+        };
+        Formatter {
+            width: Some(width.max(1)),
+            breaking,
+            column: 0,
+        }
+    }
+
+    /// Returns a formatter that wraps text as the platform with the given
+    /// screen width did: the narrow screens break anywhere, and wider ones
+    /// break between words.
+    pub fn for_platform(columns: usize) -> Formatter {
+        let breaking = if columns < 80 {
+            Breaking::Anywhere
+        } else {
+            Breaking::Words
+        };
+        Formatter::new(columns, breaking)
+    }
+
+    /// Returns a formatter that leaves text as it is, for screens that wrap
+    /// it themselves.
+    pub fn unwrapped() -> Formatter {
+        Formatter {
+            width: None,
+            breaking: Breaking::Words,
+            column: 0,
+        }
+    }
+
+    /// Notes that the output has moved to the start of a new line, as it does
+    /// when the player presses Enter after typing a command.
+    pub fn end_line(&mut self) {
+        self.column = 0;
+    }
+
+    /// Returns the given text wrapped to the width of the screen, carrying on
+    /// from the column that the last text reached.
+    pub fn format(&mut self, text: &str) -> String {
+        let width = match self.width {
+            Some(width) => width,
+            None => {
+                self.column = match text.rfind('\n') {
+                    Some(end) => text[end + 1..].width(),
+                    None => self.column + text.width(),
+                };
+                return text.to_string();
+            }
+        };
+
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next() {
+            if ch == '\n' {
+                self.newline(&mut out);
+                rest = &rest[1..];
+            } else if ch == ' ' || self.breaking == Breaking::Anywhere {
+                self.push(&mut out, ch, width);
+                rest = &rest[ch.len_utf8()..];
+            } else {
+                let end = rest.find([' ', '\n']).unwrap_or(rest.len());
+                let word = &rest[..end];
+                if self.column > 0 && self.column + word.width() > width {
+                    self.newline(&mut out);
+                }
+                for ch in word.chars() {
+                    self.push(&mut out, ch, width);
+                }
+                rest = &rest[end..];
+            }
+        }
+        out
+    }
+
+    /// Adds a character to the output, first moving to the next line if it
+    /// won't fit on this one.  Spaces aren't carried over to the next line.
+    fn push(&mut self, out: &mut String, ch: char, width: usize) {
+        let ch_width = ch.width().unwrap_or(0);
+        if self.column + ch_width > width {
+            self.newline(out);
+            if ch == ' ' && self.breaking == Breaking::Words {
+                return;
+            }
+        }
+        out.push(ch);
+        self.column += ch_width;
+    }
+
+    /// Ends the current line, without leaving spaces at the end of it.
+    fn newline(&mut self, out: &mut String) {
+        let trimmed = out.trim_end_matches(' ').len();
+        out.truncate(trimmed);
+        out.push('\n');
+        self.column = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let mut formatter = Formatter::new(12, Breaking::Words);
+        assert_eq!(
+            formatter.format("I'm in a dismal swamp.\n"),
+            "I'm in a\ndismal\nswamp.\n"
+        );
+        assert_eq!(
+            formatter.format("Obvious exits: North.\n"),
+            "Obvious\nexits:\nNorth.\n"
+        );
+
+        // Words longer than a line are broken wherever they must be.
+        assert_eq!(formatter.format("ABRACADABRAS!"), "ABRACADABR\nAS!");
+        formatter.end_line();
+        assert_eq!(formatter.format("O.K. O.K."), "O.K. O.K.");
+        assert_eq!(formatter.format(" Done."), "\nDone.");
+    }
+
+    #[test]
+    fn test_anywhere() {
+        let mut formatter = Formatter::for_platform(10);
+        assert_eq!(
+            formatter.format("I'm in a dismal swamp.\n"),
+            "I'm in a d\nismal swam\np.\n"
+        );
+        assert_eq!(formatter.format("0123456789\n"), "0123456789\n");
+    }
+
+    #[test]
+    fn test_perspective() {
+        let rooms = [
+            Perspective::FirstPerson.room("dismal swamp", false),
+            Perspective::SecondPerson.room("dismal swamp", false),
+            Perspective::ScottFree.room("dismal swamp", false),
+            Perspective::SecondPerson.room("I'm on a ledge", true),
+        ];
+        assert_eq!(
+            rooms,
+            [
+                "I'm in a dismal swamp",
+                "You're in a dismal swamp",
+                "You are dismal swamp",
+                "I'm on a ledge",
+            ]
+        );
+        assert!(!Perspective::default().is_you());
+        assert_eq!(
+            Perspective::from_name("second"),
+            Some(Perspective::SecondPerson)
+        );
+    }
+
+    #[test]
+    fn test_width() {
+        let mut formatter = Formatter::new(8, Breaking::Anywhere);
+        assert_eq!(formatter.format("Caféñcafé"), "Caféñcaf\né");
+        formatter.end_line();
+        assert_eq!(formatter.format("日本語の本"), "日本語の\n本");
+
+        let mut formatter = Formatter::unwrapped();
+        let text = "A line far longer than any screen would allow.\nNext ";
+        assert_eq!(formatter.format(text), text);
+        assert_eq!(formatter.column, 5);
+    }
+}
@@ -5,18 +5,28 @@
 //! intention is to make this whole mess work with WebAssembly at some point
 //! after I learn it.
 
+pub mod ids;
+pub mod interpreter;
+pub mod map;
+pub mod message;
+pub mod metrics;
 mod parser;
+pub mod scottkit;
+pub mod strings;
+pub mod world;
 pub mod writer;
 
 use crate::tokenizer;
+use std::fmt::{Display, Error, Formatter};
 
 /// Used in the `light_duration` field of the `Header` struct` to indicate that
 /// the light source never expires.
-const ETERNAL_LIGHT: i32 = -1;
+pub const ETERNAL_LIGHT: i32 = -1;
 
-/// Used in the `location` field of the `Item` struct to indicate that the item
-/// is in the player's inventory.
-const INVENTORY: i32 = -1;
+pub use ids::{CounterId, FlagId, ItemId, NounId, RoomId, VerbId};
+pub use message::MessageId;
+pub use parser::{ParseError, ParseErrorKind, Section, TokenKind};
+pub use world::{Direction, Location, INVENTORY};
 
 /// Defines the game itself.
 #[derive(Debug)]
@@ -37,6 +47,89 @@ impl Game {
         parser::parse_game(stream)
     }
 
+    /// Parses a new game from the given stream of tokens, checking it more
+    /// strictly and returning a warning for each problem found.  The stream
+    /// must end with the game's footer.
+    pub fn new_strict(
+        stream: &mut tokenizer::Stream,
+    ) -> Result<(Game, Vec<String>), parser::ParseError> {
+        parser::parse_game_strict(stream)
+    }
+
+    /// Returns the number of treasures, which are the items whose descriptions
+    /// begin with an asterisk.
+    pub fn treasures(&self) -> usize {
+        self.items.iter().filter(|item| item.is_treasure).count()
+    }
+
+    /// Returns the version number from the footer, such as 416 for 4.16.
+    pub fn version(&self) -> i32 {
+        self.footer.version
+    }
+
+    /// Returns the adventure number from the footer.
+    pub fn adventure(&self) -> i32 {
+        self.footer.adventure
+    }
+
+    /// Returns the number of actions, word pairs, rooms, messages and items
+    /// that the header declares.
+    pub fn header_counts(&self) -> HeaderCounts {
+        self.header.counts()
+    }
+
+    /// Parses a new game from the given stream of tokens, returning whatever
+    /// could be parsed if the game file is damaged or ends too soon.
+    pub fn new_partial(stream: &mut tokenizer::Stream) -> Result<Game, Box<PartialGame>> {
+        parser::parse_game_partial(stream)
+    }
+
+    /// Parses only the header from the given stream of tokens, which is much
+    /// quicker than parsing the whole game and works even when the rest of the
+    /// game file is damaged.
+    pub fn read_header(
+        stream: &mut tokenizer::Stream,
+    ) -> Result<HeaderCounts, parser::ParseError> {
+        parser::parse_header(stream).map(|header| header.counts())
+    }
+
+    /// Returns the verbs, with each one's synonyms grouped under it.
+    pub fn verb_groups(&self) -> Vec<WordGroup> {
+        group_words(&self.verbs)
+    }
+
+    /// Returns the nouns, with each one's synonyms grouped under it.
+    pub fn noun_groups(&self) -> Vec<WordGroup> {
+        group_words(&self.nouns)
+    }
+
+    /// Returns the room the player starts in, or None if the header gives a
+    /// room that doesn't exist.
+    pub fn starting_room(&self) -> Option<RoomId> {
+        RoomId::new(self.header.starting_room, self.rooms.len())
+    }
+
+    /// Returns the room where treasures are stored to score them, or None if
+    /// the header gives a room that doesn't exist.
+    pub fn treasure_room(&self) -> Option<RoomId> {
+        RoomId::new(self.header.treasure_room, self.rooms.len())
+    }
+
+    /// Returns the description of a room, or None if there is no such room.
+    pub fn room_description(&self, room: usize) -> Option<&str> {
+        self.rooms.get(room).map(|room| room.description.as_str())
+    }
+
+    /// Returns the description of an item, or None if there is no such item.
+    pub fn item_description(&self, item: usize) -> Option<&str> {
+        self.items.get(item).map(|item| item.description.as_str())
+    }
+
+    /// Returns the text of a message, which the parser has checked exists.
+    pub fn message(&self, message: MessageId) -> &str {
+        &self.messages[message.index()]
+    }
+
     /// Prints a version of the game to stdout for debugging.
     pub fn print_debug(&self) {
         println!("{:?}", self.header);
@@ -62,6 +155,171 @@ impl Game {
     }
 }
 
+/// Holds a word of the vocabulary along with its synonyms, all of which the
+/// interpreter treats as the same word.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordGroup {
+    /// The index that the word and its synonyms share, which is the one that
+    /// actions refer to.
+    pub index: usize,
+    /// The word itself.
+    pub word: String,
+    /// The words that mean the same thing, in the order they appear.
+    pub synonyms: Vec<String>,
+}
+
+impl Display for WordGroup {
+    /// Shows the word followed by its synonyms, if it has any.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.word)?;
+        if !self.synonyms.is_empty() {
+            write!(f, " = {}", self.synonyms.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Groups a list of words under the words they are synonyms of.  A synonym
+/// belongs to the nearest word before it that isn't a synonym, just as the
+/// interpreter matches it.  Word 0 stands alone, and the empty words that
+/// pad out the vocabulary are left out.
+fn group_words(words: &[Word]) -> Vec<WordGroup> {
+    let mut groups: Vec<WordGroup> = vec![];
+    for (i, entry) in words.iter().enumerate() {
+        let new_group = i <= 1 || !entry.is_synonym;
+        if new_group {
+            groups.push(WordGroup {
+                index: i,
+                word: entry.word.clone(),
+                synonyms: vec![],
+            });
+        } else if !entry.word.is_empty() {
+            if let Some(group) = groups.last_mut() {
+                group.synonyms.push(entry.word.clone());
+            }
+        }
+    }
+    groups.retain(|group| !group.word.is_empty() || !group.synonyms.is_empty());
+    groups
+}
+
+/// Holds the sizes of a game's tables as its header declares them.  (The
+/// game file itself gives the highest index in each table, which is one less.)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeaderCounts {
+    /// The number of actions.
+    pub actions: i32,
+    /// The number of word pairs.
+    pub words: i32,
+    /// The number of rooms.
+    pub rooms: i32,
+    /// The number of messages.
+    pub messages: i32,
+    /// The number of items.
+    pub items: i32,
+    /// The number of treasures.
+    pub treasures: i32,
+}
+
+/// Holds the sections of a game file that were parsed before an error, for
+/// a game file that is damaged or ends too soon.  The sections are parsed in
+/// order, so each one that is present was followed by all those before it.
+#[derive(Debug)]
+pub struct PartialGame {
+    header: Option<Header>,
+    actions: Option<Vec<Action>>,
+    words: Option<(Vec<Word>, Vec<Word>)>,
+    rooms: Option<Vec<Room>>,
+    messages: Option<Vec<String>>,
+    items: Option<Vec<Item>>,
+    error: String,
+}
+
+impl PartialGame {
+    /// Returns a partial game of which nothing could be parsed.
+    pub(crate) fn nothing(error: String) -> PartialGame {
+        PartialGame {
+            header: None,
+            actions: None,
+            words: None,
+            rooms: None,
+            messages: None,
+            items: None,
+            error,
+        }
+    }
+
+    /// Replaces the error with one that explains it better.
+    pub(crate) fn set_error(&mut self, error: String) {
+        self.error = error;
+    }
+
+    /// Returns the error that stopped the parser.
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    /// Returns the names of the sections that were parsed, in order.
+    pub fn sections(&self) -> Vec<&'static str> {
+        [
+            ("header", self.header.is_some()),
+            ("actions", self.actions.is_some()),
+            ("words", self.words.is_some()),
+            ("rooms", self.rooms.is_some()),
+            ("messages", self.messages.is_some()),
+            ("items", self.items.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, parsed)| parsed.then_some(name))
+        .collect()
+    }
+
+    /// Returns the sizes of the tables that the header declares, if the
+    /// header was parsed.
+    pub fn header_counts(&self) -> Option<HeaderCounts> {
+        self.header.as_ref().map(Header::counts)
+    }
+
+    /// Returns the number of treasures, if the items were parsed.
+    pub fn treasures(&self) -> Option<usize> {
+        self.items
+            .as_ref()
+            .map(|items| items.iter().filter(|item| item.is_treasure).count())
+    }
+
+    /// Returns a game made of the sections that were parsed, with the rest
+    /// left empty and the header's counts fixed to match, or None if not
+    /// even the header was parsed.  The game can be written out, decompiled
+    /// or have its text exported, but it isn't likely to be playable.
+    pub fn salvage(self) -> Option<Game> {
+        let mut header = self.header?;
+        let actions = self.actions.unwrap_or_default();
+        let (verbs, nouns) = self.words.unwrap_or_default();
+        let rooms = self.rooms.unwrap_or_default();
+        let messages = self.messages.unwrap_or_default();
+        let items = self.items.unwrap_or_default();
+        header.num_actions = actions.len() as i32;
+        header.num_words = verbs.len() as i32;
+        header.num_rooms = rooms.len() as i32;
+        header.num_messages = messages.len() as i32;
+        header.num_items = items.len() as i32;
+        Some(Game {
+            header,
+            actions,
+            verbs,
+            nouns,
+            rooms,
+            messages,
+            items,
+            footer: Footer {
+                version: 0,
+                adventure: 0,
+                magic: 0,
+            },
+        })
+    }
+}
+
 /// Defines the header.
 #[derive(Debug)]
 struct Header {
@@ -91,13 +349,25 @@ struct Header {
     treasure_room: i32,
 }
 
+impl Header {
+    /// Returns the sizes of the tables that the header declares.
+    fn counts(&self) -> HeaderCounts {
+        HeaderCounts {
+            actions: self.num_actions,
+            words: self.num_words,
+            rooms: self.num_rooms,
+            messages: self.num_messages,
+            items: self.num_items,
+            treasures: self.num_treasures,
+        }
+    }
+}
+
 /// Defines a single action.
 #[derive(Debug)]
 struct Action {
-    /// The verb index.
-    verb_index: i32,
-    /// The noun index.
-    noun_index: i32,
+    /// What sets the action off.
+    trigger: Trigger,
     /// The conditions (five in all).
     conditions: [Condition; 5],
     /// The actions (four in all).
@@ -106,6 +376,43 @@ struct Action {
     comment: Option<String>,
 }
 
+/// Defines what sets an action off, which the game file stores as a verb and
+/// a noun.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Trigger {
+    /// An automatic action (verb 0), which runs at the start of a turn with
+    /// the percentage chance given by its noun.  One with no chance at all
+    /// only ever continues the action before it.
+    Occurrence(i32),
+    /// An action for a command with the given verb, and the given noun or
+    /// (noun 0) any noun at all.
+    Command(VerbId, Option<NounId>),
+}
+
+impl Trigger {
+    /// Converts an integer expressed as (verb * 150) + noun into a Trigger,
+    /// or None if the verb or noun isn't among the given number of words.
+    pub fn from_i32(num: i32, words: usize) -> Option<Trigger> {
+        let (verb, noun) = (num / 150, num % 150);
+        if verb == 0 {
+            return Some(Trigger::Occurrence(noun));
+        }
+        let noun = match noun {
+            0 => None,
+            _ => Some(NounId::new(noun, words)?),
+        };
+        Some(Trigger::Command(VerbId::new(verb, words)?, noun))
+    }
+
+    /// Converts a Trigger back into an integer.
+    pub fn to_i32(self) -> i32 {
+        match self {
+            Trigger::Occurrence(chance) => chance,
+            Trigger::Command(verb, noun) => verb.to_i32() * 150 + noun.map_or(0, NounId::to_i32),
+        }
+    }
+}
+
 /// Defines a condition, which is a parameterized predicate.
 #[derive(Debug)]
 enum Condition {
@@ -125,9 +432,9 @@ enum Condition {
     ItemInGame(i32),
     ItemNotInGame(i32),
     CounterLE(i32),
-    CounterGE(i32),
-    ItemMoved(i32),
+    CounterGT(i32),
     ItemNotMoved(i32),
+    ItemMoved(i32),
     CounterEQ(i32),
     Invalid(i32, i32),
 }
@@ -153,9 +460,9 @@ impl Condition {
             13 => Condition::ItemInGame(param),
             14 => Condition::ItemNotInGame(param),
             15 => Condition::CounterLE(param),
-            16 => Condition::CounterGE(param),
-            17 => Condition::ItemMoved(param),
-            18 => Condition::ItemNotMoved(param),
+            16 => Condition::CounterGT(param),
+            17 => Condition::ItemNotMoved(param),
+            18 => Condition::ItemMoved(param),
             19 => Condition::CounterEQ(param),
             _ => Condition::Invalid(num % 20, param),
         }
@@ -164,7 +471,7 @@ impl Condition {
     /// Converts a Condition back into an integer.
     pub fn to_i32(&self) -> i32 {
         match self {
-            Condition::Parameter(n) => 0 + (n * 20),
+            Condition::Parameter(n) => n * 20,
             Condition::ItemCarried(n) => 1 + (n * 20),
             Condition::ItemInRoom(n) => 2 + (n * 20),
             Condition::ItemPresent(n) => 3 + (n * 20),
@@ -180,9 +487,9 @@ impl Condition {
             Condition::ItemInGame(n) => 13 + (n * 20),
             Condition::ItemNotInGame(n) => 14 + (n * 20),
             Condition::CounterLE(n) => 15 + (n * 20),
-            Condition::CounterGE(n) => 16 + (n * 20),
-            Condition::ItemMoved(n) => 17 + (n * 20),
-            Condition::ItemNotMoved(n) => 18 + (n * 20),
+            Condition::CounterGT(n) => 16 + (n * 20),
+            Condition::ItemNotMoved(n) => 17 + (n * 20),
+            Condition::ItemMoved(n) => 18 + (n * 20),
             Condition::CounterEQ(n) => 19 + (n * 20),
             Condition::Invalid(typ, n) => typ + (n * 20),
         }
@@ -191,10 +498,10 @@ impl Condition {
 
 /// Defines the type of an action -- or rather, a subaction, as there are up to
 /// four subactions associated with an action.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum ActionType {
     Nothing,
-    Message(i32),
+    Message(MessageId),
     GetItem,
     DropItem,
     MovePlayer,
@@ -239,7 +546,6 @@ impl ActionType {
     pub fn from_i32(num: i32) -> ActionType {
         match num {
             0 => ActionType::Nothing,
-            1..=51 => ActionType::Message(num - 1),
             52 => ActionType::GetItem,
             53 => ActionType::DropItem,
             54 => ActionType::MovePlayer,
@@ -278,8 +584,10 @@ impl ActionType {
             87 => ActionType::SwapLocationN,
             88 => ActionType::Delay,
             89 => ActionType::DrawPicture,
-            102..=150 => ActionType::Message(num - 51),
-            _ => ActionType::Invalid(num),
+            _ => match MessageId::from_code(num) {
+                Some(message) => ActionType::Message(message),
+                None => ActionType::Invalid(num),
+            },
         }
     }
             
@@ -287,13 +595,7 @@ impl ActionType {
     pub fn to_i32(&self) -> i32 {
         match self {
             ActionType::Nothing => 0,
-            ActionType::Message(num) => {
-                match num {
-                    0..=50 => num + 1,
-                    51..=99 => num + 51,
-                    _ => 0, // doesn't happen
-                }
-            },
+            ActionType::Message(message) => message.code(),
             ActionType::GetItem => 52,
             ActionType::DropItem => 53,
             ActionType::MovePlayer => 54,
@@ -351,8 +653,8 @@ struct Room {
     description: String,
     /// Indicates the description is to be printed literally (no "I'm in a" prefix).
     is_literal: bool,
-    /// The room exits.
-    exits: [i32; 6],
+    /// The room exits, where None means there is no exit that way.
+    exits: [Option<RoomId>; 6],
 }
 
 /// Defines an item (object).
@@ -360,8 +662,8 @@ struct Room {
 struct Item {
     /// The item description.
     description: String,
-    /// The item location (possibly `INVENTORY`).
-    location: i32,
+    /// The item location.
+    location: Location,
     /// Is the item a treasure (denoted by asterisks in the description)?
     is_treasure: bool,
     /// If set, automatic get/drop works, using this name.
@@ -0,0 +1,169 @@
+//! This module gives each kind of index into a game its own type, so that a
+//! room can't be passed where an item is wanted, nor a flag where a counter
+//! is.
+//!
+//! The game file stores every index as a plain integer.  Each type can only
+//! be made from one by a conversion that checks the integer is in range,
+//! usually against the size of the table it indexes, and returns None if it
+//! isn't.  The `game` module itself may make them directly, where it has
+//! already checked.  Messages have a type of their own in the `message`
+//! module, since actions number them oddly.
+
+use std::fmt::{Display, Error, Formatter};
+
+/// The number of flags, which actions set and clear and conditions test.
+pub const NUM_FLAGS: usize = 32;
+
+/// The number of counters and saved rooms that actions can select between.
+pub const NUM_COUNTERS: usize = 16;
+
+/// Defines an index into one of the game's tables, which is checked against
+/// the size of the table when it is made from an integer.
+macro_rules! table_id {
+    ($(#[$doc:meta])* $name:ident, $what:literal) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub(super) usize);
+
+        impl $name {
+            #[doc = concat!("Returns the ", $what, " with the given index, if the index is")]
+            #[doc = concat!("less than the number of ", $what, "s there are.")]
+            pub fn new(index: i32, count: usize) -> Option<$name> {
+                usize::try_from(index)
+                    .ok()
+                    .filter(|&index| index < count)
+                    .map($name)
+            }
+
+            /// Returns the index, for looking up the table.
+            pub const fn index(self) -> usize {
+                self.0
+            }
+
+            /// Returns the index as the game file stores it.
+            pub const fn to_i32(self) -> i32 {
+                self.0 as i32
+            }
+        }
+
+        impl Display for $name {
+            /// Shows the index as it would appear in a listing.
+            fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+                write!(f, concat!($what, " {}"), self.0)
+            }
+        }
+    };
+}
+
+table_id!(
+    /// Refers to one of the game's rooms.  Room 0 is the storeroom.
+    RoomId,
+    "room"
+);
+
+table_id!(
+    /// Refers to one of the game's items.
+    ItemId,
+    "item"
+);
+
+table_id!(
+    /// Refers to one of the game's verbs, and so to its synonyms.  Verb 0
+    /// stands for the automatic actions rather than a word.
+    VerbId,
+    "verb"
+);
+
+table_id!(
+    /// Refers to one of the game's nouns, and so to its synonyms.  Nouns 1
+    /// to 6 are the directions, in the order that rooms list their exits.
+    NounId,
+    "noun"
+);
+
+impl RoomId {
+    /// The room that holds the items that are out of play, which the player
+    /// can never reach.
+    pub const STOREROOM: RoomId = RoomId(0);
+}
+
+/// Defines an index into a fixed set of the interpreter's own, which is
+/// checked against the size of the set when it is made from an integer.
+macro_rules! fixed_id {
+    ($(#[$doc:meta])* $name:ident, $what:literal, $count:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub(super) usize);
+
+        impl $name {
+            #[doc = concat!("Returns the ", $what, " with the given number, if there is one.")]
+            pub fn new(number: i32) -> Option<$name> {
+                usize::try_from(number)
+                    .ok()
+                    .filter(|&number| number < $count)
+                    .map($name)
+            }
+
+            /// Returns the number, for looking up the set.
+            pub fn index(self) -> usize {
+                self.0
+            }
+        }
+
+        impl Display for $name {
+            /// Shows the number as it would appear in a listing.
+            fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+                write!(f, concat!($what, " {}"), self.0)
+            }
+        }
+    };
+}
+
+fixed_id!(
+    /// Refers to one of the flags.
+    FlagId,
+    "flag",
+    NUM_FLAGS
+);
+
+fixed_id!(
+    /// Refers to one of the counters that `SelectCounter` swaps in, or one
+    /// of the saved rooms that `SwapLocationN` swaps with the player's room.
+    CounterId,
+    "counter",
+    NUM_COUNTERS
+);
+
+impl FlagId {
+    /// Returns the flag's bit within a set of flags.
+    pub fn bit(self) -> u32 {
+        1 << self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_ids() {
+        assert_eq!(RoomId::new(0, 3), Some(RoomId::STOREROOM));
+        assert_eq!(RoomId::new(2, 3).map(RoomId::index), Some(2));
+        assert_eq!(RoomId::new(3, 3), None);
+        assert_eq!(RoomId::new(-1, 3), None);
+        assert_eq!(ItemId::new(9, 10).map(ItemId::to_i32), Some(9));
+        assert_eq!(VerbId::new(1, 1), None);
+        assert_eq!(NounId::new(6, 7).unwrap().to_string(), "noun 6");
+    }
+
+    #[test]
+    fn test_fixed_ids() {
+        assert_eq!(FlagId::new(15).map(FlagId::bit), Some(1 << 15));
+        assert_eq!(FlagId::new(31).map(FlagId::index), Some(31));
+        for number in [-1, 32, 1000] {
+            assert_eq!(FlagId::new(number), None);
+        }
+        assert_eq!(CounterId::new(15).map(CounterId::index), Some(15));
+        assert_eq!(CounterId::new(16), None);
+    }
+}
@@ -0,0 +1,1496 @@
+//! This module contains the interpreter, which holds the state of a game in
+//! progress and carries out the player's commands.
+//!
+//! Like the rest of the `game` module, it does no I/O of its own: every
+//! method returns the text to show the player, and it is up to the caller to
+//! print it.  The output follows the conventions of ScottFree, whose source is
+//! included in this crate for reference.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashMap;
+
+use crate::format::Perspective;
+
+mod author;
+pub mod diff;
+pub mod hint;
+pub mod save;
+pub mod timer;
+
+use super::ids::NUM_COUNTERS;
+use super::world::{can_carry, is_carried, is_in_play, is_in_room, is_present};
+use super::*;
+
+/// The flag that is set while it is dark.
+const DARKNESS_FLAG: FlagId = FlagId(15);
+
+/// The flag that is set once the light source has run out.
+const LIGHT_OUT_FLAG: FlagId = FlagId(16);
+
+/// The item that lights the way in the dark.  It is always item 9, if the
+/// game has that many items.
+const LIGHT_SOURCE: i32 = 9;
+
+/// The verb for moving, which is always verb 1.  Nouns 1 to 6 are always the
+/// directions, as `Direction::from_noun` has it.
+const GO: VerbId = VerbId(1);
+
+/// The verbs for picking up and putting down items, which are always verbs
+/// 10 and 18.  If no action handles them, items with an autograb name can be
+/// picked up and put down by that name.
+const GET: VerbId = VerbId(10);
+const DROP: VerbId = VerbId(18);
+
+/// The number of actions that may run in a single turn before the turn is
+/// abandoned.  This is far more than any of the classic games needs.
+pub const DEFAULT_ACTION_BUDGET: usize = 1000;
+
+/// The line drawn under a room's description on the TRS-80.
+const TRS80_LINE: &str = "<------------------------------------------------------------>\n";
+
+/// Selects between the variations in output that ScottFree offers with its
+/// command-line flags, all of which are off by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Style {
+    /// Speak of the player as "I" or as "you".  ScottFree's `-y` flag gives
+    /// `Perspective::ScottFree`.
+    pub perspective: Perspective,
+    /// Count down the light source's last turns as Scott Adams' own
+    /// interpreters did (`-s`).
+    pub scott_light: bool,
+    /// Lay out rooms and the inventory as the TRS-80 did, with periods
+    /// rather than hyphens between items (`-t`).
+    pub trs80: bool,
+    /// Destroy the light source when it runs out, for the earliest games
+    /// that don't check the light-out flag (`-p`).
+    pub prehistoric_lamp: bool,
+}
+
+/// Decides when the automatic actions run, as the platforms the games were
+/// ported to didn't all agree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OccurrencePolicy {
+    /// Each automatic action runs with the percentage chance given by its
+    /// noun, as in ScottFree and the TRS-80 originals.
+    #[default]
+    Percent,
+    /// Each automatic action runs every turn, whatever its noun, as some
+    /// ports did.
+    Always,
+    /// No automatic action ever runs, which is only useful for testing.
+    Never,
+}
+
+impl OccurrencePolicy {
+    /// Looks up a policy by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "percent" => Some(OccurrencePolicy::Percent),
+            "always" => Some(OccurrencePolicy::Always),
+            "never" => Some(OccurrencePolicy::Never),
+            _ => None,
+        }
+    }
+}
+
+/// The phases of a turn, in the order that ScottFree runs them.  Many timed
+/// puzzles depend on this order: the light source burns down after the
+/// player's command has been carried out, and the automatic actions then see
+/// the result before the player is asked for the next command.  The order
+/// deliberately follows ScottFree rather than running the light before the
+/// player's command, since the games were written against the original
+/// interpreters and would play differently otherwise.
+///
+/// ```text
+/// start() -> Occurrences -> Command
+/// command() -> Actions -> Light -> Occurrences -> Command
+/// ```
+///
+/// A command made up of unknown words doesn't take a turn, and once the game
+/// is over no further phases begin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnPhase {
+    /// Any timers that are due go off, and then the automatic actions run,
+    /// each with the chance given by its noun.
+    Occurrences,
+    /// The interpreter waits for the player's next command.
+    Command,
+    /// The actions for the player's command run, or the built-in commands if
+    /// none of them handle it.
+    Actions,
+    /// The light source burns down, and the player is warned if it is
+    /// growing dim or has run out.
+    Light,
+}
+
+/// Describes why the interpreter gave up on a turn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineError {
+    /// More actions ran in a single turn than the budget allows, so the turn
+    /// was abandoned at the given action.  The table of actions is scanned
+    /// once per pass, so a chain of Continue actions can't loop forever on
+    /// its own, but GET ALL, DROP ALL and the automatic actions can between
+    /// them do an unreasonable amount of work.
+    RunawayAction { action: usize, budget: usize },
+}
+
+impl Display for EngineError {
+    /// Makes an engine error human-readable.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            EngineError::RunawayAction { action, budget } => write!(
+                f,
+                "Gave up on the turn at action {}, after running {} actions",
+                action, budget
+            ),
+        }
+    }
+}
+
+/// What came of looking through the actions for those that match a command.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// At least one action ran.
+    Done,
+    /// No action has the command's words.
+    NotUnderstood,
+    /// Some actions have the command's words, but none of their conditions
+    /// were met.
+    NotYet,
+}
+
+/// Holds a game in progress.
+pub struct Interpreter {
+    game: Game,
+    /// The room the player is in.
+    room: RoomId,
+    /// The current location of each item, which starts out as the location
+    /// given in the game file.
+    locations: Vec<Location>,
+    /// The location of each item when the game began, which the moved and
+    /// not moved conditions compare against.  This belongs to the item, so
+    /// it doesn't follow the item's location when items swap places.
+    original_locations: Vec<Location>,
+    /// The 32 flags that are set and cleared by actions.
+    flags: u32,
+    /// The counter that the counter actions and conditions work on.
+    counter: i32,
+    /// The other counters, which `SelectCounter` swaps with `counter`.
+    counters: [i32; NUM_COUNTERS],
+    /// The room that `SwapLocation` swaps with the player's room.
+    saved_room: RoomId,
+    /// The rooms that `SwapLocationN` swaps with the player's room.
+    saved_rooms: [RoomId; NUM_COUNTERS],
+    /// The number of turns left before the light source runs out.
+    light_time: i32,
+    /// The noun of the current command, as the player typed it.
+    noun: String,
+    /// Set when an action has changed what can be seen, so that the room
+    /// should be described again.
+    redraw: bool,
+    /// Decides whether the occasional automatic actions happen.  Saves
+    /// record its state, so that a restored game rolls the same dice.
+    rng: ChaCha12Rng,
+    /// Decides when the automatic actions run at all.
+    occurrence_policy: OccurrencePolicy,
+    /// The number of actions that may run in a turn, if there is a limit.
+    action_budget: Option<usize>,
+    /// The number of actions that have run this turn.
+    actions_run: usize,
+    /// The action at which this turn was abandoned, if it was.
+    runaway: Option<usize>,
+    /// The variations in output to use.
+    style: Style,
+    /// Set when the modern conveniences are turned off.
+    purist: bool,
+    /// The words that stand for others in the player's commands, in
+    /// capitals.
+    aliases: HashMap<String, String>,
+    /// Set when an action has asked to save the game, which is up to the
+    /// caller to do.
+    save_requested: bool,
+    /// Set once the game is over, because the player quit or died.
+    finished: bool,
+    /// The number of times the player has died, which saves don't record.
+    deaths: u32,
+    /// How much the HINT command may give away.
+    hint_level: hint::HintLevel,
+    /// The action that the last hint was about, and how much it gave away.
+    last_hint: Option<(usize, hint::HintLevel)>,
+    /// The phase of the turn that the game is in.
+    phase: TurnPhase,
+    /// Called as each phase of a turn begins.
+    phase_hook: Option<Box<dyn FnMut(TurnPhase)>>,
+    /// The number of turns that have gone by.
+    turns: u32,
+    /// The timers that the game's sidecar gives, if any.
+    timers: Vec<timer::Timer>,
+    /// Whether each timer has gone off.
+    fired: Vec<bool>,
+    /// Says how long the game has been played, for the timers.
+    clock: Option<timer::Clock>,
+    /// Set when the game's author is playtesting it, and wants to be warned
+    /// about what looks like a mistake in it.
+    author: bool,
+    /// The warnings for the author that have yet to be given.
+    warnings: Vec<String>,
+}
+
+impl Interpreter {
+    /// Starts a new game in the starting room, or in the storeroom if the
+    /// starting room doesn't exist.
+    pub fn new(game: Game) -> Interpreter {
+        let room = game.starting_room().unwrap_or(RoomId::STOREROOM);
+        let locations: Vec<Location> = game.items.iter().map(|item| item.location).collect();
+        let light_time = game.header.light_duration;
+        Interpreter {
+            game,
+            room,
+            original_locations: locations.clone(),
+            locations,
+            flags: 0,
+            counter: 0,
+            counters: [0; NUM_COUNTERS],
+            saved_room: RoomId::STOREROOM,
+            saved_rooms: [RoomId::STOREROOM; NUM_COUNTERS],
+            light_time,
+            noun: String::new(),
+            redraw: false,
+            rng: ChaCha12Rng::from_entropy(),
+            occurrence_policy: OccurrencePolicy::default(),
+            action_budget: Some(DEFAULT_ACTION_BUDGET),
+            actions_run: 0,
+            runaway: None,
+            style: Style::default(),
+            purist: false,
+            aliases: HashMap::new(),
+            save_requested: false,
+            finished: false,
+            deaths: 0,
+            hint_level: hint::HintLevel::default(),
+            last_hint: None,
+            phase: TurnPhase::Command,
+            phase_hook: None,
+            turns: 0,
+            timers: vec![],
+            fired: vec![],
+            clock: None,
+            author: false,
+            warnings: vec![],
+        }
+    }
+
+    /// Changes the variations in output to use.
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    /// Turns the modern conveniences off, or back on, for players who want
+    /// to finish a game as it was first played.  In purist mode there is no
+    /// GET ALL or DROP ALL and no HINT, LOOK and INVENTORY work only if the
+    /// game itself understands them, the automatic actions always run by
+    /// their chance, and the final score notes that the game was played this
+    /// way.  Any convenience added to the interpreter should check
+    /// `is_purist`.
+    pub fn set_purist(&mut self, purist: bool) {
+        self.purist = purist;
+    }
+
+    /// Returns whether the modern conveniences are turned off.
+    pub fn is_purist(&self) -> bool {
+        self.purist
+    }
+
+    /// Gives words that the player may type in place of others, such as
+    /// XYZZY for SAY XYZZY.  An alias is replaced wherever it is a whole
+    /// word of a command, in any case.  Aliases are a convenience, so they
+    /// are ignored in purist mode.
+    pub fn set_aliases(&mut self, aliases: impl IntoIterator<Item = (String, String)>) {
+        self.aliases = aliases
+            .into_iter()
+            .map(|(alias, words)| (alias.to_uppercase(), words))
+            .collect();
+    }
+
+    /// Changes whether the player is spoken of as "I" or as "you", leaving
+    /// the rest of the style as it is.
+    pub fn set_perspective(&mut self, perspective: Perspective) {
+        self.style.perspective = perspective;
+    }
+
+    /// Seeds the dice that decide whether the occasional automatic actions
+    /// happen, so that the same commands bring about the same events.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    /// Changes when the automatic actions run.  This has no effect in purist
+    /// mode.
+    pub fn set_occurrence_policy(&mut self, policy: OccurrencePolicy) {
+        self.occurrence_policy = policy;
+    }
+
+    /// Changes the number of actions that may run in a single turn, or
+    /// removes the limit, for games that deliberately run very long chains.
+    pub fn set_action_budget(&mut self, budget: Option<usize>) {
+        self.action_budget = budget;
+    }
+
+    /// Starts the game by describing the starting room and running the
+    /// automatic actions for the first turn.  Call this once, before the
+    /// first command.
+    pub fn start(&mut self) -> String {
+        self.try_start().unwrap_or_else(|err| format!("{}\n", err))
+    }
+
+    /// Starts the game as `start` does, but returns an error if the turn had
+    /// to be abandoned.
+    pub fn try_start(&mut self) -> Result<String, EngineError> {
+        self.new_turn();
+        let mut text = self.describe_room();
+        text.push_str(&self.occurrences());
+        self.check_runaway()?;
+        Ok(text + &self.take_warnings())
+    }
+
+    /// Returns the phase of the turn that the game is in, which is `Command`
+    /// whenever the game is waiting for the player.
+    pub fn phase(&self) -> TurnPhase {
+        self.phase
+    }
+
+    /// Calls the given function as each phase of a turn begins, so that a
+    /// frontend can follow along.  This replaces any earlier hook.
+    pub fn on_phase(&mut self, hook: impl FnMut(TurnPhase) + 'static) {
+        self.phase_hook = Some(Box::new(hook));
+    }
+
+    /// Begins the given phase of the turn.
+    fn enter(&mut self, phase: TurnPhase) {
+        self.phase = phase;
+        if let Some(hook) = &mut self.phase_hook {
+            hook(phase);
+        }
+    }
+
+    /// Returns the room the player is in.
+    pub fn room(&self) -> RoomId {
+        self.room
+    }
+
+    /// Is the game over?
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the game being played.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Returns the number of times the player has died since this
+    /// interpreter was made.  Saves don't record it, so restoring a game
+    /// leaves it as it is.
+    pub fn deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    /// Is it too dark to see?  It is dark when the darkness flag is set,
+    /// unless the light source is carried or lying in the room.
+    pub fn is_dark(&self) -> bool {
+        let light = self.light_source().map(|item| self.locations[item.index()]);
+        self.flag(DARKNESS_FLAG) && !light.is_some_and(|location| is_present(location, self.room))
+    }
+
+    /// Describes the room the player is in, listing its exits and the items
+    /// that can be seen there, in the standard format:
+    ///
+    /// ```text
+    /// I'm in a forest
+    ///
+    /// Obvious exits: North, East.
+    ///
+    /// I can also see: Trees - Sign
+    /// ```
+    ///
+    /// A room whose description is literal is described in its own words,
+    /// without the "I'm in a" prefix.  This is what the `DescribeRoom`
+    /// action shows.
+    pub fn describe_room(&self) -> String {
+        let line = if self.style.trs80 { TRS80_LINE } else { "" };
+        if self.is_dark() {
+            let you = self.says("I", "You");
+            return format!("{} can't see. It is too dark!\n{}", you, line);
+        }
+
+        let room = &self.game.rooms[self.room.index()];
+        let mut text = format!(
+            "{}\n",
+            self.style.perspective.room(&room.description, room.is_literal)
+        );
+
+        let exits: Vec<&str> = Direction::ALL
+            .into_iter()
+            .filter(|dir| room.exits[dir.index()].is_some())
+            .map(Direction::name)
+            .collect();
+        if exits.is_empty() {
+            text.push_str("\nObvious exits: none.\n");
+        } else {
+            text.push_str(&format!("\nObvious exits: {}.\n", exits.join(", ")));
+        }
+
+        let items: Vec<&str> = self
+            .game
+            .items
+            .iter()
+            .zip(&self.locations)
+            .filter(|&(_, &location)| is_in_room(location, self.room))
+            .map(|(item, _)| item.description.as_str())
+            .collect();
+        if !items.is_empty() {
+            let you = self.says("I", "You");
+            if self.style.trs80 {
+                text.push_str(&format!("\n{} can also see: {}. ", you, items.join(". ")));
+            } else {
+                text.push_str(&format!("\n{} can also see: {}", you, items.join(" - ")));
+            }
+        }
+        text.push('\n');
+        text.push_str(line);
+        text
+    }
+
+    /// Lists the items the player is carrying, which are only ever shown
+    /// here and never as part of a room.  This is what the `Inventory` action
+    /// shows.
+    pub fn inventory(&self) -> String {
+        let items: Vec<&str> = self
+            .game
+            .items
+            .iter()
+            .zip(&self.locations)
+            .filter(|&(_, &location)| is_carried(location))
+            .map(|(item, _)| item.description.as_str())
+            .collect();
+        let carrying = self.says("I'm carrying", "You are carrying");
+        let separator = if self.style.trs80 { ". " } else { " - " };
+        if items.is_empty() {
+            format!("{}:\nNothing.\n", carrying)
+        } else {
+            format!("{}:\n{}.\n", carrying, items.join(separator))
+        }
+    }
+
+    /// Has an action asked to save the game since this was last called?  It
+    /// is up to the caller to ask the player where to save it.
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
+    /// Carries out a command typed by the player and returns the response.
+    /// Unless the command was empty or used words the game doesn't know, it
+    /// takes a turn: the light source burns down and the automatic actions
+    /// for the next turn run.
+    ///
+    /// ```
+    /// let fixture = rusty_adams::fixtures::find("counter").unwrap();
+    /// let mut interpreter = fixture.interpreter()?;
+    /// interpreter.start();
+    /// assert_eq!(
+    ///     interpreter.command("wait"),
+    ///     "Time passes.\nThe bomb ticks. Its timer reads:\n2\n"
+    /// );
+    /// assert_eq!(interpreter.command("xyzzy"), "You use word(s) I don't know!\n");
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn command(&mut self, input: &str) -> String {
+        self.try_command(input)
+            .unwrap_or_else(|err| format!("{}\n", err))
+    }
+
+    /// Carries out a command as `command` does, but returns an error if the
+    /// turn had to be abandoned, in which case whatever the turn had done so
+    /// far still stands.
+    pub fn try_command(&mut self, input: &str) -> Result<String, EngineError> {
+        let text = self.run_command(input)?;
+        Ok(text + &self.take_warnings())
+    }
+
+    /// Carries out a command for `try_command`, leaving any warnings for the
+    /// author to it.
+    fn run_command(&mut self, input: &str) -> Result<String, EngineError> {
+        span!("command", input);
+        if input.trim().is_empty() {
+            return Ok(String::new());
+        }
+
+        let expanded;
+        let input = if self.purist || self.aliases.is_empty() {
+            input
+        } else {
+            expanded = self.expand_aliases(input);
+            &expanded
+        };
+        let (verb, noun) = self.parse(input);
+        self.noun = input.split_whitespace().nth(1).unwrap_or("").to_string();
+        let Some(verb) = verb else {
+            return Ok(self
+                .meta_command(input)
+                .unwrap_or_else(|| "You use word(s) I don't know!\n".to_string()));
+        };
+
+        self.new_turn();
+        self.enter(TurnPhase::Actions);
+        let mut text = match (verb, noun.and_then(Direction::from_noun)) {
+            (GO, _) if noun.is_none() => "Give me a direction too.\n".to_string(),
+            (GO, Some(dir)) => self.go(dir),
+            _ => {
+                let mut text = String::new();
+                match self.perform(Some((verb, noun)), true, &mut text) {
+                    Outcome::Done => text,
+                    Outcome::NotUnderstood => match self.meta_command(input) {
+                        Some(meta) => meta,
+                        None => "I don't understand your command.\n".to_string(),
+                    },
+                    Outcome::NotYet => text + "I can't do that yet.\n",
+                }
+            }
+        };
+        self.check_runaway()?;
+        if self.finished {
+            return Ok(text);
+        }
+
+        self.enter(TurnPhase::Light);
+        self.turns += 1;
+        text.push_str(&self.burn_light());
+        if self.redraw {
+            text.push_str(&self.describe_room());
+            self.redraw = false;
+        }
+        text.push_str(&self.occurrences());
+        self.check_runaway()?;
+        Ok(text)
+    }
+
+    /// Returns a command with each of its words that is an alias replaced by
+    /// what it stands for.
+    fn expand_aliases(&self, input: &str) -> String {
+        input
+            .split_whitespace()
+            .map(|word| {
+                self.aliases
+                    .get(&word.to_uppercase())
+                    .map_or(word, String::as_str)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Begins counting the actions that run in a new turn.
+    fn new_turn(&mut self) {
+        self.actions_run = 0;
+        self.runaway = None;
+    }
+
+    /// Returns an error if the turn has been abandoned.
+    fn check_runaway(&self) -> Result<(), EngineError> {
+        match (self.runaway, self.action_budget) {
+            (Some(action), Some(budget)) => Err(EngineError::RunawayAction { action, budget }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handles the commands that every game understands, even if its own
+    /// vocabulary and actions don't cover them.
+    fn meta_command(&mut self, input: &str) -> Option<String> {
+        match input.trim().to_uppercase().as_str() {
+            "LOOK" | "L" if !self.purist => Some(self.describe_room()),
+            "INVENTORY" | "I" if !self.purist => Some(self.inventory()),
+            "HINT" => self.hint(),
+            "QUIT" => {
+                self.finished = true;
+                Some(String::new())
+            }
+            _ => None,
+        }
+    }
+
+    /// Runs the automatic actions, which have verb 0 and happen at the start
+    /// of each turn with the chance given by their noun.
+    fn occurrences(&mut self) -> String {
+        self.enter(TurnPhase::Occurrences);
+        self.run_timers();
+        let mut text = String::new();
+        self.perform(None, false, &mut text);
+        if !self.finished {
+            if self.redraw {
+                text.push_str(&self.describe_room());
+                self.redraw = false;
+            }
+            self.enter(TurnPhase::Command);
+        }
+        text
+    }
+
+    /// Burns down the light source by a turn, warning the player as it
+    /// grows dim and when it runs out.  The light source doesn't burn while
+    /// it is in the storeroom.
+    fn burn_light(&mut self) -> String {
+        let light = self.light_source().map(|item| self.locations[item.index()]);
+        if !light.is_some_and(is_in_play) || self.light_time == ETERNAL_LIGHT {
+            return String::new();
+        }
+
+        self.light_time -= 1;
+        let visible = light.is_some_and(|location| is_present(location, self.room));
+        let mut text = String::new();
+        if self.light_time < 1 {
+            self.set_flag(LIGHT_OUT_FLAG, true);
+            if visible && self.style.scott_light {
+                text.push_str("Light has run out!\n");
+            } else if visible {
+                text.push_str("Your light has run out.\n");
+            }
+            if self.style.prehistoric_lamp {
+                self.move_item(self.light_source(), Location::STOREROOM);
+            }
+        } else if self.light_time < 25 && visible {
+            if self.style.scott_light {
+                text.push_str(&format!("Light runs out in {} turns.\n", self.light_time));
+            } else if self.light_time % 5 == 0 {
+                text.push_str("Your light is growing dim.\n");
+            }
+        }
+        text
+    }
+
+    /// Looks through the actions for those matching the given verb and noun,
+    /// or the automatic actions if there is no command, and runs them.  For a
+    /// command, only the first action whose conditions are met runs; for the
+    /// automatic actions, every one does.  An action that ends with
+    /// `Continue` also runs the actions that follow it with verb and noun 0.
+    ///
+    /// If no action ran, GET and DROP fall back to picking up and putting
+    /// down items by their autograb names, when `builtins` is set.
+    fn perform(
+        &mut self,
+        command: Option<(VerbId, Option<NounId>)>,
+        builtins: bool,
+        text: &mut String,
+    ) -> Outcome {
+        let mut outcome = Outcome::NotUnderstood;
+        let mut continuing = false;
+        for i in 0..self.game.actions.len() {
+            if self.runaway.is_some() {
+                break;
+            }
+            let trigger = self.game.actions[i].trigger;
+            let blank = trigger == Trigger::Occurrence(0);
+            if i > 0 && !blank {
+                continuing = false;
+            }
+            if command.is_some() && !continuing && outcome == Outcome::Done {
+                break;
+            }
+
+            let matches = match (trigger, command) {
+                (Trigger::Occurrence(chance), None) => continuing || self.occurs(chance),
+                (Trigger::Command(verb, noun), Some((command_verb, command_noun))) => {
+                    verb == command_verb && (noun.is_none() || noun == command_noun)
+                }
+                _ => continuing && blank,
+            };
+            if !matches {
+                continue;
+            }
+
+            if outcome == Outcome::NotUnderstood {
+                outcome = Outcome::NotYet;
+            }
+            if let Some(continues) = self.perform_action(i, text) {
+                outcome = Outcome::Done;
+                continuing |= continues;
+                if self.finished || command.is_some() && !continuing {
+                    break;
+                }
+            }
+        }
+
+        if outcome != Outcome::Done && builtins && self.runaway.is_none() {
+            match command {
+                Some((GET, noun)) => return self.get(noun, text),
+                Some((DROP, noun)) => return self.drop(noun, text),
+                _ => {}
+            }
+        }
+        outcome
+    }
+
+    /// Runs the action at the given index if its conditions are met,
+    /// returning whether it ended with `Continue`, or `None` if it didn't run.
+    fn perform_action(&mut self, index: usize, text: &mut String) -> Option<bool> {
+        let mut params = vec![];
+        for condition in &self.game.actions[index].conditions {
+            if let Condition::Parameter(param) = condition {
+                params.push(*param);
+            } else if !self.check(condition) {
+                return None;
+            }
+        }
+        self.actions_run += 1;
+        if self
+            .action_budget
+            .is_some_and(|budget| self.actions_run > budget)
+        {
+            self.runaway = Some(index);
+            return Some(false);
+        }
+        debug!(
+            action = index,
+            comment = self.game.actions[index].comment.as_deref(),
+            "running action"
+        );
+
+        let mut params = params.into_iter();
+        let mut continues = false;
+        for i in 0..4 {
+            match self.game.actions[index].actions[i].clone() {
+                ActionType::Continue => continues = true,
+                action => {
+                    trace!(?action, "subaction");
+                    text.push_str(&self.act(index, &action, &mut params));
+                }
+            }
+            if self.finished {
+                break;
+            }
+        }
+        Some(continues)
+    }
+
+    /// Is the given condition met?
+    fn check(&self, condition: &Condition) -> bool {
+        let location = |item: &i32| self.location(self.item(*item));
+        let original = |item: &i32| match self.item(*item) {
+            Some(item) => self.original_locations[item.index()],
+            None => Location::STOREROOM,
+        };
+        let here = Location::Room(self.room);
+        let in_room = |room: &i32| RoomId::new(*room, self.game.rooms.len()) == Some(self.room);
+        let flag = |flag: &i32| FlagId::new(*flag).is_some_and(|flag| self.flag(flag));
+        match condition {
+            Condition::Parameter(_) => true,
+            Condition::ItemCarried(item) => is_carried(location(item)),
+            Condition::ItemInRoom(item) => location(item) == here,
+            Condition::ItemPresent(item) => is_present(location(item), self.room),
+            Condition::PlayerInRoom(room) => in_room(room),
+            Condition::ItemNotInRoom(item) => location(item) != here,
+            Condition::ItemNotCarried(item) => !is_carried(location(item)),
+            Condition::PlayerNotInRoom(room) => !in_room(room),
+            Condition::BitSet(n) => flag(n),
+            Condition::BitClear(n) => !flag(n),
+            Condition::InventoryNotEmpty(_) => self.carried() > 0,
+            Condition::InventoryEmpty(_) => self.carried() == 0,
+            Condition::ItemNotPresent(item) => !is_present(location(item), self.room),
+            Condition::ItemInGame(item) => is_in_play(location(item)),
+            Condition::ItemNotInGame(item) => !is_in_play(location(item)),
+            Condition::CounterLE(n) => self.counter <= *n,
+            Condition::CounterGT(n) => self.counter > *n,
+            Condition::ItemNotMoved(item) => location(item) == original(item),
+            Condition::ItemMoved(item) => location(item) != original(item),
+            Condition::CounterEQ(n) => self.counter == *n,
+            Condition::Invalid(_, _) => true,
+        }
+    }
+
+    /// Carries out a single subaction of the action at the given index,
+    /// taking any parameters it needs from those of its action, and returns
+    /// what it shows the player.
+    fn act(
+        &mut self,
+        index: usize,
+        action: &ActionType,
+        params: &mut impl Iterator<Item = i32>,
+    ) -> String {
+        let mut param = || params.next().unwrap_or(0);
+        let rooms = self.game.rooms.len();
+        match action {
+            ActionType::Nothing | ActionType::Invalid(_) => {}
+            ActionType::Message(message) => {
+                if let Some(text) = self.game.messages.get(message.index()) {
+                    return format!("{}\n", text);
+                }
+                self.warn(|_| format!("Action {} prints {}, which doesn't exist", index, message));
+            }
+            ActionType::GetItem => {
+                let number = param();
+                let item = self.item(number);
+                if !item.is_some_and(|item| is_present(self.locations[item.index()], self.room)) {
+                    self.warn(|game| match item {
+                        Some(item) => format!(
+                            "Action {} gets {} (item {}), which isn't here",
+                            index,
+                            game.game.items[item.index()].description,
+                            number
+                        ),
+                        None => {
+                            format!("Action {} gets item {}, which doesn't exist", index, number)
+                        }
+                    });
+                }
+                if !can_carry(self.carried(), self.game.header.max_inventory) {
+                    return self
+                        .says("I've too much to carry!\n", "You are carrying too much.\n")
+                        .to_string();
+                }
+                self.move_item(item, Location::Carried);
+            }
+            ActionType::DropItem => {
+                let item = self.item(param());
+                self.move_item(item, Location::Room(self.room));
+            }
+            ActionType::MovePlayer => {
+                // A room that doesn't exist is ignored, rather than leaving
+                // the player nowhere.
+                let number = param();
+                match RoomId::new(number, rooms) {
+                    Some(room) => {
+                        self.room = room;
+                        self.redraw = true;
+                    }
+                    None => self.warn(|_| {
+                        format!(
+                            "Action {} moves the player to room {}, which doesn't exist",
+                            index, number
+                        )
+                    }),
+                }
+            }
+            ActionType::RemoveItem(_) => {
+                let item = self.item(param());
+                self.move_item(item, Location::STOREROOM);
+            }
+            ActionType::SetDarkness => self.set_flag(DARKNESS_FLAG, true),
+            ActionType::ClearDarkness => self.set_flag(DARKNESS_FLAG, false),
+            ActionType::SetBit => self.set_flag(FlagId((param() & 31) as usize), true),
+            ActionType::ClearBit => self.set_flag(FlagId((param() & 31) as usize), false),
+            ActionType::SetBit0 => self.set_flag(FlagId(0), true),
+            ActionType::ClearBit0 => self.set_flag(FlagId(0), false),
+            ActionType::Death => {
+                self.deaths += 1;
+                self.set_flag(DARKNESS_FLAG, false);
+                self.room = RoomId(rooms.saturating_sub(1));
+                self.redraw = false;
+                let dead = self.says("I am dead.\n", "You are dead.\n");
+                return format!("{}{}", dead, self.describe_room());
+            }
+            ActionType::PutItem => {
+                let item = self.item(param());
+                let location = Location::new(param(), rooms);
+                self.move_item(item, location);
+                self.redraw = true;
+            }
+            ActionType::GameOver => {
+                self.finished = true;
+                return "The game is now over.\n".to_string();
+            }
+            ActionType::DescribeRoom(_) => {
+                self.redraw = false;
+                return self.describe_room();
+            }
+            ActionType::Score => return self.score(),
+            ActionType::Inventory => return self.inventory(),
+            ActionType::RefillLight => {
+                self.light_time = self.game.header.light_duration;
+                self.move_item(self.light_source(), Location::Carried);
+                self.set_flag(LIGHT_OUT_FLAG, false);
+            }
+            ActionType::ClearScreen | ActionType::Delay => {}
+            ActionType::SaveGame => self.save_requested = true,
+            ActionType::SwapItems => {
+                let (first, second) = (self.item(param()), self.item(param()));
+                let location = self.location(first);
+                self.move_item(first, self.location(second));
+                self.move_item(second, location);
+            }
+            ActionType::Continue => {}
+            ActionType::TakeItem => {
+                let item = self.item(param());
+                self.move_item(item, Location::Carried);
+            }
+            ActionType::MoveItemToItem => {
+                let (item, other) = (self.item(param()), self.item(param()));
+                self.move_item(item, self.location(other));
+            }
+            ActionType::DecrementCounter => {
+                if self.counter >= 0 {
+                    self.counter -= 1;
+                }
+            }
+            ActionType::PrintCounter => return self.counter.to_string(),
+            ActionType::SetCounter => self.counter = param(),
+            ActionType::SwapLocation => {
+                std::mem::swap(&mut self.room, &mut self.saved_room);
+                self.redraw = true;
+            }
+            ActionType::SelectCounter => {
+                if let Some(counter) = CounterId::new(param()) {
+                    std::mem::swap(&mut self.counter, &mut self.counters[counter.index()]);
+                }
+            }
+            ActionType::AddToCounter => self.counter += param(),
+            ActionType::SubFromCounter => self.counter = (self.counter - param()).max(-1),
+            ActionType::EchoNoun => return self.noun.clone(),
+            ActionType::EchoNounCR => return format!("{}\n", self.noun),
+            ActionType::EchoCR => return "\n".to_string(),
+            ActionType::SwapLocationN => {
+                if let Some(counter) = CounterId::new(param()) {
+                    std::mem::swap(&mut self.room, &mut self.saved_rooms[counter.index()]);
+                    self.redraw = true;
+                }
+            }
+            ActionType::DrawPicture => {
+                param();
+            }
+        }
+        String::new()
+    }
+
+    /// Picks up the item with the autograb name the player gave, or every
+    /// such item in the room for GET ALL.
+    fn get(&mut self, noun: Option<NounId>, text: &mut String) -> Outcome {
+        let max_inventory = self.game.header.max_inventory;
+        if !self.purist && self.noun.eq_ignore_ascii_case("ALL") {
+            if self.is_dark() {
+                text.push_str("It is dark.\n");
+                return Outcome::Done;
+            }
+            let mut taken = false;
+            for item in self.autograb_items(Location::Room(self.room)) {
+                let noun = self.autograb_noun(item);
+                self.perform(Some((GET, noun)), false, text);
+                if self.runaway.is_some() {
+                    return Outcome::Done;
+                }
+                if !can_carry(self.carried(), max_inventory) {
+                    text.push_str(
+                        self.says("I've too much to carry.\n", "You are carrying too much.\n"),
+                    );
+                    return Outcome::Done;
+                }
+                self.move_item(Some(item), Location::Carried);
+                text.push_str(&format!("{}: O.K.\n", self.game.items[item.index()].description));
+                taken = true;
+            }
+            if !taken {
+                text.push_str("Nothing taken.\n");
+            }
+            return Outcome::Done;
+        }
+
+        if noun.is_none() {
+            text.push_str("What?\n");
+        } else if !can_carry(self.carried(), max_inventory) {
+            text.push_str(self.says("I've too much to carry.\n", "You are carrying too much.\n"));
+        } else if let Some(item) = self.match_item(Location::Room(self.room)) {
+            self.move_item(Some(item), Location::Carried);
+            text.push_str("O.K.\n");
+        } else {
+            text.push_str(self.beyond_power());
+        }
+        Outcome::Done
+    }
+
+    /// Puts down the item with the autograb name the player gave, or every
+    /// such item carried for DROP ALL.
+    fn drop(&mut self, noun: Option<NounId>, text: &mut String) -> Outcome {
+        if !self.purist && self.noun.eq_ignore_ascii_case("ALL") {
+            let mut dropped = false;
+            for item in self.autograb_items(Location::Carried) {
+                let noun = self.autograb_noun(item);
+                self.perform(Some((DROP, noun)), false, text);
+                if self.runaway.is_some() {
+                    return Outcome::Done;
+                }
+                self.move_item(Some(item), Location::Room(self.room));
+                text.push_str(&format!("{}: O.K.\n", self.game.items[item.index()].description));
+                dropped = true;
+            }
+            if !dropped {
+                text.push_str("Nothing dropped.\n");
+            }
+            return Outcome::Done;
+        }
+
+        if noun.is_none() {
+            text.push_str("What?\n");
+        } else if let Some(item) = self.match_item(Location::Carried) {
+            self.move_item(Some(item), Location::Room(self.room));
+            text.push_str("O.K.\n");
+        } else {
+            text.push_str(self.beyond_power());
+        }
+        Outcome::Done
+    }
+
+    /// Lists the items at a location that can be picked up or put down by
+    /// name.
+    fn autograb_items(&self, location: Location) -> Vec<ItemId> {
+        (0..self.locations.len())
+            .filter(|&item| self.locations[item] == location)
+            .filter(|&item| self.game.items[item].autograb.is_some())
+            .map(ItemId)
+            .collect()
+    }
+
+    /// Returns the noun of an item's autograb name, if it is in the
+    /// vocabulary.
+    fn autograb_noun(&self, item: ItemId) -> Option<NounId> {
+        let name = self.game.items[item.index()].autograb.as_deref().unwrap_or("");
+        self.which_word(name, &self.game.nouns).map(NounId)
+    }
+
+    /// Finds the item at a location whose autograb name is the noun the
+    /// player gave, or one of its synonyms.
+    fn match_item(&self, location: Location) -> Option<ItemId> {
+        let noun = match self.which_word(&self.noun, &self.game.nouns) {
+            Some(index) => self.game.nouns[index].word.clone(),
+            None => self.noun.clone(),
+        };
+        self.autograb_items(location).into_iter().find(|&item| {
+            let name = self.game.items[item.index()].autograb.as_deref().unwrap_or("");
+            self.same_word(name, &noun)
+        })
+    }
+
+    /// Reports how many treasures have been stored, ending the game once
+    /// they all have.
+    fn score(&mut self) -> String {
+        let treasury = self.game.treasure_room().map(Location::Room);
+        let stored = self
+            .game
+            .items
+            .iter()
+            .zip(&self.locations)
+            .filter(|&(item, &location)| {
+                item.is_treasure && Some(location) == treasury
+            })
+            .count() as i32;
+        let total = self.game.header.num_treasures.max(1);
+        let mut text = format!(
+            "{} stored {} treasures.  On a scale of 0 to 100, that rates {}.\n",
+            self.says("I've", "You have"),
+            stored,
+            stored * 100 / total
+        );
+        if stored == self.game.header.num_treasures {
+            text.push_str("Well done.\n");
+            if self.purist {
+                text.push_str("This was a purist game, played without modern conveniences.\n");
+            }
+            text.push_str("The game is now over.\n");
+            self.finished = true;
+        }
+        text
+    }
+
+    /// Returns the item with the given number, if there is one.
+    fn item(&self, item: i32) -> Option<ItemId> {
+        ItemId::new(item, self.locations.len())
+    }
+
+    /// Returns the light source, if the game has one.
+    fn light_source(&self) -> Option<ItemId> {
+        self.item(LIGHT_SOURCE)
+    }
+
+    /// Returns the location of an item, treating an item that doesn't exist
+    /// as being in the storeroom.
+    fn location(&self, item: Option<ItemId>) -> Location {
+        item.map_or(Location::STOREROOM, |item| self.locations[item.index()])
+    }
+
+    /// Moves an item, noting when that changes what can be seen.  Items that
+    /// don't exist are ignored.
+    fn move_item(&mut self, item: Option<ItemId>, location: Location) {
+        let here = Location::Room(self.room);
+        if let Some(item) = item {
+            let current = &mut self.locations[item.index()];
+            if *current == here || location == here {
+                self.redraw = true;
+            }
+            *current = location;
+        }
+    }
+
+    /// Is the given flag set?
+    fn flag(&self, flag: FlagId) -> bool {
+        self.flags & flag.bit() != 0
+    }
+
+    /// Sets or clears the given flag.
+    fn set_flag(&mut self, flag: FlagId, set: bool) {
+        if set {
+            self.flags |= flag.bit();
+        } else {
+            self.flags &= !flag.bit();
+        }
+    }
+
+    /// Decides whether an automatic action with the given chance runs this
+    /// turn.  Those with no chance at all only ever continue other actions.
+    fn occurs(&mut self, chance: i32) -> bool {
+        let policy = if self.purist {
+            OccurrencePolicy::Percent
+        } else {
+            self.occurrence_policy
+        };
+        match policy {
+            OccurrencePolicy::Percent => self.rng.gen_range(0..100) < chance,
+            OccurrencePolicy::Always => chance > 0,
+            OccurrencePolicy::Never => false,
+        }
+    }
+
+    /// Returns the first form of a message, which speaks of the player as
+    /// "I", or the second, which speaks of them as "you", to suit the style.
+    fn says<'a>(&self, i: &'a str, you: &'a str) -> &'a str {
+        if self.style.perspective.is_you() {
+            you
+        } else {
+            i
+        }
+    }
+
+    /// Returns the message for trying to GET or DROP an item that isn't
+    /// there.
+    fn beyond_power(&self) -> &'static str {
+        self.says(
+            "It's beyond my power to do that.\n",
+            "It's beyond your power to do that.\n",
+        )
+    }
+
+    /// Returns the number of items being carried.
+    fn carried(&self) -> usize {
+        self.locations
+            .iter()
+            .filter(|&&location| is_carried(location))
+            .count()
+    }
+
+    /// Parses a command into a verb and a noun, either of which may be None
+    /// if it isn't in the vocabulary.  Only the first two words count, and a
+    /// lone N, E, S, W, U, D, or I is short for a direction or INVENTORY.  As
+    /// in the original engine, a direction can be given without GO.
+    fn parse(&self, input: &str) -> (Option<VerbId>, Option<NounId>) {
+        let mut words = input.split_whitespace();
+        let mut verb = words.next().unwrap_or("");
+        let noun = words.next().unwrap_or("");
+        if noun.is_empty() {
+            if let Some(dir) = Direction::from_name(verb) {
+                verb = dir.name();
+            } else if verb.eq_ignore_ascii_case("i") {
+                verb = "INVENTORY";
+            }
+        }
+
+        match self.which_word(verb, &self.game.nouns).map(NounId) {
+            Some(noun) if Direction::from_noun(noun).is_some() => (Some(GO), Some(noun)),
+            _ => (
+                self.which_word(verb, &self.game.verbs).map(VerbId),
+                self.which_word(noun, &self.game.nouns).map(NounId),
+            ),
+        }
+    }
+
+    /// Looks up a word in a vocabulary list, comparing only the first
+    /// `word_length` letters and ignoring case, and returns its index.  A
+    /// synonym stands for the nearest word before it that isn't a synonym.
+    /// Entry 0 is never matched.
+    fn which_word(&self, word: &str, list: &[Word]) -> Option<usize> {
+        let mut index = 1;
+        for (i, entry) in list.iter().enumerate().skip(1) {
+            if !entry.is_synonym {
+                index = i;
+            }
+            if self.same_word(&entry.word, word) {
+                return Some(index);
+            }
+        }
+        None
+    }
+
+    /// Are two words the same, comparing only the first `word_length`
+    /// letters and ignoring case?
+    fn same_word(&self, a: &str, b: &str) -> bool {
+        let length = self.game.header.word_length as usize;
+        let truncate = |word: &str| word.chars().take(length).collect::<String>().to_uppercase();
+        truncate(a) == truncate(b)
+    }
+
+    /// Moves the player in the given direction, describing the new room.
+    /// Moving in the dark is dangerous: walking into a wall is fatal.
+    fn go(&mut self, dir: Direction) -> String {
+        let dark = self.is_dark();
+        let mut text = String::new();
+        if dark {
+            text.push_str("Dangerous to move in the dark!\n");
+        }
+
+        if let Some(destination) = self.game.rooms[self.room.index()].exits[dir.index()] {
+            self.room = destination;
+            text.push_str(&self.describe_room());
+            return text;
+        }
+        self.warn(|game| {
+            format!(
+                "Room {} leads {} to room 0, which is treated as a wall",
+                game.room.index(),
+                dir
+            )
+        });
+        if dark {
+            text.push_str(self.says(
+                "I fell down and broke my neck.\n",
+                "You fell down and broke your neck.\n",
+            ));
+            self.finished = true;
+        } else {
+            text.push_str(self.says("I", "You"));
+            text.push_str(" can't go in that direction.\n");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a vocabulary list, where a leading "*" marks a synonym.
+    fn words(words: &[&str]) -> Vec<Word> {
+        words
+            .iter()
+            .map(|word| Word {
+                word: word.trim_start_matches('*').to_string(),
+                is_synonym: word.starts_with('*'),
+            })
+            .collect()
+    }
+
+    /// Builds a game with the given rooms (described by their exits and
+    /// descriptions) and items (described by their descriptions and
+    /// locations), starting in room 1.
+    fn make_game(rooms: &[([usize; 6], &str)], items: &[(&str, i32)]) -> Game {
+        Game {
+            header: Header {
+                unknown0: 0,
+                num_items: items.len() as i32,
+                num_actions: 0,
+                num_words: 8,
+                num_rooms: rooms.len() as i32,
+                max_inventory: 5,
+                starting_room: 1,
+                num_treasures: 0,
+                word_length: 3,
+                light_duration: ETERNAL_LIGHT,
+                num_messages: 0,
+                treasure_room: 0,
+            },
+            actions: vec![],
+            verbs: words(&["AUT", "GO", "*WAL", "GET", "INV"]),
+            nouns: words(&["ANY", "NOR", "SOU", "EAS", "WES", "UP", "DOW", "LAM"]),
+            rooms: rooms
+                .iter()
+                .map(|&(exits, description)| Room {
+                    description: description.trim_start_matches('*').to_string(),
+                    is_literal: description.starts_with('*'),
+                    exits: exits.map(|exit| world::exit_to(RoomId(exit))),
+                })
+                .collect(),
+            messages: vec![],
+            items: items
+                .iter()
+                .map(|&(description, location)| Item {
+                    description: description.to_string(),
+                    location: Location::new(location, rooms.len()),
+                    is_treasure: description.starts_with('*'),
+                    autograb: None,
+                })
+                .collect(),
+            footer: Footer {
+                version: 1,
+                adventure: 1,
+                magic: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_describe_room() {
+        let game = make_game(
+            &[
+                ([0; 6], ""),
+                ([2, 0, 0, 0, 0, 2], "forest"),
+                ([0; 6], "*I'm on a ledge."),
+            ],
+            &[("Trees", 1), ("*GOLD*", 1), ("Axe", 2), ("Lamp", INVENTORY)],
+        );
+        let mut interpreter = Interpreter::new(game);
+        assert_eq!(
+            interpreter.describe_room(),
+            "I'm in a forest\n\nObvious exits: North, Down.\n\nI can also see: Trees - *GOLD*\n"
+        );
+
+        assert_eq!(interpreter.inventory(), "I'm carrying:\nLamp.\n");
+
+        interpreter.room = RoomId(2);
+        assert_eq!(
+            interpreter.describe_room(),
+            "I'm on a ledge.\n\nObvious exits: none.\n\nI can also see: Axe\n"
+        );
+    }
+
+    #[test]
+    fn test_darkness() {
+        let mut items = vec![("Rock", 1); LIGHT_SOURCE as usize];
+        items.push(("Lit lamp", 0));
+        let mut interpreter =
+            Interpreter::new(make_game(&[([0; 6], ""), ([0; 6], "cave")], &items));
+        interpreter.set_flag(DARKNESS_FLAG, true);
+        assert!(interpreter.is_dark());
+        assert_eq!(
+            interpreter.describe_room(),
+            "I can't see. It is too dark!\n"
+        );
+
+        interpreter.locations[LIGHT_SOURCE as usize] = Location::Carried;
+        assert!(!interpreter.is_dark());
+        interpreter.locations[LIGHT_SOURCE as usize] = Location::Room(RoomId(1));
+        assert!(!interpreter.is_dark());
+    }
+
+    #[test]
+    fn test_command() {
+        let mut interpreter = Interpreter::new(make_game(&[([0; 6], ""), ([0; 6], "cave")], &[]));
+        assert_eq!(interpreter.command(" look "), interpreter.describe_room());
+        assert_eq!(
+            interpreter.command("xyzzy"),
+            "You use word(s) I don't know!\n"
+        );
+        assert_eq!(
+            interpreter.command("get"),
+            "I don't understand your command.\n"
+        );
+        assert!(!interpreter.is_finished());
+        interpreter.command("quit");
+        assert!(interpreter.is_finished());
+    }
+
+    #[test]
+    fn test_parse() {
+        let interpreter = Interpreter::new(make_game(&[([0; 6], "")], &[]));
+        let go = |direction| (Some(GO), Some(NounId(direction)));
+        assert_eq!(interpreter.parse("go north"), go(1));
+        assert_eq!(interpreter.parse("walk southward"), go(2));
+        assert_eq!(interpreter.parse("EAST"), go(3));
+        assert_eq!(interpreter.parse("d"), go(6));
+        assert_eq!(interpreter.parse("i"), (Some(VerbId(4)), None));
+        assert_eq!(interpreter.parse("get lamp now"), (Some(VerbId(3)), Some(NounId(7))));
+        assert_eq!(interpreter.parse("go"), (Some(GO), None));
+        assert_eq!(interpreter.parse("xyzzy lamp"), (None, Some(NounId(7))));
+        assert_eq!(interpreter.parse("any"), (None, None));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let game = make_game(&[([0; 6], ""), ([2, 0, 0, 0, 0, 0], "cave"), ([0; 6], "tunnel")], &[]);
+        let mut interpreter = Interpreter::new(game);
+        interpreter.set_aliases([("onward".to_string(), "go north".to_string())]);
+        assert_eq!(interpreter.expand_aliases("Onward  now"), "go north now");
+        interpreter.command("onward");
+        assert_eq!(interpreter.room(), RoomId(2));
+
+        interpreter.set_purist(true);
+        assert_eq!(
+            interpreter.command("onward"),
+            "You use word(s) I don't know!\n"
+        );
+    }
+
+    #[test]
+    fn test_author_mode() {
+        let mut game = make_game(&[([0; 6], ""), ([0; 6], "cave")], &[("Rock", 0)]);
+        game.actions.push(Action {
+            trigger: Trigger::Command(VerbId(3), Some(NounId(7))),
+            conditions: [
+                Condition::Parameter(0),
+                Condition::Parameter(9),
+                Condition::Parameter(0),
+                Condition::Parameter(0),
+                Condition::Parameter(0),
+            ],
+            actions: [
+                ActionType::Message(MessageId::new(3).unwrap()),
+                ActionType::GetItem,
+                ActionType::MovePlayer,
+                ActionType::Nothing,
+            ],
+            comment: None,
+        });
+        let mut interpreter = Interpreter::new(game);
+        interpreter.set_author_mode(true);
+        assert_eq!(
+            interpreter.command("get lamp"),
+            "[Warning: Action 0 prints message 3, which doesn't exist]\n\
+             [Warning: Action 0 gets Rock (item 0), which isn't here]\n\
+             [Warning: Action 0 moves the player to room 9, which doesn't exist]\n"
+        );
+        assert_eq!(
+            interpreter.command("n"),
+            "I can't go in that direction.\n\
+             [Warning: Room 1 leads North to room 0, which is treated as a wall]\n"
+        );
+
+        interpreter.set_author_mode(false);
+        assert_eq!(interpreter.command("n"), "I can't go in that direction.\n");
+    }
+
+    #[test]
+    fn test_go() {
+        let mut items = vec![("Rock", 0); LIGHT_SOURCE as usize];
+        items.push(("Lamp", 1));
+        let game = make_game(
+            &[
+                ([0; 6], ""),
+                ([2, 0, 0, 0, 0, 0], "hall"),
+                ([0, 1, 0, 0, 0, 0], "cave"),
+            ],
+            &items,
+        );
+        let mut interpreter = Interpreter::new(game);
+        assert_eq!(interpreter.command("w"), "I can't go in that direction.\n");
+        assert_eq!(interpreter.command("go"), "Give me a direction too.\n");
+        assert_eq!(interpreter.command("go north"), interpreter.describe_room());
+        assert_eq!(interpreter.room(), RoomId(2));
+
+        // Without the lamp, moving in the dark is dangerous, and walking into
+        // a wall is fatal.
+        interpreter.set_flag(DARKNESS_FLAG, true);
+        assert_eq!(
+            interpreter.command("s"),
+            "Dangerous to move in the dark!\nI'm in a hall\n\nObvious exits: North.\n\nI can also see: Lamp\n"
+        );
+        assert_eq!(interpreter.room(), RoomId(1));
+        interpreter.locations[LIGHT_SOURCE as usize] = Location::STOREROOM;
+        assert_eq!(
+            interpreter.command("east"),
+            "Dangerous to move in the dark!\nI fell down and broke my neck.\n"
+        );
+        assert!(interpreter.is_finished());
+    }
+}
@@ -0,0 +1,70 @@
+//! This module warns the author of a game, as they playtest it, about what
+//! the engine does that is more likely a mistake in the game than what was
+//! meant: an action that prints a message that doesn't exist, gets an item
+//! that isn't there, or moves the player to a room that doesn't exist, and
+//! an exit that leads to room 0, which the engine takes to be a wall.
+//!
+//! The warnings are only given in author mode.  They follow the reply to the
+//! command that led to them, one to a line, set apart in brackets:
+//!
+//! ```text
+//! I can't go in that direction.
+//! [Warning: Room 3 leads North to room 0, which is treated as a wall]
+//! ```
+
+use super::*;
+
+impl Interpreter {
+    /// Turns author mode on or off.
+    pub fn set_author_mode(&mut self, author: bool) {
+        self.author = author;
+    }
+
+    /// Returns whether the game is being played in author mode.
+    pub fn is_author_mode(&self) -> bool {
+        self.author
+    }
+
+    /// Notes a warning for the author, if the game is being played in
+    /// author mode.  The warning is only written out if it will be given.
+    pub(super) fn warn(&mut self, warning: impl FnOnce(&Interpreter) -> String) {
+        if self.author {
+            let warning = warning(self);
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Returns the warnings noted since the last were given, as lines to
+    /// follow a reply.
+    pub(super) fn take_warnings(&mut self) -> String {
+        self.warnings
+            .drain(..)
+            .map(|warning| format!("[Warning: {}]\n", warning))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warnings() {
+        let mut interpreter = Interpreter::new(crate::load_demo().unwrap());
+        interpreter.warn(|_| "Not given".to_string());
+        assert_eq!(interpreter.take_warnings(), "");
+
+        interpreter.set_author_mode(true);
+        assert!(interpreter.is_author_mode());
+        interpreter.warn(|_| "One".to_string());
+        interpreter.warn(|game| format!("Room {}", game.room.index()));
+        assert_eq!(
+            interpreter.take_warnings(),
+            format!(
+                "[Warning: One]\n[Warning: Room {}]\n",
+                interpreter.room.index()
+            )
+        );
+        assert_eq!(interpreter.take_warnings(), "");
+    }
+}
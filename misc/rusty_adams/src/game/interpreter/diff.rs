@@ -0,0 +1,161 @@
+//! This module describes how the state of a game changes from one point to
+//! another, such as between two turns, for tools like debuggers that want to
+//! show what a command did without reading the transcript.
+//!
+//! A `Snapshot` is taken of the interpreter before the commands of interest,
+//! and `Interpreter::diff_since` later compares the interpreter against it.
+//! With the `serde` feature, the resulting `StateDiff` can be serialized, for
+//! example to JSON with `serde_json`.
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use super::Interpreter;
+use crate::game::ids::NUM_FLAGS;
+use crate::game::{Location, RoomId};
+
+/// Holds a copy of the parts of the interpreter's state that `diff_since`
+/// compares.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    room: RoomId,
+    locations: Vec<Location>,
+    flags: u32,
+    counter: i32,
+    counters: Vec<i32>,
+    light_time: i32,
+}
+
+/// Lists what changed between a snapshot and the interpreter's present
+/// state.  Anything that didn't change is left out.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StateDiff {
+    /// The player's move, if they are in a different room.
+    pub room: Option<Change>,
+    /// The flags that were set or cleared.
+    pub flags: Vec<FlagChange>,
+    /// The items that are somewhere else.  Carried items are in location -1,
+    /// and those out of play in location 0.
+    pub items: Vec<ItemMove>,
+    /// The counters whose values changed.
+    pub counters: Vec<CounterChange>,
+    /// The change in the light source's turns left.
+    pub light_time: Option<Change>,
+}
+
+/// Describes a value that changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Change {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// Describes a flag that was set or cleared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FlagChange {
+    pub flag: u32,
+    /// Whether the flag is now set.
+    pub set: bool,
+}
+
+/// Describes an item that moved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ItemMove {
+    pub item: usize,
+    pub from: i32,
+    pub to: i32,
+}
+
+/// Describes a counter whose value changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CounterChange {
+    /// The number of the alternate counter, or None for the current counter,
+    /// which the counter conditions and actions work on.
+    pub counter: Option<usize>,
+    pub from: i32,
+    pub to: i32,
+    /// The difference between the two values.
+    pub delta: i32,
+}
+
+impl StateDiff {
+    /// Returns whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        *self == StateDiff::default()
+    }
+}
+
+/// Returns the change from one value to another, if they differ.
+fn change(from: i32, to: i32) -> Option<Change> {
+    (from != to).then_some(Change { from, to })
+}
+
+impl Interpreter {
+    /// Takes a snapshot of the game's state, to compare against later with
+    /// `diff_since`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            room: self.room,
+            locations: self.locations.clone(),
+            flags: self.flags,
+            counter: self.counter,
+            counters: self.counters.to_vec(),
+            light_time: self.light_time,
+        }
+    }
+
+    /// Lists what has changed since the given snapshot was taken.
+    pub fn diff_since(&self, snapshot: &Snapshot) -> StateDiff {
+        let now = self.snapshot();
+        let flags = (0..NUM_FLAGS as u32)
+            .filter(|flag| (snapshot.flags ^ now.flags) & (1 << flag) != 0)
+            .map(|flag| FlagChange {
+                flag,
+                set: now.flags & (1 << flag) != 0,
+            })
+            .collect();
+        let items = snapshot
+            .locations
+            .iter()
+            .zip(&now.locations)
+            .enumerate()
+            .filter(|(_, (from, to))| from != to)
+            .map(|(item, (from, to))| ItemMove {
+                item,
+                from: from.to_i32(),
+                to: to.to_i32(),
+            })
+            .collect();
+        let counters = [(None, snapshot.counter, now.counter)]
+            .into_iter()
+            .chain(
+                snapshot
+                    .counters
+                    .iter()
+                    .zip(&now.counters)
+                    .enumerate()
+                    .map(|(i, (&from, &to))| (Some(i), from, to)),
+            )
+            .filter(|(_, from, to)| from != to)
+            .map(|(counter, from, to)| CounterChange {
+                counter,
+                from,
+                to,
+                delta: to - from,
+            })
+            .collect();
+
+        StateDiff {
+            room: change(snapshot.room.to_i32(), now.room.to_i32()),
+            flags,
+            items,
+            counters,
+            light_time: change(snapshot.light_time, now.light_time),
+        }
+    }
+}
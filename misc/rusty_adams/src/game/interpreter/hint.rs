@@ -0,0 +1,239 @@
+//! This module gives the player hints, made up on the spot from the game's
+//! own actions rather than written by its author.
+//!
+//! A hint is about an action that the player could take right now: one for
+//! a command whose conditions are all met, and which depends on where the
+//! player is or what is at hand, so that HELP and SCORE are never suggested.
+//! Actions that change something are preferred to those that only print a
+//! message, and those that kill the player or end the game are never hinted
+//! at.  Asking again about the same action gives a more specific hint each
+//! time, up to the level that has been allowed:
+//!
+//! ```text
+//! Maybe something here could be useful.
+//! The word "LOO" might help here.
+//! Try "LOO MAT".
+//! ```
+
+use super::*;
+
+/// How much a hint may give away.  Each level allows the hints of those
+/// before it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    /// There are no hints.
+    Off,
+    /// Hints only say whether something here or something carried is
+    /// useful.
+    Nudge,
+    /// Hints may go on to name the verb.
+    Verb,
+    /// Hints may go on to give the whole command.
+    #[default]
+    Full,
+}
+
+impl HintLevel {
+    /// Looks up a level by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(HintLevel::Off),
+            "nudge" => Some(HintLevel::Nudge),
+            "verb" => Some(HintLevel::Verb),
+            "full" => Some(HintLevel::Full),
+            _ => None,
+        }
+    }
+
+    /// Returns the level after this one.
+    fn next(self) -> Self {
+        match self {
+            HintLevel::Off => HintLevel::Nudge,
+            HintLevel::Nudge => HintLevel::Verb,
+            HintLevel::Verb | HintLevel::Full => HintLevel::Full,
+        }
+    }
+}
+
+impl Interpreter {
+    /// Changes how much the HINT command may give away.  Hints are a
+    /// convenience, so there are none in purist mode.
+    pub fn set_hint_level(&mut self, level: HintLevel) {
+        self.hint_level = level;
+    }
+
+    /// Returns a hint about what to do next, more specific than the last if
+    /// it is about the same action, or None if hints are off.
+    pub(super) fn hint(&mut self) -> Option<String> {
+        if self.purist || self.hint_level == HintLevel::Off {
+            return None;
+        }
+        let Some(action) = self.hint_action() else {
+            self.last_hint = None;
+            return Some("Nothing here seems to need doing just now.\n".to_string());
+        };
+        let level = match self.last_hint {
+            Some((last, level)) if last == action => level.next().min(self.hint_level),
+            _ => HintLevel::Nudge,
+        };
+        self.last_hint = Some((action, level));
+
+        let Trigger::Command(verb, noun) = self.game.actions[action].trigger else {
+            unreachable!("only commands are hinted at");
+        };
+        let verb = &self.game.verbs[verb.index()].word;
+        Some(match (level, noun) {
+            (HintLevel::Off | HintLevel::Nudge, _) => {
+                let carried = self.game.actions[action]
+                    .conditions
+                    .iter()
+                    .any(|condition| matches!(condition, Condition::ItemCarried(_)));
+                if carried {
+                    self.says(
+                        "Maybe something I'm carrying could be useful here.\n",
+                        "Maybe something you're carrying could be useful here.\n",
+                    )
+                    .to_string()
+                } else {
+                    "Maybe something here could be useful.\n".to_string()
+                }
+            }
+            (HintLevel::Verb, Some(_)) => format!("The word \"{}\" might help here.\n", verb),
+            (_, Some(noun)) => {
+                format!("Try \"{} {}\".\n", verb, self.game.nouns[noun.index()].word)
+            }
+            (_, None) => format!("Try \"{}\".\n", verb),
+        })
+    }
+
+    /// Returns the action to hint at, if there is one: the first that could
+    /// be taken now and changes something, or failing that the first that
+    /// could be taken now at all.
+    fn hint_action(&self) -> Option<usize> {
+        let candidates: Vec<usize> = (0..self.game.actions.len())
+            .filter(|&i| self.could_take(i))
+            .collect();
+        candidates
+            .iter()
+            .find(|&&i| changes_something(&self.game.actions[i]))
+            .or(candidates.first())
+            .copied()
+    }
+
+    /// Could the player take the action at the given index now, and is it
+    /// worth a hint?
+    fn could_take(&self, index: usize) -> bool {
+        let action = &self.game.actions[index];
+        let Trigger::Command(verb, noun) = action.trigger else {
+            return false;
+        };
+        // Going in a direction is what the exits are for.
+        let direction = noun.and_then(Direction::from_noun).is_some();
+        let fatal = action
+            .actions
+            .iter()
+            .any(|action| matches!(action, ActionType::Death | ActionType::GameOver));
+        let local = action.conditions.iter().any(|condition| {
+            matches!(
+                condition,
+                Condition::ItemCarried(_)
+                    | Condition::ItemInRoom(_)
+                    | Condition::ItemPresent(_)
+                    | Condition::PlayerInRoom(_)
+            )
+        });
+        if verb == GO && direction || fatal || !local {
+            return false;
+        }
+        action
+            .conditions
+            .iter()
+            .all(|condition| self.check(condition))
+    }
+}
+
+/// Does the action do more than show the player something?
+fn changes_something(action: &Action) -> bool {
+    action.actions.iter().any(|action| {
+        !matches!(
+            action,
+            ActionType::Nothing
+                | ActionType::Message(_)
+                | ActionType::DescribeRoom(_)
+                | ActionType::Score
+                | ActionType::Inventory
+                | ActionType::ClearScreen
+                | ActionType::Continue
+                | ActionType::PrintCounter
+                | ActionType::EchoNoun
+                | ActionType::EchoNounCR
+                | ActionType::EchoCR
+                | ActionType::Delay
+                | ActionType::DrawPicture
+                | ActionType::Invalid(_)
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demo() -> Interpreter {
+        let mut interpreter = Interpreter::new(crate::load_demo().unwrap());
+        interpreter.set_occurrence_policy(OccurrencePolicy::Never);
+        interpreter.start();
+        interpreter
+    }
+
+    #[test]
+    fn test_hints() {
+        let mut interpreter = demo();
+        interpreter.command("n");
+        assert_eq!(
+            interpreter.command("hint"),
+            "Maybe something here could be useful.\n"
+        );
+        assert_eq!(
+            interpreter.command("hint"),
+            "The word \"LOO\" might help here.\n"
+        );
+        assert_eq!(interpreter.command("hint"), "Try \"LOO MAT\".\n");
+        assert_eq!(interpreter.command("hint"), "Try \"LOO MAT\".\n");
+
+        // Once the key has been found, the hints start over about the door.
+        interpreter.command("get mat");
+        interpreter.command("get key");
+        assert_eq!(
+            interpreter.command("hint"),
+            "Maybe something I'm carrying could be useful here.\n"
+        );
+        interpreter.command("hint");
+        assert_eq!(interpreter.command("hint"), "Try \"OPE DOO\".\n");
+    }
+
+    #[test]
+    fn test_hint_levels() {
+        let mut interpreter = demo();
+        interpreter.command("n");
+        interpreter.set_hint_level(HintLevel::Verb);
+        interpreter.command("hint");
+        interpreter.command("hint");
+        assert_eq!(
+            interpreter.command("hint"),
+            "The word \"LOO\" might help here.\n"
+        );
+
+        interpreter.set_hint_level(HintLevel::Off);
+        assert_eq!(
+            interpreter.command("hint"),
+            "You use word(s) I don't know!\n"
+        );
+        interpreter.set_hint_level(HintLevel::Full);
+        interpreter.set_purist(true);
+        assert_eq!(
+            interpreter.command("hint"),
+            "You use word(s) I don't know!\n"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! This module saves the state of a game in progress and restores it again.
+//!
+//! A save starts with a line giving the version of the format, followed by
+//! the numbers of a ScottFree save file and then any fields that later
+//! versions added, one to a line, each a name followed by its value:
+//!
+//! ```text
+//! rusty_adams save 3
+//! 0 0
+//! ...
+//! turns 12
+//! timers 0 2
+//! rng 5b0e...c3 1024
+//! ```
+//!
+//! Saves from every earlier version can be restored, including ScottFree's
+//! own, which have no version line and count as version 0.  A save is read
+//! as the version that wrote it and then migrated forward, one version at a
+//! time, with each field that the save is too old to have given the value
+//! that a new game starts with.  Saves from later versions than this one are
+//! refused, since there would be no telling what their fields mean.
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// The start of the first line of a save, which goes on to give the version.
+pub const SAVE_HEADER: &str = "rusty_adams save";
+
+/// The version of the format that `save` writes.
+///
+/// 0. ScottFree's save file.
+/// 1. Adds the version line and the named fields that follow the numbers.
+/// 2. Adds `turns`, the number of turns gone by, and `timers`, the timers
+///    that have gone off.
+/// 3. Adds `rng`, the state of the dice that decide whether the occasional
+///    automatic actions happen: the seed in hexadecimal, and how far through
+///    its stream the generator has gone.
+pub const SAVE_VERSION: u32 = 3;
+
+/// The location of a carried item in a saved game, where locations are
+/// stored as unsigned bytes.
+const SAVED_INVENTORY: i32 = 255;
+
+/// Holds what a save records, before it is checked against the game.
+struct Saved {
+    /// The version of the format that wrote the save.
+    version: u32,
+    /// The numbers that make up a ScottFree save.
+    numbers: Vec<i64>,
+    /// The named fields that follow the numbers, by name.
+    fields: BTreeMap<String, String>,
+}
+
+impl Saved {
+    /// Splits a save into its version, its numbers and its fields.
+    fn parse(text: &str) -> Result<Saved, String> {
+        let (version, body) = match text.strip_prefix(SAVE_HEADER) {
+            Some(rest) => {
+                let (line, body) = rest.split_once('\n').unwrap_or((rest, ""));
+                let version = line.trim().parse::<u32>().map_err(|_| {
+                    format!("The saved game has an unknown version '{}'", line.trim())
+                })?;
+                (version, body)
+            }
+            None => (0, text),
+        };
+        if version > SAVE_VERSION {
+            return Err(format!(
+                "The saved game is from a newer version of rusty_adams (format {})",
+                version
+            ));
+        }
+
+        let mut numbers = vec![];
+        let mut fields = BTreeMap::new();
+        for line in body.lines() {
+            match line.trim_start().chars().next() {
+                Some(ch) if ch.is_ascii_alphabetic() && version > 0 => {
+                    let (name, value) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+                    fields.insert(name.to_string(), value.trim().to_string());
+                }
+                _ => {
+                    for word in line.split_whitespace() {
+                        numbers.push(word.parse::<i64>().map_err(|_| {
+                            format!("The saved game has '{}' where a number should be", word)
+                        })?);
+                    }
+                }
+            }
+        }
+        Ok(Saved {
+            version,
+            numbers,
+            fields,
+        })
+    }
+
+    /// Brings a save from an older version up to the present one.  Each step
+    /// gives the fields that the next version added the values that a new
+    /// game starts with.
+    fn migrate(&mut self) {
+        while self.version < SAVE_VERSION {
+            match self.version {
+                // Version 1 added no fields of its own, only the means of
+                // having them.
+                0 => {}
+                1 => {
+                    self.fields.insert("turns".to_string(), "0".to_string());
+                    self.fields.insert("timers".to_string(), String::new());
+                }
+                // With no state to restore, the dice are left as they are.
+                2 => {
+                    self.fields.insert("rng".to_string(), String::new());
+                }
+                _ => unreachable!("every older version migrates"),
+            }
+            self.version += 1;
+        }
+    }
+
+    /// Takes the field with the given name, which every save has once it
+    /// has been migrated, and parses each of the words of its value.
+    fn take<T: std::str::FromStr>(&mut self, name: &str) -> Result<Vec<T>, String> {
+        let value = self
+            .fields
+            .remove(name)
+            .ok_or_else(|| format!("The saved game has no field '{}'", name))?;
+        value
+            .split_whitespace()
+            .map(|word| {
+                word.parse()
+                    .map_err(|_| format!("The saved game has '{}' in the field '{}'", word, name))
+            })
+            .collect()
+    }
+}
+
+impl Interpreter {
+    /// Returns the game's state as a save in the present version of the
+    /// format.  The numbers are those of a ScottFree save file: the counters
+    /// and saved rooms in pairs, then the flags, the darkness flag, the
+    /// player's room, the current counter, the saved room, and the light
+    /// source's turns left, and finally the location of each item.  The
+    /// fields that follow them are those of the latest version.
+    pub fn save(&self) -> String {
+        let mut text = format!("{} {}\n", SAVE_HEADER, SAVE_VERSION);
+        for (counter, room) in self.counters.iter().zip(&self.saved_rooms) {
+            text.push_str(&format!("{} {}\n", counter, room.to_i32()));
+        }
+        text.push_str(&format!(
+            "{} {} {} {} {} {}\n",
+            self.flags,
+            self.flag(DARKNESS_FLAG) as u8,
+            self.room.to_i32(),
+            self.counter,
+            self.saved_room.to_i32(),
+            self.light_time
+        ));
+        for &location in &self.locations {
+            let location = if is_carried(location) {
+                SAVED_INVENTORY
+            } else {
+                location.to_i32()
+            };
+            text.push_str(&format!("{}\n", location));
+        }
+        text.push_str(&format!("turns {}\ntimers", self.turns));
+        for i in (0..self.fired.len()).filter(|&i| self.fired[i]) {
+            text.push_str(&format!(" {}", i));
+        }
+        text.push_str("\nrng ");
+        for byte in self.rng.get_seed() {
+            text.push_str(&format!("{:02x}", byte));
+        }
+        text.push_str(&format!(" {}\n", self.rng.get_word_pos()));
+        text
+    }
+
+    /// Restores the game's state from a save written by `save`, by an
+    /// earlier version of it, or by ScottFree.  The state is unchanged if the
+    /// save is invalid.
+    ///
+    /// ```
+    /// let fixture = rusty_adams::fixtures::find("get_drop").unwrap();
+    /// let mut interpreter = fixture.interpreter()?;
+    /// interpreter.start();
+    /// interpreter.command("get key");
+    /// let save = interpreter.save();
+    ///
+    /// let mut restored = fixture.interpreter()?;
+    /// restored.restore(&save)?;
+    /// assert_eq!(restored.inventory(), "I'm carrying:\nBrass key.\n");
+    /// assert!(restored.restore("rusty_adams save 99\n").is_err());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn restore(&mut self, text: &str) -> Result<(), String> {
+        let mut saved = Saved::parse(text)?;
+        saved.migrate();
+        let turns = match saved.take::<u32>("turns")?[..] {
+            [turns] => turns,
+            _ => return Err("The saved game has no number of turns".to_string()),
+        };
+        // Timers that the game no longer has are ignored.
+        let mut fired = vec![false; self.timers.len()];
+        for i in saved.take::<usize>("timers")? {
+            if let Some(fired) = fired.get_mut(i) {
+                *fired = true;
+            }
+        }
+        let rng = match saved.take::<String>("rng")?[..] {
+            [] => None,
+            [ref seed, ref pos] => Some(parse_rng(seed, pos)?),
+            _ => return Err("The saved game has no state for the dice".to_string()),
+        };
+        if let Some(name) = saved.fields.keys().next() {
+            return Err(format!("The saved game has an unknown field '{}'", name));
+        }
+
+        let mut numbers = saved.numbers.into_iter();
+        let mut next = || {
+            numbers
+                .next()
+                .ok_or_else(|| "The saved game is too short".to_string())
+        };
+
+        let rooms = self.game.rooms.len();
+        let saved_room = |room: i64| {
+            RoomId::new(room as i32, rooms)
+                .ok_or_else(|| format!("The saved game has room {}, which doesn't exist", room))
+        };
+
+        let mut counters = [0; NUM_COUNTERS];
+        let mut saved_rooms = [RoomId::STOREROOM; NUM_COUNTERS];
+        for i in 0..NUM_COUNTERS {
+            counters[i] = next()? as i32;
+            saved_rooms[i] = saved_room(next()?)?;
+        }
+        let mut flags = next()? as u32;
+        if next()? != 0 {
+            flags |= DARKNESS_FLAG.bit();
+        }
+        let room = next()?;
+        let room = RoomId::new(room as i32, rooms)
+            .ok_or_else(|| format!("The saved game is in room {}, which doesn't exist", room))?;
+        let counter = next()? as i32;
+        let saved_room = saved_room(next()?)?;
+        let light_time = next()? as i32;
+        let mut locations = vec![];
+        for _ in 0..self.locations.len() {
+            locations.push(match next()? as i32 {
+                SAVED_INVENTORY => Location::Carried,
+                location => Location::new(location, rooms),
+            });
+        }
+
+        self.counters = counters;
+        self.saved_rooms = saved_rooms;
+        self.flags = flags;
+        self.room = room;
+        self.counter = counter;
+        self.saved_room = saved_room;
+        self.light_time = light_time;
+        self.locations = locations;
+        self.turns = turns;
+        self.fired = fired;
+        if let Some(rng) = rng {
+            self.rng = rng;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the generator with the given seed, in hexadecimal, that has gone
+/// the given number of words through its stream.
+fn parse_rng(seed: &str, pos: &str) -> Result<ChaCha12Rng, String> {
+    let invalid = || "The saved game has an invalid state for the dice".to_string();
+    let mut bytes = [0u8; 32];
+    if seed.len() != 2 * bytes.len() || !seed.is_ascii() {
+        return Err(invalid());
+    }
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&seed[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+    }
+    let mut rng = ChaCha12Rng::from_seed(bytes);
+    rng.set_word_pos(pos.parse().map_err(|_| invalid())?);
+    Ok(rng)
+}
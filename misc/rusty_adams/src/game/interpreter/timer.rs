@@ -0,0 +1,71 @@
+//! This module lets a game set and clear flags once enough turns or enough
+//! real time have gone by, for newly written games that want timed events
+//! beyond what the light source's counter allows.  It is an extension: the
+//! classic games know nothing of it, and a game only has timers if its
+//! sidecar gives them.
+//!
+//! Each timer goes off once, at the start of the first turn's automatic
+//! actions after it is due, so that those actions can respond to its flag
+//! on the same turn.  Turns are counted as the light source counts them,
+//! from the player's commands that take a turn.  The interpreter doesn't
+//! read the time itself: the frontend gives it a clock that says how long
+//! the game has been played, which a test can stand in for.
+
+use std::time::Duration;
+
+use super::*;
+
+/// Says when a timer goes off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum When {
+    /// Once the given number of turns have gone by.
+    Turns(u32),
+    /// Once the game has been played for the given time.
+    Elapsed(Duration),
+}
+
+/// Sets or clears a flag when it goes off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timer {
+    /// The flag to change.
+    pub flag: FlagId,
+    /// Whether to set the flag or to clear it.
+    pub set: bool,
+    /// When to change it.
+    pub when: When,
+}
+
+/// Says how long a game has been played.
+pub type Clock = Box<dyn Fn() -> Duration>;
+
+impl Interpreter {
+    /// Gives the game the given timers, none of which has gone off yet, and
+    /// the clock that timers for real time go by.
+    pub fn set_timers(&mut self, timers: Vec<Timer>, clock: Clock) {
+        self.fired = vec![false; timers.len()];
+        self.timers = timers;
+        self.clock = Some(clock);
+    }
+
+    /// Returns the number of turns that have gone by.
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    /// Sets or clears the flags of the timers that are due and haven't yet
+    /// gone off.
+    pub(super) fn run_timers(&mut self) {
+        let elapsed = self.clock.as_ref().map_or(Duration::ZERO, |clock| clock());
+        for i in 0..self.timers.len() {
+            let timer = self.timers[i];
+            let due = match timer.when {
+                When::Turns(turns) => self.turns >= turns,
+                When::Elapsed(time) => elapsed >= time,
+            };
+            if due && !self.fired[i] {
+                self.fired[i] = true;
+                self.set_flag(timer.flag, timer.set);
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! This module edits the map of a game, which is made up of the exits from
+//! each room, and checks it for the mistakes that authors commonly make.
+//!
+//! Most passages can be walked both ways, so an exit from A north to B is
+//! usually matched by one from B south to A.  `link_rooms` can set both at
+//! once, and `asymmetric_exits` warns about exits that have no match.  Some
+//! of those are deliberate, such as a slide that can't be climbed back up or
+//! a maze, so they are warnings rather than errors.
+
+use super::world::exit_to;
+use super::*;
+
+/// Returns the room that an exit leads to, or None if the room doesn't
+/// exist or there is no exit that way.
+pub fn exit(game: &Game, room: usize, dir: Direction) -> Option<usize> {
+    game.rooms.get(room)?.exits[dir.index()].map(RoomId::index)
+}
+
+/// Makes the exit from room `a` in the given direction lead to room `b`, and
+/// if `bidirectional` is set, makes the opposite exit from `b` lead back to
+/// `a`.  A one-way link to the storeroom removes the exit.
+pub fn link_rooms(
+    game: &mut Game,
+    a: usize,
+    dir: Direction,
+    b: usize,
+    bidirectional: bool,
+) -> Result<(), String> {
+    for room in [a, b] {
+        if room >= game.rooms.len() {
+            return Err(format!("There is no room {}", room));
+        }
+    }
+    let storeroom = RoomId::STOREROOM.index();
+    if a == storeroom || (bidirectional && b == storeroom) {
+        return Err("The storeroom can't have exits".to_string());
+    }
+    game.rooms[a].exits[dir.index()] = exit_to(RoomId(b));
+    if bidirectional {
+        game.rooms[b].exits[dir.opposite().index()] = exit_to(RoomId(a));
+    }
+    Ok(())
+}
+
+/// Returns a warning for each exit that leads to a room with no exit back
+/// the opposite way.
+pub fn asymmetric_exits(game: &Game) -> Vec<String> {
+    let mut warnings = vec![];
+    for a in 1..game.rooms.len() {
+        for dir in Direction::ALL {
+            let Some(b) = exit(game, a, dir) else {
+                continue;
+            };
+            let back = dir.opposite();
+            if exit(game, b, back) != Some(a) {
+                warnings.push(format!(
+                    "Room {} leads {} to room {}, which doesn't lead {} back to it",
+                    a,
+                    dir.name(),
+                    b,
+                    back.name()
+                ));
+            }
+        }
+    }
+    warnings
+}
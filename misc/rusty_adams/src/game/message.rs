@@ -0,0 +1,81 @@
+//! This module hides the odd numbering of messages within actions.
+//!
+//! An action prints a message by using its number as the action type, but
+//! action types 52 to 101 were already taken by the other subactions.  So
+//! messages 1 to 51 are printed by types 1 to 51, while messages 52 to 99 are
+//! printed by types 102 to 149.  (Message 0 is always empty and is never
+//! printed.)
+
+use std::fmt::{Display, Error, Formatter};
+
+/// The highest action type that prints a message.
+const MAX_CODE: i32 = 149;
+
+/// Refers to one of the game's messages by its index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MessageId(i32);
+
+impl MessageId {
+    /// Returns a reference to the message with the given index, if an action
+    /// is able to print it.
+    pub fn new(index: i32) -> Option<MessageId> {
+        match index {
+            1..=99 => Some(MessageId(index)),
+            _ => None,
+        }
+    }
+
+    /// Returns the message printed by the given action type, if it is one
+    /// that prints a message.
+    pub fn from_code(code: i32) -> Option<MessageId> {
+        match code {
+            1..=51 => Some(MessageId(code)),
+            102..=MAX_CODE => Some(MessageId(code - 50)),
+            _ => None,
+        }
+    }
+
+    /// Returns the action type that prints this message.
+    pub fn code(&self) -> i32 {
+        match self.0 {
+            1..=51 => self.0,
+            _ => self.0 + 50,
+        }
+    }
+
+    /// Returns the index of this message within the game's messages.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Display for MessageId {
+    /// Shows the message index as it would appear in a listing.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "message {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(MessageId::from_code(1), MessageId::new(1));
+        assert_eq!(MessageId::from_code(51), MessageId::new(51));
+        assert_eq!(MessageId::from_code(102), MessageId::new(52));
+        assert_eq!(MessageId::from_code(149), MessageId::new(99));
+        for code in [0, 52, 101, 150] {
+            assert_eq!(MessageId::from_code(code), None);
+        }
+        for index in [0, 100, -1] {
+            assert_eq!(MessageId::new(index), None);
+        }
+        for index in 1..=99 {
+            let message = MessageId::new(index).unwrap();
+            assert_eq!(MessageId::from_code(message.code()), Some(message));
+            assert_eq!(message.index(), index as usize);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! This module measures a game's design: how its actions use its vocabulary,
+//! how long its messages run, and the shape of its map.  The `corpus` module
+//! adds the measures up across a directory of games.
+//!
+//! Rooms are reachable if the player can walk to them from the starting room
+//! by the exits alone.  Rooms that only an action can take the player to,
+//! such as by a magic word, are counted as unreachable, which is usually
+//! what is interesting about them.
+
+use super::map;
+use super::*;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Holds the measures of one game.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Metrics {
+    /// The number of actions of every kind.
+    pub actions: usize,
+    /// The number of automatic actions, which run without a command.
+    pub occurrences: usize,
+    /// The number of verbs, not counting synonyms or verb 0.
+    pub verbs: usize,
+    /// The number of nouns, not counting synonyms or noun 0.
+    pub nouns: usize,
+    /// The number of verbs that at least one action is for.
+    pub verbs_used: usize,
+    /// The average number of actions for each verb that has any.
+    pub actions_per_verb: f64,
+    /// How many actions are for each verb, by the verb's word.  Verbs with
+    /// no actions are left out.
+    pub verb_usage: Vec<WordUsage>,
+    /// How many actions are for each noun, by the noun's word.  Actions for
+    /// any noun at all are left out, as are nouns with no actions.
+    pub noun_usage: Vec<WordUsage>,
+    /// The number of messages that aren't empty.
+    pub messages: usize,
+    /// The average length of those messages, in characters.
+    pub message_length: f64,
+    /// The length of the longest message, in characters.
+    pub longest_message: usize,
+    /// The number of rooms, not counting the storeroom.
+    pub rooms: usize,
+    /// The number of exits between rooms.
+    pub exits: usize,
+    /// The number of exits that have no exit back the opposite way.
+    pub one_way_exits: usize,
+    /// The number of rooms with no exits at all.
+    pub dead_ends: usize,
+    /// The number of rooms that can't be walked to from the starting room.
+    pub unreachable: usize,
+}
+
+/// Holds how many actions are for one word.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WordUsage {
+    /// The word, in capitals.
+    pub word: String,
+    /// The number of actions for it.
+    pub actions: usize,
+}
+
+/// Measures a game.
+pub fn measure(game: &Game) -> Metrics {
+    let mut verb_actions = vec![0; game.verbs.len()];
+    let mut noun_actions = vec![0; game.nouns.len()];
+    let mut occurrences = 0;
+    for action in &game.actions {
+        match action.trigger {
+            Trigger::Occurrence(_) => occurrences += 1,
+            Trigger::Command(verb, noun) => {
+                verb_actions[verb.index()] += 1;
+                if let Some(noun) = noun {
+                    noun_actions[noun.index()] += 1;
+                }
+            }
+        }
+    }
+    let verb_groups = counted_groups(game.verb_groups());
+    let noun_groups = counted_groups(game.noun_groups());
+    let verb_usage = usage(&verb_groups, &verb_actions);
+    let noun_usage = usage(&noun_groups, &noun_actions);
+    let command_actions: usize = verb_usage.iter().map(|usage| usage.actions).sum();
+
+    let lengths: Vec<usize> = game
+        .messages
+        .iter()
+        .map(|message| message.chars().count())
+        .filter(|&length| length > 0)
+        .collect();
+
+    let (exits, one_way_exits, dead_ends) = count_exits(game);
+    let rooms = game.rooms.len().saturating_sub(1);
+    Metrics {
+        actions: game.actions.len(),
+        occurrences,
+        verbs: verb_groups.len(),
+        nouns: noun_groups.len(),
+        verbs_used: verb_usage.len(),
+        actions_per_verb: mean(command_actions, verb_usage.len()),
+        verb_usage,
+        noun_usage,
+        messages: lengths.len(),
+        message_length: mean(lengths.iter().sum(), lengths.len()),
+        longest_message: lengths.iter().copied().max().unwrap_or(0),
+        rooms,
+        exits,
+        one_way_exits,
+        dead_ends,
+        unreachable: rooms - reachable(game),
+    }
+}
+
+/// Leaves out word 0, which stands for the automatic actions or for any
+/// noun rather than for a word of its own.
+fn counted_groups(groups: Vec<WordGroup>) -> Vec<WordGroup> {
+    groups.into_iter().filter(|group| group.index > 0).collect()
+}
+
+/// Lists the words with any actions, along with how many they have.
+fn usage(groups: &[WordGroup], actions: &[usize]) -> Vec<WordUsage> {
+    groups
+        .iter()
+        .filter(|group| actions[group.index] > 0)
+        .map(|group| WordUsage {
+            word: group.word.to_uppercase(),
+            actions: actions[group.index],
+        })
+        .collect()
+}
+
+/// Returns the number of exits, how many of them have no way back, and the
+/// number of rooms with no exits.
+fn count_exits(game: &Game) -> (usize, usize, usize) {
+    let (mut exits, mut one_way, mut dead_ends) = (0, 0, 0);
+    for room in 1..game.rooms.len() {
+        let mut any = false;
+        for dir in Direction::ALL {
+            if let Some(to) = map::exit(game, room, dir) {
+                any = true;
+                exits += 1;
+                if map::exit(game, to, dir.opposite()) != Some(room) {
+                    one_way += 1;
+                }
+            }
+        }
+        if !any {
+            dead_ends += 1;
+        }
+    }
+    (exits, one_way, dead_ends)
+}
+
+/// Returns the number of rooms that can be walked to from the starting room,
+/// counting the starting room itself.
+fn reachable(game: &Game) -> usize {
+    let Some(start) = game
+        .starting_room()
+        .filter(|&room| room != RoomId::STOREROOM)
+    else {
+        return 0;
+    };
+    let mut seen = vec![false; game.rooms.len()];
+    let mut queue = VecDeque::from([start.index()]);
+    seen[start.index()] = true;
+    while let Some(room) = queue.pop_front() {
+        for dir in Direction::ALL {
+            if let Some(to) = map::exit(game, room, dir) {
+                if !seen[to] {
+                    seen[to] = true;
+                    queue.push_back(to);
+                }
+            }
+        }
+    }
+    seen.iter().filter(|&&seen| seen).count()
+}
+
+/// Returns the average of some values from their total and their number, or
+/// 0 if there are none.
+fn mean(total: usize, count: usize) -> f64 {
+    match count {
+        0 => 0.0,
+        _ => total as f64 / count as f64,
+    }
+}
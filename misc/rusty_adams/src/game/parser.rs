@@ -5,33 +5,106 @@ use regex::Regex;
 use std::fmt::{Display, Error, Formatter};
 
 use super::*;
-use crate::tokenizer::Stream;
+use crate::tokenizer::{self, Stream, Token};
 
 /// Initializes a new Game structure from a stream of tokens.
 pub fn parse_game(stream: &mut Stream) -> Result<Game, ParseError> {
-    let header = parse_header(stream)?;
-    let mut actions = parse_actions(stream, header.num_actions)?;
-    let words = parse_words(stream, header.num_words)?;
-    let rooms = parse_rooms(stream, header.num_rooms)?;
-    let messages = parse_messages(stream, header.num_messages)?;
-    let items: Vec<Item> = parse_items(stream, header.num_items)?;
-    parse_comments(stream, &mut actions)?;
-    let footer = parse_footer(stream)?;
-
-    Ok(Game {
-        header,
-        actions,
-        verbs: words.0,
-        nouns: words.1,
-        rooms,
-        messages,
-        items,
-        footer,
-    })
+    parse_sections(stream, &mut PartialGame::nothing(String::new()))
+}
+
+/// Parses a game as `parse_game` does, but on an error returns the sections
+/// that were parsed before it, so that something can be made of a damaged
+/// game file.
+pub fn parse_game_partial(stream: &mut Stream) -> Result<Game, Box<PartialGame>> {
+    let mut partial = PartialGame::nothing(String::new());
+    match parse_sections(stream, &mut partial) {
+        Ok(game) => Ok(game),
+        Err(error) => {
+            partial.set_error(error.to_string());
+            Err(Box::new(partial))
+        }
+    }
+}
+
+/// Parses each section of the game in turn, keeping it in the partial game
+/// as soon as it has been parsed.
+fn parse_sections(stream: &mut Stream, partial: &mut PartialGame) -> Result<Game, ParseError> {
+    macro_rules! section {
+        ($section:expr, $parse:expr) => {
+            $parse.map_err(|kind| ParseError { section: $section, kind })?
+        };
+    }
+
+    let header = section!(Section::Header, timed!("header", read_header(stream)));
+    let counts = header.counts();
+    partial.header = Some(header);
+    partial.actions = Some(section!(Section::Actions, timed!("actions", parse_actions(stream, counts.actions, counts.words))));
+    partial.words = Some(section!(Section::Words, timed!("words", parse_words(stream, counts.words))));
+    partial.rooms = Some(section!(Section::Rooms, timed!("rooms", parse_rooms(stream, counts.rooms))));
+    partial.messages = Some(section!(Section::Messages, timed!("messages", parse_messages(stream, counts.messages))));
+    section!(Section::Actions, check_messages(partial.actions.as_ref().unwrap(),
+                                              partial.messages.as_ref().unwrap()));
+    partial.items = Some(section!(Section::Items, timed!("items", parse_items(stream, counts.items, counts.rooms))));
+    section!(Section::Comments, timed!("comments", parse_comments(stream, partial.actions.as_mut().unwrap())));
+    let footer = section!(Section::Footer, timed!("footer", parse_footer(stream)));
+
+    // The sections move out of the partial game, which is of no more use.
+    match std::mem::replace(partial, PartialGame::nothing(String::new())) {
+        PartialGame {
+            header: Some(header),
+            actions: Some(actions),
+            words: Some((verbs, nouns)),
+            rooms: Some(rooms),
+            messages: Some(messages),
+            items: Some(items),
+            ..
+        } => Ok(Game { header, actions, verbs, nouns, rooms, messages, items, footer }),
+        _ => unreachable!("every section has been parsed"),
+    }
+}
+
+/// Parses a game as `parse_game` does, and then cross-checks the parts of the
+/// game file that the interpreter otherwise takes on trust.  Each problem found
+/// is returned as a warning rather than an error, since the game may well be
+/// playable anyway.
+pub fn parse_game_strict(stream: &mut Stream) -> Result<(Game, Vec<String>), ParseError> {
+    let game = parse_game(stream)?;
+    let mut warnings = vec![];
+
+    let treasures = game.treasures();
+    if treasures != game.header.num_treasures as usize {
+        warnings.push(format!(
+            "The header says there are {} treasures, but {} items are marked as treasures",
+            game.header.num_treasures, treasures
+        ));
+    }
+    for (name, room) in [
+        ("starting room", game.header.starting_room),
+        ("treasure room", game.header.treasure_room),
+    ] {
+        if room < 0 || room >= game.header.num_rooms {
+            warnings.push(format!("The {} is {}, which doesn't exist", name, room));
+        }
+    }
+
+    let mut extra = 0;
+    while stream.next_token().is_some() {
+        extra += 1;
+    }
+    if extra > 0 {
+        let tokens = if extra == 1 { "token" } else { "tokens" };
+        warnings.push(format!("Found {} {} after the footer", extra, tokens));
+    }
+    Ok((game, warnings))
 }
 
 /// Parses the header of the game file.
-fn parse_header(stream: &mut Stream) -> Result<Header, ParseError> {
+pub fn parse_header(stream: &mut Stream) -> Result<Header, ParseError> {
+    read_header(stream).map_err(|kind| ParseError { section: Section::Header, kind })
+}
+
+/// Reads the header of the game file, leaving the section to the caller.
+fn read_header(stream: &mut Stream) -> Result<Header, ParseErrorKind> {
     Ok(Header {
         unknown0: _read_int(stream)?,
         num_items: _read_int(stream)? + 1, // adjust for option base 0
@@ -48,11 +121,12 @@ fn parse_header(stream: &mut Stream) -> Result<Header, ParseError> {
     })
 }
 
-/// Parses all of the actions from the game file.
-fn parse_actions(stream: &mut Stream, num_actions: i32) -> Result<Vec<Action>, ParseError> {
+/// Parses all of the actions from the game file, checking that their verbs
+/// and nouns are among the given number of words.
+fn parse_actions(stream: &mut Stream, num_actions: i32, num_words: i32) -> Result<Vec<Action>, ParseErrorKind> {
     let mut actions = Vec::new();
-    for _ in 0..num_actions {
-        actions.push(parse_action(stream)?);
+    for i in 0..num_actions {
+        actions.push(parse_action(stream, i, num_words)?);
     }
     Ok(actions)
 }
@@ -62,10 +136,13 @@ fn parse_actions(stream: &mut Stream, num_actions: i32) -> Result<Vec<Action>, P
 /// *   5x conditions, expressed as condition type + (20 * value)
 /// *   (150 * action0 type) + action1 type
 /// *   (150 * action2 type) + action3 type
-fn parse_action(stream: &mut Stream) -> Result<Action, ParseError> {
+fn parse_action(stream: &mut Stream, index: i32, num_words: i32) -> Result<Action, ParseErrorKind> {
     let num = _read_int(stream)?;
-    let verb_index = num / 150;
-    let noun_index = num % 150;
+    let trigger = Trigger::from_i32(num, table_size(num_words)).ok_or(ParseErrorKind::Trigger {
+        action: index,
+        verb: num / 150,
+        noun: num % 150,
+    })?;
 
     let conditions = [
         parse_condition(stream)?,
@@ -80,8 +157,7 @@ fn parse_action(stream: &mut Stream) -> Result<Action, ParseError> {
     let actions = [a0, a1, a2, a3];
 
     Ok(Action {
-        verb_index,
-        noun_index,
+        trigger,
         conditions,
         actions,
         comment: None, // comments are after items in the game file
@@ -90,21 +166,21 @@ fn parse_action(stream: &mut Stream) -> Result<Action, ParseError> {
 
 /// Parses a single condition from the game file.  Each condition is expressed
 /// condition type + (20 * parameter).
-fn parse_condition(stream: &mut Stream) -> Result<Condition, ParseError> {
+fn parse_condition(stream: &mut Stream) -> Result<Condition, ParseErrorKind> {
     let num = _read_int(stream)?;
     Ok(Condition::from_i32(num))
 }
 
 /// Parses a pair of action types from the game file.  We must parse two at a
 /// time because they are stored as (a0 * 150) + a1.
-fn parse_action_type_pair(stream: &mut Stream) -> Result<(ActionType, ActionType), ParseError> {
+fn parse_action_type_pair(stream: &mut Stream) -> Result<(ActionType, ActionType), ParseErrorKind> {
     let num = _read_int(stream)?;
     Ok((ActionType::from_i32(num / 150), ActionType::from_i32(num % 150)))
 }
 
 /// Parses all of the words from the game file, which are an interleaved array
 /// of strings.  An initial "*" indicates a synonym.
-fn parse_words(stream: &mut Stream, num_words: i32) -> Result<(Vec<Word>, Vec<Word>), ParseError> {
+fn parse_words(stream: &mut Stream, num_words: i32) -> Result<(Vec<Word>, Vec<Word>), ParseErrorKind> {
     let mut verbs = Vec::new();
     let mut nouns = Vec::new();
     for _ in 0..num_words {
@@ -123,21 +199,25 @@ fn parse_words(stream: &mut Stream, num_words: i32) -> Result<(Vec<Word>, Vec<Wo
 }
 
 /// Parses all of the rooms from the game file.
-fn parse_rooms(stream: &mut Stream, num_rooms: i32) -> Result<Vec<Room>, ParseError> {
+fn parse_rooms(stream: &mut Stream, num_rooms: i32) -> Result<Vec<Room>, ParseErrorKind> {
     let mut rooms = Vec::new();
-    for _ in 0..num_rooms {
-        rooms.push(parse_room(stream)?);
+    for i in 0..num_rooms {
+        rooms.push(parse_room(stream, i, num_rooms)?);
     }
     Ok(rooms)
 }
 
 /// Parses a single room, which consists of six directions (north, south, east,
 /// west, up, down) followed by a description. The description starts with "*"
-/// to indicate that it stands alone, with no "I'm in a" prefix.
-fn parse_room(stream: &mut Stream) -> Result<Room, ParseError> {
-    let mut exits = [(); 6].map(|_| 0);
+/// to indicate that it stands alone, with no "I'm in a" prefix.  Each exit is
+/// the room it leads to, which must exist, or 0 for none.
+fn parse_room(stream: &mut Stream, index: i32, num_rooms: i32) -> Result<Room, ParseErrorKind> {
+    let mut exits = [None; 6];
     for exit in &mut exits {
-        *exit = _read_int(stream)?;
+        let destination = _read_int(stream)?;
+        let room = RoomId::new(destination, table_size(num_rooms))
+            .ok_or(ParseErrorKind::Exit { room: index, destination })?;
+        *exit = world::exit_to(room);
     }
 
     let desc = _read_word(stream)?;
@@ -149,7 +229,7 @@ fn parse_room(stream: &mut Stream) -> Result<Room, ParseError> {
 }
 
 // Parses all of the messages from the game file.
-fn parse_messages(stream: &mut Stream, num_messages: i32) -> Result<Vec<String>, ParseError> {
+fn parse_messages(stream: &mut Stream, num_messages: i32) -> Result<Vec<String>, ParseErrorKind> {
     let mut messages = Vec::new();
     for _ in 0..num_messages {
         messages.push(_read_str(stream)?);
@@ -157,11 +237,26 @@ fn parse_messages(stream: &mut Stream, num_messages: i32) -> Result<Vec<String>,
     Ok(messages)
 }
 
+/// Checks that every message printed by an action is one of the game's
+/// messages.
+fn check_messages(actions: &[Action], messages: &[String]) -> Result<(), ParseErrorKind> {
+    for (i, action) in actions.iter().enumerate() {
+        for action_type in &action.actions {
+            if let ActionType::Message(message) = action_type {
+                if message.index() >= messages.len() {
+                    return Err(ParseErrorKind::Message { action: i, message: *message });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 // Parses all of the items from the game file.
-fn parse_items(stream: &mut Stream, num_items: i32) -> Result<Vec<Item>, ParseError> {
+fn parse_items(stream: &mut Stream, num_items: i32, num_rooms: i32) -> Result<Vec<Item>, ParseErrorKind> {
     let mut items = Vec::new();
     for _ in 0..num_items {
-        items.push(parse_item(stream)?);
+        items.push(parse_item(stream, num_rooms)?);
     }
     Ok(items)
 }
@@ -174,9 +269,9 @@ fn parse_items(stream: &mut Stream, num_items: i32) -> Result<Vec<Item>, ParseEr
 ///
 /// If the description has a suffix of `/XXX/``, then automatic GET and DROP
 /// operations can be performed using "XXX" as a noun.
-fn parse_item(stream: &mut Stream) -> Result<Item, ParseError> {
+fn parse_item(stream: &mut Stream, num_rooms: i32) -> Result<Item, ParseErrorKind> {
     let mut description = _read_str(stream)?;
-    let location = _read_int(stream)?;
+    let location = Location::new(_read_int(stream)?, table_size(num_rooms));
 
     let is_treasure = description.starts_with("*");
 
@@ -197,7 +292,7 @@ fn parse_item(stream: &mut Stream) -> Result<Item, ParseError> {
 
 /// Parses all of the comments from the game file, which are stored in the
 /// actions.
-fn parse_comments(stream: &mut Stream, actions: &mut Vec<Action>) -> Result<(), ParseError> {
+fn parse_comments(stream: &mut Stream, actions: &mut Vec<Action>) -> Result<(), ParseErrorKind> {
     for action in actions {
         let comment = _read_str(stream)?;
         if !comment.is_empty() {
@@ -208,7 +303,7 @@ fn parse_comments(stream: &mut Stream, actions: &mut Vec<Action>) -> Result<(),
 }
 
 /// Parses the footer.
-fn parse_footer(stream: &mut Stream) -> Result<Footer, ParseError> {
+fn parse_footer(stream: &mut Stream) -> Result<Footer, ParseErrorKind> {
     Ok(Footer {
         version: _read_int(stream)?,
         adventure: _read_int(stream)?,
@@ -216,25 +311,31 @@ fn parse_footer(stream: &mut Stream) -> Result<Footer, ParseError> {
     })
 }
 
+/// Returns the size of a table from its count in the header, which is
+/// nothing at all if the count is negative.
+fn table_size(count: i32) -> usize {
+    count.max(0) as usize
+}
+
 /// Reads in the next integer token.
-fn _read_int(stream: &mut Stream) -> Result<i32, ParseError> {
-    match stream.next_int() {
-        Ok(value) => Ok(value),
-        Err(e) => Err(ParseError { msg: format!("{}", e) }),
+fn _read_int(stream: &mut Stream) -> Result<i32, ParseErrorKind> {
+    match stream.next_token() {
+        Some(Token::Int(value, _)) => Ok(value),
+        token => Err(ParseErrorKind::unexpected(TokenKind::Int, token)),
     }
 }
 
 /// Reads in the next string token.
-fn _read_str(stream: &mut Stream) -> Result<String, ParseError> {
-    match stream.next_str() {
-        Ok(value) => Ok(value),
-        Err(e) => Err(ParseError { msg: format!("{}", e) }),
+fn _read_str(stream: &mut Stream) -> Result<String, ParseErrorKind> {
+    match stream.next_token() {
+        Some(Token::Str(value, _)) => Ok(value),
+        token => Err(ParseErrorKind::unexpected(TokenKind::Str, token)),
     }
 }
 
 /// Reads in the next word.  A word is distinguished from a string token by
 /// having an optional "*" prefix to indicate special handling.
-fn _read_word(stream: &mut Stream) -> Result<(String, bool), ParseError> {
+fn _read_word(stream: &mut Stream) -> Result<(String, bool), ParseErrorKind> {
     let mut word = _read_str(stream)?;
     let has_prefix = word.starts_with("*");
     if has_prefix {
@@ -243,14 +344,139 @@ fn _read_word(stream: &mut Stream) -> Result<(String, bool), ParseError> {
     Ok((word, has_prefix))
 }
 
-/// Represents an error encountered during parsing.
+/// Represents an error encountered during parsing.  It holds what went wrong
+/// rather than a description of it, which is only written out when the error
+/// is displayed.
+#[derive(Clone, Debug, PartialEq)]
 pub struct ParseError {
-    msg: String,
+    section: Section,
+    kind: ParseErrorKind,
+}
+
+/// Names the sections of a game file, in the order in which they come.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Header,
+    Actions,
+    Words,
+    Rooms,
+    Messages,
+    Items,
+    Comments,
+    Footer,
+}
+
+/// Says what went wrong in parsing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// A token wasn't of the kind expected, or the game file ended where a
+    /// token was expected, in which case nothing was found.
+    Unexpected {
+        expected: TokenKind,
+        found: Option<TokenKind>,
+        location: Option<tokenizer::Location>,
+    },
+    /// An action is for a verb or noun that doesn't exist.
+    Trigger { action: i32, verb: i32, noun: i32 },
+    /// A room has an exit to a room that doesn't exist.
+    Exit { room: i32, destination: i32 },
+    /// An action prints a message that doesn't exist.
+    Message { action: usize, message: MessageId },
+}
+
+/// Names the two kinds of token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Int,
+    Str,
+}
+
+impl ParseErrorKind {
+    /// Returns the error for finding the given token, or the end of the game
+    /// file, where a token of the expected kind should be.
+    fn unexpected(expected: TokenKind, token: Option<Token>) -> ParseErrorKind {
+        ParseErrorKind::Unexpected {
+            expected,
+            found: token.as_ref().map(|token| match token {
+                Token::Int(..) => TokenKind::Int,
+                Token::Str(..) => TokenKind::Str,
+            }),
+            location: token.map(|token| token.location()),
+        }
+    }
+}
+
+impl ParseError {
+    /// Returns the section of the game file in which the error was found.
+    pub fn section(&self) -> Section {
+        self.section
+    }
+
+    /// Returns what went wrong.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// Returns where in the game file the error was found, if it was found
+    /// at a token rather than in what the tokens add up to.
+    pub fn location(&self) -> Option<tokenizer::Location> {
+        match self.kind {
+            ParseErrorKind::Unexpected { location, .. } => location,
+            _ => None,
+        }
+    }
+}
+
+impl Display for Section {
+    /// Names the section as the partial game does.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let name = match self {
+            Section::Header => "header",
+            Section::Actions => "actions",
+            Section::Words => "words",
+            Section::Rooms => "rooms",
+            Section::Messages => "messages",
+            Section::Items => "items",
+            Section::Comments => "comments",
+            Section::Footer => "footer",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Display for TokenKind {
+    /// Names the kind of token with its article.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            TokenKind::Int => write!(f, "an integer"),
+            TokenKind::Str => write!(f, "a string"),
+        }
+    }
 }
 
 impl Display for ParseError {
-    /// Makes a parsing error human-readable.
+    /// Makes a parsing error human-readable.  Errors at a token are given
+    /// its line and column, as the tokenizer's own errors are.
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "{}", self.msg)
+        match &self.kind {
+            ParseErrorKind::Unexpected { expected, found: Some(found), location: Some(loc) } => {
+                write!(f, "{}:{}: Expected {}, found {}", loc.line, loc.col, expected, found)
+            }
+            // The tokenizer calls the end of the game file line 0.
+            ParseErrorKind::Unexpected { .. } => write!(f, "0:0: Unexpected end of stream"),
+            ParseErrorKind::Trigger { action, verb, noun } => write!(
+                f,
+                "Action {} is for verb {} and noun {}, which don't both exist",
+                action, verb, noun
+            ),
+            ParseErrorKind::Exit { room, destination } => write!(
+                f,
+                "Room {} has an exit to room {}, which doesn't exist",
+                room, destination
+            ),
+            ParseErrorKind::Message { action, message } => {
+                write!(f, "Action {} prints {}, which doesn't exist", action, message)
+            }
+        }
     }
 }
@@ -0,0 +1,903 @@
+//! This module converts games to and from the source format of ScottKit, the
+//! Ruby toolkit that many authors write these games with, so that a game can
+//! be written in ScottKit's language and played here without compiling it
+//! first, and an existing game can be decompiled to be edited.
+//!
+//! A source file is a series of statements, one to a line, with `#` starting
+//! a comment.  The rooms and items are given names, which the rest of the
+//! source refers to them by:
+//!
+//! ```text
+//! start cove
+//! treasury cove
+//!
+//! room cove "sandy cove"
+//!     exit north cliff
+//!
+//! item lamp "Brass lamp"
+//!     called "LAMP"
+//!     at cove
+//!
+//! action light lamp when carried lamp and !flag 3
+//!     set_flag 3
+//!     print "The lamp is lit."
+//!
+//! occur 50% when at cove
+//!     print "Waves break on the shore."
+//! ```
+//!
+//! Each `action` gives the verb and noun it answers to, and each `occur` the
+//! percent chance that it happens each turn.  The conditions and
+//! instructions have ScottKit's names, and take their parameters after them
+//! rather than in the parameter slots that the game file uses.  Room and item
+//! references may also be numbers, and rooms may be `nowhere` (the
+//! storeroom) or `carried`.  An item without `at` starts out of play.
+//!
+//! The vocabulary is built from `verbgroup` and `noungroup` statements, each
+//! giving a word followed by its synonyms, and then from the words of the
+//! actions.  The words that the interpreter itself understands (GO, GET and
+//! DROP, and the six directions) are put where it expects them.  Messages are
+//! numbered in the order that they are first printed.
+//!
+//! Decompiling and compiling again gives a game that plays the same way, but
+//! not the same game file: the slots that an action leaves empty, messages
+//! that no action prints, and gaps in the vocabulary aren't kept.
+
+use std::collections::HashMap;
+
+use super::world::{exit_to, INVENTORY};
+use super::*;
+
+/// The words that the interpreter gives meanings of its own, with the
+/// indices that they must have.
+const RESERVED_VERBS: [(usize, &str); 4] = [(0, "AUTO"), (1, "GO"), (10, "GET"), (18, "DROP")];
+const RESERVED_NOUNS: [(usize, &str); 7] = [
+    (0, "ANY"),
+    (1, "NORTH"),
+    (2, "SOUTH"),
+    (3, "EAST"),
+    (4, "WEST"),
+    (5, "UP"),
+    (6, "DOWN"),
+];
+
+/// What a condition or instruction takes as a parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Item,
+    Room,
+    Number,
+}
+
+/// The conditions by type, with what each takes.
+const CONDITIONS: [(&str, Option<Kind>); 20] = [
+    ("", None),
+    ("carried", Some(Kind::Item)),
+    ("here", Some(Kind::Item)),
+    ("accessible", Some(Kind::Item)),
+    ("at", Some(Kind::Room)),
+    ("!here", Some(Kind::Item)),
+    ("!carried", Some(Kind::Item)),
+    ("!at", Some(Kind::Room)),
+    ("flag", Some(Kind::Number)),
+    ("!flag", Some(Kind::Number)),
+    ("loaded", None),
+    ("!loaded", None),
+    ("!accessible", Some(Kind::Item)),
+    ("exists", Some(Kind::Item)),
+    ("!exists", Some(Kind::Item)),
+    ("counter_le", Some(Kind::Number)),
+    ("counter_gt", Some(Kind::Number)),
+    ("!moved", Some(Kind::Item)),
+    ("moved", Some(Kind::Item)),
+    ("counter_eq", Some(Kind::Number)),
+];
+
+/// The instructions other than `print`, by action type from 52 on, with
+/// what each takes.
+const INSTRUCTIONS: [(&str, &[Kind]); 38] = [
+    ("get", &[Kind::Item]),
+    ("drop", &[Kind::Item]),
+    ("goto", &[Kind::Room]),
+    ("destroy", &[Kind::Item]),
+    ("set_dark", &[]),
+    ("clear_dark", &[]),
+    ("set_flag", &[Kind::Number]),
+    ("destroy2", &[Kind::Item]),
+    ("clear_flag", &[Kind::Number]),
+    ("die", &[]),
+    ("put", &[Kind::Item, Kind::Room]),
+    ("game_over", &[]),
+    ("look", &[]),
+    ("score", &[]),
+    ("inventory", &[]),
+    ("set_flag0", &[]),
+    ("clear_flag0", &[]),
+    ("refill_lamp", &[]),
+    ("clear", &[]),
+    ("save_game", &[]),
+    ("swap", &[Kind::Item, Kind::Item]),
+    ("continue", &[]),
+    ("superget", &[Kind::Item]),
+    ("put_with", &[Kind::Item, Kind::Item]),
+    ("look2", &[]),
+    ("dec_counter", &[]),
+    ("print_counter", &[]),
+    ("set_counter", &[Kind::Number]),
+    ("swap_room", &[]),
+    ("select_counter", &[Kind::Number]),
+    ("add_to_counter", &[Kind::Number]),
+    ("subtract_from_counter", &[Kind::Number]),
+    ("print_noun", &[]),
+    ("println_noun", &[]),
+    ("println", &[]),
+    ("swap_specific_room", &[Kind::Number]),
+    ("pause", &[]),
+    ("draw", &[Kind::Number]),
+];
+
+/// The action type of the first instruction in `INSTRUCTIONS`.
+const FIRST_INSTRUCTION: i32 = 52;
+
+/// Returns the source of a game in ScottKit's format.
+pub fn decompile(game: &Game) -> String {
+    let rooms = names(
+        game.rooms.iter().map(|room| room.description.as_str()),
+        "room",
+    );
+    let items = names(
+        game.items
+            .iter()
+            .map(|item| item.autograb.as_deref().unwrap_or(&item.description)),
+        "item",
+    );
+    let room = |room: i32| match Location::new(room, game.rooms.len()) {
+        Location::STOREROOM => "nowhere".to_string(),
+        Location::Carried => "carried".to_string(),
+        Location::Room(room) => rooms[room.index()].clone(),
+        Location::Elsewhere(room) => room.to_string(),
+    };
+    let item = |item: i32| match items.get(item as usize) {
+        Some(name) if item >= 0 => name.clone(),
+        _ => item.to_string(),
+    };
+    let param = |kind: Kind, value: i32| match kind {
+        Kind::Item => item(value),
+        Kind::Room => room(value),
+        Kind::Number => value.to_string(),
+    };
+
+    let header = &game.header;
+    let mut out = String::new();
+    out.push_str(&format!("start {}\n", room(header.starting_room)));
+    out.push_str(&format!("treasury {}\n", room(header.treasure_room)));
+    out.push_str(&format!("maxload {}\n", header.max_inventory));
+    out.push_str(&format!("lighttime {}\n", header.light_duration));
+    out.push_str(&format!("wordlen {}\n", header.word_length));
+    out.push_str(&format!("ident {}\n", game.footer.adventure));
+    out.push_str(&format!("version {}\n", game.footer.version));
+    out.push_str(&format!("unknown1 {}\n", header.unknown0));
+    out.push_str(&format!("unknown16 {}\n", game.footer.magic));
+
+    for (i, r) in game.rooms.iter().enumerate().skip(1) {
+        let literal = if r.is_literal { "*" } else { "" };
+        let description = format!("{}{}", literal, r.description);
+        out.push_str(&format!("\nroom {} {}\n", rooms[i], quote(&description)));
+        for dir in Direction::ALL {
+            if let Some(exit) = r.exits[dir.index()] {
+                let name = dir.name().to_lowercase();
+                out.push_str(&format!("\texit {} {}\n", name, room(exit.to_i32())));
+            }
+        }
+    }
+
+    for (i, it) in game.items.iter().enumerate() {
+        out.push_str(&format!("\nitem {} {}\n", items[i], quote(&it.description)));
+        if let Some(autograb) = &it.autograb {
+            out.push_str(&format!("\tcalled {}\n", quote(autograb)));
+        }
+        match it.location {
+            Location::STOREROOM => (),
+            Location::Carried => out.push_str("\tcarried\n"),
+            location => out.push_str(&format!("\tat {}\n", room(location.to_i32()))),
+        }
+    }
+
+    out.push('\n');
+    for (statement, groups) in [
+        ("verbgroup", game.verb_groups()),
+        ("noungroup", game.noun_groups()),
+    ] {
+        for group in groups {
+            let words: Vec<String> = [group.word]
+                .into_iter()
+                .chain(group.synonyms)
+                .map(|word| word_token(&word))
+                .collect();
+            out.push_str(&format!("{} {}\n", statement, words.join(" ")));
+        }
+    }
+
+    for action in &game.actions {
+        out.push('\n');
+        match action.trigger {
+            Trigger::Occurrence(chance) => out.push_str(&format!("occur {}%", chance)),
+            Trigger::Command(verb, noun) => {
+                let verb = &game.verbs[verb.index()].word;
+                out.push_str(&format!("action {}", word_token(verb)));
+                if let Some(noun) = noun {
+                    let noun = &game.nouns[noun.index()].word;
+                    out.push_str(&format!(" {}", word_token(noun)));
+                }
+            }
+        }
+
+        let mut conditions = vec![];
+        let mut params = vec![];
+        for condition in &action.conditions {
+            let code = condition.to_i32();
+            match condition {
+                Condition::Parameter(value) => params.push(*value),
+                Condition::Invalid(..) => conditions.push(format!("condition {}", code)),
+                _ => match CONDITIONS[(code % 20) as usize] {
+                    (name, Some(kind)) => {
+                        conditions.push(format!("{} {}", name, param(kind, code / 20)))
+                    }
+                    (name, None) => conditions.push(name.to_string()),
+                },
+            }
+        }
+        if !conditions.is_empty() {
+            out.push_str(&format!(" when {}", conditions.join(" and ")));
+        }
+        out.push('\n');
+
+        let mut params = params.into_iter();
+        for action_type in &action.actions {
+            let code = action_type.to_i32();
+            let instruction = match action_type {
+                ActionType::Nothing => continue,
+                ActionType::Message(message) => format!("print {}", quote(game.message(*message))),
+                ActionType::Invalid(_) => format!("instruction {}", code),
+                _ => {
+                    let (name, kinds) = INSTRUCTIONS[(code - FIRST_INSTRUCTION) as usize];
+                    let mut instruction = name.to_string();
+                    for &kind in kinds {
+                        let value = params.next().unwrap_or(0);
+                        instruction.push_str(&format!(" {}", param(kind, value)));
+                    }
+                    instruction
+                }
+            };
+            out.push_str(&format!("\t{}\n", instruction));
+        }
+        if let Some(comment) = &action.comment {
+            out.push_str(&format!("\tcomment {}\n", quote(comment)));
+        }
+    }
+    out
+}
+
+/// Makes a name for each room or item from its description, such as
+/// `dismal_swamp`, which is different from the others and from the words
+/// that have a meaning of their own.
+fn names<'a>(descriptions: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for (i, description) in descriptions.enumerate() {
+        let words: Vec<String> = description
+            .split(|ch: char| !ch.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .take(2)
+            .map(|word| word.to_ascii_lowercase())
+            .collect();
+        let mut name = words.join("_");
+        if name.is_empty() || name.starts_with(|ch: char| ch.is_ascii_digit()) {
+            name = format!("{}{}", prefix, i);
+        }
+        if names.contains(&name) || ["nowhere", "carried"].contains(&name.as_str()) {
+            name = format!("{}{}", name, i);
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Returns a word of the vocabulary as it is written in the source, which is
+/// quoted if it wouldn't otherwise read back as a single word.
+fn word_token(word: &str) -> String {
+    if word.is_empty() || word.contains(|ch: char| ch.is_whitespace() || ch == '#' || ch == '"') {
+        quote(word)
+    } else {
+        word.to_string()
+    }
+}
+
+/// Quotes a string, escaping the characters that would end it early or
+/// break its line.
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// A word or a quoted string from a line of the source.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+}
+
+impl Token {
+    /// Returns the text of the token, whether or not it was quoted.
+    fn text(&self) -> &str {
+        match self {
+            Token::Word(text) | Token::Str(text) => text,
+        }
+    }
+}
+
+/// Splits a line of the source into tokens, leaving out any comment.
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch == '#' {
+            break;
+        } else if ch == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => text.push('\n'),
+                        Some(ch) => text.push(ch),
+                        None => return Err("unfinished escape".to_string()),
+                    },
+                    Some(ch) => text.push(ch),
+                    None => return Err("unfinished string".to_string()),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() || ch == '"' || ch == '#' {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Builds up the verbs or nouns of a game, keeping the words that the
+/// interpreter understands where it expects them.
+struct Vocabulary {
+    words: Vec<Option<Word>>,
+    /// The reserved words that keep their places, which are those that the
+    /// source doesn't make synonyms of other words.
+    reserved: Vec<(usize, &'static str)>,
+    word_length: usize,
+    /// Where the next group of words is placed, if there is room.
+    cursor: usize,
+    /// The index of each word's group.
+    index: HashMap<String, usize>,
+}
+
+impl Vocabulary {
+    fn new(reserved: &[(usize, &'static str)], word_length: usize, synonyms: &[&str]) -> Self {
+        let significant =
+            |word: &str| -> String { word.to_uppercase().chars().take(word_length).collect() };
+        let reserved = reserved
+            .iter()
+            .filter(|(_, word)| {
+                !synonyms
+                    .iter()
+                    .any(|synonym| significant(synonym) == significant(word))
+            })
+            .copied()
+            .collect();
+        Vocabulary {
+            words: vec![],
+            reserved,
+            word_length,
+            cursor: 0,
+            index: HashMap::new(),
+        }
+    }
+
+    /// Returns a word as the game file has it, in capitals.  Words longer
+    /// than the word length are kept whole, as some games have them.
+    fn normalize(&self, word: &str) -> String {
+        word.to_uppercase()
+    }
+
+    /// Returns the index that a reserved word has, if the word is one and
+    /// its slot is still free.  Only the first letters of a word count.
+    fn reserved_index(&self, word: &str) -> Option<usize> {
+        let significant = |word: &str| -> String { word.chars().take(self.word_length).collect() };
+        self.reserved
+            .iter()
+            .find(|(_, reserved)| significant(reserved) == significant(word))
+            .map(|&(index, _)| index)
+            .filter(|&index| self.words.get(index).is_none_or(Option::is_none))
+    }
+
+    /// Is the slot at the given index free for the next group?
+    fn is_free(&self, index: usize) -> bool {
+        self.words.get(index).is_none_or(Option::is_none)
+            && !self.reserved.iter().any(|&(reserved, _)| reserved == index)
+    }
+
+    /// Adds a word and its synonyms, returning the index they share.  Unless
+    /// `again` is set, a word already in the vocabulary keeps its place;
+    /// otherwise it is added again, as games sometimes have the same word
+    /// in more than one place.
+    fn add(&mut self, group: &[&str], again: bool) -> Result<usize, String> {
+        let group: Vec<String> = group.iter().map(|word| self.normalize(word)).collect();
+        let group_len = group.len();
+        if let Some(&index) = self.index.get(&group[0]).filter(|_| !again) {
+            return Ok(index);
+        }
+        let start = match self.reserved_index(&group[0]) {
+            Some(index) => {
+                if !(1..group.len()).all(|i| self.is_free(index + i)) {
+                    return Err(format!("there is no room for the synonyms of {}", group[0]));
+                }
+                index
+            }
+            None => {
+                let mut start = self.cursor;
+                while !(0..group.len()).all(|i| self.is_free(start + i)) {
+                    start += 1;
+                }
+                start
+            }
+        };
+        if self.words.len() < start + group.len() {
+            self.words.resize_with(start + group.len(), || None);
+        }
+        for (i, word) in group.into_iter().enumerate() {
+            self.index.entry(word.clone()).or_insert(start);
+            self.words[start + i] = Some(Word {
+                word,
+                is_synonym: i > 0,
+            });
+        }
+        self.cursor = self.cursor.max(start + group_len);
+        Ok(start)
+    }
+
+    /// Returns the index of a word, adding it if it is new.
+    fn find(&mut self, word: &str) -> Result<usize, String> {
+        self.add(&[word], false)
+    }
+
+    /// Fills in the reserved words that weren't used.
+    fn add_reserved(&mut self) {
+        for &(index, word) in &self.reserved {
+            if self.words.get(index).is_none_or(Option::is_none) {
+                if self.words.len() <= index {
+                    self.words.resize_with(index + 1, || None);
+                }
+                let word = word.chars().take(self.word_length).collect();
+                self.words[index] = Some(Word {
+                    word,
+                    is_synonym: false,
+                });
+            }
+        }
+    }
+
+    /// Returns the given number of words, with any gaps left blank.
+    fn into_words(mut self, len: usize) -> Vec<Word> {
+        self.words.resize_with(len, || None);
+        self.words
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+}
+
+/// What the statements that follow belong to.
+enum Block {
+    None,
+    Room(usize),
+    Item(usize),
+    Action(usize),
+}
+
+/// Compiles a game from source in ScottKit's format, describing the first
+/// problem found along with its line number.
+pub fn compile(source: &str) -> Result<Game, String> {
+    let mut lines = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let tokens = tokenize(line).map_err(|err| format!("line {}: {}", i + 1, err))?;
+        if !tokens.is_empty() {
+            lines.push((i + 1, tokens));
+        }
+    }
+
+    // The rooms and items can be referred to before they are declared, and
+    // the synonyms are needed to place the first words.
+    let mut room_names: HashMap<&str, i32> = HashMap::new();
+    let mut item_names: HashMap<&str, i32> = HashMap::new();
+    let mut synonyms: [Vec<&str>; 2] = [vec![], vec![]];
+    let mut num_rooms = 1;
+    for (_, tokens) in &lines {
+        match (tokens[0].text(), tokens.get(1)) {
+            ("verbgroup", Some(_)) => synonyms[0].extend(tokens[2..].iter().map(Token::text)),
+            ("noungroup", Some(_)) => synonyms[1].extend(tokens[2..].iter().map(Token::text)),
+            ("room", Some(name)) => {
+                num_rooms += 1;
+                let index = room_names.len() as i32 + 1;
+                room_names.entry(name.text()).or_insert(index);
+            }
+            ("item", Some(name)) => {
+                let index = item_names.len() as i32;
+                item_names.entry(name.text()).or_insert(index);
+            }
+            _ => (),
+        }
+    }
+
+    let mut compiler = Compiler {
+        rooms: room_names,
+        num_rooms,
+        items: item_names,
+        game: Game {
+            header: Header {
+                unknown0: 0,
+                num_items: 0,
+                num_actions: 0,
+                num_words: 0,
+                num_rooms: 0,
+                max_inventory: 6,
+                starting_room: 1,
+                num_treasures: 0,
+                word_length: 3,
+                light_duration: 125,
+                num_messages: 0,
+                treasure_room: 0,
+            },
+            actions: vec![],
+            verbs: vec![],
+            nouns: vec![],
+            rooms: vec![Room {
+                description: String::new(),
+                is_literal: false,
+                exits: [None; 6],
+            }],
+            messages: vec![String::new()],
+            items: vec![],
+            footer: Footer {
+                version: 0,
+                adventure: 0,
+                magic: 0,
+            },
+        },
+        block: Block::None,
+        slots: 0,
+        synonyms,
+        verbs: None,
+        nouns: None,
+    };
+    for (line, tokens) in &lines {
+        compiler
+            .statement(tokens)
+            .map_err(|err| format!("line {}: {}", line, err))?;
+    }
+    compiler.finish()
+}
+
+/// Holds a game while it is being compiled.
+struct Compiler<'a> {
+    rooms: HashMap<&'a str, i32>,
+    /// The number of rooms that the source declares, including the
+    /// storeroom.
+    num_rooms: usize,
+    items: HashMap<&'a str, i32>,
+    game: Game,
+    block: Block,
+    /// The number of condition slots that the current action has filled,
+    /// with conditions or with parameters.
+    slots: usize,
+    /// The verbs and then the nouns that the source gives as synonyms.
+    synonyms: [Vec<&'a str>; 2],
+    /// The vocabulary, which is started once the word length is known.
+    verbs: Option<Vocabulary>,
+    nouns: Option<Vocabulary>,
+}
+
+impl Compiler<'_> {
+    /// Compiles one statement.
+    fn statement(&mut self, tokens: &[Token]) -> Result<(), String> {
+        let args = &tokens[1..];
+        match (tokens[0].text(), &self.block) {
+            ("start", _) => self.game.header.starting_room = self.room(one(args)?)?,
+            ("treasury", _) => self.game.header.treasure_room = self.room(one(args)?)?,
+            ("maxload", _) => self.game.header.max_inventory = number(one(args)?.text())?,
+            ("lighttime", _) => self.game.header.light_duration = number(one(args)?.text())?,
+            ("wordlen", _) => {
+                if self.verbs.is_some() {
+                    return Err("wordlen must come before the vocabulary".to_string());
+                }
+                self.game.header.word_length = number(one(args)?.text())?;
+            }
+            ("ident", _) => self.game.footer.adventure = number(one(args)?.text())?,
+            ("version", _) => self.game.footer.version = number(one(args)?.text())?,
+            ("unknown1", _) => self.game.header.unknown0 = number(one(args)?.text())?,
+            ("unknown16", _) => self.game.footer.magic = number(one(args)?.text())?,
+            ("room", _) => {
+                let [_, description] = args else {
+                    return Err("expected a name and a description".to_string());
+                };
+                let description = description.text();
+                let is_literal = description.starts_with('*');
+                self.game.rooms.push(Room {
+                    description: description
+                        .strip_prefix('*')
+                        .unwrap_or(description)
+                        .to_string(),
+                    is_literal,
+                    exits: [None; 6],
+                });
+                self.block = Block::Room(self.game.rooms.len() - 1);
+            }
+            ("exit", &Block::Room(room)) => {
+                let [dir, to] = args else {
+                    return Err("expected a direction and a room".to_string());
+                };
+                let dir = Direction::from_name(dir.text())
+                    .ok_or_else(|| format!("there is no direction {}", dir.text()))?;
+                let to = self.room(to)?;
+                let to = RoomId::new(to, self.num_rooms)
+                    .ok_or_else(|| format!("there is no room {}", to))?;
+                self.game.rooms[room].exits[dir.index()] = exit_to(to);
+            }
+            ("item", _) => {
+                let [_, description] = args else {
+                    return Err("expected a name and a description".to_string());
+                };
+                self.game.items.push(Item {
+                    description: description.text().to_string(),
+                    location: Location::STOREROOM,
+                    is_treasure: description.text().starts_with('*'),
+                    autograb: None,
+                });
+                self.block = Block::Item(self.game.items.len() - 1);
+            }
+            ("called", &Block::Item(item)) => {
+                self.game.items[item].autograb = Some(one(args)?.text().to_string())
+            }
+            ("at", &Block::Item(item)) => {
+                let location = self.room(one(args)?)?;
+                self.game.items[item].location = Location::new(location, self.num_rooms)
+            }
+            ("carried", &Block::Item(item)) => self.game.items[item].location = Location::Carried,
+            ("nowhere", &Block::Item(item)) => self.game.items[item].location = Location::STOREROOM,
+            ("verbgroup" | "noungroup", _) => {
+                let words: Vec<&str> = args.iter().map(Token::text).collect();
+                if words.is_empty() {
+                    return Err("expected words".to_string());
+                }
+                self.vocabulary(tokens[0].text() == "verbgroup")
+                    .add(&words, true)?;
+            }
+            ("action" | "occur", _) => self.action(tokens)?,
+            ("comment", &Block::Action(action)) => {
+                self.game.actions[action].comment = Some(one(args)?.text().to_string())
+            }
+            (_, &Block::Action(action)) => self.instruction(action, tokens)?,
+            (name, _) => return Err(format!("unexpected {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Returns the verbs or the nouns, starting them if need be.
+    fn vocabulary(&mut self, verbs: bool) -> &mut Vocabulary {
+        let word_length = self.game.header.word_length.max(1) as usize;
+        let synonyms = &self.synonyms;
+        if verbs {
+            self.verbs
+                .get_or_insert_with(|| Vocabulary::new(&RESERVED_VERBS, word_length, &synonyms[0]))
+        } else {
+            self.nouns
+                .get_or_insert_with(|| Vocabulary::new(&RESERVED_NOUNS, word_length, &synonyms[1]))
+        }
+    }
+
+    /// Starts an action or occurrence, with its conditions.
+    fn action(&mut self, tokens: &[Token]) -> Result<(), String> {
+        let when = tokens
+            .iter()
+            .position(|token| *token == Token::Word("when".to_string()))
+            .unwrap_or(tokens.len());
+        let trigger = match (tokens[0].text(), &tokens[1..when]) {
+            ("occur", []) => Trigger::Occurrence(100),
+            ("occur", [chance]) => {
+                let chance = chance.text();
+                Trigger::Occurrence(number(chance.strip_suffix('%').unwrap_or(chance))?)
+            }
+            ("action", [verb]) => {
+                Trigger::Command(VerbId(self.vocabulary(true).find(verb.text())?), None)
+            }
+            ("action", [verb, noun]) => {
+                let verb = VerbId(self.vocabulary(true).find(verb.text())?);
+                let noun = NounId(self.vocabulary(false).find(noun.text())?);
+                // Noun 0 is any noun at all.
+                Trigger::Command(verb, Some(noun).filter(|noun| noun.index() != 0))
+            }
+            _ => return Err(format!("malformed {}", tokens[0].text())),
+        };
+
+        let mut conditions = vec![];
+        let mut rest = tokens[when..].iter().skip(1);
+        while let Some(name) = rest.next() {
+            let code = match name.text() {
+                "condition" => number(next(&mut rest)?.text())?,
+                name => {
+                    let typ = CONDITIONS
+                        .iter()
+                        .skip(1)
+                        .position(|(condition, _)| *condition == name)
+                        .ok_or_else(|| format!("there is no condition {}", name))?
+                        as i32
+                        + 1;
+                    let param = match CONDITIONS[typ as usize].1 {
+                        Some(kind) => self.param(kind, next(&mut rest)?)?,
+                        None => 0,
+                    };
+                    typ + param * 20
+                }
+            };
+            conditions.push(Condition::from_i32(code));
+            match rest.next().map(Token::text) {
+                Some("and") | None => (),
+                Some(other) => return Err(format!("expected and, not {}", other)),
+            }
+        }
+
+        self.game.actions.push(Action {
+            trigger,
+            conditions: [0; 5].map(Condition::from_i32),
+            actions: [0; 4].map(ActionType::from_i32),
+            comment: None,
+        });
+        let action = self.game.actions.len() - 1;
+        if conditions.len() > 5 {
+            return Err("an action can have at most 5 conditions".to_string());
+        }
+        self.slots = conditions.len();
+        for (i, condition) in conditions.into_iter().enumerate() {
+            self.game.actions[action].conditions[i] = condition;
+        }
+        self.block = Block::Action(action);
+        Ok(())
+    }
+
+    /// Adds an instruction to an action, putting its parameters in the
+    /// action's free condition slots.
+    fn instruction(&mut self, action: usize, tokens: &[Token]) -> Result<(), String> {
+        let name = tokens[0].text();
+        let mut args = tokens[1..].iter();
+        let mut params = vec![];
+        let action_type = match name {
+            "print" => {
+                let text = next(&mut args)?.text();
+                let index = match self.game.messages.iter().skip(1).position(|m| m == text) {
+                    Some(i) => i + 1,
+                    None => {
+                        self.game.messages.push(text.to_string());
+                        self.game.messages.len() - 1
+                    }
+                };
+                let message = MessageId::new(index as i32)
+                    .ok_or_else(|| "there are too many messages".to_string())?;
+                ActionType::Message(message)
+            }
+            "instruction" => ActionType::from_i32(number(next(&mut args)?.text())?),
+            name => {
+                let i = INSTRUCTIONS
+                    .iter()
+                    .position(|(instruction, _)| *instruction == name)
+                    .ok_or_else(|| format!("there is no instruction {}", name))?;
+                for &kind in INSTRUCTIONS[i].1 {
+                    params.push(self.param(kind, next(&mut args)?)?);
+                }
+                ActionType::from_i32(i as i32 + FIRST_INSTRUCTION)
+            }
+        };
+        if let Some(extra) = args.next() {
+            return Err(format!("unexpected {}", extra.text()));
+        }
+
+        let action = &mut self.game.actions[action];
+        let slot = action
+            .actions
+            .iter()
+            .position(|action_type| matches!(action_type, ActionType::Nothing))
+            .ok_or_else(|| "an action can have at most 4 instructions".to_string())?;
+        action.actions[slot] = action_type;
+        // The parameters go after those of the earlier instructions.
+        for param in params {
+            let slot = action
+                .conditions
+                .get_mut(self.slots)
+                .ok_or_else(|| "there are too many conditions and parameters".to_string())?;
+            *slot = Condition::Parameter(param);
+            self.slots += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of the room that a token refers to.
+    fn room(&self, token: &Token) -> Result<i32, String> {
+        self.param(Kind::Room, token)
+    }
+
+    /// Returns the value of a parameter of the given kind.
+    fn param(&self, kind: Kind, token: &Token) -> Result<i32, String> {
+        let text = token.text();
+        if let Ok(value) = text.parse() {
+            return Ok(value);
+        }
+        let found = match kind {
+            Kind::Room => match text {
+                "nowhere" => Some(Location::STOREROOM.to_i32()),
+                "carried" => Some(INVENTORY),
+                _ => self.rooms.get(text).copied(),
+            },
+            Kind::Item => self.items.get(text).copied(),
+            Kind::Number => None,
+        };
+        found.ok_or_else(|| format!("there is no {:?} {}", kind, text).to_lowercase())
+    }
+
+    /// Fills in the vocabulary and the counts in the header.
+    fn finish(mut self) -> Result<Game, String> {
+        self.vocabulary(true).add_reserved();
+        self.vocabulary(false).add_reserved();
+        let (verbs, nouns) = (self.verbs.unwrap(), self.nouns.unwrap());
+        let len = verbs.words.len().max(nouns.words.len());
+        let mut game = self.game;
+        game.verbs = verbs.into_words(len);
+        game.nouns = nouns.into_words(len);
+
+        let header = &mut game.header;
+        header.num_items = game.items.len() as i32;
+        header.num_actions = game.actions.len() as i32;
+        header.num_words = len as i32;
+        header.num_rooms = game.rooms.len() as i32;
+        header.num_messages = game.messages.len() as i32;
+        header.num_treasures = game.items.iter().filter(|item| item.is_treasure).count() as i32;
+        Ok(game)
+    }
+}
+
+/// Returns the only argument of a statement.
+fn one(args: &[Token]) -> Result<&Token, String> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err("expected one argument".to_string()),
+    }
+}
+
+/// Returns the next argument of a statement.
+fn next<'a>(args: &mut impl Iterator<Item = &'a Token>) -> Result<&'a Token, String> {
+    args.next().ok_or_else(|| "missing argument".to_string())
+}
+
+/// Parses a number.
+fn number(text: &str) -> Result<i32, String> {
+    text.parse()
+        .map_err(|_| format!("expected a number, not {}", text))
+}
@@ -0,0 +1,238 @@
+//! This module extracts the text of a game for translation, and merges the
+//! translated text back in.
+//!
+//! The text is exchanged as a gettext PO file, which translation tools
+//! understand, with each string keyed by its `msgctxt`: `room.N`,
+//! `message.N`, `item.N`, `verb.N` or `noun.N`.  Strings left untranslated
+//! keep their original text.
+//!
+//! Translated vocabulary is upper-cased and cut down to the game's word
+//! length, as the original words are, and an item's autograb name follows the
+//! noun it matched.  Since the interpreter only compares the first few letters
+//! of each word, the import warns when two words that meant different things
+//! can no longer be told apart.
+
+use std::collections::HashMap;
+
+use super::*;
+
+/// Returns the game's text as a PO file, ready to be translated.
+pub fn export(game: &Game) -> String {
+    let mut po =
+        String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    for (key, text) in strings(game) {
+        if !text.is_empty() {
+            po.push_str(&format!(
+                "\nmsgctxt {}\nmsgid {}\nmsgstr \"\"\n",
+                quote(&key),
+                quote(&text)
+            ));
+        }
+    }
+    po
+}
+
+/// Merges the translations in a PO file into the game, returning a warning
+/// for each problem that doesn't stop the game from being written.
+pub fn import(game: &mut Game, po: &str) -> Result<Vec<String>, String> {
+    let mut warnings = vec![];
+    let confused = confused_words(game);
+    let originals: HashMap<String, String> = strings(game).into_iter().collect();
+    for entry in parse_po(po)? {
+        let (key, text) = match (entry.context, entry.translation) {
+            (Some(key), Some(text)) if !text.is_empty() => (key, text),
+            _ => continue,
+        };
+        match originals.get(&key) {
+            None => warnings.push(format!("There is no string called {}", key)),
+            Some(original) if *original == text => {}
+            Some(_) => set_string(game, &key, text, &mut warnings),
+        }
+    }
+    for warning in confused_words(game) {
+        if !confused.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    Ok(warnings)
+}
+
+/// Returns every string in the game that the player may see, or type, along
+/// with its key.
+fn strings(game: &Game) -> Vec<(String, String)> {
+    let mut strings = vec![];
+    for (i, room) in game.rooms.iter().enumerate() {
+        strings.push((format!("room.{}", i), room.description.clone()));
+    }
+    for (i, message) in game.messages.iter().enumerate() {
+        strings.push((format!("message.{}", i), message.clone()));
+    }
+    for (i, item) in game.items.iter().enumerate() {
+        strings.push((format!("item.{}", i), item.description.clone()));
+    }
+    for (i, verb) in game.verbs.iter().enumerate() {
+        strings.push((format!("verb.{}", i), verb.word.clone()));
+    }
+    for (i, noun) in game.nouns.iter().enumerate() {
+        strings.push((format!("noun.{}", i), noun.word.clone()));
+    }
+    strings
+}
+
+/// Replaces the string with the given key, which is known to exist.
+fn set_string(game: &mut Game, key: &str, text: String, warnings: &mut Vec<String>) {
+    let (kind, index) = key.split_once('.').unwrap();
+    let index: usize = index.parse().unwrap();
+    let length = game.header.word_length as usize;
+    let word = || text.to_uppercase().chars().take(length).collect::<String>();
+    match kind {
+        "room" => game.rooms[index].description = text,
+        "message" => game.messages[index] = text,
+        "item" => {
+            let item = &mut game.items[index];
+            if item.is_treasure != text.starts_with('*') {
+                warnings.push(format!(
+                    "The translation of {} changes whether it is a treasure, which depends on \
+                     its starting with '*'",
+                    key
+                ));
+            }
+            item.is_treasure = text.starts_with('*');
+            item.description = text;
+        }
+        "verb" => game.verbs[index].word = word(),
+        _ => {
+            let word = word();
+            let old = std::mem::replace(&mut game.nouns[index].word, word.clone());
+            for item in &mut game.items {
+                if item
+                    .autograb
+                    .as_ref()
+                    .is_some_and(|name| truncate(name, length) == truncate(&old, length))
+                {
+                    item.autograb = Some(word.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Describes each word of the vocabulary that can't be told apart from a
+/// word with a different meaning, since only the first few letters of each
+/// word are compared.
+fn confused_words(game: &Game) -> Vec<String> {
+    let length = game.header.word_length as usize;
+    let mut confused = vec![];
+    for (kind, words) in [("verb", &game.verbs), ("noun", &game.nouns)] {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for group in group_words(words) {
+            let members = std::iter::once(&group.word).chain(&group.synonyms);
+            for word in members.filter(|word| !word.is_empty()) {
+                let index = *seen.entry(truncate(word, length)).or_insert(group.index);
+                if index != group.index {
+                    confused.push(format!(
+                        "The {} '{}' can't be told apart from {} {}",
+                        kind, word, kind, index
+                    ));
+                }
+            }
+        }
+    }
+    confused
+}
+
+/// Cuts a word down to the letters that the interpreter compares.
+fn truncate(word: &str, length: usize) -> String {
+    word.chars().take(length).collect::<String>().to_uppercase()
+}
+
+/// Quotes a string in the manner of a PO file.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reverses `quote`, returning None if the text isn't a quoted string.
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unquoted.push(ch);
+            continue;
+        }
+        match chars.next()? {
+            'n' => unquoted.push('\n'),
+            't' => unquoted.push('\t'),
+            ch => unquoted.push(ch),
+        }
+    }
+    Some(unquoted)
+}
+
+/// Holds one entry of a PO file.
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    context: Option<String>,
+    id: Option<String>,
+    translation: Option<String>,
+}
+
+/// Parses the entries of a PO file.  Comments are ignored, as are plural
+/// forms, which game text never needs.
+fn parse_po(po: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    let mut entry = Entry::default();
+    let mut keyword = "";
+    for (i, line) in po.lines().enumerate() {
+        let line = line.trim();
+        let error = || format!("Line {} of the translation can't be understood", i + 1);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // A string on a line of its own continues the one before.
+        let text = if line.starts_with('"') {
+            unquote(line).ok_or_else(error)?
+        } else {
+            let (word, rest) = line.split_once(' ').ok_or_else(error)?;
+            keyword = word;
+            let starts_entry = match keyword {
+                "msgctxt" => entry != Entry::default(),
+                "msgid" => entry.id.is_some(),
+                _ => false,
+            };
+            if starts_entry {
+                entries.push(std::mem::take(&mut entry));
+            }
+            let text = unquote(rest.trim()).ok_or_else(error)?;
+            match keyword {
+                "msgctxt" => entry.context = Some(String::new()),
+                "msgid" => entry.id = Some(String::new()),
+                "msgstr" => entry.translation = Some(String::new()),
+                _ => {}
+            }
+            text
+        };
+        let field = match keyword {
+            "msgctxt" => &mut entry.context,
+            "msgid" => &mut entry.id,
+            "msgstr" => &mut entry.translation,
+            _ => continue,
+        };
+        field.as_mut().ok_or_else(error)?.push_str(&text);
+    }
+    entries.push(entry);
+    Ok(entries)
+}
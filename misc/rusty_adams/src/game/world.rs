@@ -0,0 +1,250 @@
+//! This module encodes what item locations and room exits mean, so that the
+//! interpreter and anything that checks a game agree on them.
+//!
+//! An item's location is the room it is in, or the inventory if the player
+//! is carrying it.  Room 0 is the storeroom, which the player can never
+//! reach: items there are out of play, and an exit leading there is no exit
+//! at all.
+//!
+//! Every room has an exit for each of the six directions, in a fixed order,
+//! and nouns 1 to 6 are the same directions in the same order.
+
+use std::fmt::{Display, Error, Formatter};
+
+use super::ids::{NounId, RoomId};
+
+/// The location of an item that the player is carrying, as the game file
+/// stores it.
+pub const INVENTORY: i32 = -1;
+
+/// Where an item is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// Carried by the player.
+    Carried,
+    /// Lying in a room, or out of play in the storeroom.
+    Room(RoomId),
+    /// At a number that is neither a room nor the inventory, as a few of the
+    /// classic game files have.  The item is in play, but it can't be seen
+    /// until an action moves it somewhere else.
+    Elsewhere(i32),
+}
+
+impl Location {
+    /// The location of the items that are out of play.
+    pub const STOREROOM: Location = Location::Room(RoomId::STOREROOM);
+
+    /// Returns the location that the game file stores as the given number,
+    /// in a game with the given number of rooms.
+    pub fn new(location: i32, rooms: usize) -> Location {
+        match location {
+            INVENTORY => Location::Carried,
+            _ => RoomId::new(location, rooms).map_or(Location::Elsewhere(location), Location::Room),
+        }
+    }
+
+    /// Returns the number that the game file stores for the location.
+    pub fn to_i32(self) -> i32 {
+        match self {
+            Location::Carried => INVENTORY,
+            Location::Room(room) => room.to_i32(),
+            Location::Elsewhere(location) => location,
+        }
+    }
+}
+
+/// Is an item at this location being carried?
+pub fn is_carried(location: Location) -> bool {
+    location == Location::Carried
+}
+
+/// Is an item at this location lying in the given room, where it is listed
+/// when the room is described?
+pub fn is_in_room(location: Location, room: RoomId) -> bool {
+    location == Location::Room(room) && room != RoomId::STOREROOM
+}
+
+/// Is an item at this location at hand, either carried or in the given room?
+pub fn is_present(location: Location, room: RoomId) -> bool {
+    is_carried(location) || is_in_room(location, room)
+}
+
+/// Is an item at this location in play, rather than in the storeroom?
+pub fn is_in_play(location: Location) -> bool {
+    location != Location::STOREROOM
+}
+
+/// The directions that exits lead in, in the order that rooms list them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// Every direction, in the order that rooms list their exits.
+    pub const ALL: [Direction; 6] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    /// Looks up a direction by its name or by its first letter, in any case.
+    ///
+    /// ```
+    /// use rusty_adams::game::Direction;
+    ///
+    /// assert_eq!(Direction::from_name("north"), Some(Direction::North));
+    /// assert_eq!(Direction::from_name("D"), Some(Direction::Down));
+    /// assert_eq!(Direction::from_name("northeast"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|dir| {
+            dir.name().eq_ignore_ascii_case(name) || dir.name()[..1].eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// Returns the direction whose exit is at the given position in a room's
+    /// list, if there is one.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the direction that the given noun stands for, if it is one of
+    /// the six that do.
+    pub fn from_noun(noun: NounId) -> Option<Self> {
+        noun.index().checked_sub(1).and_then(Self::from_index)
+    }
+
+    /// Returns the direction's name, as the room descriptions give it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::North => "North",
+            Direction::South => "South",
+            Direction::East => "East",
+            Direction::West => "West",
+            Direction::Up => "Up",
+            Direction::Down => "Down",
+        }
+    }
+
+    /// Returns the direction that leads back the way this one came.
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+
+    /// Returns the position of the direction's exit in a room's list.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the noun that stands for the direction.
+    pub fn noun(self) -> NounId {
+        NounId(self.index() + 1)
+    }
+}
+
+impl Display for Direction {
+    /// Writes the direction's name.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Returns the exit that leads to the given room, which is no exit at all if
+/// the room is the storeroom.
+pub fn exit_to(room: RoomId) -> Option<RoomId> {
+    (room != RoomId::STOREROOM).then_some(room)
+}
+
+/// Can the player pick up another item while carrying this many?  A negative
+/// limit means there is no limit.
+pub fn can_carry(carried: usize, max_inventory: i32) -> bool {
+    max_inventory < 0 || carried < max_inventory as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locations() {
+        let room = |room| Location::Room(RoomId(room));
+        assert!(is_carried(Location::Carried));
+        assert!(!is_carried(room(3)));
+
+        assert!(is_in_room(room(3), RoomId(3)));
+        assert!(!is_in_room(Location::Carried, RoomId(3)));
+        assert!(!is_in_room(Location::STOREROOM, RoomId::STOREROOM));
+        assert!(!is_in_room(Location::Elsewhere(255), RoomId(3)));
+
+        assert!(is_present(Location::Carried, RoomId(3)));
+        assert!(is_present(room(3), RoomId(3)));
+        assert!(!is_present(room(4), RoomId(3)));
+        assert!(!is_present(Location::STOREROOM, RoomId::STOREROOM));
+
+        assert!(is_in_play(Location::Carried));
+        assert!(is_in_play(room(1)));
+        assert!(is_in_play(Location::Elsewhere(255)));
+        assert!(!is_in_play(Location::STOREROOM));
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(Location::new(INVENTORY, 5), Location::Carried);
+        assert_eq!(Location::new(0, 5), Location::STOREROOM);
+        assert_eq!(Location::new(4, 5), Location::Room(RoomId(4)));
+        assert_eq!(Location::new(5, 5), Location::Elsewhere(5));
+        for location in [INVENTORY, 0, 4, 5, 255] {
+            assert_eq!(Location::new(location, 5).to_i32(), location);
+        }
+    }
+
+    #[test]
+    fn test_exits() {
+        assert_eq!(exit_to(RoomId(1)), Some(RoomId(1)));
+        assert_eq!(exit_to(RoomId::STOREROOM), None);
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction::from_name("north"), Some(Direction::North));
+        assert_eq!(Direction::from_name("D"), Some(Direction::Down));
+        assert_eq!(Direction::from_name("w"), Some(Direction::West));
+        assert_eq!(Direction::from_name("nor"), None);
+        assert_eq!(Direction::from_name("i"), None);
+        for (i, dir) in Direction::ALL.into_iter().enumerate() {
+            assert_eq!(Direction::from_index(i), Some(dir));
+            assert_eq!(Direction::from_noun(dir.noun()), Some(dir));
+            assert_eq!(dir.opposite().opposite(), dir);
+        }
+        assert_eq!(Direction::from_index(6), None);
+        assert_eq!(Direction::from_noun(NounId(0)), None);
+        assert_eq!(Direction::from_noun(NounId(7)), None);
+        assert_eq!(Direction::Up.noun(), NounId(5));
+        assert_eq!(Direction::East.to_string(), "East");
+    }
+
+    #[test]
+    fn test_can_carry() {
+        assert!(can_carry(0, 1));
+        assert!(can_carry(5, 6));
+        assert!(!can_carry(6, 6));
+        assert!(!can_carry(0, 0));
+        assert!(can_carry(100, -1));
+    }
+}
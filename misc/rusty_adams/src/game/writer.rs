@@ -51,7 +51,7 @@ fn write_actions<W: Write>(writer: &mut W, actions: &[Action]) -> std::io::Resul
 
 /// Writes a single action.
 fn write_action<W: Write>(writer: &mut W, action: &Action) -> std::io::Result<()> {
-    writeln!(writer, " {} ", action.verb_index * 150 + action.noun_index)?;
+    writeln!(writer, " {} ", action.trigger.to_i32())?;
     for cond in action.conditions.iter() {
         write_condition(writer, cond)?;
     }
@@ -79,9 +79,9 @@ fn write_words<W: Write>(writer: &mut W, verbs: &[Word], nouns: &[Word]) -> std:
 /// Writes a single word.
 fn write_word<W: Write>(writer: &mut W, word: &Word) -> std::io::Result<()> {
     if word.is_synonym {
-        writeln!(writer, r#""*{}""#, word.word)
+        writeln!(writer, r#""*{}""#, escape(&word.word))
     } else {
-        writeln!(writer, r#""{}""#, word.word)
+        writeln!(writer, r#""{}""#, escape(&word.word))
     }
 }
 
@@ -96,19 +96,19 @@ fn write_rooms<W: Write>(writer: &mut W, rooms: &[Room]) -> std::io::Result<()>
 /// Writes a single room.
 fn write_room<W: Write>(writer: &mut W, room: &Room) -> std::io::Result<()> {
     for exit in room.exits.iter() {
-        writeln!(writer, " {} ", exit)?;
+        writeln!(writer, " {} ", exit.map_or(0, RoomId::to_i32))?;
     }
     if room.is_literal {
-        writeln!(writer, r#""*{}""#, room.description)
+        writeln!(writer, r#""*{}""#, escape(&room.description))
     } else {
-        writeln!(writer, r#""{}""#, room.description)
+        writeln!(writer, r#""{}""#, escape(&room.description))
     }
 }
 
 /// Writes all messages.
 fn write_messages<W: Write>(writer: &mut W, messages: &[String]) -> std::io::Result<()> {
     for message in messages.iter() {
-        writeln!(writer, r#""{}""#, message)?
+        writeln!(writer, r#""{}""#, escape(message))?
     }
     Ok(())
 }
@@ -128,14 +128,14 @@ fn write_item<W: Write>(writer: &mut W, item: &Item) -> std::io::Result<()> {
     } else {
         item.description.clone()
     };
-    writeln!(writer, r#""{}" {} "#, description, item.location)
+    writeln!(writer, r#""{}" {} "#, escape(&description), item.location.to_i32())
 }
 
 /// Writes all comments.
 fn write_comments<W: Write>(writer: &mut W, actions: &[Action]) -> std::io::Result<()> {
     for action in actions.iter() {
         match &action.comment {
-            Some(comment) => writeln!(writer, r#""{}""#, comment)?,
+            Some(comment) => writeln!(writer, r#""{}""#, escape(comment))?,
             None => writeln!(writer, r#""""#)?,
         }
     }
@@ -149,3 +149,21 @@ fn write_footer<W: Write>(writer: &mut W, footer: &Footer) -> std::io::Result<()
     writeln!(writer, " {} ", footer.magic)?;
     Ok(())
 }
+
+/// Escapes a string so that the tokenizer reads it back unchanged.  Double
+/// quotes become backticks, as in the original games, and backticks and
+/// backslashes are escaped with a backslash.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push('`'),
+            '`' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,156 @@
+//! This module keeps the commands that the player has typed into each game,
+//! as a shell keeps its history, so that they can be listed with `HISTORY`
+//! and recalled with the up arrow in later sessions as well as this one.
+//!
+//! A game's history is kept in a file named for its adventure number, such
+//! as `history01.txt` for Adventureland, in the data directory that
+//! `appdirs` finds.  It has one command to a line, the oldest first, and
+//! only the last `LIMIT` are kept.
+
+use appdirs::AppDirs;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The most commands that are kept for a game.
+pub const LIMIT: usize = 500;
+
+/// The commands typed into a game, which are written to its history file as
+/// they are added, if it has one.
+///
+/// ```
+/// use rusty_adams::history::History;
+///
+/// let mut history = History::new();
+/// history.push("get lamp")?;
+/// history.push("get lamp")?;
+/// history.push("light lamp")?;
+/// assert_eq!(history.list(), "1  get lamp\n2  light lamp\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    commands: Vec<String>,
+    file: Option<PathBuf>,
+}
+
+/// Returns the name of the history file for the given adventure number.
+pub fn file_name(adventure: i32) -> String {
+    format!("history{:02}.txt", adventure)
+}
+
+impl History {
+    /// Returns an empty history that is only kept for this session.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the history kept in the given file, which needn't exist yet.
+    /// A file that has grown past `LIMIT` commands is cut back to them.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let mut history = History {
+            commands: text.lines().map(str::to_string).collect(),
+            file: Some(path.to_path_buf()),
+        };
+        if history.commands.len() > LIMIT {
+            history.commands.drain(..history.commands.len() - LIMIT);
+            let mut text = history.commands.join("\n");
+            text.push('\n');
+            fs::write(path, text)?;
+        }
+        Ok(history)
+    }
+
+    /// Returns the history of the game with the given adventure number, or
+    /// an empty one if its file can't be read.
+    pub fn for_adventure(adventure: i32) -> Self {
+        let path = AppDirs::new("rusty_adams").data_file(&file_name(adventure));
+        Self::open(&path).unwrap_or_default()
+    }
+
+    /// Returns the commands, the oldest first.
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Adds a command to the end of the history, and to its file.  Blank
+    /// commands, and the same command twice in a row, aren't added.
+    pub fn push(&mut self, command: &str) -> io::Result<()> {
+        let command = command.trim();
+        if command.is_empty() || self.commands.last().is_some_and(|last| last == command) {
+            return Ok(());
+        }
+        self.commands.push(command.to_string());
+        if self.commands.len() > LIMIT {
+            self.commands.remove(0);
+        }
+        match &self.file {
+            Some(path) => {
+                AppDirs::prepare(path)?;
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", command)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the reply to `HISTORY`: the commands, numbered as a shell
+    /// numbers them.
+    pub fn list(&self) -> String {
+        if self.commands.is_empty() {
+            return "No commands yet.\n".to_string();
+        }
+        let width = self.commands.len().to_string().len();
+        self.commands
+            .iter()
+            .enumerate()
+            .map(|(i, command)| format!("{:>width$}  {}\n", i + 1, command))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_history() {
+        assert_eq!(file_name(1), "history01.txt");
+        assert_eq!(History::new().list(), "No commands yet.\n");
+
+        let dir = env::temp_dir().join(format!("history-test-{}", process::id()));
+        let path = dir.join(file_name(3));
+        let mut history = History::open(&path).unwrap();
+        for command in ["go north", " ", "get lamp", "get lamp", "light lamp"] {
+            history.push(command).unwrap();
+        }
+        assert_eq!(history.commands(), ["go north", "get lamp", "light lamp"]);
+
+        // The next session carries on from this one.
+        let mut history = History::open(&path).unwrap();
+        history.push("score").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "go north\nget lamp\nlight lamp\nscore\n"
+        );
+        for i in 0..8 {
+            history.push(&format!("wait {}", i)).unwrap();
+        }
+        assert!(history.list().starts_with(" 1  go north\n 2  get lamp\n"));
+        assert!(history.list().ends_with("\n12  wait 7\n"));
+
+        let lines: Vec<String> = (0..LIMIT + 20).map(|i| i.to_string()).collect();
+        fs::write(&path, lines.join("\n")).unwrap();
+        let history = History::open(&path).unwrap();
+        assert_eq!(history.commands(), &lines[20..]);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), LIMIT);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
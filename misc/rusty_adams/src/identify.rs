@@ -0,0 +1,78 @@
+//! This module recognizes the known releases of the games from their content,
+//! so that a game file can be identified whatever it happens to be called.
+//!
+//! A game's hash is taken over the game as it would be written back out,
+//! with all whitespace removed, so that files differing only in line endings,
+//! padding, or the spacing of numbers are recognized as the same release.
+//! Any change to the game itself, even to a single message, gives a different
+//! hash.
+
+use crate::game::{writer, Game};
+use sha2::{Digest, Sha256};
+use std::fmt::{Display, Error, Formatter};
+
+/// Describes a known release of a game.
+#[derive(Debug, PartialEq)]
+pub struct Release {
+    /// The hash of the release's content, as `content_hash` returns it.
+    pub hash: &'static str,
+    /// The game's title.
+    pub title: &'static str,
+    /// The version that the release gives, if any.
+    pub version: Option<&'static str>,
+    /// Where the release comes from, such as the platform it was converted
+    /// from.
+    pub source: &'static str,
+}
+
+impl Display for Release {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.title)?;
+        if let Some(version) = self.version {
+            write!(f, " v{}", version)?;
+        }
+        write!(f, " ({})", self.source)
+    }
+}
+
+/// The releases that can be recognized, which are those of the Scott Adams
+/// collection (with the platform each was converted from) and the demo game.
+#[rustfmt::skip]
+pub static RELEASES: [Release; 19] = [
+    Release { hash: "d9e61fe58af8909743e236c50657b8661b0716f312ff6e4afca5454841f11b9f", title: "Adventureland", version: Some("4.16"), source: "TRS-80 release" },
+    Release { hash: "4cc415c636d77f227b43bb4678edcb2334b1f14be28cc64114666b15535c30b8", title: "Pirate Adventure", version: Some("4.08"), source: "TRS-80 release" },
+    Release { hash: "28a232ca2797c5fe3c41e7920c890db36e44a47b223cbc89bb791a806aa0cb47", title: "Secret Mission", version: Some("3.06"), source: "TRS-80 release" },
+    Release { hash: "fba609f8e89a6f7387e7f5aaee253983b1202728f27a8a81af228c6dc9feca6e", title: "Voodoo Castle", version: Some("1.19"), source: "Apple II release" },
+    Release { hash: "ad92fe4531e6caba92c7af80e13d4bf39ba621da91c887268e7c8707c715e0fb", title: "The Count", version: Some("1.15"), source: "TRS-80 release" },
+    Release { hash: "302011ccf2f4f2badeadc42b6986b3286ab17c6e0e2349c230ae1973abdba32f", title: "Strange Odyssey", version: Some("1.19"), source: "TRS-80 release" },
+    Release { hash: "dd69197c8cfa71e660035ac84afd3fec9d4804b5a2c1833317d347e94624ae5a", title: "Mystery Fun House", version: Some("1.45"), source: "TRS-80 release" },
+    Release { hash: "e213e055e17a7e4f0cac76912382264b78f8a6160592d45a12002bfa904ad2a5", title: "Pyramid of Doom", version: Some("1.25"), source: "Apple II release" },
+    Release { hash: "d940cc6be3f77e1222c53168558e333468fd2114c58d5e97b54afa9d3eabb312", title: "Ghost Town", version: Some("2.23"), source: "TRS-80 release" },
+    Release { hash: "59270044459f51a08bbbc0e895ca63d386f53f584b999deb7c40790eaca6cf47", title: "Savage Island, Part I", version: Some("1.23"), source: "TRS-80 release" },
+    Release { hash: "1759d1f460b84f6e7fba2916c6c9c744c52801b615310ba7dacd6855642d5038", title: "Savage Island, Part II", version: Some("1.33"), source: "TRS-80 release" },
+    Release { hash: "1dc5da9ca046b7d6e0493ea3a866c9e49a5ed93c75935ab20489b0409b242127", title: "The Golden Voyage", version: Some("1.84"), source: "TRS-80 release" },
+    Release { hash: "bbb38774deb06fca2b8cecd7dc61815f6ed0e68d3ccc32f4b6b08c50a0bb81c2", title: "Sorcerer of Claymorgue Castle", version: Some("1.26"), source: "TRS-80 release" },
+    Release { hash: "a8c5db636d1f5931b8d851215f87f1505f87e2c37c7170bf229d431732dccb03", title: "Return to Pirate's Isle", version: None, source: "TI-99/4A release" },
+    Release { hash: "7aac79012b53aebc6b913f51e1ecdd737061b27472d18d27a34800574ca692a0", title: "Buckaroo Banzai", version: None, source: "ZX Spectrum release" },
+    Release { hash: "91e2bd6a5a96a5c3a948693ed2be0b9d425bca84fd9bdb31034c6de31ae5761f", title: "The Hulk", version: Some("1.27"), source: "TRS-80 release" },
+    Release { hash: "91ed148737ee9bf2b96a4dbb6472b21e696236fd1f6f1f524eca29c48e0f64a6", title: "Spiderman", version: None, source: "Commodore 64 release" },
+    Release { hash: "b5f1670ba1e72c6514532d9e19f305b77b9e70dc005dc629b7a7b35509853808", title: "Adventureland (sampler version)", version: Some("1.01"), source: "Apple II release" },
+    Release { hash: "e9eedcaf7bde26668d38d249f5258c38a08e80cfd0deb2d600a6667ab45ff13d", title: "The Lighthouse", version: Some("1.00"), source: "rusty_adams demo" },
+];
+
+/// Returns the hash of a game's content, in hex.
+pub fn content_hash(game: &Game) -> String {
+    let mut written: Vec<u8> = Vec::new();
+    writer::write_game(&mut written, game).expect("writing to memory can't fail");
+    written.retain(|byte| !byte.is_ascii_whitespace());
+    Sha256::digest(&written)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the known release that a game matches, if any.
+pub fn identify(game: &Game) -> Option<&'static Release> {
+    let hash = content_hash(game);
+    RELEASES.iter().find(|release| release.hash == hash)
+}
@@ -1,25 +1,276 @@
 //! We put most of our functionality here so that we can write integration
 //! without difficulty.
+//!
+//! A game is loaded from a game file, or from the bytes of one, and played by
+//! an `Interpreter`, which returns what the game says for the caller to show
+//! however it likes:
+//!
+//! ```
+//! use rusty_adams::game::interpreter::Interpreter;
+//!
+//! let mut interpreter = Interpreter::new(rusty_adams::load_demo()?);
+//! let opening = interpreter.start();
+//! assert!(opening.contains("Obvious exits:"));
+//! assert!(interpreter.command("inventory").starts_with("I'm carrying:"));
+//! # Ok::<(), String>(())
+//! ```
+//!
+//! The examples in this documentation play the tiny games of the
+//! conformance suite, which ship inside the crate with the `test-fixtures`
+//! feature; see `fixtures`.
+//!
+//! Without any features, which is the default, the crate only parses and
+//! plays games.  The terminal, the catalogue and the other frontends each
+//! have a feature of their own, and `cli` turns on everything the program
+//! needs.
 
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
+#[macro_use]
+mod log;
+
+#[cfg(feature = "console")]
+pub mod banner;
+#[cfg(feature = "bot")]
+pub mod bot;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+#[cfg(feature = "console")]
+pub mod console;
+#[cfg(feature = "catalog")]
+pub mod corpus;
+#[cfg(feature = "test-fixtures")]
+pub mod fixtures;
+pub mod format;
 pub mod game;
-mod tokenizer;
+#[cfg(feature = "console")]
+pub mod history;
+#[cfg(feature = "identify")]
+pub mod identify;
+#[cfg(feature = "mail")]
+pub mod mail;
+#[cfg(all(feature = "console", feature = "serde"))]
+pub mod session;
+#[cfg(feature = "sidecar")]
+pub mod sidecar;
+pub mod tokenizer;
+#[cfg(all(feature = "console", feature = "serde"))]
+pub mod transcript;
+#[cfg(feature = "walkthrough")]
+pub mod walkthrough;
 
-/// Loads a game from the given path.
+/// Loads a game from the given path.  A path ending in `.sck` is compiled
+/// from ScottKit source rather than read as a game file.
 pub fn load_game(path: &str) -> Result<game::Game, String> {
-    let data = match fs::read(path) {
-        Ok(data) => data,
-        Err(err) => return Err(format!("Error: {}", err)),
-    };
+    if path.ends_with(".sck") {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => return Err(format!("Error: {}", err)),
+        };
+        return game::scottkit::compile(&source).map_err(|err| format!("{}: {}", path, err));
+    }
+    let mut stream = read_tokens(path)?;
+    match game::Game::new(&mut stream) {
+        Ok(game) => Ok(game),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// The demo game, a small original adventure that ships inside the binary so
+/// that there is something to play without the commercial games.  Like the
+/// rest of this repository, it is dedicated to the public domain.
+pub const DEMO: &[u8] = include_bytes!("../demo/lighthouse.dat");
+
+/// Loads the demo game.
+pub fn load_demo() -> Result<game::Game, String> {
+    load_game_from_bytes(DEMO)
+}
 
-    let mut stream = match tokenizer::Stream::new(data) {
+/// Loads a game from the contents of a game file, such as one built into
+/// the program.
+///
+/// ```
+/// let fixture = rusty_adams::fixtures::find("get_drop").unwrap();
+/// let game = rusty_adams::load_game_from_bytes(fixture.data)?;
+/// assert_eq!(game.room_description(1), Some("small room"));
+/// assert!(rusty_adams::load_game_from_bytes(b"0 0 0").is_err());
+/// # Ok::<(), String>(())
+/// ```
+pub fn load_game_from_bytes(data: &[u8]) -> Result<game::Game, String> {
+    let mut stream = match tokenizer::Stream::new(data.to_vec()) {
         Ok(stream) => stream,
         Err(err) => return Err(err.to_string()),
     };
-
     match game::Game::new(&mut stream) {
         Ok(game) => Ok(game),
         Err(err) => Err(err.to_string()),
     }
 }
+
+/// Loads a game from the given path, checking it strictly, and returns it
+/// along with any warnings about problems in the game file.
+pub fn load_game_strict(path: &str) -> Result<(game::Game, Vec<String>), String> {
+    let mut stream = read_tokens(path)?;
+    match game::Game::new_strict(&mut stream) {
+        Ok(result) => Ok(result),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Loads a game from the given path as `load_game` does, but if the game file
+/// is damaged or ends too soon, returns whatever could be parsed of it along
+/// with the error.  Damage after the footer is ignored, since the game itself
+/// is whole.
+pub fn load_game_partial(path: &str) -> Result<game::Game, Box<game::PartialGame>> {
+    if path.ends_with(".sck") {
+        return load_game(path).map_err(|err| Box::new(game::PartialGame::nothing(err)));
+    }
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) => return Err(Box::new(game::PartialGame::nothing(format!("Error: {}", err)))),
+    };
+    let (mut stream, token_err) = tokenizer::Stream::new_partial(data);
+    match game::Game::new_partial(&mut stream) {
+        Ok(game) => Ok(game),
+        // The parser ran out of tokens where the tokenizer found the damage,
+        // which is the better thing to report.
+        Err(mut partial) => {
+            if let Some(err) = token_err {
+                partial.set_error(err.to_string());
+            }
+            Err(partial)
+        }
+    }
+}
+
+/// Reads only the header of the game file at the given path, returning the
+/// sizes of the game's tables.
+pub fn load_header(path: &str) -> Result<game::HeaderCounts, String> {
+    let mut stream = read_tokens(path)?;
+    match game::Game::read_header(&mut stream) {
+        Ok(counts) => Ok(counts),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Reads the game file at the given path as a stream of tokens.
+fn read_tokens(path: &str) -> Result<tokenizer::Stream, String> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) => return Err(format!("Error: {}", err)),
+    };
+
+    match tokenizer::Stream::new(data) {
+        Ok(stream) => Ok(stream),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Lists the game files (those ending in `.dat`) in the given directory,
+/// sorted by name.
+pub fn list_games(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut games = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "dat") {
+            games.push(path);
+        }
+    }
+    games.sort();
+    Ok(games)
+}
+
+/// Plays the game at the given path on the terminal, until the player quits or
+/// there is no more input.
+#[cfg(feature = "console")]
+pub fn run(path: &str) -> Result<(), String> {
+    let mut interpreter = game::interpreter::Interpreter::new(load_game(path)?);
+    play(
+        &mut interpreter,
+        &mut format::Formatter::unwrapped(),
+        &mut console::Stdio::new(),
+    )
+}
+
+/// Starts a game and plays it on the given console, until the player quits or
+/// there is no more input.  All of the output is wrapped by the given
+/// formatter.  Games are saved to the file that the player names when asked.
+/// If the console keeps a history, each command is added to it, and
+/// `HISTORY` lists it, except in purist mode.  The listing is about the
+/// session rather than the game, so neither it nor the command is
+/// transcribed.
+///
+/// ```
+/// use rusty_adams::console::Dumb;
+/// use rusty_adams::format::Formatter;
+///
+/// let fixture = rusty_adams::fixtures::find("get_drop").unwrap();
+/// let mut interpreter = fixture.interpreter()?;
+/// let mut output = Vec::new();
+/// let mut console = Dumb::new(&b"get rock\nquit\n"[..], &mut output, 0);
+/// rusty_adams::play(&mut interpreter, &mut Formatter::unwrapped(), &mut console)?;
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("Tell me what to do ? It's too heavy to lift.\r\n"));
+/// # Ok::<(), String>(())
+/// ```
+#[cfg(feature = "console")]
+pub fn play(
+    interpreter: &mut game::interpreter::Interpreter,
+    formatter: &mut format::Formatter,
+    console: &mut dyn console::Console,
+) -> Result<(), String> {
+    let error = |err: io::Error| format!("Error: {}", err);
+    let read_line = |console: &mut dyn console::Console,
+                     formatter: &mut format::Formatter,
+                     prompt: &str| {
+        console.write(&formatter.format(prompt)).map_err(error)?;
+        formatter.end_line();
+        console.read_line().map_err(error)
+    };
+    let reply = |console: &mut dyn console::Console,
+                 formatter: &mut format::Formatter,
+                 text: &str| {
+        console.transcribe(console::Role::Engine, text)?;
+        console.write(&formatter.format(text))
+    };
+
+    reply(console, formatter, &interpreter.start()).map_err(error)?;
+    while !interpreter.is_finished() {
+        let line = match read_line(console, formatter, "\nTell me what to do ? ")? {
+            Some(line) => line,
+            None => break,
+        };
+        // The history is a convenience, and the game goes on without it.
+        let listing = match console.history() {
+            Some(history) => {
+                let _ = history.push(&line);
+                let asked = line.trim().eq_ignore_ascii_case("HISTORY");
+                (asked && !interpreter.is_purist()).then(|| history.list())
+            }
+            None => None,
+        };
+        if let Some(listing) = listing {
+            console.write(&formatter.format(&listing)).map_err(error)?;
+            continue;
+        }
+        console
+            .transcribe(console::Role::Player, &line)
+            .map_err(error)?;
+        let text = interpreter.command(&line);
+        reply(console, formatter, &text).map_err(error)?;
+        if interpreter.take_save_request() {
+            let name = match read_line(console, formatter, "Filename: ")? {
+                Some(name) => name,
+                None => break,
+            };
+            let result = match fs::write(name.trim(), interpreter.save()) {
+                Ok(()) => "Saved.\n",
+                Err(_) => "Unable to create save file.\n",
+            };
+            console.write(&formatter.format(result)).map_err(error)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! This module provides the macros that the parser and interpreter use to
+//! report what they are doing.  With the `tracing` feature, they forward to
+//! the `tracing` crate, so that embedders can collect the diagnostics with a
+//! subscriber of their own; without it, they compile to nothing.
+//!
+//! Arguments aren't evaluated when the feature is off, so they must not have
+//! side effects.
+
+/// Reports a low-level event, such as reading a single token.
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($($arg:tt)*) => { ::tracing::trace!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {};
+}
+
+/// Reports a higher-level event, such as an action running.
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg:tt)*) => { ::tracing::debug!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {};
+}
+
+/// Enters a span that lasts until the end of the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($arg:tt)*) => {
+        let _span = ::tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($arg:tt)*) => {};
+}
+
+/// Evaluates an expression that parses one section of a game file, inside a
+/// span for the section, and reports how long it took.
+#[cfg(feature = "tracing")]
+macro_rules! timed {
+    ($section:expr, $parse:expr) => {{
+        let _span = ::tracing::debug_span!("section", name = $section).entered();
+        let start = ::std::time::Instant::now();
+        let result = $parse;
+        ::tracing::debug!(elapsed = ?start.elapsed(), "parsed the {}", $section);
+        result
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! timed {
+    ($section:expr, $parse:expr) => {
+        $parse
+    };
+}
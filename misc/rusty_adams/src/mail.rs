@@ -0,0 +1,138 @@
+//! This module plays games by mail: one command at a time, with the game
+//! saved in a file between commands, so that nothing needs to keep running
+//! while the player thinks.  Whatever relays the commands, whether email,
+//! text messages or a chat bot, only has to run `rusty_adams turn` with the
+//! save file and the command, and send back what it prints.
+//!
+//! A save file for play by mail is an ordinary save after a short header,
+//! which names the game file so that the command doesn't have to:
+//!
+//! ```text
+//! rusty_adams turn
+//! game /home/me/games/adv01.dat
+//! rusty_adams save 3
+//! 0 0
+//! ...
+//! ```
+//!
+//! Once the game is over, the header says so with an `over` line, and any
+//! further command is refused.  The state of the dice that decide the
+//! automatic actions is saved with the rest of the game, so a game by mail
+//! goes the same way as one played straight through with the same commands
+//! from the same start.
+
+use std::path::PathBuf;
+
+use crate::game::interpreter::Interpreter;
+
+/// The first line of every save file for play by mail.
+pub const HEADER: &str = "rusty_adams turn";
+
+/// A game being played by mail, as kept in its save file.
+#[derive(Debug, PartialEq)]
+pub struct Mailbox {
+    /// The game file.
+    pub game: PathBuf,
+    /// Set once the game is over.
+    pub finished: bool,
+    /// The state of the game, as `Interpreter::save` writes it.
+    pub state: String,
+}
+
+impl Mailbox {
+    /// Parses the contents of a save file.
+    pub fn parse(text: &str) -> Result<Mailbox, String> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim_end) != Some(HEADER) {
+            return Err("This isn't a game being played by mail".to_string());
+        }
+        let game = match lines.next().and_then(|line| line.strip_prefix("game ")) {
+            Some(game) => PathBuf::from(game.trim_end()),
+            None => return Err("The saved game doesn't say which game it is".to_string()),
+        };
+        let mut state: Vec<&str> = lines.collect();
+        let finished = state.first().is_some_and(|line| line.trim_end() == "over");
+        if finished {
+            state.remove(0);
+        }
+        Ok(Mailbox {
+            game,
+            finished,
+            state: state.join("\n") + "\n",
+        })
+    }
+
+    /// Returns the contents of the save file.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\ngame {}\n", HEADER, self.game.display());
+        if self.finished {
+            text.push_str("over\n");
+        }
+        text + &self.state
+    }
+}
+
+/// Starts the given game, which was loaded from the given file, returning
+/// the opening text and the game to save.
+pub fn start(interpreter: &mut Interpreter, game: PathBuf) -> (String, Mailbox) {
+    let text = interpreter.start();
+    interpreter.take_save_request();
+    let mailbox = Mailbox {
+        game,
+        finished: interpreter.is_finished(),
+        state: interpreter.save(),
+    };
+    (text, mailbox)
+}
+
+/// Restores the game in the mailbox to the given interpreter, which must be
+/// playing the same game, carries out one command, and updates the mailbox
+/// with the result.  Returns what the game said.
+pub fn turn(
+    interpreter: &mut Interpreter,
+    mailbox: &mut Mailbox,
+    command: &str,
+) -> Result<String, String> {
+    if mailbox.finished {
+        return Err("The game is over.".to_string());
+    }
+    interpreter.restore(&mailbox.state)?;
+    let text = interpreter.command(command);
+    // Every turn is saved anyway.
+    interpreter.take_save_request();
+    mailbox.finished = interpreter.is_finished();
+    mailbox.state = interpreter.save();
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        let demo = || Interpreter::new(crate::load_demo().unwrap());
+        let (text, mut mailbox) = start(&mut demo(), PathBuf::from("demo.dat"));
+        assert!(text.starts_with("I'm in a sandy cove"));
+
+        // Each turn starts from what was saved, not from the interpreter.
+        turn(&mut demo(), &mut mailbox, "go north").unwrap();
+        let mut mailbox = Mailbox::parse(&mailbox.to_text()).unwrap();
+        assert_eq!(mailbox.game, PathBuf::from("demo.dat"));
+        let mut interpreter = demo();
+        turn(&mut interpreter, &mut mailbox, "look").unwrap();
+        assert_eq!(interpreter.room().index(), 2);
+
+        turn(&mut demo(), &mut mailbox, "quit").unwrap();
+        assert!(mailbox.finished);
+        let mut mailbox = Mailbox::parse(&mailbox.to_text()).unwrap();
+        assert!(mailbox.finished);
+        assert_eq!(
+            turn(&mut demo(), &mut mailbox, "look"),
+            Err("The game is over.".to_string())
+        );
+
+        assert!(Mailbox::parse("0 0\n").is_err());
+        assert!(Mailbox::parse("rusty_adams turn\n0 0\n").is_err());
+    }
+}
@@ -1,8 +1,1197 @@
-fn main() {
-    let game = match rusty_adams::load_game("games/adv01.dat") {
-        Ok(game) => game,
-        Err(err) => panic!("{}", err.to_string()),
+mod scottfree;
+
+use clap::{Parser, Subcommand};
+use rusty_adams::banner;
+use rusty_adams::bot::{self, Bot, Strategy};
+use rusty_adams::catalog;
+use rusty_adams::console::{self, Backend, Console};
+use rusty_adams::corpus;
+use rusty_adams::format::{self, Breaking, Formatter, Perspective};
+use rusty_adams::game::interpreter::hint::HintLevel;
+use rusty_adams::game::interpreter::{Interpreter, OccurrencePolicy};
+use rusty_adams::game::map;
+use rusty_adams::game::{scottkit, strings, writer, Direction, Game, PartialGame};
+use rusty_adams::identify;
+use rusty_adams::history::History;
+use rusty_adams::mail;
+use rusty_adams::session::{self, Chart};
+use rusty_adams::sidecar::{self, Overrides};
+use rusty_adams::transcript::{self, Recorder};
+use rusty_adams::walkthrough;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Plays Scott Adams adventures.
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// A directory to search for games before the rest of the games path
+    #[arg(long, global = true, value_name = "DIR")]
+    games_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a game
+    Play {
+        /// The game: a path, an adventure number, or all or part of a title
+        #[arg(required_unless_present = "demo")]
+        game: Option<String>,
+        /// Play the demo game that is built in, instead of a game file
+        #[arg(long, conflicts_with = "game")]
+        demo: bool,
+        /// When the automatic actions run: percent (by the chance each one
+        /// gives, the default), always, or never
+        #[arg(long, value_name = "POLICY", value_parser = parse_policy)]
+        occurrences: Option<OccurrencePolicy>,
+        /// Wrap the output to a screen of 40, 64 or 80 columns, as the Apple
+        /// II, the TRS-80 or ScottFree did
+        #[arg(long, value_name = "N", value_parser = parse_columns)]
+        columns: Option<usize>,
+        /// Speak of the player as "I" (first, the default), as "you"
+        /// (second), or as ScottFree does with -y (scottfree)
+        #[arg(long, value_name = "PERSON", value_parser = parse_perspective)]
+        perspective: Option<Perspective>,
+        /// Play without modern conveniences such as GET ALL, as the game was
+        /// first played
+        #[arg(long, conflicts_with = "occurrences")]
+        purist: bool,
+        /// Play with the modern conveniences, even if the game's settings
+        /// turn them off
+        #[arg(long, conflicts_with = "purist")]
+        no_purist: bool,
+        /// How much HINT may give away: off, nudge (only whether something
+        /// here helps), verb, or full (the whole command, the default)
+        #[arg(long, value_name = "LEVEL", value_parser = parse_hints, conflicts_with = "purist")]
+        hints: Option<HintLevel>,
+        /// Play on a dumb terminal or teletype, with no escape sequences and
+        /// a --more-- prompt after each screenful
+        #[arg(long)]
+        dumb: bool,
+        /// The number of lines on the dumb terminal's screen, or 0 never to
+        /// stop for --more--
+        #[arg(long, value_name = "N", requires = "dumb")]
+        lines: Option<usize>,
+        /// How to drive the terminal: stdio (plain standard input and
+        /// output), or crossterm (which also suits the Windows console).  By
+        /// default crossterm is used on a terminal and stdio otherwise
+        #[arg(long, value_name = "NAME", value_parser = parse_backend, conflicts_with = "dumb")]
+        backend: Option<Backend>,
+        /// Write a transcript of the game to a file, as JSON Lines
+        #[arg(long, value_name = "FILE")]
+        transcript: Option<PathBuf>,
+        /// Start without showing the game's banner
+        #[arg(long)]
+        no_banner: bool,
+        /// Warn, as the game is played, about what looks like a mistake in
+        /// it, such as an exit to room 0 or an action that gets an item that
+        /// isn't there
+        #[arg(long)]
+        author: bool,
+    },
+    /// Replay a transcript of a game, checking that the game says the same
+    /// things in reply to the same commands
+    Replay {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The transcript, as written by `play --transcript`
+        transcript: PathBuf,
+        /// When the automatic actions run: percent (by the chance each one
+        /// gives, the default), always, or never
+        #[arg(long, value_name = "POLICY", value_parser = parse_policy)]
+        occurrences: Option<OccurrencePolicy>,
+        /// Speak of the player as "I" (first, the default), as "you"
+        /// (second), or as ScottFree does with -y (scottfree)
+        #[arg(long, value_name = "PERSON", value_parser = parse_perspective)]
+        perspective: Option<Perspective>,
+        /// Play without modern conveniences, as the game was first played
+        #[arg(long, conflicts_with = "occurrences")]
+        purist: bool,
+        /// Play with the modern conveniences, even if the game's settings
+        /// turn them off
+        #[arg(long, conflicts_with = "purist")]
+        no_purist: bool,
+    },
+    /// Play a game from a walkthrough, and fail unless the game says what is
+    /// expected of it, to check that a game can be won
+    Check {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The walkthrough: one command to a line, skipping blank lines and
+        /// lines starting with #
+        #[arg(long, value_name = "FILE")]
+        script: PathBuf,
+        /// Text that the game must say at some point, such as "stored 13
+        /// treasures"; give it more than once to expect more than one
+        #[arg(long, value_name = "TEXT", required = true)]
+        expect: Vec<String>,
+        /// When the automatic actions run: percent (by the chance each one
+        /// gives, the default), always, or never
+        #[arg(long, value_name = "POLICY", value_parser = parse_policy)]
+        occurrences: Option<OccurrencePolicy>,
+        /// Play without modern conveniences, as the game was first played
+        #[arg(long, conflicts_with = "occurrences")]
+        purist: bool,
+        /// Play with the modern conveniences, even if the game's settings
+        /// turn them off
+        #[arg(long, conflicts_with = "purist")]
+        no_purist: bool,
+    },
+    /// Replay a transcript of a game and print a timeline of the rooms
+    /// visited, the items gained and lost, the deaths, and the flags changed
+    AnalyzeSession {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The transcript, as written by `play --transcript`
+        transcript: PathBuf,
+        /// How to draw the timeline: mermaid (the default) or dot
+        #[arg(long, value_name = "CHART", default_value = "mermaid", value_parser = parse_chart)]
+        chart: Chart,
+        /// When the automatic actions run: percent (by the chance each one
+        /// gives, the default), always, or never
+        #[arg(long, value_name = "POLICY", value_parser = parse_policy)]
+        occurrences: Option<OccurrencePolicy>,
+        /// Speak of the player as "I" (first, the default), as "you"
+        /// (second), or as ScottFree does with -y (scottfree)
+        #[arg(long, value_name = "PERSON", value_parser = parse_perspective)]
+        perspective: Option<Perspective>,
+        /// Play without modern conveniences, as the game was first played
+        #[arg(long, conflicts_with = "occurrences")]
+        purist: bool,
+        /// Play with the modern conveniences, even if the game's settings
+        /// turn them off
+        #[arg(long, conflicts_with = "purist")]
+        no_purist: bool,
+    },
+    /// Play one turn of a game by mail: restore it from a save file, carry
+    /// out one command, print the reply, and save the game again
+    Turn {
+        /// The save file for the game
+        save: PathBuf,
+        /// The command to carry out
+        #[arg(required_unless_present = "game")]
+        command: Option<String>,
+        /// Start a new game in the save file, replacing whatever was there:
+        /// a path, an adventure number, or all or part of a title
+        #[arg(long)]
+        game: Option<String>,
+    },
+    /// Print a parsed game for debugging
+    Dump {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// Check the game file strictly, warning about each problem found
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print a game's vocabulary, with synonyms grouped together
+    Vocab {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+    },
+    /// Print the exits from each room of a game, warning about any that
+    /// have no exit back the opposite way
+    Map {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+    },
+    /// Identify a game file as one of the known releases, and print its
+    /// details
+    Info {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+    },
+    /// List the games in a directory, with details of each
+    List {
+        /// The directory to look in
+        dir: PathBuf,
+        /// Print the list as JSON instead of as a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Measure every game in a directory, and add up how they use their
+    /// vocabularies, as CSV
+    CorpusStats {
+        /// The directory to look in
+        dir: PathBuf,
+        /// Print the table of words rather than the table of games
+        #[arg(long)]
+        words: bool,
+        /// Print both tables as JSON instead
+        #[arg(long, conflicts_with = "words")]
+        json: bool,
+    },
+    /// Have a bot play every game in a directory, reporting any turn on which
+    /// the interpreter panicked or gave up
+    Soak {
+        /// The directory to look in
+        dir: PathBuf,
+        /// The number of turns to play in each game
+        #[arg(long, value_name = "N", default_value_t = 1000)]
+        turns: usize,
+        /// How the bot chooses its commands: random, or greedy (trying what
+        /// it hasn't tried before where it is)
+        #[arg(long, default_value = "greedy", value_parser = parse_strategy)]
+        strategy: Strategy,
+        /// The seed for the bot's choices, so that a soak can be repeated
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        seed: u64,
+        /// The most turns to play a second
+        #[arg(long, value_name = "N")]
+        rate: Option<f64>,
+    },
+    /// Export a game's text for translation, or import a translation
+    Strings {
+        #[command(subcommand)]
+        command: StringsCommand,
+    },
+    /// Convert games to and from ScottKit's source format
+    Scottkit {
+        #[command(subcommand)]
+        command: ScottkitCommand,
+    },
+    /// Play a game with ScottFree's flags, as when run as `scottfree`
+    #[command(name = scottfree::NAME)]
+    ScottFree(scottfree::Args),
+}
+
+#[derive(Subcommand)]
+enum StringsCommand {
+    /// Write the text of a game as a PO file, ready to be translated
+    Export {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The file to write, instead of standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Merge a translated PO file into a game, writing a new game file
+    Import {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The translated PO file
+        po: PathBuf,
+        /// The game file to write
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScottkitCommand {
+    /// Decompile a game to ScottKit source
+    Export {
+        /// The game: a path, an adventure number, or all or part of a title
+        game: String,
+        /// The file to write, instead of standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Compile ScottKit source, writing a game file
+    Import {
+        /// The ScottKit source file
+        source: PathBuf,
+        /// The game file to write
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+}
+
+fn parse_policy(text: &str) -> Result<OccurrencePolicy, String> {
+    OccurrencePolicy::from_name(text)
+        .ok_or_else(|| "expected percent, always, or never".to_string())
+}
+
+fn parse_perspective(text: &str) -> Result<Perspective, String> {
+    Perspective::from_name(text).ok_or_else(|| "expected first, second, or scottfree".to_string())
+}
+
+fn parse_hints(text: &str) -> Result<HintLevel, String> {
+    HintLevel::from_name(text).ok_or_else(|| "expected off, nudge, verb, or full".to_string())
+}
+
+fn parse_backend(text: &str) -> Result<Backend, String> {
+    Backend::from_name(text).ok_or_else(|| "expected stdio or crossterm".to_string())
+}
+
+fn parse_strategy(text: &str) -> Result<Strategy, String> {
+    Strategy::from_name(text).ok_or_else(|| "expected random or greedy".to_string())
+}
+
+fn parse_chart(text: &str) -> Result<Chart, String> {
+    Chart::from_name(text).ok_or_else(|| "expected mermaid or dot".to_string())
+}
+
+fn parse_columns(text: &str) -> Result<usize, String> {
+    match text.parse() {
+        Ok(columns) if format::COLUMNS.contains(&columns) => Ok(columns),
+        _ => Err("expected 40, 64, or 80".to_string()),
+    }
+}
+
+// Loads a game along with the settings from its sidecar, if it has one,
+// applying the sidecar's patches and warning about any problems with them.
+fn load(path: &Path) -> Result<(Game, Overrides), String> {
+    let mut game = rusty_adams::load_game(&path.to_string_lossy())?;
+    let overrides = sidecar::load(path)?.unwrap_or_default();
+    for warning in overrides.apply_patches(&mut game)? {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok((game, overrides))
+}
+
+// Makes an interpreter for a game that runs its automatic actions by the
+// given policy, speaks from the given perspective, and has or hasn't the
+// modern conveniences.  Whatever isn't given is taken from the game's
+// sidecar settings, and failing those from the defaults.  The sidecar's
+// timers, if any, go by the time from now.
+fn interpreter(
+    game: Game,
+    occurrences: Option<OccurrencePolicy>,
+    perspective: Option<Perspective>,
+    purist: Option<bool>,
+    overrides: &Overrides,
+) -> Interpreter {
+    let mut interpreter = Interpreter::new(game);
+    interpreter.set_style(overrides.style());
+    interpreter.set_occurrence_policy(occurrences.or(overrides.occurrences).unwrap_or_default());
+    interpreter.set_perspective(perspective.or(overrides.perspective).unwrap_or_default());
+    interpreter.set_purist(purist.unwrap_or(overrides.purist));
+    interpreter.set_aliases(overrides.aliases.clone());
+    if !overrides.timers.is_empty() {
+        let started = Instant::now();
+        interpreter.set_timers(overrides.timers.clone(), Box::new(move || started.elapsed()));
+    }
+    interpreter
+}
+
+// Returns whether the modern conveniences were turned off with --purist or
+// on with --no-purist, or None to leave it to the game's sidecar.
+fn purist_flag(purist: bool, no_purist: bool) -> Option<bool> {
+    match (purist, no_purist) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+// Plays a game on the terminal with the given interpreter and backend,
+// wrapping the output to the given number of columns, or failing that to the
+// width of the window if the backend knows it.  A dumb terminal's output is
+// always wrapped, since it has to count the lines.  A transcript is written
+// to the given file, if any.  The banner, if any, is shown first just as it
+// is, and isn't part of the transcript.  Consoles that keep a history of
+// commands keep the game's own, from one session to the next.
+fn play(
+    mut interpreter: Interpreter,
+    columns: Option<usize>,
+    backend: Backend,
+    transcript: Option<&Path>,
+    banner: Option<&str>,
+) -> Result<(), String> {
+    let mut console = backend.open();
+    if let Some(history) = console.history() {
+        *history = History::for_adventure(interpreter.game().adventure());
+    }
+    let mut formatter = match (backend, columns, console.columns()) {
+        (Backend::Dumb(_), columns, _) => Formatter::for_platform(columns.unwrap_or(80)),
+        (_, Some(columns), _) => Formatter::for_platform(columns),
+        (_, None, Some(width)) => Formatter::new(width, Breaking::Words),
+        (_, None, None) => Formatter::unwrapped(),
     };
+    if let Some(path) = transcript {
+        let file = fs::File::create(path).map_err(|err| format!("Error: {}", err))?;
+        console = Box::new(Recorder::new(console, io::BufWriter::new(file)));
+    }
+    if let Some(banner) = banner {
+        console
+            .write(banner)
+            .map_err(|err| format!("Error: {}", err))?;
+    }
+    rusty_adams::play(&mut interpreter, &mut formatter, console.as_mut())
+}
 
+// Replays a transcript with the given interpreter, and says how far it got.
+fn replay(mut interpreter: Interpreter, path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Error: {}", err))?;
+    let events = transcript::parse(&text)?;
+    let turns = transcript::replay(&mut interpreter, &events)?;
+    println!("Replayed {} turns.", turns);
+    Ok(())
+}
+
+// Plays a walkthrough with the given interpreter, failing if the game never
+// says one of the expected texts.
+fn check(mut interpreter: Interpreter, path: &Path, expect: &[String]) -> Result<(), String> {
+    let script = fs::read_to_string(path).map_err(|err| format!("Error: {}", err))?;
+    let outcome = walkthrough::play(&mut interpreter, &script, expect);
+    let ended = match outcome.finished {
+        true => ", and the game ended",
+        false => "",
+    };
+    if let Some(text) = outcome.missing.first() {
+        return Err(format!(
+            "The game never said {:?} in {} commands{}",
+            text, outcome.commands, ended
+        ));
+    }
+    println!("Passed after {} commands{}.", outcome.commands, ended);
+    Ok(())
+}
+
+// Replays a transcript with the given interpreter, and prints a timeline of
+// what happened in it, titled with the name of the transcript.
+fn analyze_session(mut interpreter: Interpreter, path: &Path, chart: Chart) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Error: {}", err))?;
+    let events = transcript::parse(&text)?;
+    let moments = session::analyze(&mut interpreter, &events)?;
+    let title = path.file_name().unwrap_or_default().to_string_lossy();
+    print!("{}", chart.draw(&title, &moments));
+    Ok(())
+}
+
+// Plays one turn of a game by mail, starting the given game in the save file
+// first if there is one, and prints what the game said.  The save file is
+// replaced only once the turn is over, so that it is never left half
+// written.
+fn turn(
+    save: &Path,
+    command: Option<&str>,
+    game: Option<&str>,
+    dirs: &[PathBuf],
+) -> Result<(), String> {
+    let (mut interpreter, mut mailbox) = match game {
+        Some(game) => {
+            let path = catalog::resolve(game, dirs)?;
+            let (game, overrides) = load(&path)?;
+            let mut interpreter = interpreter(game, None, None, None, &overrides);
+            // The scheduler may run later turns from another directory.
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            let (text, mailbox) = mail::start(&mut interpreter, path);
+            print!("{}", text);
+            (interpreter, mailbox)
+        }
+        None => {
+            let text = fs::read_to_string(save).map_err(|err| format!("Error: {}", err))?;
+            let mailbox = mail::Mailbox::parse(&text)?;
+            let (game, overrides) = load(&mailbox.game)?;
+            (interpreter(game, None, None, None, &overrides), mailbox)
+        }
+    };
+    if let Some(command) = command {
+        print!("{}", mail::turn(&mut interpreter, &mut mailbox, command)?);
+    }
+    let temp = save.with_extension("tmp");
+    fs::write(&temp, mailbox.to_text())
+        .and_then(|()| fs::rename(&temp, save))
+        .map_err(|err| format!("Error: {}", err))
+}
+
+// Prints a game for debugging, along with any warnings from checking it
+// strictly.
+fn dump(path: &str, strict: bool) -> Result<(), String> {
+    let game = if strict {
+        let (game, warnings) = rusty_adams::load_game_strict(path)?;
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        game
+    } else {
+        rusty_adams::load_game(path)?
+    };
     game.print_debug();
+    Ok(())
+}
+
+// Prints the verbs and nouns that a game understands, each with its index and
+// its synonyms.
+fn vocab(path: &str) -> Result<(), String> {
+    let game = rusty_adams::load_game(path)?;
+    for (heading, groups) in [("Verbs", game.verb_groups()), ("Nouns", game.noun_groups())] {
+        println!("{}:", heading);
+        for group in groups {
+            println!("{:4}  {}", group.index, group);
+        }
+    }
+    Ok(())
+}
+
+// Prints the exits from each room that has any, with a warning for each exit
+// that isn't matched by one leading back.
+fn map(path: &str) -> Result<(), String> {
+    let game = rusty_adams::load_game(path)?;
+    for room in 1..game.header_counts().rooms as usize {
+        let exits: Vec<String> = Direction::ALL
+            .into_iter()
+            .filter_map(|dir| {
+                map::exit(&game, room, dir).map(|to| format!("{}: {}", dir, to))
+            })
+            .collect();
+        if !exits.is_empty() {
+            println!("{:4}  {}", room, exits.join(", "));
+        }
+    }
+    for warning in map::asymmetric_exits(&game) {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(())
+}
+
+// Writes a game's text as a PO file, to a file or to standard output.
+fn export_strings(path: &str, output: Option<&Path>) -> Result<(), String> {
+    let po = strings::export(&rusty_adams::load_game(path)?);
+    match output {
+        Some(output) => fs::write(output, po).map_err(|err| format!("Error: {}", err)),
+        None => {
+            print!("{}", po);
+            Ok(())
+        }
+    }
+}
+
+// Merges a translated PO file into a game and writes the result, along with
+// any warnings about the translation.
+fn import_strings(path: &str, po: &Path, output: &Path) -> Result<(), String> {
+    let mut game = rusty_adams::load_game(path)?;
+    let po = fs::read_to_string(po).map_err(|err| format!("Error: {}", err))?;
+    for warning in strings::import(&mut game, &po)? {
+        eprintln!("Warning: {}", warning);
+    }
+    let mut file = fs::File::create(output).map_err(|err| format!("Error: {}", err))?;
+    writer::write_game(&mut file, &game).map_err(|err| format!("Error: {}", err))
+}
+
+// Decompiles a game to ScottKit source, writing it to the given file or to
+// standard output.
+fn export_scottkit(path: &str, output: Option<&Path>) -> Result<(), String> {
+    let source = scottkit::decompile(&rusty_adams::load_game(path)?);
+    match output {
+        Some(output) => fs::write(output, source).map_err(|err| format!("Error: {}", err)),
+        None => {
+            print!("{}", source);
+            Ok(())
+        }
+    }
+}
+
+// Compiles ScottKit source and writes the result as a game file.
+fn import_scottkit(source: &Path, output: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(source).map_err(|err| format!("Error: {}", err))?;
+    let game = scottkit::compile(&source)?;
+    let mut file = fs::File::create(output).map_err(|err| format!("Error: {}", err))?;
+    writer::write_game(&mut file, &game).map_err(|err| format!("Error: {}", err))
+}
+
+// Prints the release that a game file is, as far as its content shows, along
+// with the details from its header and footer.
+fn info(path: &str) -> Result<(), String> {
+    let game = match rusty_adams::load_game_partial(path) {
+        Ok(game) => game,
+        Err(partial) => return damaged_info(path, &partial),
+    };
+    let counts = game.header_counts();
+    println!("File:      {}", path);
+    match identify::identify(&game) {
+        Some(release) => println!("Release:   {}", release),
+        None => println!("Release:   unknown"),
+    }
+    println!("Adventure: {}", game.adventure());
+    println!(
+        "Version:   {}.{:02}",
+        game.version() / 100,
+        game.version() % 100
+    );
+    println!(
+        "Contents:  {} rooms, {} items, {} treasures",
+        counts.rooms,
+        counts.items,
+        game.treasures()
+    );
+    println!("Hash:      {}", identify::content_hash(&game));
+    Ok(())
+}
+
+// Prints what could be parsed of a damaged game file, and then fails with the
+// error that stopped the parser.
+fn damaged_info(path: &str, partial: &PartialGame) -> Result<(), String> {
+    let sections = partial.sections();
+    if sections.is_empty() {
+        return Err(partial.error().to_string());
+    }
+    println!("File:      {}", path);
+    println!("Parsed:    {}", sections.join(", "));
+    if let Some(counts) = partial.header_counts() {
+        let treasures = partial
+            .treasures()
+            .map_or(counts.treasures, |treasures| treasures as i32);
+        println!(
+            "Contents:  {} rooms, {} items, {} treasures",
+            counts.rooms, counts.items, treasures
+        );
+    }
+    Err(partial.error().to_string())
+}
+
+// Prints a table of the games in a directory, or the same details as JSON.
+fn list(dir: &Path, json: bool) -> Result<(), String> {
+    let entries = catalog::survey(dir).map_err(|err| format!("Error: {}", err))?;
+    if json {
+        let text = serde_json::to_string_pretty(&entries).map_err(|err| err.to_string())?;
+        println!("{}", text);
+    } else {
+        for line in catalog::table(&entries) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+// Prints the measures of the games in a directory as CSV, either a row for
+// each game or one for each word, or prints both tables as JSON.  Games that
+// couldn't be loaded are warned about.
+fn corpus_stats(dir: &Path, words: bool, json: bool) -> Result<(), String> {
+    let corpus = corpus::survey(dir).map_err(|err| format!("Error: {}", err))?;
+    for failure in &corpus.failures {
+        eprintln!("Warning: {}: {}", failure.file, failure.error);
+    }
+    if json {
+        let text = serde_json::to_string_pretty(&corpus).map_err(|err| err.to_string())?;
+        println!("{}", text);
+    } else if words {
+        print!("{}", corpus.words_csv());
+    } else {
+        print!("{}", corpus.games_csv());
+    }
+    Ok(())
+}
+
+// Has a bot play each game in a directory for the given number of turns, at
+// no more than the given rate, and reports how each soak went.  Fails if any
+// game couldn't be loaded or any turn failed.
+fn soak(
+    dir: &Path,
+    turns: usize,
+    strategy: Strategy,
+    seed: u64,
+    rate: Option<f64>,
+) -> Result<(), String> {
+    let pause = rate.map_or(Duration::ZERO, |rate| Duration::from_secs_f64(1.0 / rate));
+    let mut failed = false;
+    for path in rusty_adams::list_games(dir).map_err(|err| format!("Error: {}", err))? {
+        let name = path.to_string_lossy();
+        let load = || rusty_adams::load_game(&name);
+        let result = load().and_then(|game| {
+            let mut bot = Bot::new(&game, strategy, seed);
+            bot::soak(load, &mut bot, turns, pause)
+        });
+        match result {
+            Ok(soak) => {
+                println!(
+                    "{}: {} turns, {} games finished, {} failures",
+                    name,
+                    soak.turns,
+                    soak.finished,
+                    soak.failures.len()
+                );
+                for failure in &soak.failures {
+                    println!("  {}", failure);
+                }
+                failed |= !soak.failures.is_empty();
+            }
+            Err(err) => {
+                println!("{}: {}", name, err);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err("Some games failed the soak.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<_> = env::args_os().collect();
+    let result = if args.first().is_some_and(scottfree::is_invoked_as) {
+        scottfree::parse(args).and_then(|args| scottfree::run(&args, &catalog::games_path(None)))
+    } else {
+        let options = Options::parse_from(args);
+        let dirs = catalog::games_path(options.games_dir.as_deref());
+        match &options.command {
+            Command::Play {
+                game,
+                occurrences,
+                columns,
+                perspective,
+                purist,
+                no_purist,
+                hints,
+                dumb,
+                lines,
+                backend,
+                transcript,
+                no_banner,
+                author,
+                ..
+            } => match game {
+                Some(game) => catalog::resolve(game, &dirs)
+                    .and_then(|path| load(&path).map(|loaded| (Some(path), loaded))),
+                None => rusty_adams::load_demo().map(|game| (None, (game, Overrides::default()))),
+            }
+            .and_then(|(path, (game, overrides))| {
+                let banner = (!no_banner)
+                    .then(|| banner::for_game(path.as_deref(), game.adventure(), &dirs));
+                let mut interpreter = interpreter(
+                    game,
+                    *occurrences,
+                    *perspective,
+                    purist_flag(*purist, *no_purist),
+                    &overrides,
+                );
+                interpreter.set_hint_level(hints.unwrap_or_default());
+                interpreter.set_author_mode(*author);
+                let backend = match dumb {
+                    true => Backend::Dumb(lines.unwrap_or(console::DEFAULT_LINES)),
+                    false => backend.unwrap_or_else(Backend::detect),
+                };
+                let columns = columns.or(overrides.columns);
+                play(
+                    interpreter,
+                    columns,
+                    backend,
+                    transcript.as_deref(),
+                    banner.as_deref(),
+                )
+            }),
+            Command::Replay {
+                game,
+                transcript,
+                occurrences,
+                perspective,
+                purist,
+                no_purist,
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| load(&path))
+                .and_then(|(game, overrides)| {
+                    let interpreter = interpreter(
+                        game,
+                        *occurrences,
+                        *perspective,
+                        purist_flag(*purist, *no_purist),
+                        &overrides,
+                    );
+                    replay(interpreter, transcript)
+                }),
+            Command::Check {
+                game,
+                script,
+                expect,
+                occurrences,
+                purist,
+                no_purist,
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| load(&path))
+                .and_then(|(game, overrides)| {
+                    let interpreter = interpreter(
+                        game,
+                        *occurrences,
+                        None,
+                        purist_flag(*purist, *no_purist),
+                        &overrides,
+                    );
+                    check(interpreter, script, expect)
+                }),
+            Command::AnalyzeSession {
+                game,
+                transcript,
+                chart,
+                occurrences,
+                perspective,
+                purist,
+                no_purist,
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| load(&path))
+                .and_then(|(game, overrides)| {
+                    let interpreter = interpreter(
+                        game,
+                        *occurrences,
+                        *perspective,
+                        purist_flag(*purist, *no_purist),
+                        &overrides,
+                    );
+                    analyze_session(interpreter, transcript, *chart)
+                }),
+            Command::Turn {
+                save,
+                command,
+                game,
+            } => turn(save, command.as_deref(), game.as_deref(), &dirs),
+            Command::Dump { game, strict } => catalog::resolve(game, &dirs)
+                .and_then(|path| dump(&path.to_string_lossy(), *strict)),
+            Command::Vocab { game } => {
+                catalog::resolve(game, &dirs).and_then(|path| vocab(&path.to_string_lossy()))
+            }
+            Command::Map { game } => {
+                catalog::resolve(game, &dirs).and_then(|path| map(&path.to_string_lossy()))
+            }
+            Command::Info { game } => {
+                catalog::resolve(game, &dirs).and_then(|path| info(&path.to_string_lossy()))
+            }
+            Command::List { dir, json } => list(dir, *json),
+            Command::CorpusStats { dir, words, json } => corpus_stats(dir, *words, *json),
+            Command::Soak {
+                dir,
+                turns,
+                strategy,
+                seed,
+                rate,
+            } => soak(dir, *turns, *strategy, *seed, *rate),
+            Command::Strings {
+                command: StringsCommand::Export { game, output },
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| export_strings(&path.to_string_lossy(), output.as_deref())),
+            Command::Strings {
+                command: StringsCommand::Import { game, po, output },
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| import_strings(&path.to_string_lossy(), po, output)),
+            Command::Scottkit {
+                command: ScottkitCommand::Export { game, output },
+            } => catalog::resolve(game, &dirs)
+                .and_then(|path| export_scottkit(&path.to_string_lossy(), output.as_deref())),
+            Command::Scottkit {
+                command: ScottkitCommand::Import { source, output },
+            } => import_scottkit(source, output),
+            Command::ScottFree(args) => scottfree::run(args, &dirs),
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_purist_overrides() {
+        let sidecar = Overrides {
+            purist: true,
+            ..Overrides::default()
+        };
+        let purist = |flag| {
+            let game = rusty_adams::load_demo().unwrap();
+            interpreter(game, None, None, flag, &sidecar).is_purist()
+        };
+        assert_eq!(purist_flag(false, false), None);
+        assert!(purist(purist_flag(false, false)));
+        assert!(!purist(purist_flag(false, true)));
+        assert!(purist(purist_flag(true, false)));
+    }
+
+    #[test]
+    fn test_options() {
+        Options::command().debug_assert();
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "count", "--games-dir", "games"])
+                .unwrap();
+        assert_eq!(options.games_dir, Some(PathBuf::from("games")));
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                game: Some(game),
+                occurrences: None,
+                columns: None,
+                demo: false,
+                perspective: None,
+                purist: false,
+                no_purist: false,
+                hints: None,
+                dumb: false,
+                lines: None,
+                backend: None,
+                transcript: None,
+                no_banner: false,
+                author: false,
+            } if game == "count"
+        ));
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--occurrences", "never"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                occurrences: Some(OccurrencePolicy::Never),
+                ..
+            }
+        ));
+        assert!(
+            Options::try_parse_from(["rusty_adams", "play", "1", "--occurrences", "often"])
+                .is_err()
+        );
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--columns", "40"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                columns: Some(40),
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from(["rusty_adams", "play", "1", "--columns", "72"]).is_err());
+        assert!(Options::try_parse_from(["rusty_adams", "count"]).is_err());
+        let options = Options::try_parse_from(["rusty_adams", "play", "--demo"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                game: None,
+                demo: true,
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from(["rusty_adams", "play"]).is_err());
+        assert!(Options::try_parse_from(["rusty_adams", "play", "1", "--demo"]).is_err());
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--perspective", "second"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                perspective: Some(Perspective::SecondPerson),
+                ..
+            }
+        ));
+        let options = Options::try_parse_from(["rusty_adams", "play", "1", "--purist"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play { purist: true, .. }
+        ));
+        let options = Options::try_parse_from(["rusty_adams", "play", "1", "--no-purist"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                purist: false,
+                no_purist: true,
+                ..
+            }
+        ));
+        assert!(
+            Options::try_parse_from(["rusty_adams", "play", "1", "--purist", "--no-purist"])
+                .is_err()
+        );
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--hints", "nudge"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                hints: Some(HintLevel::Nudge),
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from([
+            "rusty_adams",
+            "play",
+            "1",
+            "--hints",
+            "off",
+            "--purist"
+        ])
+        .is_err());
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--dumb", "--lines", "0"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                dumb: true,
+                lines: Some(0),
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from(["rusty_adams", "play", "1", "--lines", "24"]).is_err());
+        let options =
+            Options::try_parse_from(["rusty_adams", "play", "1", "--backend", "crossterm"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Play {
+                backend: Some(Backend::Crossterm),
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from([
+            "rusty_adams",
+            "play",
+            "1",
+            "--dumb",
+            "--backend",
+            "stdio"
+        ])
+        .is_err());
+        assert!(Options::try_parse_from([
+            "rusty_adams",
+            "play",
+            "1",
+            "--purist",
+            "--occurrences",
+            "never"
+        ])
+        .is_err());
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "replay", "1", "game.jsonl", "--purist"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Replay { purist: true, .. }
+        ));
+        assert!(Options::try_parse_from(["rusty_adams", "replay", "1"]).is_err());
+
+        let options = Options::try_parse_from([
+            "rusty_adams",
+            "analyze-session",
+            "1",
+            "game.jsonl",
+            "--chart",
+            "dot",
+        ])
+        .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::AnalyzeSession {
+                chart: Chart::Dot,
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from([
+            "rusty_adams",
+            "analyze-session",
+            "1",
+            "game.jsonl",
+            "--chart",
+            "svg"
+        ])
+        .is_err());
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "soak", "games", "--strategy", "random"])
+                .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Soak {
+                turns: 1000,
+                strategy: Strategy::Random,
+                rate: None,
+                ..
+            }
+        ));
+        assert!(
+            Options::try_parse_from(["rusty_adams", "soak", "games", "--strategy", "lazy"])
+                .is_err()
+        );
+
+        let options = Options::try_parse_from([
+            "rusty_adams",
+            "check",
+            "1",
+            "--script",
+            "adv01.txt",
+            "--expect",
+            "stored 13 treasures",
+            "--expect",
+            "well done",
+        ])
+        .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Check { expect, purist: false, .. } if expect.len() == 2
+        ));
+        assert!(
+            Options::try_parse_from(["rusty_adams", "check", "1", "--script", "adv01.txt"])
+                .is_err()
+        );
+
+        let options = Options::try_parse_from(["rusty_adams", "list", "games", "--json"]).unwrap();
+        assert!(matches!(options.command, Command::List { json: true, .. }));
+        assert!(Options::try_parse_from(["rusty_adams", "list"]).is_err());
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "corpus-stats", "games", "--words"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::CorpusStats {
+                words: true,
+                json: false,
+                ..
+            }
+        ));
+        assert!(Options::try_parse_from([
+            "rusty_adams",
+            "corpus-stats",
+            "games",
+            "--words",
+            "--json"
+        ])
+        .is_err());
+
+        let options = Options::try_parse_from([
+            "rusty_adams",
+            "strings",
+            "import",
+            "1",
+            "fr.po",
+            "-o",
+            "fr.dat",
+        ])
+        .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Strings { command: StringsCommand::Import { output, .. } }
+                if output == Path::new("fr.dat")
+        ));
+        assert!(
+            Options::try_parse_from(["rusty_adams", "strings", "import", "1", "fr.po"]).is_err()
+        );
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "turn", "me.sav", "go north"]).unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Turn { command: Some(command), game: None, .. } if command == "go north"
+        ));
+        assert!(Options::try_parse_from(["rusty_adams", "turn", "me.sav"]).is_err());
+        assert!(Options::try_parse_from(["rusty_adams", "turn", "me.sav", "--game", "1"]).is_ok());
+
+        let options = Options::try_parse_from([
+            "rusty_adams",
+            "scottkit",
+            "import",
+            "count.sck",
+            "-o",
+            "count.dat",
+        ])
+        .unwrap();
+        assert!(matches!(
+            options.command,
+            Command::Scottkit { command: ScottkitCommand::Import { output, .. } }
+                if output == Path::new("count.dat")
+        ));
+
+        let options =
+            Options::try_parse_from(["rusty_adams", "scottfree", "-y", "-d", "count"]).unwrap();
+        assert!(matches!(options.command, Command::ScottFree(_)));
+    }
 }
@@ -0,0 +1,182 @@
+//! This module lets rusty_adams stand in for ScottFree, accepting the same
+//! flags and arguments and following its conventions for output, so that
+//! scripts written to drive ScottFree can drive rusty_adams instead.  It is
+//! used when the program is run as `scottfree` (say through a symlink), or
+//! with the `scottfree` subcommand.
+
+use clap::Parser;
+use rusty_adams::catalog;
+use rusty_adams::console::Stdio;
+use rusty_adams::format::{Breaking, Formatter, Perspective};
+use rusty_adams::game::interpreter::{Interpreter, Style};
+use rusty_adams::game::Game;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The name that makes the program act as ScottFree.
+pub const NAME: &str = "scottfree";
+
+/// The banner that ScottFree prints before loading a game.
+const BANNER: &str = "\
+Scott Free, A Scott Adams game driver in C.
+Release 1.14, (c) 1993,1994,1995 Swansea University Computer Society.
+Distributed under the GNU software license
+
+";
+
+/// Plays a game with ScottFree's command-line flags.
+#[derive(Debug, Parser)]
+#[command(name = NAME)]
+pub struct Args {
+    /// Speak of the player as "you"
+    #[arg(short = 'y', overrides_with = "i")]
+    you: bool,
+    /// Speak of the player as "I", which is the default
+    #[arg(short = 'i', overrides_with = "you")]
+    i: bool,
+    /// Count down the light source's last turns
+    #[arg(short = 's')]
+    scott_light: bool,
+    /// Use the TRS-80's layout
+    #[arg(short = 't')]
+    trs80: bool,
+    /// Print details of the game as it loads
+    #[arg(short = 'd')]
+    debug: bool,
+    /// Destroy the light source when it runs out
+    #[arg(short = 'p')]
+    prehistoric_lamp: bool,
+    /// The game: a path, an adventure number, or all or part of a title
+    game: String,
+    /// A game saved by ScottFree or rusty_adams to restore
+    saved_game: Option<PathBuf>,
+}
+
+/// Returns whether the program was run under ScottFree's name.
+pub fn is_invoked_as(arg0: &OsString) -> bool {
+    Path::new(arg0)
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().eq_ignore_ascii_case(NAME))
+}
+
+/// Parses ScottFree's arguments, reporting errors as ScottFree does rather
+/// than as clap would.
+pub fn parse(args: Vec<OsString>) -> Result<Args, String> {
+    let arg0 = args
+        .first()
+        .map_or(NAME.into(), |arg| arg.to_string_lossy());
+    Args::try_parse_from(&args).map_err(|_| {
+        format!(
+            "{}: [-h] [-y] [-s] [-i] [-t] [-d] [-p] <gamename> [savedgame].",
+            arg0
+        )
+    })
+}
+
+/// Plays a game as ScottFree would, looking for it along the given games
+/// path.
+pub fn run(args: &Args, dirs: &[PathBuf]) -> Result<(), String> {
+    let path = catalog::resolve(&args.game, dirs)?;
+    print!("{}", BANNER);
+    let game = rusty_adams::load_game(&path.to_string_lossy())?;
+    if args.debug {
+        print!("{}", load_report(&game));
+    }
+
+    let mut interpreter = Interpreter::new(game);
+    interpreter.set_style(Style {
+        perspective: if args.you {
+            Perspective::ScottFree
+        } else {
+            Perspective::FirstPerson
+        },
+        scott_light: args.scott_light,
+        trs80: args.trs80,
+        prehistoric_lamp: args.prehistoric_lamp,
+    });
+    if let Some(saved_game) = &args.saved_game {
+        let restored = fs::read_to_string(saved_game)
+            .map_err(|err| err.to_string())
+            .and_then(|text| interpreter.restore(&text));
+        if restored.is_err() {
+            println!("Unable to restore game.");
+        }
+    }
+    let columns = if args.trs80 { 64 } else { 80 };
+    rusty_adams::play(
+        &mut interpreter,
+        &mut Formatter::new(columns, Breaking::Words),
+        &mut Stdio::new(),
+    )
+}
+
+/// Returns the details that ScottFree prints as it loads a game in debugging
+/// mode, which gives the highest index in each table rather than its size.
+fn load_report(game: &Game) -> String {
+    let counts = game.header_counts();
+    format!(
+        "Reading {} actions.\nReading {} word pairs.\nReading {} rooms.\n\
+         Reading {} messages.\nReading {} items.\n\
+         Version {}.{:02} of Adventure {}.\nLoad Complete.\n\n",
+        counts.actions - 1,
+        counts.words - 1,
+        counts.rooms - 1,
+        counts.messages - 1,
+        counts.items - 1,
+        game.version() / 100,
+        game.version() % 100,
+        game.adventure()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_from(args: &[&str]) -> Result<Args, String> {
+        parse(args.iter().map(OsString::from).collect())
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = parse_from(&["scottfree", "-y", "-t", "adv01.dat", "saved"]).unwrap();
+        assert!(args.you && args.trs80 && !args.scott_light);
+        assert_eq!(args.game, "adv01.dat");
+        assert_eq!(args.saved_game, Some(PathBuf::from("saved")));
+
+        // As in ScottFree, the last of -y and -i wins.
+        assert!(
+            !parse_from(&["scottfree", "-y", "-i", "adv01.dat"])
+                .unwrap()
+                .you
+        );
+        assert!(
+            parse_from(&["scottfree", "-i", "-y", "adv01.dat"])
+                .unwrap()
+                .you
+        );
+
+        assert_eq!(
+            parse_from(&["/usr/bin/ScottFree", "-x", "adv01.dat"]).unwrap_err(),
+            "/usr/bin/ScottFree: [-h] [-y] [-s] [-i] [-t] [-d] [-p] <gamename> [savedgame]."
+        );
+        assert!(parse_from(&["scottfree"]).is_err());
+        assert!(parse_from(&["scottfree", "a", "b", "c"]).is_err());
+    }
+
+    #[test]
+    fn test_is_invoked_as() {
+        assert!(is_invoked_as(&"scottfree".into()));
+        assert!(is_invoked_as(&"/usr/local/bin/ScottFree.exe".into()));
+        assert!(!is_invoked_as(&"rusty_adams".into()));
+    }
+
+    #[test]
+    fn test_load_report() {
+        let game = rusty_adams::load_game("games/adv01.dat").unwrap();
+        let report = load_report(&game);
+        assert!(report.starts_with("Reading 169 actions.\nReading 69 word pairs.\n"));
+        assert!(report.ends_with("Version 4.16 of Adventure 1.\nLoad Complete.\n\n"));
+    }
+}
@@ -0,0 +1,265 @@
+//! This module turns a transcript of a game into a timeline of what happened
+//! in it, for people writing walkthroughs.  The transcript is replayed against
+//! the game, and after each turn the timeline notes the room the player moved
+//! to, the items gained and lost, any death, and the flags set and cleared.
+//! Turns in which none of those changed are left out.
+//!
+//! The timeline can be drawn as a Mermaid timeline:
+//!
+//! ```text
+//! timeline
+//!     title adv01.dat
+//!     Start : Room#58; forest
+//!     Turn 3 (climb tree) : Room#58; top of a tall cypress tree
+//! ```
+//!
+//! or as a Graphviz graph, with a box for each turn in a chain.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::console::Role;
+use crate::game::interpreter::diff::Snapshot;
+use crate::game::interpreter::Interpreter;
+use crate::game::INVENTORY;
+use crate::transcript::{self, Event};
+
+/// Something that happened in a turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The player came into the room with the given description.
+    Entered(String),
+    /// The player picked up the item with the given description.
+    Gained(String),
+    /// The player no longer has the item with the given description.
+    Lost(String),
+    /// The player died.
+    Died,
+    /// The flag with the given number was set.
+    FlagSet(u32),
+    /// The flag with the given number was cleared.
+    FlagCleared(u32),
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Change::Entered(room) => write!(f, "Room: {}", room),
+            Change::Gained(item) => write!(f, "Gained {}", item),
+            Change::Lost(item) => write!(f, "Lost {}", item),
+            Change::Died => write!(f, "Died"),
+            Change::FlagSet(flag) => write!(f, "Set flag {}", flag),
+            Change::FlagCleared(flag) => write!(f, "Cleared flag {}", flag),
+        }
+    }
+}
+
+/// A turn in which something happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Moment {
+    /// The turn, where turn 0 is the game's opening.
+    pub turn: u32,
+    /// The command that began the turn, if it wasn't the opening.
+    pub command: Option<String>,
+    /// What happened, in the order listed above.
+    pub changes: Vec<Change>,
+}
+
+impl Moment {
+    /// Returns the heading for the turn, such as "Turn 3 (climb tree)".
+    pub fn heading(&self) -> String {
+        match &self.command {
+            Some(command) => format!("Turn {} ({})", self.turn, command),
+            None => "Start".to_string(),
+        }
+    }
+}
+
+/// Returns what happened in a turn: the state that changed since the given
+/// snapshot, along with any death.
+fn changes(interpreter: &Interpreter, since: &Snapshot, deaths: u32) -> Vec<Change> {
+    let game = interpreter.game();
+    let describe = |description: Option<&str>| description.unwrap_or("?").trim().to_string();
+    let diff = interpreter.diff_since(since);
+    let mut changes = vec![];
+    if let Some(room) = diff.room {
+        changes.push(Change::Entered(describe(
+            game.room_description(room.to as usize),
+        )));
+    }
+    for moved in diff.items.iter().filter(|moved| moved.to == INVENTORY) {
+        changes.push(Change::Gained(describe(game.item_description(moved.item))));
+    }
+    for moved in diff.items.iter().filter(|moved| moved.from == INVENTORY) {
+        changes.push(Change::Lost(describe(game.item_description(moved.item))));
+    }
+    if interpreter.deaths() > deaths {
+        changes.push(Change::Died);
+    }
+    for flag in diff.flags {
+        changes.push(if flag.set {
+            Change::FlagSet(flag.flag)
+        } else {
+            Change::FlagCleared(flag.flag)
+        });
+    }
+    changes
+}
+
+/// Replays a transcript on the given interpreter, which should have just been
+/// made for the game, and returns the turns in which something happened.
+pub fn analyze(interpreter: &mut Interpreter, events: &[Event]) -> Result<Vec<Moment>, String> {
+    let commands: HashMap<u32, &str> = events
+        .iter()
+        .filter(|event| event.role == Role::Player)
+        .map(|event| (event.turn, event.text.as_str()))
+        .collect();
+    let mut moments = vec![];
+    let mut last: Option<(Snapshot, u32)> = None;
+    transcript::replay_with(interpreter, events, |turn, interpreter| {
+        let changes = match &last {
+            Some((snapshot, deaths)) => changes(interpreter, snapshot, *deaths),
+            // The opening only says where the game begins.
+            None => {
+                let room = interpreter
+                    .game()
+                    .room_description(interpreter.room().index());
+                vec![Change::Entered(room.unwrap_or("?").trim().to_string())]
+            }
+        };
+        if !changes.is_empty() {
+            moments.push(Moment {
+                turn,
+                command: commands.get(&turn).map(|command| command.to_string()),
+                changes,
+            });
+        }
+        last = Some((interpreter.snapshot(), interpreter.deaths()));
+    })?;
+    Ok(moments)
+}
+
+/// The ways that a timeline can be drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Chart {
+    /// A Mermaid timeline.
+    #[default]
+    Mermaid,
+    /// A Graphviz graph.
+    Dot,
+}
+
+impl Chart {
+    /// Looks up a chart by its name, in lower case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mermaid" => Some(Chart::Mermaid),
+            "dot" => Some(Chart::Dot),
+            _ => None,
+        }
+    }
+
+    /// Draws a timeline with the given title.
+    pub fn draw(self, title: &str, moments: &[Moment]) -> String {
+        match self {
+            Chart::Mermaid => mermaid(title, moments),
+            Chart::Dot => dot(title, moments),
+        }
+    }
+}
+
+/// Draws a timeline as a Mermaid timeline with the given title.
+pub fn mermaid(title: &str, moments: &[Moment]) -> String {
+    // Mermaid splits on colons and ends statements at semicolons, so both
+    // are written as entities.
+    let escape = |text: &str| text.replace(';', "#59;").replace(':', "#58;");
+    let mut text = format!("timeline\n    title {}\n", escape(title));
+    for moment in moments {
+        text.push_str(&format!("    {}", escape(&moment.heading())));
+        for change in &moment.changes {
+            text.push_str(&format!(" : {}", escape(&change.to_string())));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Draws a timeline as a Graphviz graph with the given title.
+pub fn dot(title: &str, moments: &[Moment]) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut text = format!(
+        "digraph session {{\n    label=\"{}\";\n    rankdir=LR;\n    node [shape=box];\n",
+        escape(title)
+    );
+    for (i, moment) in moments.iter().enumerate() {
+        // Each line of the label is left-justified.
+        let mut label = format!("{}\\l", escape(&moment.heading()));
+        for change in &moment.changes {
+            label.push_str(&format!("{}\\l", escape(&change.to_string())));
+        }
+        text.push_str(&format!("    t{} [label=\"{}\"];\n", i, label));
+        if i > 0 {
+            text.push_str(&format!("    t{} -> t{};\n", i - 1, i));
+        }
+    }
+    text.push_str("}\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::interpreter::OccurrencePolicy;
+
+    fn demo() -> Interpreter {
+        let mut interpreter = Interpreter::new(crate::load_demo().unwrap());
+        interpreter.set_occurrence_policy(OccurrencePolicy::Never);
+        interpreter
+    }
+
+    // Plays the given commands, returning the transcript.
+    fn play(commands: &[&str]) -> Vec<Event> {
+        let event = |role, text: String, turn| Event {
+            role,
+            text,
+            turn,
+            timestamp: 0,
+        };
+        let mut interpreter = demo();
+        let mut events = vec![event(Role::Engine, interpreter.start(), 0)];
+        for (turn, command) in (1..).zip(commands) {
+            events.push(event(Role::Player, command.to_string(), turn));
+            events.push(event(Role::Engine, interpreter.command(command), turn));
+        }
+        events
+    }
+
+    #[test]
+    fn test_analyze() {
+        let events = play(&["look", "n", "get mat", "s", "drop mat"]);
+        let moments = analyze(&mut demo(), &events).unwrap();
+        let headings: Vec<String> = moments.iter().map(Moment::heading).collect();
+        assert_eq!(
+            headings,
+            [
+                "Start",
+                "Turn 2 (n)",
+                "Turn 3 (get mat)",
+                "Turn 4 (s)",
+                "Turn 5 (drop mat)"
+            ]
+        );
+        assert_eq!(moments[2].changes, [Change::Gained("Doormat".to_string())]);
+        assert_eq!(moments[4].changes, [Change::Lost("Doormat".to_string())]);
+
+        let chart = mermaid("demo: lighthouse", &moments[..1]);
+        assert_eq!(
+            chart,
+            "timeline\n    title demo#58; lighthouse\n    \
+             Start : Room#58; sandy cove. Waves break gently on the shore\n"
+        );
+        let graph = dot("demo", &moments[2..4]);
+        assert!(graph.contains("    t0 [label=\"Turn 3 (get mat)\\lGained Doormat\\l\"];\n"));
+        assert!(graph.contains("    t0 -> t1;\n"));
+    }
+}
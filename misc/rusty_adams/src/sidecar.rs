@@ -0,0 +1,300 @@
+//! This module reads the settings that a game can have in a sidecar, a TOML
+//! file beside the game file with the same name, such as `adv01.toml` for
+//! `adv01.dat`.  The settings are those that a particular release needs to
+//! play properly, or that a player always wants for it:
+//!
+//! ```toml
+//! # ScottFree's compatibility flags, and the rest of the play options.
+//! occurrences = "always"
+//! perspective = "second"
+//! purist = false
+//! scott_light = true
+//! trs80 = false
+//! prehistoric_lamp = true
+//! columns = 64
+//!
+//! # PO files of corrections, merged into the game as it is loaded, relative
+//! # to the sidecar.
+//! patches = ["adv01-typos.po"]
+//!
+//! # Words that the player may type in place of others.
+//! [aliases]
+//! XYZZY = "SAY XYZZY"
+//!
+//! # Flags to set, or with `clear = true` to clear, once the given number of
+//! # turns or seconds of play have gone by.  See `game::interpreter::timer`.
+//! [[timers]]
+//! flag = 20
+//! turns = 50
+//!
+//! [[timers]]
+//! flag = 21
+//! seconds = 300
+//! ```
+//!
+//! Every setting may be left out.  The options given on the command line
+//! take the place of the sidecar's, which take the place of the defaults;
+//! `--no-purist` turns off a sidecar's `purist = true`.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::format::{self, Perspective};
+use crate::game::interpreter::timer::{Timer, When};
+use crate::game::interpreter::{OccurrencePolicy, Style};
+use crate::game::{strings, FlagId, Game};
+
+/// The settings as they are written in the file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct File {
+    occurrences: Option<String>,
+    perspective: Option<String>,
+    purist: bool,
+    scott_light: bool,
+    trs80: bool,
+    prehistoric_lamp: bool,
+    columns: Option<usize>,
+    patches: Vec<PathBuf>,
+    aliases: BTreeMap<String, String>,
+    timers: Vec<TimerEntry>,
+}
+
+/// A timer as it is written in the file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimerEntry {
+    flag: i32,
+    #[serde(default)]
+    clear: bool,
+    turns: Option<u32>,
+    seconds: Option<u64>,
+}
+
+impl TimerEntry {
+    /// Checks the timer, which must give a flag that exists and either
+    /// turns or seconds.
+    fn to_timer(&self) -> Result<Timer, String> {
+        let flag =
+            FlagId::new(self.flag).ok_or_else(|| format!("there is no flag {}", self.flag))?;
+        let when = match (self.turns, self.seconds) {
+            (Some(turns), None) => When::Turns(turns),
+            (None, Some(seconds)) => When::Elapsed(Duration::from_secs(seconds)),
+            _ => {
+                return Err(format!(
+                    "the timer for flag {} needs turns or seconds",
+                    self.flag
+                ))
+            }
+        };
+        Ok(Timer {
+            flag,
+            set: !self.clear,
+            when,
+        })
+    }
+}
+
+/// The settings for a game, from its sidecar.
+#[derive(Debug, Default, PartialEq)]
+pub struct Overrides {
+    /// When the automatic actions run, if the sidecar says.
+    pub occurrences: Option<OccurrencePolicy>,
+    /// How the player is spoken of, if the sidecar says.
+    pub perspective: Option<Perspective>,
+    /// Whether the modern conveniences are turned off.
+    pub purist: bool,
+    /// Count down the light source as Scott Adams' own interpreters did.
+    pub scott_light: bool,
+    /// Lay out rooms and the inventory as the TRS-80 did.
+    pub trs80: bool,
+    /// Destroy the light source when it runs out.
+    pub prehistoric_lamp: bool,
+    /// The number of columns to wrap the output to, if the sidecar says.
+    pub columns: Option<usize>,
+    /// The PO files to merge into the game, in order.
+    pub patches: Vec<PathBuf>,
+    /// The words that the player may type in place of others.
+    pub aliases: BTreeMap<String, String>,
+    /// The flags to change once enough turns or time have gone by.
+    pub timers: Vec<Timer>,
+}
+
+impl Overrides {
+    /// Parses the text of a sidecar, taking the patches to be relative to the
+    /// given directory.
+    pub fn parse(text: &str, dir: &Path) -> Result<Overrides, String> {
+        let file: File = toml::from_str(text).map_err(|err| err.message().to_string())?;
+        let occurrences = match file.occurrences {
+            Some(name) => Some(
+                OccurrencePolicy::from_name(&name)
+                    .ok_or_else(|| format!("unknown occurrences {:?}", name))?,
+            ),
+            None => None,
+        };
+        let perspective = match file.perspective {
+            Some(name) => Some(
+                Perspective::from_name(&name)
+                    .ok_or_else(|| format!("unknown perspective {:?}", name))?,
+            ),
+            None => None,
+        };
+        if let Some(columns) = file.columns {
+            if !format::COLUMNS.contains(&columns) {
+                return Err(format!("can't wrap to {} columns", columns));
+            }
+        }
+        Ok(Overrides {
+            occurrences,
+            perspective,
+            purist: file.purist,
+            scott_light: file.scott_light,
+            trs80: file.trs80,
+            prehistoric_lamp: file.prehistoric_lamp,
+            columns: file.columns,
+            patches: file
+                .patches
+                .into_iter()
+                .map(|patch| dir.join(patch))
+                .collect(),
+            aliases: file.aliases,
+            timers: file
+                .timers
+                .iter()
+                .map(TimerEntry::to_timer)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Returns the style of output that the compatibility flags ask for.
+    pub fn style(&self) -> Style {
+        Style {
+            perspective: self.perspective.unwrap_or_default(),
+            scott_light: self.scott_light,
+            trs80: self.trs80,
+            prehistoric_lamp: self.prehistoric_lamp,
+        }
+    }
+
+    /// Merges the patches into the game, returning any warnings about them.
+    pub fn apply_patches(&self, game: &mut Game) -> Result<Vec<String>, String> {
+        let mut warnings = vec![];
+        for patch in &self.patches {
+            let po = fs::read_to_string(patch)
+                .map_err(|err| format!("Error: {}: {}", patch.display(), err))?;
+            let found = strings::import(game, &po)
+                .map_err(|err| format!("{}: {}", patch.display(), err))?;
+            warnings.extend(
+                found
+                    .into_iter()
+                    .map(|warning| format!("{}: {}", patch.display(), warning)),
+            );
+        }
+        Ok(warnings)
+    }
+}
+
+/// Returns the path of the sidecar for the game file at the given path.
+pub fn path_for(game: &Path) -> PathBuf {
+    game.with_extension("toml")
+}
+
+/// Reads the sidecar for the game file at the given path, if it has one.
+pub fn load(game: &Path) -> Result<Option<Overrides>, String> {
+    let path = path_for(game);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Error: {}: {}", path.display(), err)),
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    Overrides::parse(&text, dir)
+        .map(Some)
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let overrides = Overrides::parse(
+            "occurrences = \"always\"\ntrs80 = true\ncolumns = 40\npatches = [\"fix.po\"]\n\
+             [aliases]\nxyzzy = \"say xyzzy\"\n",
+            Path::new("games"),
+        )
+        .unwrap();
+        assert_eq!(
+            overrides,
+            Overrides {
+                occurrences: Some(OccurrencePolicy::Always),
+                trs80: true,
+                columns: Some(40),
+                patches: vec![PathBuf::from("games/fix.po")],
+                aliases: BTreeMap::from([("xyzzy".to_string(), "say xyzzy".to_string())]),
+                ..Overrides::default()
+            }
+        );
+        assert!(overrides.style().trs80);
+
+        assert_eq!(
+            Overrides::parse("", Path::new("")),
+            Ok(Overrides::default())
+        );
+        assert_eq!(
+            Overrides::parse("perspective = \"third\"", Path::new("")),
+            Err("unknown perspective \"third\"".to_string())
+        );
+        assert!(Overrides::parse("columns = 72", Path::new("")).is_err());
+        assert!(Overrides::parse("colour = true", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_timers() {
+        let overrides = Overrides::parse(
+            "[[timers]]\nflag = 20\nturns = 50\n\
+             [[timers]]\nflag = 21\nseconds = 300\nclear = true\n",
+            Path::new(""),
+        )
+        .unwrap();
+        assert_eq!(
+            overrides.timers,
+            [
+                Timer {
+                    flag: FlagId::new(20).unwrap(),
+                    set: true,
+                    when: When::Turns(50),
+                },
+                Timer {
+                    flag: FlagId::new(21).unwrap(),
+                    set: false,
+                    when: When::Elapsed(Duration::from_secs(300)),
+                },
+            ]
+        );
+        assert_eq!(
+            Overrides::parse("[[timers]]\nflag = 40\nturns = 1\n", Path::new("")),
+            Err("there is no flag 40".to_string())
+        );
+        assert!(Overrides::parse("[[timers]]\nflag = 1\n", Path::new("")).is_err());
+        assert!(Overrides::parse(
+            "[[timers]]\nflag = 1\nturns = 1\nseconds = 1\n",
+            Path::new("")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        assert_eq!(
+            path_for(Path::new("games/adv01.dat")),
+            Path::new("games/adv01.toml")
+        );
+        assert_eq!(load(Path::new("games/adv01.dat")), Ok(None));
+    }
+}
@@ -1,4 +1,4 @@
-//! THe tokenizer module provides support for translating the literal contents
+//! The tokenizer module provides support for translating the literal contents
 //! of a Scott Adams adventure file in the ScottFree (TRS-80) format into a
 //! sequence of tokens.
 //!
@@ -6,28 +6,87 @@
 //! with surrounding whitespace) and quote-delimited strings (possibly with
 //! internal newlines).
 //!
-//! We don't pay the slightest bit of attention to Unicode or processing the
-//! data as runes, since this file format is from the 8-bit days.
+//! A string can't simply contain a double quote, so the games use a backtick
+//! in its place, which ScottFree prints as a double quote.  We read backticks
+//! the same way.  Files written by modern tools may also escape a character
+//! with a backslash, as in `\"` or `` \` ``, which is read as the character
+//! itself.
+//!
+//! The format is from the 8-bit days, so the data is read a byte at a time.
+//! Only strings can hold anything but ASCII: a string that is valid UTF-8, as
+//! in a translated game, is read as UTF-8, and any other is read as Latin-1,
+//! one character to a byte.
+//!
+//! This module is public for the sake of tools that need to see a game file
+//! as raw tokens, such as format converters.  Most callers want
+//! `load_game` instead.
+//!
+//! ```
+//! use rusty_adams::tokenizer::Stream;
+//!
+//! let mut stream = Stream::new(br#" 42 "It's a \"ring\"." "#.to_vec()).unwrap();
+//! assert_eq!(stream.next_int().unwrap(), 42);
+//! assert_eq!(stream.next_str().unwrap(), "It's a \"ring\".");
+//! assert!(stream.done());
+//! ```
 
 use std::collections::VecDeque;
 use std::fmt::{Display, Error, Formatter};
 
 /// A Location identifies line number and column within the original game file.
-#[derive(Clone, Copy, Debug)]
+/// Both are counted from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Location {
     pub line: usize,
     pub col: usize,
 }
 
-/// There are only two kinds of token, Int and Str.
-#[derive(Debug)]
+/// There are only two kinds of token, Int and Str.  Each records where it
+/// started in the game file.
+#[derive(Debug, PartialEq)]
 pub enum Token {
     Int(i32, Location),
     Str(String, Location),
 }
 
+impl Token {
+    /// Returns where the token started in the game file.
+    pub fn location(&self) -> Location {
+        match self {
+            Token::Int(_, loc) | Token::Str(_, loc) => *loc,
+        }
+    }
+}
+
+/// Limits bounds the memory that tokenizing a file can take, so that a
+/// malicious or corrupt file, such as one with a quote that is never closed,
+/// is rejected rather than read into one enormous string.  The defaults are
+/// far beyond anything in the published games.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    /// The most bytes in a single token.
+    pub max_token_len: usize,
+    /// The most tokens in the file.
+    pub max_tokens: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { max_token_len: 16 * 1024, max_tokens: 1_000_000 }
+    }
+}
+
 /// A Stream contains a fully parsed sequence of tokens and a current-position
 /// marker.
+///
+/// ```
+/// use rusty_adams::tokenizer::{Location, Stream, Token};
+///
+/// let mut stream = Stream::new(b"-1\n\"*GOLD*\"\n".to_vec()).unwrap();
+/// assert_eq!(stream.next_token(), Some(Token::Int(-1, Location { line: 1, col: 1 })));
+/// assert_eq!(stream.peek_token().unwrap().location(), Location { line: 2, col: 1 });
+/// assert!(stream.next_int().is_err());
+/// ```
 pub struct Stream {
     tokens: VecDeque<Token>,
 }
@@ -35,7 +94,7 @@ pub struct Stream {
 /// These states are used by the finite state machine in `new` for parsing the
 /// input data.  The individual states are documented inline.
 #[derive(Debug)]
-pub enum State {
+enum State {
     Init,
     Sign,
     Num,
@@ -47,19 +106,70 @@ impl Stream {
     /// new initializes a new Stream from the given game data.  Because the
     /// game files are small and we never read them partially, we do all of
     /// the parsing up front.
+    ///
+    /// ```
+    /// use rusty_adams::tokenizer::Stream;
+    ///
+    /// assert!(Stream::new(b"1 2 3".to_vec()).is_ok());
+    /// let err = Stream::new(b"1 \"unfinished".to_vec()).err().unwrap();
+    /// assert_eq!(err.to_string(), "1:3: Unterminated string");
+    /// ```
     pub fn new(data: Vec<u8>) -> Result<Stream, TokenError> {
+        Self::with_limits(data, Limits::default())
+    }
+
+    /// with_limits initializes a new Stream as `new` does, but with the given
+    /// limits on the size of the tokens and the number of them.
+    ///
+    /// ```
+    /// use rusty_adams::tokenizer::{Limits, Stream};
+    ///
+    /// let limits = Limits { max_token_len: 4, max_tokens: 2 };
+    /// assert!(Stream::with_limits(b"1 \"ring\"".to_vec(), limits).is_ok());
+    /// let err = Stream::with_limits(b"1\n\"rings\"".to_vec(), limits).err().unwrap();
+    /// assert_eq!(err.to_string(), "2:1: Token longer than 4 bytes");
+    /// let err = Stream::with_limits(b"1 2 3".to_vec(), limits).err().unwrap();
+    /// assert_eq!(err.to_string(), "1:5: More than 2 tokens");
+    /// ```
+    pub fn with_limits(data: Vec<u8>, limits: Limits) -> Result<Stream, TokenError> {
+        let mut tokens = VecDeque::new();
+        Self::tokenize(&data, limits, &mut tokens)?;
+        Ok(Stream { tokens })
+    }
+
+    /// new_partial initializes a new Stream as `new` does, but on an error
+    /// keeps the tokens before it and returns the error alongside them, so
+    /// that as much as possible can be made of a damaged game file.
+    ///
+    /// ```
+    /// use rusty_adams::tokenizer::Stream;
+    ///
+    /// let (mut stream, err) = Stream::new_partial(b"1 \"unfinished".to_vec());
+    /// assert_eq!(stream.next_int().unwrap(), 1);
+    /// assert!(stream.done());
+    /// assert_eq!(err.unwrap().to_string(), "1:3: Unterminated string");
+    /// ```
+    pub fn new_partial(data: Vec<u8>) -> (Stream, Option<TokenError>) {
         let mut tokens = VecDeque::new();
+        let err = Self::tokenize(&data, Limits::default(), &mut tokens).err();
+        (Stream { tokens }, err)
+    }
+
+    /// Reads the tokens in the given data into `tokens`, stopping at the first
+    /// error.
+    fn tokenize(data: &[u8], limits: Limits, tokens: &mut VecDeque<Token>) -> Result<(), TokenError> {
         let mut state = State::Init;
-        let mut acc = String::new();
+        let mut acc: Vec<u8> = Vec::new();
 
-        let mut current_loc = Location{line: 1, col: 1};
-        let mut token_loc = Location{line: 1, col: 1};
+        let mut current_loc = Location{line: 1, col: 0};
+        let mut token_loc = Location{line: 1, col: 0};
 
         for offset in 0..data.len() {
-            let ch = *data.get(offset).unwrap() as char;
+            let byte = *data.get(offset).unwrap();
+            let ch = byte as char;
             if ch == '\n' {
                 current_loc.line += 1;
-                current_loc.col = 1;
+                current_loc.col = 0;
             } else {
                 current_loc.col += 1;
             }
@@ -69,13 +179,15 @@ impl Stream {
                 State::Init => {
                     if ch.is_ascii_whitespace() {
                         // pass
+                    } else if tokens.len() >= limits.max_tokens {
+                        return Err(TokenError { loc: current_loc, msg: format!("More than {} tokens", limits.max_tokens) });
                     } else if ch == '-' {
                         token_loc = current_loc;
-                        acc.push(ch);
+                        acc.push(byte);
                         state = State::Sign;
                     } else if ch.is_ascii_digit() {
                         token_loc = current_loc;
-                        acc.push(ch);
+                        acc.push(byte);
                         state = State::Num;
                     } else if ch == '"' {
                         token_loc = current_loc;
@@ -88,7 +200,7 @@ impl Stream {
                 // Sign state: Read the initial '-' of a negative integer.
                 State::Sign => {
                     if ch.is_ascii_digit() {
-                        acc.push(ch);
+                        acc.push(byte);
                         state = State::Num;
                     } else {
                         return Err(TokenError { loc: current_loc, msg: format!("Unexpected character '{}' in integer", ch) });
@@ -98,14 +210,14 @@ impl Stream {
                 // Num state: Now reading an integer.
                 State::Num => {
                     if ch.is_ascii_whitespace() {
-                        match acc.parse::<i32>() {
-                            Ok(val) => tokens.push_back(Token::Int(val, token_loc.clone())),
+                        match parse_int(&acc) {
+                            Ok(val) => tokens.push_back(Token::Int(val, token_loc)),
                             Err(_) => return Err(TokenError { loc: current_loc, msg: "Malformed integer".to_string() }),
                         }
                         acc.clear();
                         state = State::Init;
                     } else if ch.is_ascii_digit() {
-                        acc.push(ch);
+                        acc.push(byte);
                     } else {
                         return Err(TokenError { loc: current_loc, msg: format!("Unexpected character '{}' in integer", ch) });
                     }
@@ -116,22 +228,40 @@ impl Stream {
                     if ch == '\\' {
                         state = State::Escape;
                     } else if ch == '"' {
-                        tokens.push_back(Token::Str(acc.clone(), token_loc.clone()));
+                        tokens.push_back(Token::Str(decode(&acc), token_loc));
                         acc.clear();
                         state = State::Init;
+                    } else if ch == '`' {
+                        acc.push(b'"');
                     } else {
-                        acc.push(ch);
+                        acc.push(byte);
                     }
                 }
 
                 // Escape state: Read the next character in a string unconditionally.
                 State::Escape => {
-                    acc.push(ch);
+                    acc.push(byte);
                     state = State::Quote;
                 }
             }
+
+            if acc.len() > limits.max_token_len {
+                return Err(TokenError { loc: token_loc, msg: format!("Token longer than {} bytes", limits.max_token_len) });
+            }
         }
-        Ok(Stream { tokens })
+
+        // The data may end in the middle of a token, which is only acceptable
+        // for an integer.
+        match state {
+            State::Init => {}
+            State::Num => match parse_int(&acc) {
+                Ok(val) => tokens.push_back(Token::Int(val, token_loc)),
+                Err(_) => return Err(TokenError { loc: current_loc, msg: "Malformed integer".to_string() }),
+            },
+            State::Sign => return Err(TokenError { loc: current_loc, msg: "Unexpected end of data in integer".to_string() }),
+            State::Quote | State::Escape => return Err(TokenError { loc: token_loc, msg: "Unterminated string".to_string() }),
+        }
+        Ok(())
     }
 
     /// Checks if we're at the end of the stream.
@@ -141,7 +271,7 @@ impl Stream {
 
     /// Returns the next integer in the stream.
     pub fn next_int(&mut self) -> Result<i32, TokenError> {
-        println!("next_int");
+        trace!(token = ?self.tokens.front(), "next_int");
         match self.tokens.pop_front() {
             Some(Token::Int(val, _)) => Ok(val),
             Some(Token::Str(_, loc)) => Err(TokenError{ loc, msg: "Expected an integer, found a string".to_string() }),
@@ -151,7 +281,7 @@ impl Stream {
 
     /// Returns the next string in the stream.
     pub fn next_str(&mut self) -> Result<String, TokenError> {
-        println!("next_str");
+        trace!(token = ?self.tokens.front(), "next_str");
         match self.tokens.pop_front() {
             Some(Token::Str(val, _)) => Ok(val),
             Some(Token::Int(_, loc)) => Err(TokenError{ loc, msg: "Expected a string, found an integer".to_string() }),
@@ -163,14 +293,48 @@ impl Stream {
     pub fn next_token(&mut self) -> Option<Token> {
         self.tokens.pop_front()
     }
+
+    /// Returns the next token without consuming it.
+    pub fn peek_token(&self) -> Option<&Token> {
+        self.tokens.front()
+    }
+}
+
+/// Returns the integer whose digits, with any sign, are the given bytes.
+fn parse_int(acc: &[u8]) -> Result<i32, std::num::ParseIntError> {
+    // The bytes can only be ASCII, as anything else ends the integer.
+    String::from_utf8_lossy(acc).parse::<i32>()
+}
+
+/// Returns the string made of the given bytes, as UTF-8 if they are valid
+/// UTF-8 and as Latin-1 otherwise.
+fn decode(acc: &[u8]) -> String {
+    match std::str::from_utf8(acc) {
+        Ok(text) => text.to_string(),
+        Err(_) => acc.iter().map(|&byte| byte as char).collect(),
+    }
 }
 
 /// Represents an error encountered during tokenization.
+#[derive(Debug)]
 pub struct TokenError {
     loc: Location,
     msg: String,
 }
 
+impl TokenError {
+    /// Returns where the error was found, or line 0 if it was found at the
+    /// end of the stream.
+    pub fn location(&self) -> Location {
+        self.loc
+    }
+
+    /// Returns a description of the error, without its location.
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+}
+
 impl Display for TokenError {
     /// Makes a tokenization error human-readable.
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
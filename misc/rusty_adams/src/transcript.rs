@@ -0,0 +1,319 @@
+//! This module keeps transcripts of games in the JSON Lines format that chat
+//! logs are usually kept in, for people building agents that play text
+//! adventures.  Each line is one event:
+//!
+//! ```text
+//! {"role":"engine","text":"I'm in a sandy cove. ...","turn":0,"timestamp":1760572800}
+//! {"role":"player","text":"go north","turn":1,"timestamp":1760572805}
+//! {"role":"engine","text":"OK\n","turn":1,"timestamp":1760572805}
+//! ```
+//!
+//! Turn 0 is the game's opening, and each command the player gives begins the
+//! next turn.  The game's text is kept as the interpreter produced it, before
+//! it was wrapped to the screen.  Timestamps are in seconds since the Unix
+//! epoch.
+//!
+//! A transcript can be replayed against the game it was made with, which
+//! checks that the game still says the same things in reply to the same
+//! commands.  The automatic actions have to run the same way both times, so
+//! a transcript made with them running by chance will usually only replay if
+//! it never gave them the chance.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::console::{Console, Role};
+use crate::game::interpreter::Interpreter;
+use crate::history::History;
+
+/// One line of a transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Event {
+    pub role: Role,
+    pub text: String,
+    pub turn: u32,
+    pub timestamp: u64,
+}
+
+/// A console that writes a transcript of the game as it is played on another
+/// console.
+pub struct Recorder<C, W> {
+    console: C,
+    output: W,
+    turn: u32,
+}
+
+impl<C: Console, W: Write> Recorder<C, W> {
+    /// Returns a console that plays on the given one, writing the transcript
+    /// to the given output.
+    pub fn new(console: C, output: W) -> Recorder<C, W> {
+        Recorder {
+            console,
+            output,
+            turn: 0,
+        }
+    }
+}
+
+impl<C: Console, W: Write> Console for Recorder<C, W> {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        self.console.write(text)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.output.flush()?;
+        self.console.read_line()
+    }
+
+    fn transcribe(&mut self, role: Role, text: &str) -> io::Result<()> {
+        if role == Role::Player {
+            self.turn += 1;
+        }
+        let event = Event {
+            role,
+            text: text.to_string(),
+            turn: self.turn,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+        };
+        serde_json::to_writer(&mut self.output, &event)?;
+        writeln!(self.output)?;
+        self.console.transcribe(role, text)
+    }
+
+    fn columns(&self) -> Option<usize> {
+        self.console.columns()
+    }
+
+    fn history(&mut self) -> Option<&mut History> {
+        self.console.history()
+    }
+}
+
+/// Reads a transcript, checking that its turns are in order: the game speaks
+/// first, and each of the player's commands begins the next turn.  Blank lines
+/// are ignored.
+pub fn parse(text: &str) -> Result<Vec<Event>, String> {
+    let mut events: Vec<Event> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: Event =
+            serde_json::from_str(line).map_err(|err| format!("line {}: {}", i + 1, err))?;
+        let turn = events.last().map_or(0, |last| last.turn);
+        let expected = match event.role {
+            Role::Player => turn + 1,
+            Role::Engine => turn,
+        };
+        if event.turn != expected {
+            return Err(format!(
+                "line {}: expected turn {}, not {}",
+                i + 1,
+                expected,
+                event.turn
+            ));
+        }
+        if events
+            .last()
+            .is_some_and(|last| event.timestamp < last.timestamp)
+        {
+            return Err(format!("line {}: timestamp goes backward", i + 1));
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Replays a transcript on the given interpreter, which should have just been
+/// made for the game.  Returns the number of turns replayed, or describes the
+/// first turn where the game said something other than the transcript has.
+pub fn replay(interpreter: &mut Interpreter, events: &[Event]) -> Result<u32, String> {
+    replay_with(interpreter, events, |_, _| {})
+}
+
+/// Replays a transcript as `replay` does, calling `observe` with the turn and
+/// the interpreter once the game has started and again after each command.
+pub fn replay_with(
+    interpreter: &mut Interpreter,
+    events: &[Event],
+    mut observe: impl FnMut(u32, &Interpreter),
+) -> Result<u32, String> {
+    let mut turn = 0;
+    let mut said = interpreter.start();
+    observe(turn, interpreter);
+    for event in events {
+        match event.role {
+            Role::Player => {
+                check_all_said(turn, &said)?;
+                if interpreter.is_finished() {
+                    return Err(format!("turn {}: the game is already over", event.turn));
+                }
+                turn = event.turn;
+                said = interpreter.command(&event.text);
+                interpreter.take_save_request();
+                observe(turn, interpreter);
+            }
+            Role::Engine => match said.strip_prefix(event.text.as_str()) {
+                Some(rest) => said = rest.to_string(),
+                None => {
+                    return Err(format!(
+                        "turn {}: the game said {:?}, but the transcript has {:?}",
+                        event.turn, said, event.text
+                    ))
+                }
+            },
+        }
+    }
+    check_all_said(turn, &said)?;
+    Ok(turn)
+}
+
+/// Checks that the transcript has everything the game said in a turn.
+fn check_all_said(turn: u32, rest: &str) -> Result<(), String> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "turn {}: the game also said {:?}, which the transcript leaves out",
+            turn, rest
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::Dumb;
+    use crate::format::{Breaking, Formatter};
+    use crate::game::interpreter::OccurrencePolicy;
+
+    fn interpreter() -> Interpreter {
+        let mut interpreter = Interpreter::new(crate::load_demo().unwrap());
+        interpreter.set_occurrence_policy(OccurrencePolicy::Never);
+        interpreter
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut transcript = Vec::new();
+        let console = Dumb::new(&b"look\ninventory\n"[..], io::sink(), 0);
+        let mut recorder = Recorder::new(console, &mut transcript);
+        crate::play(
+            &mut interpreter(),
+            &mut Formatter::new(40, Breaking::Words),
+            &mut recorder,
+        )
+        .unwrap();
+
+        let events = parse(&String::from_utf8(transcript).unwrap()).unwrap();
+        let roles: Vec<_> = events
+            .iter()
+            .map(|event| (event.role, event.turn))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                (Role::Engine, 0),
+                (Role::Player, 1),
+                (Role::Engine, 1),
+                (Role::Player, 2),
+                (Role::Engine, 2)
+            ]
+        );
+        assert_eq!(events[3].text, "inventory");
+        assert_eq!(replay(&mut interpreter(), &events), Ok(2));
+
+        let mut changed = events.clone();
+        changed[4].text = "I'm carrying a lot.\n".to_string();
+        assert!(replay(&mut interpreter(), &changed)
+            .unwrap_err()
+            .starts_with("turn 2: the game said"));
+        assert!(replay(&mut interpreter(), &events[..4])
+            .unwrap_err()
+            .starts_with("turn 2: the game also said"));
+    }
+
+    /// A console that keeps a history of its commands, as the terminal does.
+    struct Remembering<C> {
+        console: C,
+        history: History,
+    }
+
+    impl<C: Console> Console for Remembering<C> {
+        fn write(&mut self, text: &str) -> io::Result<()> {
+            self.console.write(text)
+        }
+
+        fn read_line(&mut self) -> io::Result<Option<String>> {
+            self.console.read_line()
+        }
+
+        fn history(&mut self) -> Option<&mut History> {
+            Some(&mut self.history)
+        }
+    }
+
+    #[test]
+    fn test_history_not_transcribed() {
+        let record = |interpreter: &mut Interpreter| {
+            let mut transcript = Vec::new();
+            let mut output = Vec::new();
+            let console = Remembering {
+                console: Dumb::new(&b"look\nhistory\ninventory\n"[..], &mut output, 0),
+                history: History::new(),
+            };
+            let mut recorder = Recorder::new(console, &mut transcript);
+            crate::play(interpreter, &mut Formatter::unwrapped(), &mut recorder).unwrap();
+            drop(recorder);
+            (
+                String::from_utf8(transcript).unwrap(),
+                String::from_utf8(output).unwrap(),
+            )
+        };
+
+        let (transcript, output) = record(&mut interpreter());
+        assert!(output.contains("1  look\r\n2  history\r\n"));
+        let events = parse(&transcript).unwrap();
+        assert!(events.iter().all(|event| event.text != "history"));
+        assert_eq!(replay(&mut interpreter(), &events), Ok(2));
+
+        // In purist mode the game is asked, as it is for any other command.
+        let mut purist = interpreter();
+        purist.set_purist(true);
+        let (transcript, output) = record(&mut purist);
+        assert!(!output.contains("2  history"));
+        let events = parse(&transcript).unwrap();
+        assert_eq!(events[3].text, "history");
+        let mut purist = interpreter();
+        purist.set_purist(true);
+        assert_eq!(replay(&mut purist, &events), Ok(3));
+    }
+
+    #[test]
+    fn test_parse() {
+        let event = |role, turn| {
+            format!(
+                r#"{{"role":"{}","text":"","turn":{},"timestamp":0}}"#,
+                role, turn
+            )
+        };
+        let text = [event("engine", 0), event("player", 1), event("engine", 1)].join("\n");
+        assert_eq!(parse(&text).unwrap().len(), 3);
+        assert!(parse(&event("player", 2))
+            .unwrap_err()
+            .contains("expected turn 1"));
+        assert!(parse(&event("narrator", 0))
+            .unwrap_err()
+            .starts_with("line 1:"));
+        assert!(parse(
+            r#"{"role":"engine","text":"","turn":0,"timestamp":5}
+{"role":"engine","text":"","turn":0,"timestamp":4}"#
+        )
+        .unwrap_err()
+        .contains("backward"));
+    }
+}
@@ -0,0 +1,112 @@
+//! This module plays a game from a walkthrough, a script of commands, and
+//! checks that the game says what is expected of it along the way, so that
+//! a collection of games can be checked to be winnable without anyone at the
+//! keyboard.
+//!
+//! A walkthrough has one command to a line, as the player would type it.
+//! Blank lines, and lines starting with `#`, are skipped, so that it can be
+//! annotated.  The game is played until the walkthrough runs out or the game
+//! ends, whichever comes first.
+
+use crate::game::interpreter::Interpreter;
+
+/// Holds the result of playing a walkthrough.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// The number of commands given.
+    pub commands: usize,
+    /// Whether the game ended, which may have been before the walkthrough
+    /// did.
+    pub finished: bool,
+    /// The expected texts that the game never said, in the order given.
+    pub missing: Vec<String>,
+}
+
+impl Outcome {
+    /// Returns whether the game said everything expected of it.
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Returns the commands of a walkthrough, without its blank lines and
+/// comments.
+pub fn commands(script: &str) -> Vec<&str> {
+    script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Starts the game in the given interpreter, plays the walkthrough, and
+/// checks that each of the expected texts appears somewhere in what the game
+/// said, from the start to the last command.
+///
+/// ```
+/// use rusty_adams::walkthrough;
+///
+/// let fixture = rusty_adams::fixtures::find("counter").unwrap();
+/// let mut interpreter = fixture.interpreter()?;
+/// let script = "# Stop the bomb.\ncut wire\nwait\n";
+/// let outcome = walkthrough::play(&mut interpreter, script, &["Click.".to_string()]);
+/// assert!(outcome.passed() && !outcome.finished);
+/// assert_eq!(outcome.commands, 2);
+/// # Ok::<(), String>(())
+/// ```
+pub fn play(interpreter: &mut Interpreter, script: &str, expect: &[String]) -> Outcome {
+    let mut said = interpreter.start();
+    let mut count = 0;
+    for command in commands(script) {
+        if interpreter.is_finished() {
+            break;
+        }
+        said.push_str(&interpreter.command(command));
+        count += 1;
+    }
+    Outcome {
+        commands: count,
+        finished: interpreter.is_finished(),
+        missing: expect
+            .iter()
+            .filter(|text| !said.contains(text.as_str()))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play() {
+        let script = "# Take the key, but not the rock.\nget key\n\n  get rock  \ni\n";
+        assert_eq!(commands(script), ["get key", "get rock", "i"]);
+
+        let game = crate::load_game("tests/fixtures/get_drop.dat").unwrap();
+        let mut interpreter = Interpreter::new(game);
+        let expect = [
+            "too heavy".to_string(),
+            "I'm carrying:\nBrass key.".to_string(),
+            "Painting.".to_string(),
+        ];
+        let outcome = play(&mut interpreter, script, &expect);
+        assert_eq!(
+            outcome,
+            Outcome {
+                commands: 3,
+                finished: false,
+                missing: vec!["Painting.".to_string()],
+            }
+        );
+        assert!(!outcome.passed());
+
+        // The bomb goes off partway through, and the rest isn't played.
+        let game = crate::load_game("tests/fixtures/counter.dat").unwrap();
+        let mut interpreter = Interpreter::new(game);
+        let outcome = play(&mut interpreter, &"wait\n".repeat(6), &[]);
+        assert_eq!(outcome.commands, 2);
+        assert!(outcome.finished && outcome.passed());
+    }
+}
@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use rusty_adams::game::{writer, Game};
+use rusty_adams::tokenizer::Stream;
+
+/// Parses a game from the given bytes.
+pub fn parse(data: Vec<u8>) -> Game {
+    let mut stream = Stream::new(data).unwrap();
+    match Game::new(&mut stream) {
+        Ok(game) => game,
+        Err(err) => panic!("Error: {}", err),
+    }
+}
+
+/// Writes a game out as text.
+pub fn write(game: &Game) -> String {
+    let mut out: Vec<u8> = Vec::new();
+    writer::write_game(&mut out, game).unwrap();
+    String::from_utf8(out).unwrap()
+}
@@ -0,0 +1,33 @@
+//! Plays the conformance suite that the `test-fixtures` feature publishes,
+//! so that the transcripts other implementations are held to stay true of
+//! this one.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::fixtures::{self, FIXTURES};
+
+#[test]
+fn test_fixtures() {
+    for fixture in &FIXTURES {
+        let mut interpreter = fixture.interpreter().unwrap();
+        assert_eq!(fixture.check(&mut interpreter), Ok(()));
+    }
+}
+
+#[test]
+fn test_find() {
+    assert_eq!(
+        fixtures::find("counter").map(|fixture| fixture.name),
+        Some("counter")
+    );
+    assert!(fixtures::find("no_such_game").is_none());
+}
+
+#[test]
+fn test_mismatch() {
+    let fixture = fixtures::find("get_drop").unwrap();
+    let mut interpreter = fixture.interpreter().unwrap();
+    interpreter.start();
+    interpreter.command("get key");
+    let err = fixture.check(&mut interpreter).unwrap_err();
+    assert!(err.starts_with("get_drop: after \"the start\""), "{}", err);
+}
@@ -0,0 +1,79 @@
+//! These tests make sure that the demo game built into the binary is sound,
+//! and can be played through to the end.
+
+use rusty_adams::game::interpreter::Interpreter;
+
+extern crate rusty_adams;
+
+/// The commands that win the demo game.
+const WALKTHROUGH: [&str; 20] = [
+    "north",
+    "look mat",
+    "get key",
+    "open door",
+    "enter door",
+    "get lantern",
+    "go steps",
+    "get knife",
+    "go steps",
+    "up",
+    "get spyglass",
+    "down",
+    "drop spyglass",
+    "go door",
+    "south",
+    "east",
+    "open oyster",
+    "get pearl",
+    "west",
+    "north",
+];
+
+#[test]
+fn test_demo_is_strictly_valid() {
+    let mut stream = rusty_adams::tokenizer::Stream::new(rusty_adams::DEMO.to_vec()).unwrap();
+    let warnings = match rusty_adams::game::Game::new_strict(&mut stream) {
+        Ok((_, warnings)) => warnings,
+        Err(err) => panic!("Error: {}", err),
+    };
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_demo_walkthrough() {
+    let mut interpreter = Interpreter::new(rusty_adams::load_demo().unwrap());
+    assert!(interpreter.start().contains("A storm is gathering"));
+    for command in WALKTHROUGH {
+        let text = interpreter.command(command);
+        assert!(!interpreter.is_finished(), "{}: {}", command, text);
+    }
+    assert!(interpreter.command("go door").contains("snug room"));
+    assert!(interpreter.command("drop pearl").contains("*Pearl*"));
+    assert!(interpreter.command("score").contains("Well done."));
+    assert!(interpreter.is_finished());
+}
+
+#[test]
+fn test_demo_dark_cellar() {
+    let mut interpreter = Interpreter::new(rusty_adams::load_demo().unwrap());
+    interpreter.start();
+    for command in &WALKTHROUGH[..5] {
+        interpreter.command(command);
+    }
+    assert!(interpreter.command("go steps").contains("too dark"));
+    assert!(interpreter.command("up").contains("broke my neck"));
+    assert!(interpreter.is_finished());
+}
+
+#[test]
+fn test_demo_purist() {
+    let mut interpreter = Interpreter::new(rusty_adams::load_demo().unwrap());
+    interpreter.set_purist(true);
+    interpreter.start();
+    for command in WALKTHROUGH.iter().chain(&["go door", "drop pearl"]) {
+        interpreter.command(command);
+    }
+    assert!(interpreter
+        .command("score")
+        .ends_with("Well done.\nThis was a purist game, played without modern conveniences.\nThe game is now over.\n"));
+}
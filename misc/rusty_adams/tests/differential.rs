@@ -0,0 +1,120 @@
+//! These tests play the same commands through rusty_adams and through a
+//! reference build of ScottFree, and compare what the two print, to catch any
+//! place where the interpreters disagree about what a game does.
+//!
+//! The reference binary is named by the `SCOTTFREE` environment variable; when
+//! it isn't set, the tests pass without doing anything, so that they can run
+//! anywhere.  ScottFree must be built to write plain text to its standard
+//! output rather than drawing its screen with curses.
+//!
+//! Only the fixtures without random events are played, since the two
+//! interpreters can't be made to roll the same dice.
+
+#![cfg(feature = "cli")]
+
+use pretty_assertions::assert_eq;
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+extern crate rusty_adams;
+
+/// The environment variable that names the reference binary.
+const REFERENCE: &str = "SCOTTFREE";
+
+/// Each fixture that is played, with the commands that are typed into it.
+const SCRIPTS: [(&str, &[&str]); 5] = [
+    (
+        "get_drop",
+        &["i", "get key", "get rock", "i", "drop all", "look"],
+    ),
+    ("dark_room", &["i", "w", "e", "i", "score"]),
+    (
+        "counter",
+        &["wait", "wind timer", "wait", "cut wire", "wait"],
+    ),
+    ("continue", &["push button", "push button", "wait"]),
+    (
+        "moved",
+        &["read coin", "open door", "read coin", "e", "read painting"],
+    ),
+];
+
+/// Runs a program with ScottFree's arguments for the given fixture, typing
+/// the given commands into it, and returns what it printed.
+fn play(mut program: Command, name: &str, commands: &[&str]) -> String {
+    let mut child = program
+        .arg(format!("tests/fixtures/{}.dat", name))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| panic!("Error: {}", err));
+    let mut stdin = child.stdin.take().unwrap();
+    for command in commands {
+        writeln!(stdin, "{}", command).unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    normalize(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Removes the differences between the two interpreters' output that don't
+/// matter: terminal escapes, trailing spaces, blank lines, and the banner.
+fn normalize(output: &str) -> String {
+    let mut text = String::new();
+    let mut chars = output.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // Skip a control sequence, which ends with a letter.
+            chars.find(|ch| ch.is_ascii_alphabetic());
+        } else if ch != '\r' {
+            text.push(ch);
+        }
+    }
+    let lines: Vec<_> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("Distributed under"))
+        .map_or(0, |banner| banner + 1);
+    lines[start..].join("\n")
+}
+
+#[test]
+fn test_against_scottfree() {
+    let reference = match env::var_os(REFERENCE) {
+        Some(reference) => reference,
+        None => {
+            eprintln!(
+                "{} isn't set, so rusty_adams isn't compared to it",
+                REFERENCE
+            );
+            return;
+        }
+    };
+    for (name, commands) in SCRIPTS {
+        let want = play(Command::new(&reference), name, commands);
+        let mut ours = Command::new(env!("CARGO_BIN_EXE_rusty_adams"));
+        ours.arg("scottfree");
+        let got = play(ours, name, commands);
+        assert_eq!(
+            got, want,
+            "rusty_adams and ScottFree disagree about {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_normalize() {
+    let output = "Scott Free, A Scott Adams game driver in C.\n\
+                  Distributed under the GNU software license\n\n\
+                  \x1b[2JI'm in a small room  \r\n\nTell me what to do ? ";
+    assert_eq!(
+        normalize(output),
+        "I'm in a small room\nTell me what to do ?"
+    );
+}
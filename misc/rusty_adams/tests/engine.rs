@@ -0,0 +1,543 @@
+//! These integration tests drive the interpreter through the tiny games in
+//! `tests/fixtures`, each of which exercises one part of the engine, so that
+//! its behavior can be checked without relying on the commercial games.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::format::Perspective;
+use rusty_adams::game::interpreter::diff::{Change, CounterChange, FlagChange, ItemMove};
+use rusty_adams::game::interpreter::save::{SAVE_HEADER, SAVE_VERSION};
+use rusty_adams::game::interpreter::timer::{Timer, When};
+use rusty_adams::game::interpreter::{
+    EngineError, Interpreter, OccurrencePolicy, Style, TurnPhase,
+};
+use rusty_adams::game::FlagId;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+extern crate rusty_adams;
+
+/// Starts the fixture game with the given name, returning the interpreter
+/// and the text shown before the first command.
+fn start(name: &str) -> (Interpreter, String) {
+    let game = match rusty_adams::load_game(&format!("tests/fixtures/{}.dat", name)) {
+        Ok(game) => game,
+        Err(err) => panic!("Error: {}", err),
+    };
+    let mut interpreter = Interpreter::new(game);
+    let text = interpreter.start();
+    (interpreter, text)
+}
+
+#[test]
+fn test_get_and_drop() {
+    let (mut interpreter, text) = start("get_drop");
+    let room = "I'm in a small room\n\nObvious exits: none.\n\n";
+    assert_eq!(
+        text,
+        format!(
+            "{}I can also see: Brass key - Heavy rock - Painting\n",
+            room
+        )
+    );
+
+    // Items with an autograb name can be picked up by it, unless an action
+    // handles the command first.
+    assert_eq!(
+        interpreter.command("get key"),
+        format!("O.K.\n{}I can also see: Heavy rock - Painting\n", room)
+    );
+    assert_eq!(interpreter.command("i"), "I'm carrying:\nBrass key.\n");
+    assert_eq!(
+        interpreter.command("take rock"),
+        "It's too heavy to lift.\n"
+    );
+    assert_eq!(interpreter.command("get"), "What?\n");
+    assert_eq!(
+        interpreter.command("get painting"),
+        "I've too much to carry.\n"
+    );
+    assert_eq!(
+        interpreter.command("drop key"),
+        format!(
+            "O.K.\n{}I can also see: Brass key - Heavy rock - Painting\n",
+            room
+        )
+    );
+    assert_eq!(
+        interpreter.command("drop key"),
+        "It's beyond my power to do that.\n"
+    );
+
+    // GET ALL tries each item in turn, running its actions first.
+    assert_eq!(
+        interpreter.command("get all"),
+        format!(
+            "Brass key: O.K.\nIt's too heavy to lift.\nI've too much to carry.\n{}I can also see: Heavy rock - Painting\n",
+            room
+        )
+    );
+    assert_eq!(
+        interpreter.command("drop all"),
+        format!(
+            "Brass key: O.K.\n{}I can also see: Brass key - Heavy rock - Painting\n",
+            room
+        )
+    );
+    assert_eq!(interpreter.command("drop all"), "Nothing dropped.\n");
+}
+
+#[test]
+fn test_dark_room() {
+    let (mut interpreter, text) = start("dark_room");
+    let clearing = "I'm in a forest clearing\n\nObvious exits: Down.\n\n";
+    let cave = "I'm in a damp cave\n\nObvious exits: Up.\n\nI can also see: Crystal\n";
+    assert_eq!(text, format!("{}I can also see: Lamp\n", clearing));
+
+    // Night has fallen, so the cave is dark without the lamp.
+    assert_eq!(interpreter.command("d"), "I can't see. It is too dark!\n");
+    assert_eq!(
+        interpreter.command("u"),
+        format!(
+            "Dangerous to move in the dark!\n{}I can also see: Lamp\n",
+            clearing
+        )
+    );
+    assert_eq!(
+        interpreter.command("get lamp"),
+        format!("O.K.\nYour light is growing dim.\n{}", clearing)
+    );
+    assert_eq!(interpreter.command("d"), cave);
+
+    // The lamp burns out, but it is up to the game to decide what that means.
+    // As in the original engine, the player is told twice.
+    for _ in 0..3 {
+        interpreter.command("n");
+    }
+    for _ in 0..2 {
+        assert_eq!(
+            interpreter.command("n"),
+            "I can't go in that direction.\nYour light has run out.\n"
+        );
+    }
+    assert_eq!(interpreter.command("u"), clearing);
+    interpreter.command("drop lamp");
+    assert_eq!(interpreter.command("d"), "I can't see. It is too dark!\n");
+    assert_eq!(
+        interpreter.command("e"),
+        "Dangerous to move in the dark!\nI fell down and broke my neck.\n"
+    );
+    assert!(interpreter.is_finished());
+}
+
+#[test]
+fn test_counter() {
+    let (mut interpreter, text) = start("counter");
+    assert!(text.ends_with("The bomb ticks. Its timer reads:\n3\n"));
+    assert_eq!(
+        interpreter.command("wait"),
+        "Time passes.\nThe bomb ticks. Its timer reads:\n2\n"
+    );
+    assert_eq!(
+        interpreter.command("wind"),
+        "I wind the timer back.\nThe bomb ticks. Its timer reads:\n3\n"
+    );
+    interpreter.command("wait");
+    assert_eq!(
+        interpreter.command("wait"),
+        "Time passes.\nThe bomb ticks. Its timer reads:\n1\nBOOM!\nThe game is now over.\n"
+    );
+    assert!(interpreter.is_finished());
+
+    let (mut interpreter, _) = start("counter");
+    assert_eq!(interpreter.command("cut wire"), "Click. The timer stops.\n");
+    for _ in 0..5 {
+        assert_eq!(interpreter.command("wait"), "Time passes.\n");
+    }
+    assert!(!interpreter.is_finished());
+}
+
+#[test]
+fn test_continue_chain() {
+    let (mut interpreter, _) = start("continue");
+
+    // The chain runs every following action with no words whose conditions
+    // are met, and the automatic actions can continue too.
+    assert_eq!(
+        interpreter.command("push button"),
+        "Click!\nA bell rings.\nA door opens.\nThe door creaks.\nSomething stirs.\n"
+    );
+
+    // The actions for PUSH BUTTON can no longer run, which isn't the same
+    // as there being none for PUSH WIDGET.
+    assert_eq!(interpreter.command("push button"), "I can't do that yet.\n");
+    assert_eq!(
+        interpreter.command("push widget"),
+        "I don't understand your command.\n"
+    );
+    assert_eq!(
+        interpreter.command("xyzzy"),
+        "You use word(s) I don't know!\n"
+    );
+}
+
+#[test]
+fn test_style() {
+    let (mut interpreter, _) = start("get_drop");
+    interpreter.set_style(Style {
+        perspective: Perspective::ScottFree,
+        trs80: true,
+        ..Style::default()
+    });
+    let line = "<------------------------------------------------------------>\n";
+    assert_eq!(
+        interpreter.command("get key"),
+        format!(
+            "O.K.\nYou are small room\n\nObvious exits: none.\n\nYou can also see: Heavy rock. Painting. \n{}",
+            line
+        )
+    );
+    interpreter.command("get painting");
+    assert_eq!(interpreter.command("i"), "You are carrying:\nBrass key.\n");
+    assert_eq!(
+        interpreter.command("get painting"),
+        "You are carrying too much.\n"
+    );
+}
+
+#[test]
+fn test_save_and_restore() {
+    let (mut interpreter, _) = start("dark_room");
+    interpreter.command("get lamp");
+    interpreter.command("d");
+    let saved = interpreter.save();
+    assert!(saved.contains("\n255\nturns 2\ntimers\nrng "));
+
+    let (mut restored, _) = start("dark_room");
+    assert_eq!(restored.restore(&saved), Ok(()));
+    assert_eq!(restored.save(), saved);
+    assert_eq!(
+        restored.command("i"),
+        "I'm carrying:\nLamp.\nYour light is growing dim.\n"
+    );
+
+    let (mut restored, _) = start("dark_room");
+    assert!(restored.restore("1 2 3").is_err());
+    assert!(restored.restore(&saved.replace("255", "lamp")).is_err());
+}
+
+#[test]
+fn test_save_versions() {
+    let (mut interpreter, _) = start("dark_room");
+    interpreter.command("get lamp");
+    let saved = interpreter.save();
+    assert!(saved.starts_with(&format!("{} {}\n", SAVE_HEADER, SAVE_VERSION)));
+
+    // ScottFree's saves have no version line and no fields, and are
+    // migrated from version 0.  Version 1 had the line but no fields.  The
+    // turns that they don't count start again from 0, and the dice that
+    // they don't record are left as they were.
+    let lines = |text: &str, keep: fn(&str) -> bool| -> String {
+        text.lines()
+            .filter(|line| keep(line))
+            .map(|line| format!("{}\n", line))
+            .collect()
+    };
+    let numbers = lines(&saved, |line| !line.starts_with(char::is_alphabetic));
+    let migrated = lines(&saved, |line| !line.starts_with("rng ")).replace("turns 1", "turns 0");
+    for old in [
+        numbers.clone(),
+        format!("{} 1\n{}", SAVE_HEADER, numbers),
+        format!("{} 2\n{}turns 0\ntimers\n", SAVE_HEADER, numbers),
+    ] {
+        let (mut restored, _) = start("dark_room");
+        assert_eq!(restored.restore(&old), Ok(()));
+        let save = restored.save();
+        assert_eq!(lines(&save, |line| !line.starts_with("rng ")), migrated);
+    }
+
+    let (mut restored, _) = start("dark_room");
+    let newer = saved.replace(
+        &format!("save {}", SAVE_VERSION),
+        &format!("save {}", SAVE_VERSION + 1),
+    );
+    assert!(restored
+        .restore(&newer)
+        .unwrap_err()
+        .contains("newer version"));
+    assert_eq!(
+        restored.restore(&format!("{}colour blue\n", saved)),
+        Err("The saved game has an unknown field 'colour'".to_string())
+    );
+    assert!(restored.restore("rusty_adams save x\n").is_err());
+}
+
+#[test]
+fn test_save_dice() {
+    // Whether the bird sings is down to the dice, which a restored game
+    // rolls just as the saved one would have.
+    let play = |interpreter: &mut Interpreter| -> Vec<String> {
+        (0..100).map(|_| interpreter.command("wait")).collect()
+    };
+    let (mut interpreter, _) = start("chance");
+    interpreter.set_seed(1723);
+    play(&mut interpreter);
+    let saved = interpreter.save();
+    let expected = play(&mut interpreter);
+    assert!(expected.iter().any(|said| said.contains("sings")));
+    assert!(expected.iter().any(|said| !said.contains("sings")));
+
+    let (mut restored, _) = start("chance");
+    assert_eq!(restored.restore(&saved), Ok(()));
+    assert_eq!(play(&mut restored), expected);
+
+    let (mut restored, _) = start("chance");
+    let rng = saved.lines().find(|line| line.starts_with("rng ")).unwrap();
+    for bad in ["rng 12 0", "rng 12", &format!("{}x", rng)] {
+        assert!(restored.restore(&saved.replace(rng, bad)).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_turn_phases() {
+    let game = rusty_adams::load_game("tests/fixtures/counter.dat").unwrap();
+    let mut interpreter = Interpreter::new(game);
+    let phases = Rc::new(RefCell::new(vec![]));
+    let seen = phases.clone();
+    interpreter.on_phase(move |phase| seen.borrow_mut().push(phase));
+
+    interpreter.start();
+    assert_eq!(phases.take(), [TurnPhase::Occurrences, TurnPhase::Command]);
+    interpreter.command("wait");
+    assert_eq!(
+        phases.take(),
+        [
+            TurnPhase::Actions,
+            TurnPhase::Light,
+            TurnPhase::Occurrences,
+            TurnPhase::Command
+        ]
+    );
+
+    // Unknown words don't take a turn, and nothing follows the end.
+    interpreter.command("xyzzy");
+    assert_eq!(phases.take(), []);
+    interpreter.command("wait");
+    assert!(interpreter.is_finished());
+    assert_eq!(phases.take().last(), Some(&TurnPhase::Occurrences));
+    assert_eq!(interpreter.phase(), TurnPhase::Occurrences);
+}
+
+#[test]
+fn test_item_moved() {
+    let (mut interpreter, _) = start("moved");
+    let coin_at_home = "The coin is where it started.\n";
+    let coin_moved = "The coin has been moved.\n";
+    assert_eq!(interpreter.command("read coin"), coin_at_home);
+
+    // Carrying an item moves it, but putting it back where it began doesn't.
+    interpreter.command("get coin");
+    assert_eq!(interpreter.command("read coin"), coin_moved);
+    interpreter.command("drop coin");
+    assert_eq!(interpreter.command("read coin"), coin_at_home);
+
+    // Items that swap places have both moved, since each remembers where it
+    // began rather than where the other began.
+    interpreter.command("open door");
+    assert_eq!(interpreter.command("read coin"), coin_moved);
+    assert_eq!(
+        interpreter.command("read painting"),
+        "The painting has been moved.\n"
+    );
+
+    // Putting the coin back in the hall leaves the painting there too.
+    interpreter.command("close door");
+    assert_eq!(interpreter.command("read coin"), coin_at_home);
+    assert_eq!(
+        interpreter.command("read painting"),
+        "The painting has been moved.\n"
+    );
+}
+
+#[test]
+fn test_occurrence_policy() {
+    let load = |name: &str, policy| {
+        let game = rusty_adams::load_game(&format!("tests/fixtures/{}.dat", name)).unwrap();
+        let mut interpreter = Interpreter::new(game);
+        interpreter.set_occurrence_policy(policy);
+        interpreter
+    };
+
+    // The bird sings only one turn in a hundred, unless every automatic
+    // action always runs.
+    let mut interpreter = load("chance", OccurrencePolicy::Always);
+    assert!(interpreter.start().ends_with("A bird sings.\n"));
+    assert_eq!(interpreter.command("wait"), "Time passes.\nA bird sings.\n");
+
+    // Without the automatic actions, the bomb is never armed.
+    let mut interpreter = load("counter", OccurrencePolicy::Never);
+    assert!(!interpreter.start().contains("The bomb ticks."));
+    for _ in 0..5 {
+        assert_eq!(interpreter.command("wait"), "Time passes.\n");
+    }
+}
+
+#[test]
+fn test_action_budget() {
+    // Pushing the button runs a chain of three actions, and then two more
+    // automatic ones.
+    let (mut interpreter, _) = start("continue");
+    interpreter.set_action_budget(Some(2));
+    assert_eq!(
+        interpreter.try_command("push button"),
+        Err(EngineError::RunawayAction {
+            action: 3,
+            budget: 2
+        })
+    );
+
+    let (mut interpreter, _) = start("continue");
+    interpreter.set_action_budget(Some(4));
+    assert_eq!(
+        interpreter.command("push button"),
+        "Gave up on the turn at action 6, after running 4 actions\n"
+    );
+
+    // The budget is for a single turn.
+    let (mut interpreter, _) = start("continue");
+    interpreter.set_action_budget(Some(5));
+    assert!(interpreter.try_command("push button").is_ok());
+    interpreter.set_action_budget(None);
+    assert!(interpreter.try_command("push button").is_ok());
+}
+
+#[test]
+fn test_purist() {
+    let (mut interpreter, _) = start("get_drop");
+    interpreter.set_purist(true);
+    assert!(interpreter.is_purist());
+
+    // The conveniences are gone, but the game's own commands still work.
+    assert_eq!(interpreter.command("get all"), "What?\n");
+    assert!(interpreter.command("get key").starts_with("O.K.\n"));
+    assert_eq!(interpreter.command("drop all"), "What?\n");
+    assert_eq!(interpreter.command("l"), "You use word(s) I don't know!\n");
+    assert_eq!(interpreter.command("i"), "I'm carrying:\nBrass key.\n");
+
+    // Purist mode can't be combined with changing the automatic actions, so
+    // the bomb is armed after all.
+    let game = rusty_adams::load_game("tests/fixtures/counter.dat").unwrap();
+    let mut interpreter = Interpreter::new(game);
+    interpreter.set_occurrence_policy(OccurrencePolicy::Never);
+    interpreter.set_purist(true);
+    assert!(interpreter.start().contains("The bomb ticks."));
+}
+
+#[test]
+fn test_state_diff() {
+    let (mut interpreter, _) = start("moved");
+    let snapshot = interpreter.snapshot();
+    assert!(interpreter.diff_since(&snapshot).is_empty());
+
+    interpreter.command("get coin");
+    interpreter.command("e");
+    let diff = interpreter.diff_since(&snapshot);
+    assert_eq!(diff.room, Some(Change { from: 1, to: 2 }));
+    assert_eq!(
+        diff.items,
+        [ItemMove {
+            item: 0,
+            from: 1,
+            to: -1
+        }]
+    );
+    #[cfg(feature = "serde")]
+    assert_eq!(
+        serde_json::to_value(&diff).unwrap(),
+        serde_json::json!({
+            "room": { "from": 1, "to": 2 },
+            "flags": [],
+            "items": [{ "item": 0, "from": 1, "to": -1 }],
+            "counters": [],
+            "light_time": null,
+        })
+    );
+
+    // The bomb's timer is armed on the first turn, and then counts down a
+    // turn at a time.
+    let (mut interpreter, _) = start("counter");
+    let snapshot = interpreter.snapshot();
+    interpreter.command("wait");
+    let diff = interpreter.diff_since(&snapshot);
+    assert_eq!(diff.flags, []);
+    assert_eq!(
+        diff.counters,
+        [CounterChange {
+            counter: None,
+            from: 2,
+            to: 1,
+            delta: -1
+        }]
+    );
+}
+
+#[test]
+fn test_perspective() {
+    let (mut interpreter, _) = start("get_drop");
+    interpreter.set_perspective(Perspective::SecondPerson);
+    assert_eq!(
+        interpreter.command("look"),
+        "You're in a small room\n\nObvious exits: none.\n\nYou can also see: Brass key - Heavy rock - Painting\n"
+    );
+    assert_eq!(
+        interpreter.command("drop key"),
+        "It's beyond your power to do that.\n"
+    );
+
+    // Rooms that the game describes in full are left alone.
+    let (mut interpreter, _) = start("counter");
+    interpreter.set_perspective(Perspective::SecondPerson);
+    assert!(interpreter
+        .describe_room()
+        .starts_with("I'm standing beside a bomb.\n"));
+}
+
+#[test]
+fn test_timers() {
+    let (mut interpreter, _) = start("counter");
+    interpreter.command("cut wire");
+    let now = Rc::new(Cell::new(Duration::ZERO));
+    let clock = now.clone();
+    let timer = |flag, when| Timer {
+        flag: FlagId::new(flag).unwrap(),
+        set: true,
+        when,
+    };
+    interpreter.set_timers(
+        vec![
+            timer(20, When::Turns(3)),
+            timer(21, When::Elapsed(Duration::from_secs(60))),
+        ],
+        Box::new(move || clock.get()),
+    );
+    let flags = |interpreter: &mut Interpreter, command| {
+        let snapshot = interpreter.snapshot();
+        interpreter.command(command);
+        interpreter.diff_since(&snapshot).flags
+    };
+    let set = |flag| FlagChange { flag, set: true };
+
+    assert_eq!(flags(&mut interpreter, "wait"), []);
+    assert_eq!(flags(&mut interpreter, "wait"), [set(20)]);
+    assert_eq!(interpreter.turns(), 3);
+    now.set(Duration::from_secs(61));
+    let saved = interpreter.save();
+    assert!(saved.contains("turns 3\ntimers 0\n"));
+    assert_eq!(flags(&mut interpreter, "wait"), [set(21)]);
+
+    // A timer goes off only once, even if its flag is cleared again, and
+    // the save remembers which have.
+    interpreter.restore(&saved).unwrap();
+    assert_eq!(flags(&mut interpreter, "wait"), [set(21)]);
+    assert_eq!(flags(&mut interpreter, "wait"), []);
+}
@@ -2,10 +2,16 @@
 //! process and write a game back out in exactly the form it was read in.
 
 use pretty_assertions::assert_eq;
+use rusty_adams::game::interpreter::Interpreter;
 use std::fs;
+use std::path::Path;
 
 extern crate rusty_adams;
 
+mod common;
+
+use common::{parse, write};
+
 #[test]
 fn test_parse_and_write_are_inverses() {
     let game = match rusty_adams::load_game("games/adv01.dat") {
@@ -28,3 +34,40 @@ fn test_parse_and_write_are_inverses() {
     let want: String = String::from_utf8(want).unwrap();
     assert_eq!(got, want);
 }
+
+#[test]
+fn test_every_game_reads_back_as_written() {
+    // Not every file in the collection is laid out as we write it, but what
+    // we write must read back the same, quotes and all.
+    for path in rusty_adams::list_games(Path::new("games")).unwrap() {
+        let written = write(&rusty_adams::load_game(&path.to_string_lossy()).unwrap());
+        assert_eq!(
+            write(&parse(written.clone().into_bytes())),
+            written,
+            "{} doesn't read back as written",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_quotes_and_escapes() {
+    // The rock's message has a double quote in each of the ways a game file
+    // can give one, along with an escaped backtick and backslash.
+    let data = fs::read_to_string("tests/fixtures/get_drop.dat").unwrap();
+    let data = data.replace("It's too heavy to lift.", r#"`Ugh!` \"Heavy.\" \` \\"#);
+    let game = parse(data.into_bytes());
+
+    // Double quotes are written back as backticks, and the rest escaped, so
+    // that the file reads the same again.
+    let written = write(&game);
+    assert!(written.contains(r#""`Ugh!` `Heavy.` \` \\""#));
+    assert_eq!(write(&parse(written.clone().into_bytes())), written);
+
+    let mut interpreter = Interpreter::new(parse(written.into_bytes()));
+    interpreter.start();
+    assert_eq!(
+        interpreter.command("get rock"),
+        "\"Ugh!\" \"Heavy.\" ` \\\n"
+    );
+}
@@ -0,0 +1,49 @@
+//! These tests check that the games of the collection are recognized by their
+//! content, however their files are laid out.
+
+#![cfg(all(feature = "catalog", feature = "identify"))]
+
+use rusty_adams::catalog;
+use rusty_adams::identify::{self, RELEASES};
+use std::fs;
+use std::path::Path;
+
+extern crate rusty_adams;
+
+mod common;
+
+use common::parse;
+
+#[test]
+fn test_collection() {
+    for path in rusty_adams::list_games(Path::new("games")).unwrap() {
+        let game = rusty_adams::load_game(&path.to_string_lossy()).unwrap();
+        let release = identify::identify(&game).unwrap();
+        assert_eq!(
+            Some(release.title),
+            catalog::title_of(&path).map(|title| title.name)
+        );
+    }
+    let demo = rusty_adams::load_demo().unwrap();
+    assert_eq!(
+        identify::identify(&demo).map(|release| release.to_string()),
+        Some("The Lighthouse v1.00 (rusty_adams demo)".to_string())
+    );
+
+    let hashes: Vec<_> = RELEASES.iter().map(|release| release.hash).collect();
+    assert!(hashes
+        .iter()
+        .all(|hash| hashes.iter().filter(|other| *other == hash).count() == 1));
+}
+
+#[test]
+fn test_whitespace_is_ignored() {
+    let data = fs::read_to_string("games/adv01.dat").unwrap();
+    let relaid = data.replace('\n', "\r\n").replace(" 0 ", "  0   ");
+    let release = identify::identify(&parse(relaid.into_bytes())).unwrap();
+    assert_eq!(release.to_string(), "Adventureland v4.16 (TRS-80 release)");
+
+    let changed = data.replace("*MAGIC MIRROR*", "*MAGICK MIRROR*");
+    assert_ne!(changed, data);
+    assert_eq!(identify::identify(&parse(changed.into_bytes())), None);
+}
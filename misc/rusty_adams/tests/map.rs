@@ -0,0 +1,45 @@
+//! These tests check that the exits of a game can be edited and that exits
+//! with no way back are warned about.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::{map, Direction};
+
+extern crate rusty_adams;
+
+#[test]
+fn test_link_rooms() {
+    // The demo's rooms are all linked both ways.
+    let mut game = rusty_adams::load_demo().unwrap();
+    assert_eq!(map::asymmetric_exits(&game), Vec::<String>::new());
+    assert_eq!(map::exit(&game, 1, Direction::North), Some(2));
+
+    map::link_rooms(&mut game, 2, Direction::Up, 4, false).unwrap();
+    assert_eq!(
+        map::asymmetric_exits(&game),
+        ["Room 2 leads Up to room 4, which doesn't lead Down back to it"]
+    );
+    map::link_rooms(&mut game, 2, Direction::Up, 4, true).unwrap();
+    assert_eq!(map::exit(&game, 4, Direction::Down), Some(2));
+    assert_eq!(map::asymmetric_exits(&game), Vec::<String>::new());
+
+    // Linking one way to the storeroom removes the exit.
+    map::link_rooms(&mut game, 2, Direction::Up, 0, false).unwrap();
+    assert_eq!(map::exit(&game, 2, Direction::Up), None);
+    assert_eq!(
+        map::asymmetric_exits(&game),
+        ["Room 4 leads Down to room 2, which doesn't lead Up back to it"]
+    );
+
+    assert!(map::link_rooms(&mut game, 1, Direction::West, 99, true).is_err());
+    assert!(map::link_rooms(&mut game, 1, Direction::West, 0, true).is_err());
+}
+
+#[test]
+fn test_direction() {
+    assert_eq!(Direction::from_name("up"), Some(Direction::Up));
+    assert_eq!(Direction::from_name("sideways"), None);
+    for dir in Direction::ALL {
+        assert_eq!(dir.opposite().opposite(), dir);
+        assert_ne!(dir.opposite(), dir);
+    }
+}
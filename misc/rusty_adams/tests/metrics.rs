@@ -0,0 +1,37 @@
+//! These tests check the measures of a game's design that `corpus-stats`
+//! adds up.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::{map, metrics, Direction};
+
+extern crate rusty_adams;
+
+#[test]
+fn test_measure() {
+    let mut game = rusty_adams::load_demo().unwrap();
+    let metrics = metrics::measure(&game);
+    assert_eq!(
+        (metrics.actions, metrics.occurrences, metrics.verbs_used),
+        (24, 1, 11)
+    );
+    assert_eq!(
+        metrics.actions_per_verb,
+        23.0 / 11.0,
+        "the automatic action is for no verb"
+    );
+    assert_eq!(metrics.longest_message, 182);
+    assert_eq!(
+        (
+            metrics.rooms,
+            metrics.exits,
+            metrics.one_way_exits,
+            metrics.unreachable
+        ),
+        (6, 6, 0, 3)
+    );
+
+    // An exit with no way back is counted as one way.
+    map::link_rooms(&mut game, 2, Direction::Up, 4, false).unwrap();
+    let metrics = metrics::measure(&game);
+    assert_eq!((metrics.exits, metrics.one_way_exits), (7, 1));
+}
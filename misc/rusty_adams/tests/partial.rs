@@ -0,0 +1,49 @@
+//! This integration test makes sure that what can be parsed of a damaged game
+//! file is kept, and that it can be salvaged into a game file of its own.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::{writer, Game};
+use rusty_adams::tokenizer::Stream;
+
+extern crate rusty_adams;
+
+#[test]
+fn test_truncated_fixture() {
+    let partial = match rusty_adams::load_game_partial("tests/fixtures/truncated.dat") {
+        Ok(_) => panic!("The truncated game parsed"),
+        Err(partial) => partial,
+    };
+    assert_eq!(partial.sections(), ["header"]);
+    assert_eq!(partial.header_counts().unwrap().rooms, 2);
+    assert_eq!(partial.treasures(), None);
+    assert_eq!(partial.error(), "0:0: Unexpected end of stream");
+}
+
+#[test]
+fn test_salvage_of_cut_game() {
+    let data = std::fs::read("games/adv01.dat").unwrap();
+    let whole = rusty_adams::load_game("games/adv01.dat").unwrap();
+
+    // Cut the game off in the middle of a string among the items.
+    let (mut stream, err) = Stream::new_partial(data[..15500].to_vec());
+    assert_eq!(err.unwrap().to_string(), "1964:1: Unterminated string");
+    let partial = match Game::new_partial(&mut stream) {
+        Ok(_) => panic!("The cut game parsed"),
+        Err(partial) => partial,
+    };
+    assert_eq!(
+        partial.sections(),
+        ["header", "actions", "words", "rooms", "messages", "items"]
+    );
+    assert_eq!(partial.treasures(), Some(whole.treasures()));
+
+    // Everything parsed is written out, and reads back as a whole game.
+    let salvaged = partial.salvage().unwrap();
+    assert_eq!(salvaged.header_counts(), whole.header_counts());
+    assert_eq!(salvaged.version(), 0);
+    let mut written = vec![];
+    writer::write_game(&mut written, &salvaged).unwrap();
+    let reread =
+        Game::new(&mut Stream::new(written).unwrap()).unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!(reread.noun_groups(), whole.noun_groups());
+}
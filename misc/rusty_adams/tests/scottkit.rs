@@ -0,0 +1,100 @@
+//! These tests check that games can be decompiled to ScottKit's source
+//! format and compiled back into games that play the same way.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::interpreter::{Interpreter, OccurrencePolicy};
+use rusty_adams::game::scottkit;
+use rusty_adams::game::Game;
+use std::path::Path;
+
+extern crate rusty_adams;
+
+// Plays the given commands, with the automatic actions that have any chance
+// of running always running, and returns everything the game said.
+fn play(game: Game, commands: &[&str]) -> String {
+    let mut interpreter = Interpreter::new(game);
+    interpreter.set_occurrence_policy(OccurrencePolicy::Always);
+    let mut transcript = interpreter.start();
+    for command in commands {
+        transcript.push_str(&interpreter.command(command));
+    }
+    transcript
+}
+
+#[test]
+fn test_round_trip_of_collection() {
+    let commands = [
+        "look", "i", "n", "s", "e", "w", "u", "d", "get all", "score", "climb tree", "go east",
+    ];
+    for path in rusty_adams::list_games(Path::new("games")).unwrap() {
+        let path = path.to_string_lossy();
+        let source = scottkit::decompile(&rusty_adams::load_game(&path).unwrap());
+        let compiled = match scottkit::compile(&source) {
+            Ok(game) => game,
+            Err(err) => panic!("Error in {}: {}", path, err),
+        };
+        // Once decompiled, a game compiles and decompiles to the same source.
+        assert_eq!(scottkit::decompile(&compiled), source, "{}", path);
+        assert_eq!(
+            play(compiled, &commands),
+            play(rusty_adams::load_game(&path).unwrap(), &commands),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_compile() {
+    let source = r#"
+        # A very small adventure.
+        start cove
+        treasury cove
+        maxload 2
+
+        room cove "sandy cove"
+            exit north cliff
+        room cliff "*I'm on top of a \"cliff\"."
+            exit south cove
+
+        item shell "*Pink shell*"
+            called "SHELL"
+            at cliff
+        item lamp "Lamp"
+            carried
+
+        verbgroup blow puff
+        action blow shell when carried shell and !flag 1
+            set_flag 1
+            print "It echoes."
+            put shell nowhere
+        occur when at cliff and !flag 2
+            set_flag 2
+            print "Gulls wheel overhead."
+    "#;
+    let game = scottkit::compile(source).unwrap();
+    assert_eq!(game.treasures(), 1);
+    assert_eq!(game.header_counts().rooms, 3);
+    assert_eq!(
+        play(game, &["n", "get shell", "puff shell", "blow shell", "i"]),
+        "I'm in a sandy cove\n\nObvious exits: North.\n\n\
+         I'm on top of a \"cliff\".\n\nObvious exits: South.\n\nI can also see: *Pink shell*\n\
+         Gulls wheel overhead.\n\
+         O.K.\n\
+         I'm on top of a \"cliff\".\n\nObvious exits: South.\n\n\
+         It echoes.\n\
+         I'm on top of a \"cliff\".\n\nObvious exits: South.\n\n\
+         I can't do that yet.\n\
+         I'm carrying:\nLamp.\n"
+    );
+
+    assert_eq!(
+        scottkit::compile("room cove \"cove\"\n    exit northwest cove\n").err(),
+        Some("line 2: there is no direction northwest".to_string())
+    );
+    assert_eq!(
+        scottkit::compile("action dance\n    tango\n").err(),
+        Some("line 2: there is no instruction tango".to_string())
+    );
+    assert!(scottkit::compile("item lamp \"Lamp\"\n    at attic\n").is_err());
+}
@@ -0,0 +1,102 @@
+//! This integration test makes sure that strict parsing accepts all of the
+//! games in the collection and warns about a game file with problems, and
+//! that any parsing rejects exits and actions that refer to rooms and words
+//! that don't exist.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::{Game, ParseErrorKind, Section, TokenKind};
+use rusty_adams::tokenizer::{Location, Stream};
+use std::path::Path;
+
+extern crate rusty_adams;
+
+#[test]
+fn test_strict_parse_of_collection() {
+    for path in rusty_adams::list_games(Path::new("games")).unwrap() {
+        let path = path.to_string_lossy();
+        match rusty_adams::load_game_strict(&path) {
+            Ok((_, warnings)) => assert_eq!(warnings, Vec::<String>::new(), "{}", path),
+            Err(err) => panic!("Error in {}: {}", path, err),
+        }
+    }
+}
+
+#[test]
+fn test_strict_parse_warnings() {
+    let (_, warnings) = match rusty_adams::load_game_strict("tests/fixtures/bad_header.dat") {
+        Ok(result) => result,
+        Err(err) => panic!("Error: {}", err),
+    };
+    assert_eq!(
+        warnings,
+        [
+            "The header says there are 2 treasures, but 0 items are marked as treasures",
+            "The treasure room is 5, which doesn't exist",
+            "Found 1 token after the footer",
+        ]
+    );
+}
+
+#[test]
+fn test_parse_of_missing_references() {
+    let text = std::fs::read_to_string("tests/fixtures/get_drop.dat").unwrap();
+    let parse = |line: usize, value: &str| {
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines[line] = value;
+        let mut stream = Stream::new(lines.join("\n").into_bytes()).unwrap();
+        match Game::new(&mut stream) {
+            Ok(_) => panic!("Line {} parsed as {}", line, value),
+            Err(err) => err.to_string(),
+        }
+    };
+
+    // The first exit of room 1, and then the verb and noun of action 0.
+    assert_eq!(
+        parse(82, " 2 "),
+        "Room 1 has an exit to room 2, which doesn't exist"
+    );
+    assert_eq!(
+        parse(12, " 3000 "),
+        "Action 0 is for verb 20 and noun 0, which don't both exist"
+    );
+}
+
+#[test]
+fn test_parse_error_fields() {
+    let text = std::fs::read_to_string("tests/fixtures/get_drop.dat").unwrap();
+    let parse = |line: usize, value: &str| {
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines[line] = value;
+        let mut stream = Stream::new(lines.join("\n").into_bytes()).unwrap();
+        Game::new(&mut stream).unwrap_err()
+    };
+
+    let err = parse(82, " 2 ");
+    assert_eq!(err.section(), Section::Rooms);
+    assert_eq!(
+        err.kind(),
+        &ParseErrorKind::Exit {
+            room: 1,
+            destination: 2
+        }
+    );
+    assert_eq!(err.location(), None);
+
+    let err = parse(12, "\"verb\"");
+    assert_eq!(err.section(), Section::Actions);
+    assert_eq!(
+        err.kind(),
+        &ParseErrorKind::Unexpected {
+            expected: TokenKind::Int,
+            found: Some(TokenKind::Str),
+            location: Some(Location { line: 13, col: 1 }),
+        }
+    );
+    assert_eq!(err.to_string(), "13:1: Expected an integer, found a string");
+
+    let mut stream = Stream::new(b"0 1 2".to_vec()).unwrap();
+    let err = Game::new(&mut stream).unwrap_err();
+    assert_eq!(err.section(), Section::Header);
+    assert_eq!(err.location(), None);
+    assert_eq!(err.to_string(), "0:0: Unexpected end of stream");
+}
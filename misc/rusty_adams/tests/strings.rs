@@ -0,0 +1,170 @@
+//! These tests check that a game's text can be exported for translation and
+//! the translation merged back in, and that translated text in any script
+//! survives being written out and is wrapped by the columns it fills.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::format::Formatter;
+use rusty_adams::game::interpreter::Interpreter;
+use rusty_adams::game::{strings, writer};
+use rusty_adams::tokenizer::Stream;
+use unicode_width::UnicodeWidthStr;
+
+extern crate rusty_adams;
+
+mod common;
+
+use common::write;
+
+#[test]
+fn test_unchanged_translation() {
+    let mut game = rusty_adams::load_game("games/adv01.dat").unwrap();
+    let want = write(&game);
+
+    // Translate every string as itself.
+    let po = strings::export(&game);
+    let mut translated = String::new();
+    let mut id = "";
+    for line in po.lines() {
+        if let Some(text) = line.strip_prefix("msgid ") {
+            id = text;
+        }
+        if line.starts_with("msgstr ") && id != "\"\"" {
+            translated.push_str(&format!("msgstr {}\n", id));
+        } else {
+            translated.push_str(&format!("{}\n", line));
+        }
+    }
+    assert!(translated.contains("msgctxt \"room.1\""));
+
+    assert_eq!(strings::import(&mut game, &translated), Ok(vec![]));
+    assert_eq!(write(&game), want);
+}
+
+#[test]
+fn test_translation() {
+    let mut game = rusty_adams::load_game("tests/fixtures/get_drop.dat").unwrap();
+    let po = r#"
+# Les chaînes de get_drop.
+msgctxt "room.1"
+msgid "small room"
+msgstr "petite "
+"salle"
+
+msgctxt "message.1"
+msgid "It's too heavy to lift."
+msgstr "C'est trop lourd\npour la soulever."
+
+msgctxt "noun.8"
+msgid "KEY"
+msgstr "clef"
+
+msgctxt "noun.9"
+msgid "BUT"
+msgstr "cle"
+
+msgctxt "item.2"
+msgid "Painting"
+msgstr "*Tableau*"
+
+msgctxt "item.9"
+msgid "Nothing"
+msgstr "Rien"
+"#;
+    let warnings = strings::import(&mut game, po).unwrap();
+    let written = write(&game);
+    for line in [
+        "\"petite salle\"",
+        "\"C'est trop lourd\npour la soulever.\"",
+        "\"CLE\"",
+        "\"Brass key/CLE/\" 1 ",
+        "\"*Tableau*\" 1 ",
+    ] {
+        assert!(written.contains(line), "{} is missing", line);
+    }
+    assert_eq!(
+        warnings,
+        [
+            "The translation of item.2 changes whether it is a treasure, which depends on its \
+             starting with '*'",
+            "There is no string called item.9",
+            "The noun 'CLE' can't be told apart from noun 8",
+        ]
+    );
+
+    let po = "msgctxt \"room.1\"\nmsgid \"small room\"\nmsgstr \"\\\"Bigger\\\" room\"\n";
+    assert_eq!(strings::import(&mut game, po), Ok(vec![]));
+    assert!(write(&game).contains("\"`Bigger` room\""));
+    let po = "msgctxt \"room.1\"\nmsgid small room\n";
+    assert_eq!(
+        strings::import(&mut game, po),
+        Err("Line 2 of the translation can't be understood".to_string())
+    );
+}
+
+#[test]
+fn test_utf8_translation() {
+    let mut game = rusty_adams::load_game("tests/fixtures/get_drop.dat").unwrap();
+    let po = r#"
+msgctxt "room.1"
+msgid "small room"
+msgstr "petite salle poussiéreuse, où l'on étouffe près du vieux château"
+
+msgctxt "message.1"
+msgid "It's too heavy to lift."
+msgstr "この岩はとても重くて、私には持ち上げられません。"
+
+msgctxt "item.0"
+msgid "Brass key"
+msgstr "Clé en laiton"
+
+msgctxt "item.1"
+msgid "Heavy rock"
+msgstr "重い岩"
+"#;
+    assert_eq!(strings::import(&mut game, po), Ok(vec![]));
+
+    // Strings are read as UTF-8 where they can be, and as Latin-1 otherwise,
+    // as in games from before UTF-8.
+    let mut stream = Stream::new(b"\"Caf\xc3\xa9\" \"Caf\xe9\"".to_vec()).unwrap();
+    assert_eq!(stream.next_str().unwrap(), "Café");
+    assert_eq!(stream.next_str().unwrap(), "Café");
+
+    // The translation is written as UTF-8 and read back unchanged.
+    let mut data: Vec<u8> = Vec::new();
+    writer::write_game(&mut data, &game).unwrap();
+    let game = rusty_adams::load_game_from_bytes(&data).unwrap();
+    assert_eq!(write(&game).as_bytes(), &data[..]);
+
+    // Lines are wrapped by the columns they fill, with each wide character
+    // taking two, rather than by their bytes or characters.
+    let play = |columns: usize| {
+        let mut interpreter = Interpreter::new(rusty_adams::load_game_from_bytes(&data).unwrap());
+        let mut formatter = Formatter::for_platform(columns);
+        let mut out = formatter.format(&interpreter.start());
+        for command in ["get rock", "get key", "i"] {
+            formatter.end_line();
+            out.push_str(&formatter.format(&interpreter.command(command)));
+        }
+        for line in out.lines() {
+            assert!(line.width() <= columns, "{:?} is wider than {}", line, columns);
+        }
+        out
+    };
+    assert_eq!(
+        play(40),
+        "I'm in a petite salle poussiéreuse, où l\n'on étouffe près du vieux château\n\n\
+         Obvious exits: none.\n\n\
+         I can also see: Clé en laiton - 重い岩 -\n Painting\n\
+         この岩はとても重くて、私には持ち上げられ\nません。\n\
+         O.K.\n\
+         I'm in a petite salle poussiéreuse, où l\n'on étouffe près du vieux château\n\n\
+         Obvious exits: none.\n\n\
+         I can also see: 重い岩 - Painting\n\
+         I'm carrying:\nClé en laiton.\n"
+    );
+    let out = play(64);
+    assert!(out.starts_with(
+        "I'm in a petite salle poussiéreuse, où l'on étouffe près du vieu\nx château\n"
+    ));
+    assert!(out.contains("\nこの岩はとても重くて、私には持ち上げられません。\n"));
+}
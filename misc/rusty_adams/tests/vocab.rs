@@ -0,0 +1,41 @@
+//! These tests check that the vocabulary's synonyms are grouped under the
+//! words they mean, as the interpreter matches them.
+
+use pretty_assertions::assert_eq;
+use rusty_adams::game::WordGroup;
+
+extern crate rusty_adams;
+
+fn group(index: usize, word: &str, synonyms: &[&str]) -> WordGroup {
+    WordGroup {
+        index,
+        word: word.to_string(),
+        synonyms: synonyms.iter().map(|word| word.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_adventureland() {
+    let game = rusty_adams::load_game("games/adv01.dat").unwrap();
+    let verbs = game.verb_groups();
+    assert_eq!(verbs[0], group(0, "AUT", &[]));
+    assert_eq!(verbs[1], group(1, "GO", &["ENT", "RUN", "WAL", "CLI"]));
+    assert_eq!(verbs[2], group(6, "JUM", &[]));
+    assert_eq!(verbs[1].to_string(), "GO = ENT, RUN, WAL, CLI");
+
+    let nouns = game.noun_groups();
+    assert_eq!(nouns[1], group(1, "NORTH", &[]));
+    let axe = nouns.iter().find(|group| group.word == "AXE").unwrap();
+    assert_eq!(axe.synonyms, ["AX"]);
+}
+
+#[test]
+fn test_every_word_is_grouped() {
+    let game = rusty_adams::load_game("games/adv01.dat").unwrap();
+    let counts = game.header_counts();
+    for groups in [game.verb_groups(), game.noun_groups()] {
+        let words: usize = groups.iter().map(|group| 1 + group.synonyms.len()).sum();
+        assert!(words <= counts.words as usize);
+        assert!(groups.windows(2).all(|pair| pair[0].index < pair[1].index));
+    }
+}
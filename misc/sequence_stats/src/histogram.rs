@@ -0,0 +1,143 @@
+//! A histogram of values grouped into buckets of equal width, which can be
+//! drawn as a bar chart made of text.
+
+use serde::Serialize;
+
+/// A count of the values from `start` up to and including `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Bucket {
+    pub start: u64,
+    pub end: u64,
+    pub count: u64,
+}
+
+/// Histogram counts values in buckets of `width`, so with a width of 10 the
+/// buckets hold 0-9, 10-19, and so on.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    width: u64,
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Creates an empty histogram with the given bucket width, which must be
+    /// at least 1.
+    pub fn new(width: u64) -> Self {
+        assert!(width > 0, "bucket width must be at least 1");
+        Histogram {
+            width,
+            counts: vec![],
+        }
+    }
+
+    /// Counts one more occurrence of the value.
+    pub fn add(&mut self, value: u64) {
+        let index = (value / self.width) as usize;
+        if index >= self.counts.len() {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+    }
+
+    /// Returns the buckets from the lowest to the highest one holding any
+    /// values.  Empty buckets in between are included, so gaps are visible.
+    pub fn buckets(&self) -> Vec<Bucket> {
+        let first = self.counts.iter().position(|&count| count > 0);
+        let Some(first) = first else {
+            return vec![];
+        };
+        (first..self.counts.len())
+            .map(|index| Bucket {
+                start: index as u64 * self.width,
+                end: (index as u64 + 1) * self.width - 1,
+                count: self.counts[index],
+            })
+            .collect()
+    }
+
+    /// Draws the histogram as one line per bucket, with the longest bar
+    /// `max_bar` characters wide.  Any non-empty bucket gets at least a sliver
+    /// of a bar, so that rare values still show up.
+    pub fn chart(&self, max_bar: usize) -> Vec<String> {
+        let buckets = self.buckets();
+        let largest = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(0);
+        let label_width = buckets
+            .last()
+            .map_or(1, |bucket| bucket.end.to_string().len());
+        let count_width = largest.to_string().len();
+
+        buckets
+            .iter()
+            .map(|bucket| {
+                let bar = match (bucket.count * max_bar as u64).div_ceil(largest.max(1)) {
+                    0 => String::new(),
+                    len => "█".repeat(len as usize),
+                };
+                format!(
+                    "{:>w$}-{:<w$} {:>c$} {}",
+                    bucket.start,
+                    bucket.end,
+                    bucket.count,
+                    bar,
+                    w = label_width,
+                    c = count_width
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets() {
+        let mut histogram = Histogram::new(10);
+        assert_eq!(histogram.buckets(), vec![]);
+
+        for value in [12, 15, 19, 20, 41] {
+            histogram.add(value);
+        }
+        let counts: Vec<(u64, u64, u64)> = histogram
+            .buckets()
+            .iter()
+            .map(|bucket| (bucket.start, bucket.end, bucket.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(10, 19, 3), (20, 29, 1), (30, 39, 0), (40, 49, 1)]
+        );
+    }
+
+    #[test]
+    fn test_width_one() {
+        let mut histogram = Histogram::new(1);
+        histogram.add(0);
+        histogram.add(2);
+        let buckets = histogram.buckets();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!((buckets[2].start, buckets[2].end), (2, 2));
+    }
+
+    #[test]
+    fn test_chart() {
+        let mut histogram = Histogram::new(5);
+        for _ in 0..100 {
+            histogram.add(7);
+        }
+        histogram.add(12);
+        histogram.add(20);
+        assert_eq!(
+            histogram.chart(10),
+            vec![
+                " 5-9  100 ██████████",
+                "10-14   1 █",
+                "15-19   0",
+                "20-24   1 █",
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The Collatz (3n + 1) sequence: starting from any positive number, halve it
+//! if it is even, or triple it and add one if it is odd, until reaching 1.
+//!
+//! Every function here checks its arithmetic, since the trajectory of a large
+//! enough number can climb past `u64::MAX`:
+//!
+//! ```
+//! use sequence_stats::{length, peak, SequenceError};
+//!
+//! assert_eq!(length(27), Ok(112));
+//! assert_eq!(peak(27), Ok(9232));
+//! assert_eq!(length(0), Err(SequenceError::Zero));
+//! ```
+
+pub mod histogram;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The ways that following a sequence can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceError {
+    /// The sequence was started from zero, where it is not defined.
+    Zero,
+    /// The trajectory of `n` climbed past `u64::MAX`.
+    Overflow { n: u64 },
+}
+
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SequenceError::Zero => write!(f, "the sequence is not defined for 0"),
+            SequenceError::Overflow { n } => {
+                write!(f, "the sequence for {} overflows 64 bits", n)
+            }
+        }
+    }
+}
+
+impl Error for SequenceError {}
+
+/// Returns the number that follows `value` in the trajectory of `n` (which
+/// is only used to describe an overflow).
+fn step(value: u64, n: u64) -> Result<u64, SequenceError> {
+    if value.is_multiple_of(2) {
+        Ok(value / 2)
+    } else {
+        value
+            .checked_mul(3)
+            .and_then(|value| value.checked_add(1))
+            .ok_or(SequenceError::Overflow { n })
+    }
+}
+
+/// Returns the length of the sequence starting at `n`, counting both `n` and
+/// the final 1.
+pub fn length(n: u64) -> Result<u64, SequenceError> {
+    if n == 0 {
+        return Err(SequenceError::Zero);
+    }
+    let mut length = 1;
+    let mut value = n;
+    while value != 1 {
+        value = step(value, n)?;
+        length += 1;
+    }
+    Ok(length)
+}
+
+/// Returns the highest value reached by the sequence starting at `n`.
+pub fn peak(n: u64) -> Result<u64, SequenceError> {
+    excursion(n, 1)
+}
+
+/// Returns the highest value reached by the sequence starting at `n` before
+/// it falls to a number below `n` but no smaller than `floor`.  Since the rest
+/// of the trajectory belongs to that smaller number, this is enough to find
+/// the numbers that set new records when scanning upwards from `floor`.
+pub fn excursion(n: u64, floor: u64) -> Result<u64, SequenceError> {
+    if n == 0 {
+        return Err(SequenceError::Zero);
+    }
+    let mut peak = n;
+    let mut value = n;
+    while value > 1 && (value >= n || value < floor) {
+        value = step(value, n)?;
+        peak = peak.max(value);
+    }
+    Ok(peak)
+}
+
+/// A table of known sequence lengths for the numbers below a bound, which can
+/// be shared between threads.  Two threads may compute the same entry at once,
+/// but they will always store the same value, so relaxed ordering is enough.
+pub struct Memo {
+    /// The length for each number, or zero if it isn't known yet.
+    lengths: Vec<AtomicU32>,
+}
+
+impl Memo {
+    /// Creates an empty table for the numbers below `bound`.
+    pub fn new(bound: u64) -> Self {
+        Memo {
+            lengths: (0..bound).map(|_| AtomicU32::new(0)).collect(),
+        }
+    }
+
+    fn get(&self, n: u64) -> Option<u64> {
+        let entry = self.lengths.get(usize::try_from(n).ok()?)?;
+        match entry.load(Ordering::Relaxed) {
+            0 => None,
+            length => Some(length as u64),
+        }
+    }
+
+    fn set(&self, n: u64, length: u64) {
+        let entry = usize::try_from(n).ok().and_then(|n| self.lengths.get(n));
+        if let (Some(entry), Ok(length)) = (entry, u32::try_from(length)) {
+            entry.store(length, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the same result as `length`, but follows the sequence only
+    /// until it reaches a number whose length is already known, and then
+    /// remembers the lengths of the numbers it passed along the way.
+    pub fn length(&self, n: u64) -> Result<u64, SequenceError> {
+        if n == 0 {
+            return Err(SequenceError::Zero);
+        }
+        let mut steps = 0;
+        let mut value = n;
+        let known = loop {
+            if let Some(length) = self.get(value) {
+                break length;
+            } else if value == 1 {
+                break 1;
+            }
+            value = step(value, n)?;
+            steps += 1;
+        };
+
+        // Walking the path again is cheaper than keeping it in a Vec.
+        let mut value = n;
+        for remaining in (1..=steps).rev() {
+            self.set(value, known + remaining);
+            value = step(value, n)?;
+        }
+        Ok(known + steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        assert_eq!(length(1), Ok(1));
+        assert_eq!(length(2), Ok(2));
+        assert_eq!(length(3), Ok(8));
+        assert_eq!(length(837_799), Ok(525));
+        assert_eq!(length(0), Err(SequenceError::Zero));
+    }
+
+    #[test]
+    fn test_peak() {
+        assert_eq!(peak(1), Ok(1));
+        assert_eq!(peak(7), Ok(52));
+        assert_eq!(peak(703), Ok(250_504));
+        assert_eq!(peak(0), Err(SequenceError::Zero));
+    }
+
+    #[test]
+    fn test_excursion() {
+        // 6 falls straight to 3, but with a floor above 3 it keeps going
+        // through 10 until it reaches 5.
+        assert_eq!(excursion(7, 1), Ok(52));
+        assert_eq!(excursion(6, 1), Ok(6));
+        assert_eq!(excursion(6, 4), Ok(10));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // The largest odd number whose 3n + 1 still fits, and the next one.
+        let safe = (u64::MAX - 1) / 3;
+        let limit = if safe % 2 == 1 { safe } else { safe - 1 };
+        assert!(step(limit, limit).is_ok());
+        assert_eq!(step(limit + 2, 5), Err(SequenceError::Overflow { n: 5 }));
+
+        let n = u64::MAX;
+        assert_eq!(length(n), Err(SequenceError::Overflow { n }));
+        assert_eq!(peak(n), Err(SequenceError::Overflow { n }));
+        assert_eq!(Memo::new(16).length(n), Err(SequenceError::Overflow { n }));
+    }
+
+    #[test]
+    fn test_memo_matches_length() {
+        let memo = Memo::new(1000);
+        for n in (1..5000).rev() {
+            assert_eq!(memo.length(n), length(n));
+        }
+        assert_eq!(memo.get(27), Some(112));
+    }
+}
@@ -1,23 +1,278 @@
-fn sequence(n: u64) -> u64 {
-    if n == 1 {
-        1
-    } else if n % 2 == 0 {
-        1 + sequence(n / 2)
-    } else {
-        1 + sequence(n * 3 + 1)
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use rayon::prelude::*;
+use sequence_stats::histogram::{Bucket, Histogram};
+use sequence_stats::{excursion, Memo, SequenceError};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+
+// Sequence lengths are remembered for every number below this bound.
+const MEMO_BOUND: u64 = 1 << 24;
+
+// The width of the longest bar in the histogram.
+const CHART_WIDTH: usize = 50;
+
+/// Scans the lengths and peaks of Collatz sequences over a range of starting
+/// numbers, reporting running statistics, record holders, and a histogram.
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// The first number to scan
+    #[arg(long, default_value = "1", value_parser = parse_positive)]
+    start: u64,
+    /// The last number to scan
+    #[arg(long, default_value = "10_000_000", value_parser = parse_positive)]
+    end: u64,
+    /// How often to report the running statistics
+    #[arg(long, value_name = "N", default_value = "100_000", value_parser = parse_positive)]
+    report_every: u64,
+    /// Write the running statistics to a CSV file
+    #[arg(long, value_name = "FILE")]
+    csv: Option<String>,
+    /// Print the results as JSON instead of as text
+    #[arg(long)]
+    json: bool,
+    /// The range of sequence lengths counted by each histogram bucket
+    #[arg(long, value_name = "N", default_value = "10", value_parser = parse_positive)]
+    bucket_width: u64,
+    /// Write the histogram to a CSV file
+    #[arg(long, value_name = "FILE")]
+    histogram_csv: Option<String>,
+}
+
+// The running statistics, as of the last number scanned.
+#[derive(Serialize)]
+struct Report {
+    n: u64,
+    mean: f64,
+    weighted: f64,
+}
+
+// A number that set a record, and the value that set it.
+#[derive(Clone, Copy, Serialize)]
+struct Record {
+    n: u64,
+    value: u64,
+}
+
+// The record holders so far: the numbers whose sequences are longer, and
+// whose trajectories climb higher, than those of every smaller number.
+#[derive(Default, Serialize)]
+struct Records {
+    longest: Vec<Record>,
+    highest: Vec<Record>,
+}
+
+impl Records {
+    // Records any new records set by n, printing them unless quiet.
+    fn update(&mut self, n: u64, length: u64, peak: u64, quiet: bool) {
+        if self
+            .longest
+            .last()
+            .is_none_or(|record| length > record.value)
+        {
+            self.longest.push(Record { n, value: length });
+            if !quiet {
+                println!("{:8}: new longest sequence, length {}", n, length);
+            }
+        }
+        if self.highest.last().is_none_or(|record| peak > record.value) {
+            self.highest.push(Record { n, value: peak });
+            if !quiet {
+                println!("{:8}: new highest peak, {}", n, peak);
+            }
+        }
+    }
+
+    // Prints both lists of record holders side by side.
+    fn print_table(&self) {
+        println!();
+        println!("{:>10} {:>8}   {:>10} {:>20}", "n", "length", "n", "peak");
+        for i in 0..self.longest.len().max(self.highest.len()) {
+            match self.longest.get(i) {
+                Some(record) => print!("{:>10} {:>8}   ", record.n, record.value),
+                None => print!("{:22}", ""),
+            }
+            match self.highest.get(i) {
+                Some(record) => println!("{:>10} {:>20}", record.n, record.value),
+                None => println!(),
+            }
+        }
+        println!();
     }
 }
 
-fn main() {
+// Everything printed by --json.
+#[derive(Serialize)]
+struct Results<'a> {
+    start: u64,
+    end: u64,
+    reports: &'a [Report],
+    records: &'a Records,
+    bucket_width: u64,
+    histogram: &'a [Bucket],
+    elapsed_secs: f64,
+}
+
+// Parses a number of at least 1, which may contain underscores for
+// readability, as in 10_000_000.
+fn parse_positive(text: &str) -> Result<u64, String> {
+    match text.replace('_', "").parse() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(_) => Err("expected a positive number".to_string()),
+    }
+}
+
+// Scans the range in chunks that end on reporting boundaries, each scanned in
+// parallel, and returns the running statistics at the end of each chunk along
+// with the record holders and a histogram of the sequence lengths.
+fn scan(options: &Options) -> Result<(Vec<Report>, Records, Histogram), SequenceError> {
+    let memo = Memo::new(MEMO_BOUND.min(options.end.saturating_add(1)));
+    let mut reports = vec![];
+    let mut records = Records::default();
+    let mut histogram = Histogram::new(options.bucket_width);
+
     let mut sum: u64 = 0;
     let mut weighted_sum: f64 = 0.0;
-    for i in 1..=10000000 {
-        let count: u64 = sequence(i);
-        sum += count;
-        weighted_sum += (count as f64) / ((i+1) as f64).ln();
-        if i % 100000 == 0 {
-            println!("{:8}: mean={:0.3}, weighted={:0.3}", i,
-                (sum as f64) / (i as f64), (weighted_sum / (i as f64)));
+    let mut first = options.start;
+    loop {
+        let last = first
+            .saturating_add(options.report_every - 1)
+            .min(options.end);
+        let lengths: Vec<(u64, u64)> = (first..=last)
+            .into_par_iter()
+            .map(|i| Ok((memo.length(i)?, excursion(i, options.start)?)))
+            .collect::<Result<_, SequenceError>>()?;
+
+        // Records must be found in order, but this pass is cheap.
+        for (i, &(length, peak)) in (first..).zip(&lengths) {
+            sum += length;
+            weighted_sum += (length as f64) / ((i + 1) as f64).ln();
+            records.update(i, length, peak, options.json);
+            histogram.add(length);
+        }
+
+        let scanned = (last - options.start + 1) as f64;
+        let report = Report {
+            n: last,
+            mean: (sum as f64) / scanned,
+            weighted: weighted_sum / scanned,
+        };
+        if !options.json {
+            println!(
+                "{:8}: mean={:0.3}, weighted={:0.3}",
+                report.n, report.mean, report.weighted
+            );
+        }
+        reports.push(report);
+
+        if last == options.end {
+            return Ok((reports, records, histogram));
+        }
+        first = last + 1;
+    }
+}
+
+fn write_histogram_csv(path: &str, buckets: &[Bucket]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "start,end,count")?;
+    for bucket in buckets {
+        writeln!(file, "{},{},{}", bucket.start, bucket.end, bucket.count)?;
+    }
+    file.flush()
+}
+
+fn write_csv(path: &str, reports: &[Report]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "n,mean,weighted")?;
+    for report in reports {
+        writeln!(file, "{},{},{}", report.n, report.mean, report.weighted)?;
+    }
+    file.flush()
+}
+
+fn main() -> ExitCode {
+    let options = Options::parse();
+    if options.end < options.start {
+        Options::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--end must not be less than --start",
+            )
+            .exit();
+    }
+
+    let start = Instant::now();
+    let (reports, records, histogram) = match scan(&options) {
+        Ok(results) => results,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return ExitCode::FAILURE;
         }
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+
+    if let Some(path) = &options.csv {
+        if let Err(error) = write_csv(path, &reports) {
+            eprintln!("Error writing {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let buckets = histogram.buckets();
+    if let Some(path) = &options.histogram_csv {
+        if let Err(error) = write_histogram_csv(path, &buckets) {
+            eprintln!("Error writing {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let scanned = options.end - options.start + 1;
+    if options.json {
+        let results = Results {
+            start: options.start,
+            end: options.end,
+            reports: &reports,
+            records: &records,
+            bucket_width: options.bucket_width,
+            histogram: &buckets,
+            elapsed_secs: elapsed,
+        };
+        println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    } else {
+        records.print_table();
+        println!("Sequence lengths:");
+        for line in histogram.chart(CHART_WIDTH) {
+            println!("{}", line);
+        }
+        println!();
+        println!(
+            "Scanned {} numbers in {:0.2}s ({:0.0} numbers/s)",
+            scanned,
+            elapsed,
+            scanned as f64 / elapsed
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options() {
+        Options::command().debug_assert();
+
+        let options =
+            Options::try_parse_from(["sequence_stats", "--end", "1_000", "--json"]).unwrap();
+        assert_eq!((options.start, options.end), (1, 1000));
+        assert!(options.json);
+        assert!(Options::try_parse_from(["sequence_stats", "--bucket-width", "0"]).is_err());
+        assert!(Options::try_parse_from(["sequence_stats", "--start", "-5"]).is_err());
     }
 }
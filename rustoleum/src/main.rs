@@ -0,0 +1,145 @@
+use clap::Parser;
+use rusty_adams::catalog;
+use std::io;
+use std::path::PathBuf;
+use tui_common::color::{GRAY, LT_CYAN, LT_WHITE, LT_YELLOW};
+use tui_common::Terminal;
+
+// The games on the main menu, with the key that launches each.
+const GAMES: [(char, &str); 3] = [
+    ('1', "Towers of Hanoi"),
+    ('2', "Reversi"),
+    ('3', "Scott Adams adventures"),
+];
+
+// Draws a menu of (key, label) entries below a title, and returns the key
+// pressed, which is either one of the entries' keys or 'q' to go back.
+fn menu(term: &mut Terminal, title: &str, entries: &[(char, String)]) -> io::Result<char> {
+    term.clear_screen()?;
+    term.draw_box(0, 0, title.len() + 4, 3, LT_CYAN)?;
+    term.draw_text(2, 1, LT_YELLOW, title)?;
+    for (i, (key, label)) in entries.iter().enumerate() {
+        term.draw_text(2, i + 4, LT_WHITE, &format!("{}. {}", key, label))?;
+    }
+    term.draw_text(2, entries.len() + 5, GRAY, "q. Quit")?;
+    term.goto_xy(0, entries.len() + 7)?;
+
+    loop {
+        let key = term.read_char()?.to_ascii_lowercase();
+        if key == 'q' || entries.iter().any(|&(entry, _)| entry == key) {
+            return Ok(key);
+        }
+    }
+}
+
+// Lets the player choose one of the adventures in the directories of the
+// games path, as rusty_adams finds them, returning None if they back out or
+// there are none.
+fn choose_adventure(term: &mut Terminal) -> io::Result<Option<PathBuf>> {
+    let dirs = catalog::games_path(None);
+    let games: Vec<PathBuf> = dirs
+        .iter()
+        .flat_map(|dir| rusty_adams::list_games(dir).unwrap_or_default())
+        .collect();
+    if games.is_empty() {
+        let dirs: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        term.clear_screen()?;
+        term.write_line("There are no adventures in any of the games directories:")?;
+        term.write_line(&dirs.join(", "))?;
+        term.write_line(&format!(
+            "Put the game files in one of them, or list another in {}.",
+            catalog::GAMES_VAR
+        ))?;
+        pause(term)?;
+        return Ok(None);
+    }
+    // Every letter but 'q', which is kept for going back.
+    let entries: Vec<(char, String)> = ('a'..='z')
+        .filter(|&key| key != 'q')
+        .zip(&games)
+        .map(|(key, path)| {
+            (
+                key,
+                match catalog::title_of(path) {
+                    Some(title) => title.name.to_string(),
+                    None => path.file_stem().unwrap().to_string_lossy().into_owned(),
+                },
+            )
+        })
+        .collect();
+    let key = menu(term, "Scott Adams adventures", &entries)?;
+    Ok(entries
+        .iter()
+        .position(|&(entry, _)| entry == key)
+        .map(|i| games[i].clone()))
+}
+
+// Runs the chosen adventure, reporting any problem loading it.
+fn play_adventure(term: &mut Terminal) -> io::Result<()> {
+    if let Some(path) = choose_adventure(term)? {
+        term.clear_screen()?;
+        if let Err(err) = rusty_adams::run(&path.to_string_lossy()) {
+            term.write_line(&err)?;
+        }
+        pause(term)?;
+    }
+    Ok(())
+}
+
+// Runs Reversi with the player's saved settings, as its own program does,
+// reporting any problem with them.
+fn play_reversi(term: &mut Terminal) -> io::Result<()> {
+    let setup = reversi::Settings::load()
+        .and_then(|settings| settings.setup())
+        .and_then(|setup| setup.check().map(|()| setup));
+    match setup {
+        Ok(setup) => reversi::play(&setup),
+        Err(err) => {
+            term.clear_screen()?;
+            term.write_line(&err)?;
+        }
+    }
+    Ok(())
+}
+
+// Waits for a key press so that the end of a game stays on screen until the
+// player has seen it.
+fn pause(term: &mut Terminal) -> io::Result<()> {
+    term.set_color(GRAY)?;
+    term.write_line("")?;
+    term.write_line("Press any key to return to the menu...")?;
+    term.read_char()?;
+    Ok(())
+}
+
+/// A menu of the games in the collection.
+#[derive(Parser)]
+#[command(version)]
+struct Options {}
+
+fn main() -> io::Result<()> {
+    Options::parse();
+    let mut term = Terminal::new();
+    let entries: Vec<(char, String)> = GAMES
+        .iter()
+        .map(|&(key, label)| (key, label.to_string()))
+        .collect();
+
+    loop {
+        match menu(&mut term, "Rustoleum", &entries)? {
+            '1' => {
+                term.clear_screen()?;
+                hanoi::app::play(&hanoi::app::Setup::default());
+                pause(&mut term)?;
+            }
+            '2' => {
+                play_reversi(&mut term)?;
+                pause(&mut term)?;
+            }
+            '3' => play_adventure(&mut term)?,
+            _ => break,
+        }
+    }
+
+    term.clear_screen()
+}
@@ -0,0 +1,21 @@
+//! The 16 standard terminal colors, as the ANSI codes that select them for
+//! the foreground.  The light colors are the regular ones plus 60, and each
+//! color is selected for the background by its code plus 10.
+
+pub const BLACK: u8 = 30;
+pub const RED: u8 = 31;
+pub const GREEN: u8 = 32;
+pub const YELLOW: u8 = 33;
+pub const BLUE: u8 = 34;
+pub const MAGENTA: u8 = 35;
+pub const CYAN: u8 = 36;
+pub const WHITE: u8 = 37;
+
+pub const GRAY: u8 = BLACK + 60;
+pub const LT_RED: u8 = RED + 60;
+pub const LT_GREEN: u8 = GREEN + 60;
+pub const LT_YELLOW: u8 = YELLOW + 60;
+pub const LT_BLUE: u8 = BLUE + 60;
+pub const LT_MAGENTA: u8 = MAGENTA + 60;
+pub const LT_CYAN: u8 = CYAN + 60;
+pub const LT_WHITE: u8 = WHITE + 60;
@@ -0,0 +1,73 @@
+//! The escape sequences and line-drawing characters that a Terminal writes.
+//! They are built here, apart from any terminal, so that they can be tested.
+
+/// Clears the screen and homes the cursor.
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Returns the sequence that moves the cursor to the given 0-based (x, y)
+/// coordinates.
+pub fn goto(x: usize, y: usize) -> String {
+    format!("\x1b[{};{}H", y + 1, x + 1)
+}
+
+/// Returns the sequence that selects the given color code.
+pub fn color(color: u8) -> String {
+    format!("\x1b[{}m", color)
+}
+
+/// Returns the sequence that selects the given color code for the
+/// background instead.
+pub fn background(color: u8) -> String {
+    format!("\x1b[{}m", color + 10)
+}
+
+/// Goes back to the terminal's own background color.
+pub const DEFAULT_BACKGROUND: &str = "\x1b[49m";
+
+/// Rings the terminal's bell.
+pub const BELL: &str = "\x07";
+
+/// Shows the whole screen in reverse video, for a visual bell.
+pub const REVERSE_SCREEN: &str = "\x1b[?5h";
+
+/// Shows the screen normally again.
+pub const NORMAL_SCREEN: &str = "\x1b[?5l";
+
+/// Returns the lines that draw a box of the given size, or nothing if it is
+/// smaller than 2×2.
+pub fn box_lines(width: usize, height: usize) -> Vec<String> {
+    if width < 2 || height < 2 {
+        return vec![];
+    }
+    let bar = "─".repeat(width - 2);
+    let mut lines = vec![format!("┌{}┐", bar)];
+    lines.extend((2..height).map(|_| format!("│{}│", " ".repeat(width - 2))));
+    lines.push(format!("└{}┘", bar));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goto() {
+        assert_eq!(goto(0, 0), "\x1b[1;1H");
+        assert_eq!(goto(22, 9), "\x1b[10;23H");
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(color(crate::color::LT_WHITE), "\x1b[97m");
+        assert_eq!(background(crate::color::LT_WHITE), "\x1b[107m");
+        assert_eq!(background(crate::color::BLUE), "\x1b[44m");
+    }
+
+    #[test]
+    fn test_box_lines() {
+        assert_eq!(box_lines(4, 3), vec!["┌──┐", "│  │", "└──┘"]);
+        assert_eq!(box_lines(2, 2), vec!["┌┐", "└┘"]);
+        assert!(box_lines(1, 5).is_empty());
+        assert!(box_lines(5, 1).is_empty());
+    }
+}
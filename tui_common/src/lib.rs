@@ -0,0 +1,189 @@
+//! Terminal drawing and input shared by the games in this collection.
+//!
+//! Everything is drawn with plain ANSI escape sequences at 0-based (x, y)
+//! coordinates, in one of the 16 standard colors, through a Terminal that
+//! wraps an unbuffered `console::Term` so that single keystrokes can be read.
+//!
+//! ```no_run
+//! use tui_common::{color, Key, Terminal};
+//!
+//! let mut terminal = Terminal::new();
+//! terminal.clear_screen()?;
+//! terminal.draw_box(0, 0, 20, 5, color::GRAY)?;
+//! terminal.draw_text(2, 2, color::LT_GREEN, "Hello!")?;
+//! if terminal.read_key()? == Key::Escape {
+//!     return Ok(());
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod color;
+pub mod escape;
+
+use console::Term;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+pub use console::Key;
+
+/// Terminal encapsulates the display and keyboard.  All output must go
+/// through a single Terminal rather than stdout for flushing to work properly.
+pub struct Terminal {
+    term: Term,
+    /// The key press that a read with a time limit is still waiting for, if
+    /// it ran out of time.
+    pending: Option<Receiver<io::Result<Key>>>,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Terminal {
+    /// Creates a Terminal for stdout.
+    pub fn new() -> Self {
+        Self {
+            term: Term::stdout(),
+            pending: None,
+        }
+    }
+
+    /// Clears the screen, homes the cursor, and sets the current color to
+    /// bright white.
+    pub fn clear_screen(&mut self) -> io::Result<()> {
+        self.term.write_str(escape::CLEAR)?;
+        self.set_color(color::LT_WHITE)
+    }
+
+    /// Draws a box in the given color with its top left corner at the given
+    /// coordinates.  Boxes smaller than 2×2 are not drawn.
+    pub fn draw_box(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        color: u8,
+    ) -> io::Result<()> {
+        self.set_color(color)?;
+        for (row, line) in escape::box_lines(width, height).iter().enumerate() {
+            if row == 0 || row + 1 == height {
+                self.goto_xy(x, y + row)?;
+                self.term.write_str(line)?;
+            } else {
+                // Only the sides are drawn, so the inside is left untouched.
+                self.goto_xy(x, y + row)?;
+                self.term.write_str("│")?;
+                self.goto_xy(x + width - 1, y + row)?;
+                self.term.write_str("│")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws text in the given color at the given coordinates.
+    pub fn draw_text(&mut self, x: usize, y: usize, color: u8, text: &str) -> io::Result<()> {
+        self.goto_xy(x, y)?;
+        self.set_color(color)?;
+        self.term.write_str(text)
+    }
+
+    /// Draws text in the given color, on the given background, at the given
+    /// coordinates.  The background only lasts for this text.
+    pub fn draw_text_on(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: u8,
+        background: u8,
+        text: &str,
+    ) -> io::Result<()> {
+        self.goto_xy(x, y)?;
+        self.set_color(color)?;
+        self.term.write_str(&escape::background(background))?;
+        self.term.write_str(text)?;
+        self.term.write_str(escape::DEFAULT_BACKGROUND)
+    }
+
+    /// Moves the cursor to the given coordinates.
+    pub fn goto_xy(&mut self, x: usize, y: usize) -> io::Result<()> {
+        self.term.write_str(&escape::goto(x, y))
+    }
+
+    /// Sets the color used for the text that follows.
+    pub fn set_color(&mut self, color: u8) -> io::Result<()> {
+        self.term.write_str(&escape::color(color))
+    }
+
+    /// Writes text at the cursor, followed by a new line.
+    pub fn write_line(&mut self, text: &str) -> io::Result<()> {
+        self.term.write_line(text)
+    }
+
+    /// Returns the size of the terminal as (rows, columns).
+    pub fn size(&self) -> (usize, usize) {
+        let (rows, cols) = self.term.size();
+        (rows as usize, cols as usize)
+    }
+
+    /// Rings the terminal's bell.
+    pub fn bell(&mut self) -> io::Result<()> {
+        self.term.write_str(escape::BELL)
+    }
+
+    /// Flashes the screen, as a bell that can be seen rather than heard.
+    pub fn flash(&mut self) -> io::Result<()> {
+        self.term.write_str(escape::REVERSE_SCREEN)?;
+        thread::sleep(Duration::from_millis(100));
+        self.term.write_str(escape::NORMAL_SCREEN)
+    }
+
+    /// Reads a single key press.
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        match self.pending.take() {
+            Some(pending) => pending
+                .recv()
+                .unwrap_or_else(|_| Err(io::Error::other("key reader failed"))),
+            None => self.term.read_key(),
+        }
+    }
+
+    /// Reads a single key press, or returns None if there is none within the
+    /// given time.  A key pressed later is kept for the next read.
+    pub fn read_key_within(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        // The key is read on another thread, which carries on waiting for it
+        // after a time out.
+        let pending = self.pending.take().unwrap_or_else(|| {
+            let (sender, receiver) = mpsc::channel();
+            let term = self.term.clone();
+            thread::spawn(move || sender.send(term.read_key()));
+            receiver
+        });
+        match pending.recv_timeout(timeout) {
+            Ok(key) => key.map(Some),
+            Err(RecvTimeoutError::Timeout) => {
+                self.pending = Some(pending);
+                Ok(None)
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::other("key reader failed")),
+        }
+    }
+
+    /// Reads a single character, ignoring keys that don't produce one.
+    pub fn read_char(&mut self) -> io::Result<char> {
+        if self.pending.is_none() {
+            return self.term.read_char();
+        }
+        loop {
+            match self.read_key()? {
+                Key::Char(ch) => return Ok(ch),
+                Key::Enter => return Ok('\n'),
+                _ => (),
+            }
+        }
+    }
+}